@@ -0,0 +1,105 @@
+use std::hint::black_box;
+
+use chrono::NaiveDate;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use wakalyze::core::{
+    build_sessions, estimate_seconds, filter_sessions, DaySessions, RawHeartbeat,
+    DEFAULT_MAX_GAP_SECONDS,
+};
+use wakalyze::summary::summarize;
+
+const SIZES: [usize; 3] = [10_000, 100_000, 1_000_000];
+const PROJECTS: [&str; 5] = ["api", "web", "infra", "docs", "scratch"];
+
+/// Heartbeats every 30s, a 20-minute break every 100 beats, a project switch every 250.
+fn synthetic_heartbeats(count: usize) -> Vec<RawHeartbeat> {
+    let mut time = 1_770_000_000.0;
+    (0..count)
+        .map(|i| {
+            time += if i % 100 == 0 { 20.0 * 60.0 } else { 30.0 };
+            RawHeartbeat {
+                time: Some(time),
+                project: Some(PROJECTS[(i / 250) % PROJECTS.len()].to_string()),
+                ..Default::default()
+            }
+        })
+        .collect()
+}
+
+/// Spread sessions over days of roughly 1000 heartbeats each.
+fn synthetic_days(count: usize) -> Vec<DaySessions> {
+    let start = NaiveDate::from_ymd_opt(2026, 1, 1).unwrap();
+    synthetic_heartbeats(count)
+        .chunks(1000)
+        .zip(start.iter_days())
+        .map(|(chunk, date)| DaySessions {
+            date,
+            sessions: build_sessions(chunk, DEFAULT_MAX_GAP_SECONDS),
+        })
+        .collect()
+}
+
+fn bench_estimate_seconds(c: &mut Criterion) {
+    let mut group = c.benchmark_group("estimate_seconds");
+    for size in SIZES {
+        let times: Vec<i64> = synthetic_heartbeats(size)
+            .iter()
+            .filter_map(|hb| hb.time.map(|t| t as i64))
+            .collect();
+        group.throughput(Throughput::Elements(size as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(size), &times, |b, times| {
+            b.iter(|| estimate_seconds(black_box(times), DEFAULT_MAX_GAP_SECONDS))
+        });
+    }
+    group.finish();
+}
+
+fn bench_build_sessions(c: &mut Criterion) {
+    let mut group = c.benchmark_group("build_sessions");
+    group.sample_size(10);
+    for size in SIZES {
+        let heartbeats = synthetic_heartbeats(size);
+        group.throughput(Throughput::Elements(size as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(size), &heartbeats, |b, hbs| {
+            b.iter(|| build_sessions(black_box(hbs), DEFAULT_MAX_GAP_SECONDS))
+        });
+    }
+    group.finish();
+}
+
+fn bench_filter_sessions(c: &mut Criterion) {
+    let mut group = c.benchmark_group("filter_sessions");
+    for size in SIZES {
+        let days = synthetic_days(size);
+        group.throughput(Throughput::Elements(size as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(size), &days, |b, days| {
+            b.iter_batched(
+                || days.clone(),
+                |days| filter_sessions(days, Some("api,docs")),
+                criterion::BatchSize::LargeInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+fn bench_summarize(c: &mut Criterion) {
+    let mut group = c.benchmark_group("summarize");
+    for size in SIZES {
+        let days = synthetic_days(size);
+        group.throughput(Throughput::Elements(size as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(size), &days, |b, days| {
+            b.iter(|| summarize(black_box(days)))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_estimate_seconds,
+    bench_build_sessions,
+    bench_filter_sessions,
+    bench_summarize
+);
+criterion_main!(benches);
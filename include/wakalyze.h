@@ -0,0 +1,24 @@
+#ifndef WAKALYZE_H
+#define WAKALYZE_H
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/*
+ * Analyze a UTF-8 JSON request:
+ *   {"start": "YYYY-MM-DD", "end": "YYYY-MM-DD", "heartbeats": [...],
+ *    "filter": "...", "max_gap_seconds": 900, "label": "...", "timezone": "Asia/Tokyo"}
+ *
+ * Returns the report JSON (see `wakalyze schema`) or {"error": "..."}.
+ * Never returns NULL. Release the result with wakalyze_free_string().
+ */
+char *wakalyze_analyze_json(const char *input);
+
+void wakalyze_free_string(char *value);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* WAKALYZE_H */
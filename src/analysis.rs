@@ -0,0 +1,1343 @@
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveTime, Utc, Weekday};
+use chrono_tz::Tz;
+use regex::Regex;
+use serde::Serialize;
+
+use crate::browsing::{by_domain, domain_totals, is_browsing, DomainTotal};
+use crate::categories::category_allowed;
+use crate::conflicts::{resolve_conflicts, ConflictPolicy, ProjectConflict};
+use crate::core::{
+    build_day_sessions, day_of, exclude_sessions, filter_needles, filter_sessions,
+    filter_sessions_by_branch, filter_sessions_regex, group_heartbeats_by_local_date, is_weekend,
+    iter_dates, local_timestamp, parse_project_regex, project_matches, sanitize_heartbeats,
+    verify_sessions, DaySessions, EntryRules, Estimator, GapEstimator, RawHeartbeat, SanitizeStats,
+    Sanitizer, DEFAULT_MAX_GAP_SECONDS,
+};
+use crate::corrections::{apply_corrections, AppliedCorrection, Correction};
+use crate::entities::{entity_totals, EntityTotal};
+use crate::error::{Result, WakalyzeError};
+use crate::ignore::{glob_match, IgnoreRules};
+use crate::machines::{dedupe_overlaps, detect_overlaps, MachineOverlap};
+use crate::manual::ManualEntry;
+use crate::meetings::{apply_meetings, Meeting, MeetingMode, MeetingOverlap};
+use crate::projects::ProjectMap;
+use crate::punchcard::{punch_card, PunchCard};
+use crate::rounding::{round_days, Rounding, RoundingScope};
+use crate::stats::FocusFormula;
+use crate::summary::{summarize_period, PeriodSummary};
+use crate::zone;
+
+/// Anything that can provide the raw heartbeats recorded on a given date.
+pub trait HeartbeatSource {
+    fn fetch_heartbeats(&self, date: NaiveDate) -> Result<Vec<RawHeartbeat>>;
+
+    /// Heartbeats on `date` newer than `since`.
+    ///
+    /// The default fetches the whole day and drops older heartbeats; sources that can skip
+    /// them earlier should override it.
+    fn fetch_heartbeats_since(
+        &self,
+        date: NaiveDate,
+        since: Option<f64>,
+    ) -> Result<Vec<RawHeartbeat>> {
+        let mut heartbeats = self.fetch_heartbeats(date)?;
+        if let Some(since) = since {
+            heartbeats.retain(|hb| hb.time.is_some_and(|t| t > since));
+        }
+        Ok(heartbeats)
+    }
+
+    /// Hand each heartbeat on `date` to `sink`.
+    ///
+    /// The default fetches the whole day first; sources that parse heartbeats one by one
+    /// should override it so no day is held twice.
+    fn for_each_heartbeat(
+        &self,
+        date: NaiveDate,
+        sink: &mut dyn FnMut(RawHeartbeat),
+    ) -> Result<()> {
+        self.fetch_heartbeats(date)?.into_iter().for_each(sink);
+        Ok(())
+    }
+}
+
+impl<F> HeartbeatSource for F
+where
+    F: Fn(NaiveDate) -> Result<Vec<RawHeartbeat>>,
+{
+    fn fetch_heartbeats(&self, date: NaiveDate) -> Result<Vec<RawHeartbeat>> {
+        self(date)
+    }
+}
+
+/// Heartbeats already in memory, served by local date.
+#[derive(Debug, Clone, Default)]
+pub struct MemorySource {
+    by_date: BTreeMap<NaiveDate, Vec<RawHeartbeat>>,
+}
+
+impl MemorySource {
+    pub fn new(heartbeats: Vec<RawHeartbeat>) -> Self {
+        Self {
+            by_date: group_heartbeats_by_local_date(heartbeats),
+        }
+    }
+}
+
+impl HeartbeatSource for MemorySource {
+    fn fetch_heartbeats(&self, date: NaiveDate) -> Result<Vec<RawHeartbeat>> {
+        Ok(self.by_date.get(&date).cloned().unwrap_or_default())
+    }
+}
+
+/// One user's heartbeats from several servers, merged per date. Heartbeats with the time and
+/// project of one an earlier source returned are dropped, so the first source wins.
+pub struct MergedSource<S> {
+    sources: Vec<S>,
+}
+
+impl<S> MergedSource<S> {
+    pub fn new(sources: Vec<S>) -> Self {
+        Self { sources }
+    }
+}
+
+impl<S: HeartbeatSource> HeartbeatSource for MergedSource<S> {
+    fn fetch_heartbeats(&self, date: NaiveDate) -> Result<Vec<RawHeartbeat>> {
+        let key = |hb: &RawHeartbeat| (hb.time.map(f64::to_bits), hb.project.clone());
+        let mut seen = HashSet::new();
+        let mut merged = Vec::new();
+        for source in &self.sources {
+            let heartbeats: Vec<_> = source
+                .fetch_heartbeats(date)?
+                .into_iter()
+                .filter(|hb| !seen.contains(&key(hb)))
+                .collect();
+            seen.extend(heartbeats.iter().map(key));
+            merged.extend(heartbeats);
+        }
+        Ok(merged)
+    }
+}
+
+/// Result of running an [`Analysis`].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Report {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    pub max_gap_seconds: i64,
+    /// [`Estimator::name`] of the estimator the sessions were counted with.
+    pub estimator: String,
+    pub days: Vec<DaySessions>,
+    pub summary: PeriodSummary,
+    /// Windows where heartbeats from several machines overlap, before any de-duplication.
+    pub machine_overlaps: Vec<MachineOverlap>,
+    /// Heartbeats of the range (after `filter`) by weekday and hour.
+    pub punch_card: PunchCard,
+    /// Timestamps fixed or dropped by the [`Sanitizer`] over everything fetched.
+    pub sanitized: SanitizeStats,
+    /// Timestamps in the range where heartbeats disagreed on the project.
+    pub project_conflicts: Vec<ProjectConflict>,
+    /// Corrections that changed the sessions, as an audit trail.
+    pub corrections: Vec<AppliedCorrection>,
+    /// Whether Saturdays and Sundays were left out of the range.
+    pub skip_weekends: bool,
+    /// Time on `browsing` heartbeats per domain, kept out of the sessions.
+    pub domains: Vec<DomainTotal>,
+    /// Time per file with [`Analysis::entities`], else empty.
+    pub entities: Vec<EntityTotal>,
+    /// Session time per day during the meetings of [`Analysis::meetings`], before any
+    /// exclusion.
+    pub meeting_overlaps: Vec<MeetingOverlap>,
+}
+
+impl Report {
+    /// Every reported date of the range, with no sessions on the days nothing was worked.
+    pub fn all_days(&self) -> Vec<DaySessions> {
+        let mut worked = self.days.iter().peekable();
+        iter_dates(self.start, self.end)
+            .into_iter()
+            .filter(|date| !(self.skip_weekends && is_weekend(*date)))
+            .map(|date| match worked.next_if(|day| day.date == date) {
+                Some(day) => day.clone(),
+                None => DaySessions {
+                    date,
+                    sessions: Vec::new(),
+                },
+            })
+            .collect()
+    }
+}
+
+/// Fluent configuration of a session analysis over a date range.
+///
+/// ```no_run
+/// # use chrono::{DateTime, FixedOffset, NaiveDate, NaiveTime};
+/// # use wakalyze::analysis::Analysis;
+/// # use wakalyze::client::WakapiClient;
+/// let client = WakapiClient::new("https://wakapi.dev", "me", "Basic ...", 15.0);
+/// let report = Analysis::range(
+///     NaiveDate::from_ymd_opt(2026, 2, 1).unwrap(),
+///     NaiveDate::from_ymd_opt(2026, 2, 28).unwrap(),
+/// )
+/// .filter("myproject")
+/// .max_gap(10 * 60)
+/// .run(&client)?;
+/// # Ok::<(), wakalyze::error::WakalyzeError>(())
+/// ```
+pub struct Analysis {
+    start: NaiveDate,
+    end: NaiveDate,
+    filter: Option<String>,
+    exclude: Option<String>,
+    filter_regex: Option<Regex>,
+    branch: Option<String>,
+    entities: bool,
+    max_gap: i64,
+    estimator: Box<dyn Estimator>,
+    dedupe_machines: bool,
+    focus: FocusFormula,
+    sanitizer: Sanitizer,
+    conflict_policy: ConflictPolicy,
+    day_start: NaiveTime,
+    fetch_offset: Option<FixedOffset>,
+    verify: bool,
+    manual: Vec<ManualEntry>,
+    corrections: Vec<Correction>,
+    rules: EntryRules,
+    skip_weekends: bool,
+    categories: Vec<String>,
+    meetings: Vec<Meeting>,
+    meeting_mode: MeetingMode,
+    rounding: Option<(Rounding, RoundingScope)>,
+    timezone: Option<Tz>,
+}
+
+impl Analysis {
+    pub fn range(start: NaiveDate, end: NaiveDate) -> Self {
+        Self {
+            start,
+            end,
+            filter: None,
+            exclude: None,
+            filter_regex: None,
+            branch: None,
+            entities: false,
+            max_gap: DEFAULT_MAX_GAP_SECONDS,
+            estimator: Box::new(GapEstimator),
+            dedupe_machines: false,
+            focus: FocusFormula::default(),
+            sanitizer: Sanitizer::at(Utc::now().timestamp()),
+            conflict_policy: ConflictPolicy::default(),
+            day_start: NaiveTime::MIN,
+            fetch_offset: None,
+            verify: false,
+            manual: Vec::new(),
+            corrections: Vec::new(),
+            rules: EntryRules::default(),
+            skip_weekends: false,
+            categories: Vec::new(),
+            meetings: Vec::new(),
+            meeting_mode: MeetingMode::default(),
+            rounding: None,
+            timezone: None,
+        }
+    }
+
+    /// Keep only sessions whose project matches (comma-separated substrings, OR).
+    pub fn filter(mut self, filter: impl Into<String>) -> Self {
+        self.filter = Some(filter.into());
+        self
+    }
+
+    /// Drop sessions whose project matches (comma-separated substrings), after `filter`.
+    pub fn exclude(mut self, exclude: impl Into<String>) -> Self {
+        self.exclude = Some(exclude.into());
+        self
+    }
+
+    /// Keep only sessions whose project matches a regular expression, besides `filter`.
+    pub fn filter_regex(mut self, pattern: &str) -> Result<Self> {
+        self.filter_regex = Some(parse_project_regex(pattern)?);
+        Ok(self)
+    }
+
+    /// Keep only sessions on a branch matching a glob, e.g. `feature/*`.
+    pub fn branch(mut self, pattern: impl Into<String>) -> Self {
+        self.branch = Some(pattern.into());
+        self
+    }
+
+    /// Total the time per file (entity) in [`Report::entities`].
+    pub fn entities(mut self, enabled: bool) -> Self {
+        self.entities = enabled;
+        self
+    }
+
+    /// Max gap in seconds between heartbeats to treat as continuous work.
+    pub fn max_gap(mut self, seconds: i64) -> Self {
+        self.max_gap = seconds;
+        self
+    }
+
+    pub fn estimator(mut self, estimator: impl Estimator + 'static) -> Self {
+        self.estimator = Box::new(estimator);
+        self
+    }
+
+    /// Count overlapping wall-clock time from several machines only once, keeping the
+    /// machine with the most heartbeats in each overlap.
+    pub fn dedupe_machines(mut self, enabled: bool) -> Self {
+        self.dedupe_machines = enabled;
+        self
+    }
+
+    /// Formula of the per-day focus score in the summary.
+    pub fn focus(mut self, formula: FocusFormula) -> Self {
+        self.focus = formula;
+        self
+    }
+
+    /// Bounds of plausible heartbeat timestamps; by default 2000-01-01 to a day from now.
+    pub fn sanitizer(mut self, sanitizer: Sanitizer) -> Self {
+        self.sanitizer = sanitizer;
+        self
+    }
+
+    /// Which project wins when heartbeats with the same timestamp disagree.
+    pub fn conflict_policy(mut self, policy: ConflictPolicy) -> Self {
+        self.conflict_policy = policy;
+        self
+    }
+
+    /// Local time at which a day starts; heartbeats before it count towards the previous day
+    /// (e.g. 04:00 for work past midnight). Midnight by default.
+    pub fn day_boundary(mut self, day_start: NaiveTime) -> Self {
+        self.day_start = day_start;
+        self
+    }
+
+    /// Time zone of the day boundaries and session times; by default the one passed to
+    /// [`zone::select_timezone`], else the system's.
+    pub fn timezone(mut self, zone: Tz) -> Self {
+        self.timezone = Some(zone);
+        self
+    }
+
+    /// UTC offset in which the source buckets its `date`s, if known.
+    pub fn fetch_offset(mut self, offset: FixedOffset) -> Self {
+        self.fetch_offset = Some(offset);
+        self
+    }
+
+    /// Fail with [`WakalyzeError::InvariantViolated`] if the built sessions overlap, are out
+    /// of order, or count more time than they span (see [`verify_sessions`]).
+    pub fn verify(mut self, enabled: bool) -> Self {
+        self.verify = enabled;
+        self
+    }
+
+    /// Time entered by hand, merged into the days as `manual` sessions. Entries outside
+    /// the range are ignored.
+    pub fn manual_entries(mut self, entries: Vec<ManualEntry>) -> Self {
+        self.manual = entries;
+        self
+    }
+
+    /// Fixes applied in order to the sessions of the range, manual entries included.
+    pub fn corrections(mut self, corrections: Vec<Correction>) -> Self {
+        self.corrections = corrections;
+        self
+    }
+
+    /// Projects and entities whose heartbeats are left out of the sessions.
+    pub fn ignore(mut self, rules: IgnoreRules) -> Self {
+        self.rules.ignore = rules;
+        self
+    }
+
+    /// Renames of projects (exact aliases and glob rules), applied as heartbeats are read so
+    /// sessions, filters, and totals all see the new names.
+    pub fn project_map(mut self, projects: ProjectMap) -> Self {
+        self.rules.projects = projects;
+        self
+    }
+
+    /// Leave Saturdays and Sundays out of the fetch, the days, and the weekday averages.
+    pub fn skip_weekends(mut self, enabled: bool) -> Self {
+        self.skip_weekends = enabled;
+        self
+    }
+
+    /// Count only heartbeats of these categories (any case; no category counts as `coding`).
+    /// Every category counts when empty.
+    pub fn categories(mut self, categories: Vec<String>) -> Self {
+        self.categories = categories;
+        self
+    }
+
+    /// Calendar meetings whose overlap with the sessions is reported, or with
+    /// [`MeetingMode::Exclude`] taken off them.
+    pub fn meetings(mut self, meetings: Vec<Meeting>, mode: MeetingMode) -> Self {
+        self.meetings = meetings;
+        self.meeting_mode = mode;
+        self
+    }
+
+    /// Round every session, or each day's total, to a billing increment before the totals
+    /// are summed.
+    pub fn rounding(mut self, rounding: Rounding, scope: RoundingScope) -> Self {
+        self.rounding = Some((rounding, scope));
+        self
+    }
+
+    /// Dates that `run` will request from the source.
+    ///
+    /// With a [`Self::fetch_offset`], exactly the source dates overlapping the range; otherwise
+    /// the range expanded by ±1 day to capture heartbeats near timezone boundaries. When
+    /// skipping weekends, only the dates overlapping weekdays, and without an offset no
+    /// weekend dates at all.
+    pub fn fetch_dates(&self) -> Vec<NaiveDate> {
+        zone::scoped(self.timezone, || self.fetch_dates_in_zone())
+    }
+
+    fn fetch_dates_in_zone(&self) -> Vec<NaiveDate> {
+        if self.skip_weekends {
+            let mut dates = BTreeSet::new();
+            for day in iter_dates(self.start, self.end) {
+                if is_weekend(day) {
+                    continue;
+                }
+                match self
+                    .fetch_offset
+                    .and_then(|offset| self.source_dates(day, day, offset))
+                {
+                    Some(source) => dates.extend(source),
+                    None => dates.extend(
+                        [day.pred_opt(), Some(day), day.succ_opt()]
+                            .into_iter()
+                            .flatten()
+                            .filter(|date| !is_weekend(*date)),
+                    ),
+                }
+            }
+            return dates.into_iter().collect();
+        }
+        if let Some(dates) = self
+            .fetch_offset
+            .and_then(|offset| self.source_dates(self.start, self.end, offset))
+        {
+            return dates;
+        }
+        let fetch_start = self.start.pred_opt().unwrap_or(self.start);
+        let fetch_end = self.end.succ_opt().unwrap_or(self.end);
+        iter_dates(fetch_start, fetch_end)
+    }
+
+    /// Dates, in `offset`, of the first and last instant of `start`..=`end`.
+    fn source_dates(
+        &self,
+        start: NaiveDate,
+        end: NaiveDate,
+        offset: FixedOffset,
+    ) -> Option<Vec<NaiveDate>> {
+        let first = local_timestamp(start, self.day_start)?;
+        let last = local_timestamp(end.succ_opt()?, self.day_start)? - 1;
+        let date_in_offset = |timestamp| {
+            DateTime::from_timestamp(timestamp, 0).map(|dt| dt.with_timezone(&offset).date_naive())
+        };
+        Some(iter_dates(date_in_offset(first)?, date_in_offset(last)?))
+    }
+
+    /// Report of sessions the server built rather than of heartbeats, named `estimator`.
+    /// The range, ignore rules, project renames, manual entries, corrections, filters, and
+    /// meetings apply as in [`Self::run`]; what needs the heartbeats stays empty.
+    pub fn run_sessions(&self, days: Vec<DaySessions>, estimator: &str) -> Result<Report> {
+        zone::scoped(self.timezone, || self.run_sessions_in_zone(days, estimator))
+    }
+
+    fn run_sessions_in_zone(&self, days: Vec<DaySessions>, estimator: &str) -> Result<Report> {
+        let mut days: Vec<DaySessions> = days
+            .into_iter()
+            .filter(|day| self.includes(day.date))
+            .map(|mut day| {
+                day.sessions
+                    .retain(|session| !self.rules.ignore.ignores(session.project.as_deref(), None));
+                for session in &mut day.sessions {
+                    if let Some(project) = &session.project {
+                        session.project = Some(self.rules.projects.resolve(project).into());
+                    }
+                }
+                day
+            })
+            .filter(|day| !day.sessions.is_empty())
+            .collect();
+        self.merge_manual(&mut days);
+        let corrections = apply_corrections(&mut days, &self.corrections);
+        let mut days = self.filtered(days);
+        let meeting_overlaps = apply_meetings(&mut days, &self.meetings, self.meeting_mode);
+        if let Some((rounding, scope)) = &self.rounding {
+            round_days(&mut days, rounding, *scope);
+        }
+        let summary = self.summary(&days);
+        Ok(Report {
+            start: self.start,
+            end: self.end,
+            max_gap_seconds: self.max_gap,
+            estimator: estimator.to_string(),
+            days,
+            summary,
+            machine_overlaps: Vec::new(),
+            punch_card: PunchCard::default(),
+            sanitized: SanitizeStats::default(),
+            project_conflicts: Vec::new(),
+            corrections,
+            skip_weekends: self.skip_weekends,
+            domains: Vec::new(),
+            entities: Vec::new(),
+            meeting_overlaps,
+        })
+    }
+
+    /// `days` with the project and branch filters applied.
+    fn filtered(&self, days: Vec<DaySessions>) -> Vec<DaySessions> {
+        let days = filter_sessions(days, self.filter.as_deref());
+        let days = filter_sessions_regex(days, self.filter_regex.as_ref());
+        let days = filter_sessions_by_branch(days, self.branch.as_deref());
+        exclude_sessions(days, self.exclude.as_deref())
+    }
+
+    fn summary(&self, days: &[DaySessions]) -> PeriodSummary {
+        let mut summary = summarize_period(days, self.start, self.end, &self.focus);
+        if self.skip_weekends {
+            summary
+                .weekdays
+                .retain(|average| !matches!(average.weekday, Weekday::Sat | Weekday::Sun));
+        }
+        summary
+    }
+
+    /// Whether a date is reported: within the range, and not a skipped weekend.
+    fn includes(&self, date: NaiveDate) -> bool {
+        date >= self.start && date <= self.end && !(self.skip_weekends && is_weekend(date))
+    }
+
+    /// Whether a timestamp falls on a reported day.
+    fn in_range(&self, timestamp: i64) -> bool {
+        day_of(timestamp, self.day_start).is_some_and(|date| self.includes(date))
+    }
+
+    /// Time per domain of the `browsing` heartbeats on the reported days, skipping those the
+    /// ignore rules match.
+    fn domain_totals(&self, mut browsing: Vec<RawHeartbeat>) -> Vec<DomainTotal> {
+        browsing.retain(|hb| {
+            !self
+                .rules
+                .ignore
+                .ignores(hb.project.as_deref(), hb.entity.as_deref())
+        });
+        let mut days = build_day_sessions(
+            &by_domain(browsing),
+            self.max_gap,
+            self.estimator.as_ref(),
+            self.day_start,
+            &EntryRules::default(),
+        );
+        days.retain(|day| self.includes(day.date));
+        domain_totals(&days)
+    }
+
+    /// Add the manual entries of the range to their days, keeping days and sessions in order.
+    fn merge_manual(&self, days: &mut Vec<DaySessions>) {
+        let mut added = false;
+        for entry in &self.manual {
+            if !self.includes(entry.date) {
+                continue;
+            }
+            let Some(session) = entry.to_session() else {
+                continue;
+            };
+            match days.iter_mut().find(|day| day.date == entry.date) {
+                Some(day) => day.sessions.push(session),
+                None => days.push(DaySessions {
+                    date: entry.date,
+                    sessions: vec![session],
+                }),
+            }
+            added = true;
+        }
+        if added {
+            days.sort_by_key(|day| day.date);
+            for day in days.iter_mut() {
+                day.sessions.sort_by_key(|session| session.start);
+            }
+        }
+    }
+
+    /// Every heartbeat fetched for the range, sanitized, sorted by time, and deduplicated
+    /// across the overlapping day fetches. Includes the neighbouring days from
+    /// [`Self::fetch_dates`].
+    fn fetch_all<S: HeartbeatSource + ?Sized>(
+        &self,
+        source: &S,
+    ) -> Result<(Vec<RawHeartbeat>, SanitizeStats)> {
+        let mut all_heartbeats: Vec<RawHeartbeat> = Vec::new();
+        for date in self.fetch_dates() {
+            source.for_each_heartbeat(date, &mut |hb| all_heartbeats.push(hb))?;
+        }
+        let fetched = all_heartbeats.len();
+
+        // Deduplicate heartbeats that may appear in adjacent day fetches. Copies of one second
+        // need not be adjacent in time order, so sort by the whole key first, then restore the
+        // fetched order within each second for `--conflicts first`.
+        let by_time = |a: &RawHeartbeat, b: &RawHeartbeat| {
+            a.time
+                .partial_cmp(&b.time)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        };
+        let mut indexed: Vec<(usize, RawHeartbeat)> =
+            all_heartbeats.into_iter().enumerate().collect();
+        indexed.sort_by(|(i, a), (j, b)| {
+            by_time(a, b)
+                .then_with(|| (&a.project, &a.machine).cmp(&(&b.project, &b.machine)))
+                .then(i.cmp(j))
+        });
+        indexed.dedup_by(|(_, a), (_, b)| {
+            a.time == b.time && a.project == b.project && a.machine == b.machine
+        });
+        indexed.sort_by(|(i, a), (j, b)| by_time(a, b).then(i.cmp(j)));
+        let mut all_heartbeats: Vec<RawHeartbeat> = indexed.into_iter().map(|(_, hb)| hb).collect();
+
+        let duplicates = fetched - all_heartbeats.len();
+
+        let mut sanitized = sanitize_heartbeats(&mut all_heartbeats, &self.sanitizer);
+        sanitized.fetched = fetched;
+        sanitized.duplicates = duplicates;
+        if sanitized.normalized > 0 {
+            all_heartbeats.sort_by(by_time);
+        }
+        Ok((all_heartbeats, sanitized))
+    }
+
+    /// The raw heartbeats recorded in the range (by local date), sorted by time.
+    pub fn heartbeats<S: HeartbeatSource + ?Sized>(&self, source: &S) -> Result<Vec<RawHeartbeat>> {
+        zone::scoped(self.timezone, || {
+            let (mut heartbeats, _) = self.fetch_all(source)?;
+            heartbeats.retain(|hb| hb.time.is_some_and(|t| self.in_range(t as i64)));
+            Ok(heartbeats)
+        })
+    }
+
+    pub fn run<S: HeartbeatSource + ?Sized>(&self, source: &S) -> Result<Report> {
+        zone::scoped(self.timezone, || self.run_in_zone(source))
+    }
+
+    fn run_in_zone<S: HeartbeatSource + ?Sized>(&self, source: &S) -> Result<Report> {
+        if self.max_gap <= 0 {
+            return Err(WakalyzeError::InvalidMaxGap);
+        }
+
+        let (mut all_heartbeats, sanitized) = self.fetch_all(source)?;
+        all_heartbeats.retain(|hb| category_allowed(hb, &self.categories));
+        let mut project_conflicts = resolve_conflicts(&mut all_heartbeats, self.conflict_policy);
+        project_conflicts.retain(|conflict| self.in_range(conflict.time));
+        let mut machine_overlaps = detect_overlaps(&all_heartbeats, self.max_gap);
+        if self.dedupe_machines {
+            dedupe_overlaps(&mut all_heartbeats, &machine_overlaps);
+        }
+        machine_overlaps.retain(|overlap| self.in_range(overlap.start));
+        let (browsing, all_heartbeats): (Vec<_>, Vec<_>) =
+            all_heartbeats.into_iter().partition(is_browsing);
+        let domains = self.domain_totals(browsing);
+
+        let needles = filter_needles(self.filter.as_deref());
+        let excluded = filter_needles(self.exclude.as_deref());
+        // Sessions carry the renamed projects, so the filters match those here too.
+        let selected = |hb: &&RawHeartbeat| {
+            let project = hb
+                .project
+                .as_deref()
+                .map(|p| self.rules.projects.resolve(p));
+            hb.time.is_some_and(|t| self.in_range(t as i64))
+                && project_matches(project, &needles)
+                && (excluded.is_empty() || !project_matches(project, &excluded))
+                && self
+                    .filter_regex
+                    .as_ref()
+                    .is_none_or(|regex| regex.is_match(project.unwrap_or("")))
+                && self.branch.as_deref().is_none_or(|pattern| {
+                    hb.branch
+                        .as_deref()
+                        .is_some_and(|branch| glob_match(pattern, branch))
+                })
+        };
+        let punch_card = punch_card(all_heartbeats.iter().filter(selected));
+        let entities = if self.entities {
+            entity_totals(
+                all_heartbeats.iter().filter(selected).filter(|hb| {
+                    !self
+                        .rules
+                        .ignore
+                        .ignores(hb.project.as_deref(), hb.entity.as_deref())
+                }),
+                self.max_gap,
+                &self.rules.projects,
+            )
+        } else {
+            Vec::new()
+        };
+
+        // Sessions are built across the whole fetch so work past midnight stays one session,
+        // then split into days and restricted to the requested range.
+        let mut days = build_day_sessions(
+            &all_heartbeats,
+            self.max_gap,
+            self.estimator.as_ref(),
+            self.day_start,
+            &self.rules,
+        );
+        days.retain(|day| self.includes(day.date));
+        if self.verify {
+            verify_sessions(days.iter().flat_map(|day| &day.sessions))?;
+        }
+        self.merge_manual(&mut days);
+        let corrections = apply_corrections(&mut days, &self.corrections);
+        let mut days = self.filtered(days);
+        let meeting_overlaps = apply_meetings(&mut days, &self.meetings, self.meeting_mode);
+        if let Some((rounding, scope)) = &self.rounding {
+            round_days(&mut days, rounding, *scope);
+        }
+        let summary = self.summary(&days);
+        Ok(Report {
+            start: self.start,
+            end: self.end,
+            max_gap_seconds: self.max_gap,
+            estimator: self.estimator.name().to_string(),
+            days,
+            summary,
+            machine_overlaps,
+            punch_card,
+            sanitized,
+            project_conflicts,
+            corrections,
+            skip_weekends: self.skip_weekends,
+            domains,
+            entities,
+            meeting_overlaps,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Session;
+    use chrono::{Local, Offset, TimeZone};
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 2, day).unwrap()
+    }
+
+    fn hb_at(day: u32, hour: u32, min: u32, project: &str) -> RawHeartbeat {
+        let ts = Local
+            .with_ymd_and_hms(2026, 2, day, hour, min, 0)
+            .unwrap()
+            .timestamp();
+        RawHeartbeat {
+            time: Some(ts as f64),
+            project: Some(project.to_string()),
+            ..Default::default()
+        }
+    }
+
+    /// Serves the same heartbeats for every requested date, like overlapping day fetches.
+    fn repeat_source(
+        heartbeats: Vec<RawHeartbeat>,
+    ) -> impl Fn(NaiveDate) -> Result<Vec<RawHeartbeat>> {
+        move |_| Ok(heartbeats.clone())
+    }
+
+    #[test]
+    fn merged_source_drops_heartbeats_of_earlier_sources() {
+        let work = MemorySource::new(vec![hb_at(2, 9, 0, "api"), hb_at(2, 9, 0, "api")]);
+        let oss = MemorySource::new(vec![
+            hb_at(2, 9, 0, "api"),
+            hb_at(2, 9, 0, "lib"),
+            hb_at(2, 21, 0, "lib"),
+        ]);
+        let merged = MergedSource::new(vec![work, oss])
+            .fetch_heartbeats(date(2))
+            .unwrap();
+        let projects: Vec<_> = merged.iter().map(|hb| hb.project.as_deref()).collect();
+        assert_eq!(
+            projects,
+            vec![Some("api"), Some("api"), Some("lib"), Some("lib")]
+        );
+    }
+
+    #[test]
+    fn fetch_dates_expanded_by_one_day() {
+        let dates = Analysis::range(date(2), date(3)).fetch_dates();
+        assert_eq!(dates, vec![date(1), date(2), date(3), date(4)]);
+    }
+
+    #[test]
+    fn skip_weekends_leaves_out_saturday_and_sunday() {
+        let analysis = Analysis::range(date(6), date(9)).skip_weekends(true);
+        assert_eq!(
+            analysis.fetch_dates(),
+            vec![date(5), date(6), date(9), date(10)]
+        );
+        let source = MemorySource::new(vec![
+            hb_at(6, 9, 0, "foo"),
+            hb_at(6, 9, 10, "foo"),
+            hb_at(7, 9, 0, "foo"),
+            hb_at(7, 9, 10, "foo"),
+            hb_at(9, 9, 0, "foo"),
+            hb_at(9, 9, 5, "foo"),
+        ]);
+        let report = analysis.run(&source).unwrap();
+        let dates: Vec<_> = report.days.iter().map(|day| day.date).collect();
+        assert_eq!(dates, vec![date(6), date(9)]);
+        assert_eq!(report.summary.seconds, 900);
+        let weekdays: Vec<_> = report.summary.weekdays.iter().map(|w| w.weekday).collect();
+        assert_eq!(weekdays, vec![Weekday::Mon, Weekday::Fri]);
+    }
+
+    #[test]
+    fn browsing_counts_per_domain_not_for_the_project() {
+        let browse = |min: u32, url: &str| RawHeartbeat {
+            entity: Some(url.into()),
+            category: Some("browsing".into()),
+            ..hb_at(2, 10, min, "api")
+        };
+        let source = MemorySource::new(vec![
+            hb_at(2, 9, 0, "api"),
+            hb_at(2, 9, 10, "api"),
+            browse(0, "https://docs.rs/serde"),
+            browse(5, "https://docs.rs/chrono"),
+            browse(8, "https://stackoverflow.com/q/1"),
+            browse(9, "https://stackoverflow.com/q/2"),
+        ]);
+        let report = Analysis::range(date(2), date(2)).run(&source).unwrap();
+        assert_eq!(report.summary.seconds, 600);
+        let domains: Vec<_> = report
+            .domains
+            .iter()
+            .map(|d| (d.domain.as_deref(), d.seconds))
+            .collect();
+        assert_eq!(
+            domains,
+            vec![(Some("docs.rs"), 300), (Some("stackoverflow.com"), 60)]
+        );
+    }
+
+    #[test]
+    fn categories_split_and_restrict_the_time() {
+        let of = |hour: u32, min: u32, category: &str| RawHeartbeat {
+            category: Some(category.into()),
+            ..hb_at(2, hour, min, "api")
+        };
+        let source = MemorySource::new(vec![
+            hb_at(2, 9, 0, "api"),
+            hb_at(2, 9, 10, "api"),
+            of(11, 0, "Debugging"),
+            of(11, 5, "debugging"),
+            of(14, 0, "meeting"),
+            of(14, 15, "meeting"),
+        ]);
+        let report = Analysis::range(date(2), date(2)).run(&source).unwrap();
+        let categories: Vec<_> = report
+            .summary
+            .categories
+            .iter()
+            .map(|c| (c.name.as_deref(), c.seconds))
+            .collect();
+        assert_eq!(
+            categories,
+            vec![
+                (Some("meeting"), 900),
+                (Some("coding"), 600),
+                (Some("debugging"), 300)
+            ]
+        );
+
+        let report = Analysis::range(date(2), date(2))
+            .categories(vec!["coding".into(), "debugging".into()])
+            .run(&source)
+            .unwrap();
+        assert_eq!(report.summary.seconds, 900);
+    }
+
+    #[test]
+    fn rounding_applies_before_the_totals() {
+        use crate::rounding::RoundingMode;
+
+        let source = MemorySource::new(vec![hb_at(6, 9, 0, "foo"), hb_at(6, 9, 10, "foo")]);
+        let report = Analysis::range(date(6), date(6))
+            .rounding(
+                Rounding::new(15 * 60, RoundingMode::Up),
+                RoundingScope::Session,
+            )
+            .run(&source)
+            .unwrap();
+        assert_eq!(report.days[0].sessions[0].seconds, 900);
+        assert_eq!(report.summary.seconds, 900);
+        assert_eq!(report.summary.projects[0].seconds, 900);
+    }
+
+    #[test]
+    fn all_days_fill_in_days_without_sessions() {
+        let source = MemorySource::new(vec![hb_at(6, 9, 0, "foo"), hb_at(6, 9, 10, "foo")]);
+        let report = Analysis::range(date(5), date(9)).run(&source).unwrap();
+        let all = report.all_days();
+        let dates: Vec<_> = all.iter().map(|day| day.date).collect();
+        assert_eq!(dates, iter_dates(date(5), date(9)));
+        assert!(all[0].sessions.is_empty());
+        assert_eq!(all[1].sessions.len(), 1);
+
+        let report = Analysis::range(date(5), date(9))
+            .skip_weekends(true)
+            .run(&source)
+            .unwrap();
+        let dates: Vec<_> = report.all_days().iter().map(|day| day.date).collect();
+        assert_eq!(dates, vec![date(5), date(6), date(9)]);
+    }
+
+    #[test]
+    fn run_dedups_and_restricts_to_range() {
+        let source = repeat_source(vec![
+            hb_at(1, 9, 0, "foo"),
+            hb_at(1, 9, 10, "foo"),
+            hb_at(5, 9, 0, "foo"),
+        ]);
+        let report = Analysis::range(date(1), date(2)).run(&source).unwrap();
+        assert_eq!(report.days.len(), 1);
+        assert_eq!(report.days[0].date, date(1));
+        assert_eq!(report.days[0].sessions.len(), 1);
+        assert_eq!(report.days[0].sessions[0].seconds, 600);
+        assert_eq!(report.summary.seconds, 600);
+    }
+
+    #[test]
+    fn heartbeats_dedup_interleaved_copies_of_one_second() {
+        // Each fetched date returns both, so the copies alternate foo, bar, foo, bar, ...
+        let source = repeat_source(vec![hb_at(1, 9, 0, "foo"), hb_at(1, 9, 0, "bar")]);
+        let heartbeats = Analysis::range(date(1), date(1))
+            .heartbeats(&source)
+            .unwrap();
+        let projects: Vec<_> = heartbeats.iter().map(|hb| hb.project.as_deref()).collect();
+        assert_eq!(projects, vec![Some("foo"), Some("bar")]);
+    }
+
+    #[test]
+    fn run_applies_filter() {
+        let source = repeat_source(vec![hb_at(1, 9, 0, "foo"), hb_at(1, 10, 0, "bar")]);
+        let report = Analysis::range(date(1), date(1))
+            .filter("bar")
+            .run(&source)
+            .unwrap();
+        assert_eq!(report.days[0].sessions.len(), 1);
+        assert_eq!(report.days[0].sessions[0].project.as_deref(), Some("bar"));
+        assert_eq!(report.punch_card.max(), 1);
+        assert_eq!(report.punch_card.counts.iter().flatten().sum::<u32>(), 1);
+    }
+
+    #[test]
+    fn run_applies_exclude_after_filter() {
+        let source = repeat_source(vec![
+            hb_at(1, 9, 0, "client-api"),
+            hb_at(1, 10, 0, "client-scratch"),
+            hb_at(1, 11, 0, "home"),
+        ]);
+        let report = Analysis::range(date(1), date(1))
+            .filter("client")
+            .exclude("scratch")
+            .run(&source)
+            .unwrap();
+        assert_eq!(report.days[0].sessions.len(), 1);
+        assert_eq!(
+            report.days[0].sessions[0].project.as_deref(),
+            Some("client-api")
+        );
+        assert_eq!(report.punch_card.counts.iter().flatten().sum::<u32>(), 1);
+    }
+
+    #[test]
+    fn run_applies_filter_regex() {
+        let source = repeat_source(vec![
+            hb_at(1, 9, 0, "client-acme"),
+            hb_at(1, 10, 0, "client-acme2"),
+        ]);
+        let report = Analysis::range(date(1), date(1))
+            .filter_regex("^client-acme$")
+            .unwrap()
+            .run(&source)
+            .unwrap();
+        assert_eq!(report.days[0].sessions.len(), 1);
+        assert_eq!(report.punch_card.counts.iter().flatten().sum::<u32>(), 1);
+    }
+
+    #[test]
+    fn run_applies_branch_filter() {
+        let on = |hour: u32, branch: &str| RawHeartbeat {
+            branch: Some(branch.to_string()),
+            ..hb_at(1, hour, 0, "api")
+        };
+        let source = repeat_source(vec![on(9, "feature/login"), on(10, "main")]);
+        let report = Analysis::range(date(1), date(1))
+            .branch("feature/*")
+            .run(&source)
+            .unwrap();
+        assert_eq!(report.days[0].sessions.len(), 1);
+        assert_eq!(
+            report.days[0].sessions[0].branch.as_deref(),
+            Some("feature/login")
+        );
+        assert_eq!(report.punch_card.counts.iter().flatten().sum::<u32>(), 1);
+    }
+
+    #[test]
+    fn run_uses_max_gap_and_estimator() {
+        let source = repeat_source(vec![hb_at(1, 9, 0, "foo"), hb_at(1, 9, 10, "foo")]);
+        let report = Analysis::range(date(1), date(1))
+            .max_gap(5 * 60)
+            .run(&source)
+            .unwrap();
+        assert_eq!(report.days[0].sessions.len(), 2);
+        assert_eq!(report.max_gap_seconds, 300);
+
+        let report = Analysis::range(date(1), date(1))
+            .estimator(|times: &[i64], _: i64| times.len() as i64)
+            .run(&source)
+            .unwrap();
+        assert_eq!(report.summary.seconds, 2);
+    }
+
+    #[test]
+    fn memory_source_serves_by_date() {
+        let source = MemorySource::new(vec![hb_at(1, 9, 0, "foo"), hb_at(2, 9, 0, "bar")]);
+        assert_eq!(source.fetch_heartbeats(date(1)).unwrap().len(), 1);
+        assert!(source.fetch_heartbeats(date(3)).unwrap().is_empty());
+
+        let report = Analysis::range(date(1), date(2)).run(&source).unwrap();
+        assert_eq!(report.days.len(), 2);
+    }
+
+    #[test]
+    fn run_reports_and_dedupes_machine_overlaps() {
+        let on = |mut hb: RawHeartbeat, machine: &str| {
+            hb.machine = Some(machine.to_string());
+            hb
+        };
+        let source = repeat_source(vec![
+            on(hb_at(1, 9, 0, "foo"), "desk"),
+            on(hb_at(1, 9, 3, "foo"), "desk"),
+            on(hb_at(1, 9, 5, "foo"), "desk"),
+            on(hb_at(1, 9, 7, "foo"), "desk"),
+            on(hb_at(1, 9, 10, "foo"), "desk"),
+            on(hb_at(1, 9, 2, "bar"), "laptop"),
+            on(hb_at(1, 9, 8, "bar"), "laptop"),
+        ]);
+        let report = Analysis::range(date(1), date(1)).run(&source).unwrap();
+        assert_eq!(report.machine_overlaps.len(), 1);
+        assert_eq!(report.machine_overlaps[0].seconds(), 360);
+        assert_eq!(report.days[0].sessions.len(), 5);
+
+        let deduped = Analysis::range(date(1), date(1))
+            .dedupe_machines(true)
+            .run(&source)
+            .unwrap();
+        assert_eq!(deduped.machine_overlaps, report.machine_overlaps);
+        assert_eq!(deduped.days[0].sessions.len(), 1);
+        assert_eq!(deduped.summary.seconds, 600);
+    }
+
+    #[test]
+    fn heartbeats_restricted_to_range_and_sorted() {
+        let source = repeat_source(vec![
+            hb_at(2, 10, 0, "foo"),
+            hb_at(2, 9, 0, "foo"),
+            hb_at(3, 9, 0, "foo"),
+        ]);
+        let heartbeats = Analysis::range(date(2), date(2))
+            .heartbeats(&source)
+            .unwrap();
+        assert_eq!(
+            heartbeats,
+            vec![hb_at(2, 9, 0, "foo"), hb_at(2, 10, 0, "foo")]
+        );
+    }
+
+    #[test]
+    fn run_normalizes_millisecond_times_and_drops_implausible_ones() {
+        let mut millis = hb_at(1, 9, 10, "foo");
+        millis.time = millis.time.map(|t| t * 1000.0);
+        let source = repeat_source(vec![
+            hb_at(1, 9, 0, "foo"),
+            millis,
+            RawHeartbeat {
+                time: Some(42.0),
+                project: Some("foo".into()),
+                ..Default::default()
+            },
+        ]);
+        let report = Analysis::range(date(1), date(1)).run(&source).unwrap();
+        assert_eq!(report.days[0].sessions[0].seconds, 600);
+        assert_eq!(
+            report.sanitized,
+            SanitizeStats {
+                normalized: 1,
+                dropped: 1,
+                too_early: 1,
+                // The three heartbeats come back for each of the three dates fetched.
+                fetched: 9,
+                duplicates: 6,
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn run_resolves_project_conflicts() {
+        let source = repeat_source(vec![
+            hb_at(1, 9, 0, "api"),
+            hb_at(1, 9, 5, "web"),
+            hb_at(1, 9, 5, "api"),
+            hb_at(1, 9, 10, "api"),
+        ]);
+        let report = Analysis::range(date(1), date(1)).run(&source).unwrap();
+        assert_eq!(report.days[0].sessions.len(), 1);
+        assert_eq!(report.project_conflicts.len(), 1);
+        assert_eq!(report.project_conflicts[0].chosen.as_deref(), Some("api"));
+
+        let report = Analysis::range(date(1), date(1))
+            .conflict_policy(ConflictPolicy::First)
+            .run(&source)
+            .unwrap();
+        assert_eq!(report.days[0].sessions.len(), 3);
+    }
+
+    #[test]
+    fn fetch_dates_follow_the_source_offset() {
+        let local = Local
+            .with_ymd_and_hms(2026, 2, 2, 12, 0, 0)
+            .unwrap()
+            .offset()
+            .fix();
+        let dates = Analysis::range(date(2), date(3))
+            .fetch_offset(local)
+            .fetch_dates();
+        assert_eq!(dates, vec![date(2), date(3)]);
+    }
+
+    #[test]
+    fn run_with_day_boundary_counts_late_night_on_the_previous_day() {
+        let source = repeat_source(vec![
+            hb_at(2, 1, 0, "foo"),
+            hb_at(2, 1, 10, "foo"),
+            hb_at(2, 9, 0, "foo"),
+        ]);
+        let report = Analysis::range(date(1), date(2))
+            .day_boundary(NaiveTime::from_hms_opt(4, 0, 0).unwrap())
+            .run(&source)
+            .unwrap();
+        let dates: Vec<NaiveDate> = report.days.iter().map(|d| d.date).collect();
+        assert_eq!(dates, vec![date(1), date(2)]);
+        assert_eq!(report.days[0].sessions[0].seconds, 600);
+    }
+
+    #[test]
+    fn run_counts_sessions_across_midnight_on_both_days() {
+        let source = repeat_source(vec![hb_at(1, 23, 55, "foo"), hb_at(2, 0, 5, "foo")]);
+        let report = Analysis::range(date(2), date(2)).run(&source).unwrap();
+        assert_eq!(report.days.len(), 1);
+        assert_eq!(report.days[0].sessions[0].seconds, 300);
+        let report = Analysis::range(date(1), date(2)).run(&source).unwrap();
+        assert_eq!(report.summary.seconds, 600);
+    }
+
+    #[test]
+    fn run_verify_rejects_overcounting_estimator() {
+        let source = repeat_source(vec![hb_at(1, 9, 0, "foo"), hb_at(1, 9, 1, "foo")]);
+        let analysis = Analysis::range(date(1), date(1)).estimator(|_: &[i64], _: i64| 3600);
+        assert!(analysis.run(&source).is_ok());
+        let result = analysis.verify(true).run(&source);
+        assert!(matches!(result, Err(WakalyzeError::InvariantViolated(_))));
+    }
+
+    #[test]
+    fn run_merges_manual_entries_in_range() {
+        let entry = |day: u32, hour: u32| ManualEntry {
+            date: date(day),
+            start: NaiveTime::from_hms_opt(hour, 0, 0).unwrap(),
+            end: NaiveTime::from_hms_opt(hour + 1, 0, 0).unwrap(),
+            project: "calls".into(),
+            note: None,
+        };
+        let source = repeat_source(vec![hb_at(2, 9, 0, "foo"), hb_at(2, 9, 10, "foo")]);
+        let report = Analysis::range(date(1), date(2))
+            .manual_entries(vec![entry(2, 8), entry(1, 13), entry(3, 13)])
+            .run(&source)
+            .unwrap();
+        let dates: Vec<NaiveDate> = report.days.iter().map(|d| d.date).collect();
+        assert_eq!(dates, vec![date(1), date(2)]);
+        let day2: Vec<bool> = report.days[1].sessions.iter().map(|s| s.manual).collect();
+        assert_eq!(day2, vec![true, false]);
+        assert_eq!(report.summary.seconds, 2 * 3600 + 600);
+    }
+
+    #[test]
+    fn run_leaves_ignored_heartbeats_out_of_sessions() {
+        let mut vendored = hb_at(1, 9, 5, "foo");
+        vendored.entity = Some("/app/node_modules/x/index.js".into());
+        let source = repeat_source(vec![
+            hb_at(1, 9, 0, "foo"),
+            vendored,
+            hb_at(1, 9, 10, "scratchpad"),
+        ]);
+        let report = Analysis::range(date(1), date(1))
+            .ignore(IgnoreRules {
+                projects: vec!["scratch*".into()],
+                entities: vec!["**/node_modules/**".into()],
+            })
+            .run(&source)
+            .unwrap();
+        assert_eq!(report.days[0].sessions.len(), 1);
+        assert_eq!(report.days[0].sessions[0].seconds, 0);
+    }
+
+    #[test]
+    fn run_merges_projects_by_rules() {
+        let source = repeat_source(vec![
+            hb_at(1, 9, 0, "acme-api"),
+            hb_at(1, 9, 5, "acme-web"),
+            hb_at(1, 9, 10, "other"),
+        ]);
+        let report = Analysis::range(date(1), date(1))
+            .project_map(ProjectMap {
+                rules: vec![crate::projects::MergeRule {
+                    pattern: "acme-*".into(),
+                    regex: None,
+                    project: "Acme".into(),
+                }],
+                ..Default::default()
+            })
+            .filter("acme")
+            .run(&source)
+            .unwrap();
+        let sessions = &report.days[0].sessions;
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].project.as_deref(), Some("Acme"));
+        assert_eq!(sessions[0].seconds, 300);
+    }
+
+    #[test]
+    fn analyses_in_different_time_zones() {
+        // 2026-02-01 20:00 and 20:05 UTC, already 2026-02-02 in Tokyo.
+        let at = |time: f64| RawHeartbeat {
+            time: Some(time),
+            project: Some("api".into()),
+            ..Default::default()
+        };
+        let source = repeat_source(vec![at(1_769_976_000.0), at(1_769_976_300.0)]);
+        let dates = |zone: Tz| -> Vec<NaiveDate> {
+            Analysis::range(date(1), date(2))
+                .timezone(zone)
+                .run(&source)
+                .unwrap()
+                .days
+                .iter()
+                .map(|day| day.date)
+                .collect()
+        };
+        assert_eq!(dates(chrono_tz::UTC), [date(1)]);
+        assert_eq!(dates(chrono_tz::Asia::Tokyo), [date(2)]);
+    }
+
+    #[test]
+    fn punch_card_and_entities_follow_renamed_projects() {
+        let on = |min: u32, entity: &str| RawHeartbeat {
+            entity: Some(entity.into()),
+            ..hb_at(1, 9, min, "old-name")
+        };
+        let source = repeat_source(vec![on(0, "src/a.rs"), on(5, "src/b.rs")]);
+        let report = Analysis::range(date(1), date(1))
+            .project_map(ProjectMap {
+                aliases: [("old-name".to_string(), "new-name".to_string())].into(),
+                ..Default::default()
+            })
+            .filter("new-name")
+            .entities(true)
+            .run(&source)
+            .unwrap();
+        assert_eq!(report.punch_card.counts.iter().flatten().sum::<u32>(), 2);
+        assert_eq!(report.entities.len(), 1);
+        assert_eq!(report.entities[0].project.as_deref(), Some("new-name"));
+    }
+
+    #[test]
+    fn run_sessions_applies_rules_and_filters() {
+        let session = |project: &str, seconds: i64| Session {
+            seconds,
+            project: Some(project.into()),
+            ..Default::default()
+        };
+        let days = vec![
+            DaySessions {
+                date: date(1),
+                sessions: vec![session("acme-api", 3600), session("scratch", 600)],
+            },
+            DaySessions {
+                date: date(2),
+                sessions: vec![session("acme-web", 1800), session("other", 900)],
+            },
+            // Outside the range.
+            DaySessions {
+                date: date(5),
+                sessions: vec![session("acme-api", 60)],
+            },
+        ];
+        let report = Analysis::range(date(1), date(3))
+            .ignore(IgnoreRules {
+                projects: vec!["scratch".into()],
+                entities: vec![],
+            })
+            .project_map(ProjectMap {
+                rules: vec![crate::projects::MergeRule {
+                    pattern: "acme-*".into(),
+                    regex: None,
+                    project: "Acme".into(),
+                }],
+                ..Default::default()
+            })
+            .exclude("other")
+            .run_sessions(days, "summaries")
+            .unwrap();
+        assert_eq!(report.estimator, "summaries");
+        assert_eq!(report.days.len(), 2);
+        assert_eq!(report.summary.seconds, 5400);
+        assert!(report
+            .days
+            .iter()
+            .flat_map(|day| &day.sessions)
+            .all(|s| s.project.as_deref() == Some("Acme")));
+    }
+
+    #[test]
+    fn run_rejects_non_positive_gap() {
+        let source = repeat_source(vec![]);
+        let result = Analysis::range(date(1), date(1)).max_gap(0).run(&source);
+        assert!(matches!(result, Err(WakalyzeError::InvalidMaxGap)));
+    }
+
+    #[test]
+    fn run_propagates_source_errors() {
+        let source =
+            |_: NaiveDate| -> Result<Vec<RawHeartbeat>> { Err(WakalyzeError::MissingAuth) };
+        let result = Analysis::range(date(1), date(1)).run(&source);
+        assert!(matches!(result, Err(WakalyzeError::MissingAuth)));
+    }
+}
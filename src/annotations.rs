@@ -0,0 +1,152 @@
+//! Notes and tags on sessions, kept in a local state file keyed by session ID.
+//!
+//! Heartbeats cannot tell a meeting from pairing or billable from internal work; annotations
+//! add that context and show up wherever the session does (text, JSON, Harvest, Timewarrior).
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::{load_state, save_state, state_dir};
+use crate::core::DaySessions;
+use crate::error::{Result, WakalyzeError};
+use crate::sessions::{session_id, ID_LEN};
+
+pub fn state_path() -> PathBuf {
+    state_dir().join("annotations.json")
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Annotation {
+    pub note: Option<String>,
+    pub tags: BTreeSet<String>,
+}
+
+impl Annotation {
+    pub fn is_empty(&self) -> bool {
+        self.note.is_none() && self.tags.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Annotations {
+    pub sessions: BTreeMap<String, Annotation>,
+}
+
+/// Lowercase form of a session ID, or an error if it is not one.
+pub fn normalize_id(id: &str) -> Result<String> {
+    let id = id.trim().to_ascii_lowercase();
+    if id.len() == ID_LEN && id.chars().all(|c| c.is_ascii_hexdigit()) {
+        Ok(id)
+    } else {
+        Err(WakalyzeError::InvalidSessionId(id))
+    }
+}
+
+impl Annotations {
+    /// Annotations in `path`; none if the file does not exist yet.
+    pub fn load(path: &Path) -> Result<Self> {
+        load_state(path)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        save_state(path, self)
+    }
+
+    pub fn get(&self, id: &str) -> Annotation {
+        self.sessions.get(id).cloned().unwrap_or_default()
+    }
+
+    /// Store `annotation` for `id`, dropping the entry once it is empty.
+    pub fn set(&mut self, id: &str, annotation: Annotation) {
+        if annotation.is_empty() {
+            self.sessions.remove(id);
+        } else {
+            self.sessions.insert(id.to_string(), annotation);
+        }
+    }
+
+    /// Copy the notes and tags of `user`'s sessions onto them.
+    pub fn apply(&self, user: &str, days: &mut [DaySessions]) {
+        if self.sessions.is_empty() {
+            return;
+        }
+        for session in days.iter_mut().flat_map(|day| &mut day.sessions) {
+            if let Some(annotation) = self.sessions.get(&session_id(user, session)) {
+                session.note = annotation.note.clone().or(session.note.take());
+                session.tags = annotation.tags.iter().cloned().collect();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Session;
+    use chrono::NaiveDate;
+
+    #[test]
+    fn apply_matches_sessions_by_id() {
+        let session = |start: i64| Session {
+            start,
+            end: start + 600,
+            seconds: 600,
+            project: Some("api".into()),
+            ..Default::default()
+        };
+        let mut days = vec![DaySessions {
+            date: NaiveDate::from_ymd_opt(2026, 2, 2).unwrap(),
+            sessions: vec![session(1_770_000_000), session(1_770_003_600)],
+        }];
+        let mut annotations = Annotations::default();
+        annotations.set(
+            &session_id("me", &days[0].sessions[1]),
+            Annotation {
+                note: Some("pairing with Sam".into()),
+                tags: ["billable".to_string()].into(),
+            },
+        );
+
+        annotations.apply("you", &mut days);
+        assert!(days[0].sessions[1].tags.is_empty());
+        annotations.apply("me", &mut days);
+        assert_eq!(days[0].sessions[0].note, None);
+        assert_eq!(
+            days[0].sessions[1].note.as_deref(),
+            Some("pairing with Sam")
+        );
+        assert_eq!(days[0].sessions[1].tags, vec!["billable"]);
+    }
+
+    #[test]
+    fn save_and_load_round_trip_and_drop_empty() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state").join("annotations.json");
+        assert_eq!(Annotations::load(&path).unwrap(), Annotations::default());
+
+        let mut annotations = Annotations::default();
+        annotations.set(
+            "0123456789ab",
+            Annotation {
+                note: None,
+                tags: ["meeting".to_string()].into(),
+            },
+        );
+        annotations.set("ba9876543210", Annotation::default());
+        annotations.save(&path).unwrap();
+        let loaded = Annotations::load(&path).unwrap();
+        assert_eq!(loaded, annotations);
+        assert_eq!(loaded.sessions.len(), 1);
+    }
+
+    #[test]
+    fn normalize_id_checks_format() {
+        assert_eq!(normalize_id(" 0123456789AB ").unwrap(), "0123456789ab");
+        assert!(normalize_id("0123").is_err());
+        assert!(normalize_id("0123456789xz").is_err());
+    }
+}
@@ -0,0 +1,449 @@
+//! `wakalyze archive`: one self-contained file with the heartbeats of a period, the settings
+//! they were analyzed with, and the resulting report, so billed months can be re-analyzed or
+//! re-rendered offline exactly as they were.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::analysis::{Analysis, MemorySource, Report};
+use crate::conflicts::ConflictPolicy;
+use crate::core::{parse_clock, parse_utc_offset, RawHeartbeat};
+use crate::corrections::Correction;
+use crate::error::{Result, WakalyzeError};
+use crate::ignore::IgnoreRules;
+use crate::json::JsonReport;
+use crate::manual::ManualEntry;
+use crate::meetings::{Meeting, MeetingMode};
+use crate::projects::ProjectMap;
+
+/// Value of the `format` field, identifying archive files.
+pub const ARCHIVE_FORMAT: &str = "wakalyze-archive";
+
+/// Version of the archive layout. Bump when a field is removed, renamed, or changes meaning.
+pub const ARCHIVE_VERSION: u32 = 1;
+
+/// Everything besides the heartbeats that the report depends on.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ArchiveSettings {
+    pub max_gap_seconds: i64,
+    pub filter: Option<String>,
+    /// `--exclude`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exclude: Option<String>,
+    /// `--filter-regex`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub filter_regex: Option<String>,
+    /// `--branch`, a glob.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub branch: Option<String>,
+    /// `--day-boundary`, HH:MM.
+    pub day_boundary: Option<String>,
+    /// `--fetch-tz`, as given.
+    pub fetch_tz: Option<String>,
+    pub dedupe_machines: bool,
+    pub conflict_policy: ConflictPolicy,
+    pub manual_entries: Vec<ManualEntry>,
+    pub corrections: Vec<Correction>,
+    pub ignore: IgnoreRules,
+    pub projects: ProjectMap,
+    #[serde(default)]
+    pub skip_weekends: bool,
+    /// `--categories`; every category when empty.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub categories: Vec<String>,
+    /// Events of `--meetings`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub meetings: Vec<Meeting>,
+    #[serde(default)]
+    pub meeting_mode: MeetingMode,
+}
+
+impl ArchiveSettings {
+    /// The analysis of `start`..=`end` these settings describe.
+    pub fn analysis(&self, start: NaiveDate, end: NaiveDate) -> Result<Analysis> {
+        let mut analysis = Analysis::range(start, end)
+            .max_gap(self.max_gap_seconds)
+            .dedupe_machines(self.dedupe_machines)
+            .conflict_policy(self.conflict_policy)
+            .manual_entries(self.manual_entries.clone())
+            .corrections(self.corrections.clone())
+            .ignore(self.ignore.clone())
+            .project_map(self.projects.clone())
+            .skip_weekends(self.skip_weekends)
+            .categories(self.categories.clone())
+            .meetings(self.meetings.clone(), self.meeting_mode);
+        if let Some(filter) = self.filter.as_deref() {
+            analysis = analysis.filter(filter);
+        }
+        if let Some(exclude) = self.exclude.as_deref() {
+            analysis = analysis.exclude(exclude);
+        }
+        if let Some(pattern) = self.filter_regex.as_deref() {
+            analysis = analysis.filter_regex(pattern)?;
+        }
+        if let Some(pattern) = self.branch.as_deref() {
+            analysis = analysis.branch(pattern);
+        }
+        if let Some(day_start) = self.day_boundary.as_deref() {
+            analysis = analysis.day_boundary(parse_clock(day_start)?);
+        }
+        if let Some(offset) = self.fetch_tz.as_deref() {
+            analysis = analysis.fetch_offset(parse_utc_offset(offset)?);
+        }
+        Ok(analysis)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Archive {
+    pub format: String,
+    pub version: u32,
+    /// wakalyze version that wrote the archive.
+    pub tool_version: String,
+    pub created: DateTime<Utc>,
+    pub user: String,
+    pub base_url: String,
+    pub label: String,
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    pub settings: ArchiveSettings,
+    /// Everything fetched for the period, including the neighbouring server days.
+    pub heartbeats: Vec<RawHeartbeat>,
+    /// The `--format json` document at archive time.
+    pub report: serde_json::Value,
+    /// `sha256:<hex>` of the heartbeats of each UTC date, to tell which days were altered.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub day_hashes: BTreeMap<NaiveDate, String>,
+    /// `sha256:<hex>` of the archive with this field and `signature` empty.
+    #[serde(default)]
+    pub checksum: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<ArchiveSignature>,
+}
+
+/// Ed25519 signature of the `checksum` string.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArchiveSignature {
+    /// Hex of the 32-byte public key.
+    pub public_key: String,
+    /// Hex of the 64-byte signature.
+    pub signature: String,
+}
+
+/// Result of [`Archive::check`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArchiveCheck {
+    pub checksum_ok: bool,
+    /// UTC dates whose heartbeats no longer match their hash, or that appeared or vanished.
+    pub altered_days: Vec<NaiveDate>,
+    /// `None` if unsigned; otherwise whether the signature is valid for the checksum (and was
+    /// made by the expected key, when one was given).
+    pub signature_ok: Option<bool>,
+}
+
+impl ArchiveCheck {
+    pub fn is_ok(&self) -> bool {
+        self.checksum_ok && self.altered_days.is_empty() && self.signature_ok != Some(false)
+    }
+}
+
+fn sha256(bytes: &[u8]) -> String {
+    format!("sha256:{}", to_hex(&Sha256::digest(bytes)))
+}
+
+pub(crate) fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+fn from_hex(text: &str) -> Option<Vec<u8>> {
+    let text = text.trim();
+    if !text.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..text.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(text.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+/// Signing key from a file holding the 32-byte seed in hex (e.g. `openssl rand -hex 32`).
+pub fn load_signing_key(path: &Path) -> Result<SigningKey> {
+    let text = std::fs::read_to_string(path)?;
+    let seed: [u8; 32] = from_hex(&text)
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| WakalyzeError::InvalidKey(path.display().to_string()))?;
+    Ok(SigningKey::from_bytes(&seed))
+}
+
+/// Public key from 64 hex digits.
+pub fn parse_public_key(text: &str) -> Result<VerifyingKey> {
+    let invalid = || WakalyzeError::InvalidKey(text.to_string());
+    let bytes: [u8; 32] = from_hex(text)
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(invalid)?;
+    VerifyingKey::from_bytes(&bytes).map_err(|_| invalid())
+}
+
+fn heartbeat_day_hashes(heartbeats: &[RawHeartbeat]) -> Result<BTreeMap<NaiveDate, String>> {
+    let mut days: BTreeMap<NaiveDate, Vec<&RawHeartbeat>> = BTreeMap::new();
+    for hb in heartbeats {
+        let date = hb
+            .time
+            .and_then(|t| DateTime::from_timestamp(t as i64, 0))
+            .map(|dt| dt.date_naive());
+        if let Some(date) = date {
+            days.entry(date).or_default().push(hb);
+        }
+    }
+    days.into_iter()
+        .map(|(date, hbs)| Ok((date, sha256(&serde_json::to_vec(&hbs)?))))
+        .collect()
+}
+
+impl Archive {
+    pub fn new(
+        user: &str,
+        base_url: &str,
+        label: &str,
+        report: &Report,
+        settings: ArchiveSettings,
+        heartbeats: Vec<RawHeartbeat>,
+    ) -> Result<Self> {
+        let mut archive = Self {
+            format: ARCHIVE_FORMAT.to_string(),
+            version: ARCHIVE_VERSION,
+            tool_version: env!("CARGO_PKG_VERSION").to_string(),
+            created: Utc::now(),
+            user: user.to_string(),
+            base_url: base_url.to_string(),
+            label: label.to_string(),
+            start: report.start,
+            end: report.end,
+            settings,
+            heartbeats,
+            report: serde_json::to_value(JsonReport::new(label, report))?,
+            day_hashes: BTreeMap::new(),
+            checksum: String::new(),
+            signature: None,
+        };
+        archive.day_hashes = heartbeat_day_hashes(&archive.heartbeats)?;
+        archive.checksum = archive.compute_checksum()?;
+        Ok(archive)
+    }
+
+    fn compute_checksum(&self) -> Result<String> {
+        let unsigned = Self {
+            checksum: String::new(),
+            signature: None,
+            ..self.clone()
+        };
+        Ok(sha256(&serde_json::to_vec(&unsigned)?))
+    }
+
+    /// Sign the checksum; the archive must not change afterwards.
+    pub fn sign(&mut self, key: &SigningKey) {
+        self.signature = Some(ArchiveSignature {
+            public_key: to_hex(key.verifying_key().as_bytes()),
+            signature: to_hex(&key.sign(self.checksum.as_bytes()).to_bytes()),
+        });
+    }
+
+    /// Recompute the checksum, day hashes, and signature; `expected_key` also requires the
+    /// signature to be made by that key.
+    pub fn check(&self, expected_key: Option<&VerifyingKey>) -> Result<ArchiveCheck> {
+        let checksum_ok = self.checksum == self.compute_checksum()?;
+        let actual = heartbeat_day_hashes(&self.heartbeats)?;
+        let mut altered_days: Vec<NaiveDate> = actual
+            .iter()
+            .filter(|(date, hash)| self.day_hashes.get(date) != Some(hash))
+            .map(|(date, _)| *date)
+            .chain(
+                self.day_hashes
+                    .keys()
+                    .filter(|date| !actual.contains_key(date))
+                    .copied(),
+            )
+            .collect();
+        altered_days.sort();
+        let signature_ok = match (&self.signature, expected_key) {
+            (None, None) => None,
+            (None, Some(_)) => Some(false),
+            (Some(signature), expected) => Some(self.signature_valid(signature, expected)),
+        };
+        Ok(ArchiveCheck {
+            checksum_ok,
+            altered_days,
+            signature_ok,
+        })
+    }
+
+    fn signature_valid(
+        &self,
+        signature: &ArchiveSignature,
+        expected: Option<&VerifyingKey>,
+    ) -> bool {
+        let Ok(key) = parse_public_key(&signature.public_key) else {
+            return false;
+        };
+        let Some(bytes) = from_hex(&signature.signature).and_then(|b| <[u8; 64]>::try_from(b).ok())
+        else {
+            return false;
+        };
+        expected.is_none_or(|expected| *expected == key)
+            && key
+                .verify(self.checksum.as_bytes(), &Signature::from_bytes(&bytes))
+                .is_ok()
+    }
+
+    /// Parse an archive without checking it, see [`Archive::check`].
+    pub fn read(path: &Path) -> Result<Self> {
+        Ok(serde_json::from_slice(&std::fs::read(path)?)?)
+    }
+
+    /// Read an archive, rejecting other files, newer layouts, and checksum mismatches.
+    pub fn load(path: &Path) -> Result<Self> {
+        let archive = Self::read(path)?;
+        let invalid =
+            |reason: String| WakalyzeError::InvalidArchive(format!("{}: {reason}", path.display()));
+        if archive.format != ARCHIVE_FORMAT {
+            return Err(invalid("not a wakalyze archive".into()));
+        }
+        if archive.version > ARCHIVE_VERSION {
+            return Err(invalid(format!(
+                "archive version {} is newer than this wakalyze supports",
+                archive.version
+            )));
+        }
+        if archive.checksum != archive.compute_checksum()? {
+            return Err(invalid("checksum mismatch".into()));
+        }
+        Ok(archive)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        std::fs::write(path, serde_json::to_vec_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// The archived heartbeats, served without the network.
+    pub fn source(&self) -> MemorySource {
+        MemorySource::new(self.heartbeats.clone())
+    }
+
+    /// Analyze the archived heartbeats again with the archived settings.
+    pub fn replay(&self) -> Result<Report> {
+        self.settings
+            .analysis(self.start, self.end)?
+            .run(&self.source())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::local_timestamp;
+    use chrono::NaiveTime;
+
+    fn archive() -> Archive {
+        let date = NaiveDate::from_ymd_opt(2026, 2, 3).unwrap();
+        let start = local_timestamp(date, NaiveTime::from_hms_opt(9, 0, 0).unwrap()).unwrap();
+        let heartbeats: Vec<RawHeartbeat> = (0..10)
+            .map(|i| RawHeartbeat {
+                time: Some((start + i * 60) as f64),
+                project: Some("api".into()),
+                ..Default::default()
+            })
+            .collect();
+        let settings = ArchiveSettings {
+            max_gap_seconds: 900,
+            ..Default::default()
+        };
+        let report = settings
+            .analysis(date, date)
+            .unwrap()
+            .run(&MemorySource::new(heartbeats.clone()))
+            .unwrap();
+        Archive::new(
+            "me",
+            "https://wakapi.dev",
+            "2026-02-03",
+            &report,
+            settings,
+            heartbeats,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn save_load_and_replay() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("feb.wakalyze");
+        let archive = archive();
+        archive.save(&path).unwrap();
+
+        let loaded = Archive::load(&path).unwrap();
+        assert_eq!(loaded, archive);
+        assert!(loaded.checksum.starts_with("sha256:"));
+        let report = loaded.replay().unwrap();
+        assert_eq!(
+            serde_json::to_value(JsonReport::new(&loaded.label, &report)).unwrap(),
+            loaded.report
+        );
+    }
+
+    #[test]
+    fn check_finds_altered_days_and_bad_signatures() {
+        let key = SigningKey::from_bytes(&[7; 32]);
+        let mut archive = archive();
+        archive.sign(&key);
+        let check = archive.check(Some(&key.verifying_key())).unwrap();
+        assert!(check.is_ok());
+        assert_eq!(check.signature_ok, Some(true));
+
+        let other = SigningKey::from_bytes(&[8; 32]).verifying_key();
+        assert_eq!(
+            archive.check(Some(&other)).unwrap().signature_ok,
+            Some(false)
+        );
+
+        let time = archive.heartbeats[0].time.unwrap() as i64;
+        let date = DateTime::from_timestamp(time, 0).unwrap().date_naive();
+        archive.heartbeats[0].project = Some("other".into());
+        let check = archive.check(None).unwrap();
+        assert!(!check.checksum_ok);
+        assert_eq!(check.altered_days, vec![date]);
+
+        archive.checksum = archive.compute_checksum().unwrap();
+        assert_eq!(archive.check(None).unwrap().signature_ok, Some(false));
+    }
+
+    #[test]
+    fn keys_from_hex() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("archive.key");
+        std::fs::write(&path, format!("{}\n", "07".repeat(32))).unwrap();
+        let key = load_signing_key(&path).unwrap();
+        assert_eq!(key, SigningKey::from_bytes(&[7; 32]));
+        let public = to_hex(key.verifying_key().as_bytes());
+        assert_eq!(parse_public_key(&public).unwrap(), key.verifying_key());
+        assert!(parse_public_key("abc").is_err());
+    }
+
+    #[test]
+    fn load_rejects_tampering() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("feb.wakalyze");
+        let mut archive = archive();
+        archive.heartbeats.pop();
+        std::fs::write(&path, serde_json::to_vec(&archive).unwrap()).unwrap();
+        assert!(matches!(
+            Archive::load(&path),
+            Err(WakalyzeError::InvalidArchive(_))
+        ));
+    }
+}
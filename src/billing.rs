@@ -0,0 +1,113 @@
+//! Billing codes by branch, for contracts that bill branches of one repository to different
+//! budgets (e.g. `hotfix/*` to a maintenance retainer).
+
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::core::{DaySessions, Session};
+use crate::ignore::glob_match;
+use crate::summary::{percent_of, GroupTotal};
+
+/// Sessions on a branch matching the glob `branch` (and, if set, a project matching `project`)
+/// are billed to `code`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BillingRule {
+    pub branch: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project: Option<String>,
+    pub code: String,
+}
+
+impl BillingRule {
+    fn matches(&self, session: &Session) -> bool {
+        let Some(branch) = session.branch.as_deref() else {
+            return false;
+        };
+        let project_matches = match self.project.as_deref() {
+            Some(pattern) => session
+                .project
+                .as_deref()
+                .is_some_and(|project| glob_match(pattern, project)),
+            None => true,
+        };
+        project_matches && glob_match(&self.branch, branch)
+    }
+}
+
+/// Code of the first rule matching the session, if any.
+pub fn billing_code<'a>(rules: &'a [BillingRule], session: &Session) -> Option<&'a str> {
+    rules
+        .iter()
+        .find(|rule| rule.matches(session))
+        .map(|rule| rule.code.as_str())
+}
+
+/// Time per billing code, most first; sessions no rule matches are totalled under `None`.
+pub fn billing_totals(days: &[DaySessions], rules: &[BillingRule]) -> Vec<GroupTotal> {
+    let mut totals: BTreeMap<Option<&str>, i64> = BTreeMap::new();
+    for session in days.iter().flat_map(|day| &day.sessions) {
+        *totals.entry(billing_code(rules, session)).or_default() += session.seconds;
+    }
+    let total: i64 = totals.values().sum();
+    let mut codes: Vec<GroupTotal> = totals
+        .into_iter()
+        .map(|(code, seconds)| GroupTotal {
+            name: code.map(str::to_owned),
+            seconds,
+            percent: percent_of(seconds, total),
+        })
+        .collect();
+    codes.sort_by_key(|c| Reverse(c.seconds));
+    codes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn session(seconds: i64, project: &str, branch: Option<&str>) -> Session {
+        Session {
+            seconds,
+            project: Some(project.into()),
+            branch: branch.map(Into::into),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn first_matching_rule_wins() {
+        let rules: Vec<BillingRule> = serde_json::from_str(
+            r#"[
+                { "branch": "hotfix/*", "code": "maintenance" },
+                { "branch": "feature/*", "project": "acme-*", "code": "ACME-2026" },
+                { "branch": "**", "code": "never" }
+            ]"#,
+        )
+        .unwrap();
+        let days = vec![DaySessions {
+            date: NaiveDate::from_ymd_opt(2026, 2, 2).unwrap(),
+            sessions: vec![
+                session(600, "acme-api", Some("hotfix/login")),
+                session(1200, "acme-api", Some("feature/sso")),
+                session(300, "oss", Some("feature/x")),
+                session(100, "acme-api", None),
+            ],
+        }];
+        let totals: Vec<_> = billing_totals(&days, &rules)
+            .into_iter()
+            .map(|t| (t.name, t.seconds))
+            .collect();
+        assert_eq!(
+            totals,
+            vec![
+                (Some("ACME-2026".to_string()), 1200),
+                (Some("maintenance".to_string()), 600),
+                (Some("never".to_string()), 300),
+                (None, 100),
+            ]
+        );
+    }
+}
@@ -0,0 +1,102 @@
+//! Heartbeats of the `browsing` category (browser plugins), whose entities are URLs or domains.
+//! They are kept out of the coding sessions and totalled per domain instead.
+
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use crate::core::{DaySessions, RawHeartbeat};
+use crate::summary::percent_of;
+
+pub const BROWSING: &str = "browsing";
+
+/// Domains listed by `--domains`.
+pub const TOP_DOMAINS: usize = 10;
+
+pub fn is_browsing(hb: &RawHeartbeat) -> bool {
+    hb.category
+        .as_deref()
+        .is_some_and(|category| category.eq_ignore_ascii_case(BROWSING))
+}
+
+/// Host of a URL or bare domain, lowercased and without `www.` or a port.
+pub fn domain_of(entity: &str) -> Option<String> {
+    let rest = entity
+        .trim()
+        .split_once("://")
+        .map_or(entity.trim(), |(_, rest)| rest);
+    let host = rest.split(['/', '?', '#']).next()?;
+    let host = host.rsplit_once('@').map_or(host, |(_, host)| host);
+    let host = host.split(':').next()?.to_lowercase();
+    let host = host.strip_prefix("www.").unwrap_or(&host);
+    (!host.is_empty()).then(|| host.to_string())
+}
+
+/// The browsing heartbeats with their domain as the project, to build sessions per domain.
+pub fn by_domain(heartbeats: Vec<RawHeartbeat>) -> Vec<RawHeartbeat> {
+    heartbeats
+        .into_iter()
+        .map(|hb| RawHeartbeat {
+            project: hb.entity.as_deref().and_then(domain_of),
+            ..hb
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DomainTotal {
+    pub domain: Option<String>,
+    pub seconds: i64,
+    /// Share of the browsing total, 0–100.
+    pub percent: f64,
+}
+
+/// Time per domain of sessions built from [`by_domain`], most first; ties in name order.
+pub fn domain_totals(days: &[DaySessions]) -> Vec<DomainTotal> {
+    let mut totals: BTreeMap<Option<&str>, i64> = BTreeMap::new();
+    for session in days.iter().flat_map(|day| &day.sessions) {
+        *totals.entry(session.project.as_deref()).or_default() += session.seconds;
+    }
+    let total: i64 = totals.values().sum();
+    let mut domains: Vec<DomainTotal> = totals
+        .into_iter()
+        .map(|(domain, seconds)| DomainTotal {
+            domain: domain.map(str::to_owned),
+            seconds,
+            percent: percent_of(seconds, total),
+        })
+        .collect();
+    domains.sort_by_key(|d| Reverse(d.seconds));
+    domains
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn domains_of_urls_and_hosts() {
+        assert_eq!(
+            domain_of("https://www.StackOverflow.com/questions/1?x=2").as_deref(),
+            Some("stackoverflow.com")
+        );
+        assert_eq!(
+            domain_of("http://user@jira.local:8080/browse/API-1").as_deref(),
+            Some("jira.local")
+        );
+        assert_eq!(domain_of("docs.rs").as_deref(), Some("docs.rs"));
+        assert_eq!(domain_of("https://"), None);
+    }
+
+    #[test]
+    fn browsing_category_is_case_insensitive() {
+        let hb = |category: Option<&str>| RawHeartbeat {
+            category: category.map(Into::into),
+            ..Default::default()
+        };
+        assert!(is_browsing(&hb(Some("Browsing"))));
+        assert!(!is_browsing(&hb(Some("coding"))));
+        assert!(!is_browsing(&hb(None)));
+    }
+}
@@ -0,0 +1,601 @@
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+
+use bincode::Options;
+use chrono::{Days, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::analysis::HeartbeatSource;
+use crate::core::RawHeartbeat;
+use crate::encryption::{is_sealed, CacheCipher};
+use crate::error::Result;
+
+pub fn cache_root() -> PathBuf {
+    if let Ok(xdg) = std::env::var("XDG_CACHE_HOME") {
+        if !xdg.is_empty() {
+            return PathBuf::from(xdg).join("wakalyze");
+        }
+    }
+    dirs::home_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(".cache")
+        .join("wakalyze")
+}
+
+/// Directory-safe key for one account on one server.
+pub fn cache_key(user: &str, base_url: &str) -> String {
+    let host = base_url
+        .trim_end_matches('/')
+        .split_once("://")
+        .map_or(base_url, |(_, rest)| rest);
+    format!("{user}@{host}")
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '@' | '.' | '-' | '_') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CachedDay {
+    /// Latest heartbeat time seen for the day; refetches only merge newer heartbeats.
+    pub last_seen: Option<f64>,
+    /// The day was over when fetched, so the server will not add to it.
+    pub complete: bool,
+    /// Unix time of the last fetch, for the [`CachePolicy`] TTL.
+    #[serde(default)]
+    pub fetched_at: Option<i64>,
+    pub heartbeats: Vec<RawHeartbeat>,
+}
+
+impl CachedDay {
+    /// Merge heartbeats newer than `last_seen`, returning how many were added.
+    pub fn merge(&mut self, fetched: Vec<RawHeartbeat>) -> usize {
+        let since = self.last_seen;
+        let before = self.heartbeats.len();
+        for hb in fetched {
+            let Some(time) = hb.time else { continue };
+            if since.is_some_and(|since| time <= since) {
+                continue;
+            }
+            self.last_seen = Some(self.last_seen.map_or(time, |t| t.max(time)));
+            self.heartbeats.push(hb);
+        }
+        self.heartbeats.len() - before
+    }
+}
+
+const MAGIC: &[u8; 4] = b"WKZC";
+
+/// Version of the binary day layout. Bump whenever `DayColumns` or the heartbeat fields it
+/// stores change; files with another version are treated as cache misses.
+const FORMAT_VERSION: u8 = 9;
+
+/// Optional strings stored once each; `indexes` holds 0 for `None`, else table position + 1.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct StringColumn {
+    table: Vec<String>,
+    indexes: Vec<u32>,
+}
+
+impl StringColumn {
+    fn encode<'a>(values: impl Iterator<Item = Option<&'a str>>) -> Self {
+        let mut column = Self::default();
+        let mut positions: HashMap<&str, u32> = HashMap::new();
+        for value in values {
+            let index = match value {
+                None => 0,
+                Some(v) => *positions.entry(v).or_insert_with(|| {
+                    column.table.push(v.to_string());
+                    column.table.len() as u32
+                }),
+            };
+            column.indexes.push(index);
+        }
+        column
+    }
+
+    fn get(&self, row: usize) -> Option<Option<String>> {
+        match *self.indexes.get(row)? {
+            0 => Some(None),
+            i => self.table.get(i as usize - 1).cloned().map(Some),
+        }
+    }
+}
+
+/// Column-oriented layout of a [`CachedDay`]: repeated strings are stored once and
+/// integers are varint-encoded.
+#[derive(Debug, Serialize, Deserialize)]
+struct DayColumns {
+    last_seen: Option<f64>,
+    complete: bool,
+    fetched_at: Option<i64>,
+    times: Vec<Option<f64>>,
+    projects: StringColumn,
+    machines: StringColumn,
+    entities: StringColumn,
+    languages: StringColumn,
+    editors: StringColumn,
+    branches: StringColumn,
+    operating_systems: StringColumn,
+    categories: StringColumn,
+}
+
+fn encode_day(day: &CachedDay) -> Result<Vec<u8>> {
+    let columns = DayColumns {
+        last_seen: day.last_seen,
+        complete: day.complete,
+        fetched_at: day.fetched_at,
+        times: day.heartbeats.iter().map(|hb| hb.time).collect(),
+        projects: StringColumn::encode(day.heartbeats.iter().map(|hb| hb.project.as_deref())),
+        machines: StringColumn::encode(day.heartbeats.iter().map(|hb| hb.machine.as_deref())),
+        entities: StringColumn::encode(day.heartbeats.iter().map(|hb| hb.entity.as_deref())),
+        languages: StringColumn::encode(day.heartbeats.iter().map(|hb| hb.language.as_deref())),
+        editors: StringColumn::encode(day.heartbeats.iter().map(|hb| hb.editor.as_deref())),
+        branches: StringColumn::encode(day.heartbeats.iter().map(|hb| hb.branch.as_deref())),
+        operating_systems: StringColumn::encode(
+            day.heartbeats
+                .iter()
+                .map(|hb| hb.operating_system.as_deref()),
+        ),
+        categories: StringColumn::encode(day.heartbeats.iter().map(|hb| hb.category.as_deref())),
+    };
+    let mut bytes = MAGIC.to_vec();
+    bytes.push(FORMAT_VERSION);
+    bincode::DefaultOptions::new()
+        .serialize_into(&mut bytes, &columns)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+    Ok(bytes)
+}
+
+fn decode_day(bytes: &[u8]) -> Option<CachedDay> {
+    let body = bytes.strip_prefix(MAGIC)?.strip_prefix(&[FORMAT_VERSION])?;
+    let columns: DayColumns = bincode::DefaultOptions::new().deserialize(body).ok()?;
+    let heartbeats = (0..columns.times.len())
+        .map(|row| {
+            Some(RawHeartbeat {
+                time: columns.times[row],
+                project: columns.projects.get(row)?,
+                machine: columns.machines.get(row)?,
+                entity: columns.entities.get(row)?,
+                language: columns.languages.get(row)?,
+                editor: columns.editors.get(row)?,
+                branch: columns.branches.get(row)?,
+                operating_system: columns.operating_systems.get(row)?,
+                category: columns.categories.get(row)?,
+            })
+        })
+        .collect::<Option<Vec<_>>>()?;
+    Some(CachedDay {
+        last_seen: columns.last_seen,
+        complete: columns.complete,
+        fetched_at: columns.fetched_at,
+        heartbeats,
+    })
+}
+
+/// On-disk heartbeat cache for one account, one file per date.
+#[derive(Debug, Clone)]
+pub struct HeartbeatCache {
+    dir: PathBuf,
+    cipher: Option<CacheCipher>,
+}
+
+impl HeartbeatCache {
+    pub fn new(root: &Path, key: &str) -> Self {
+        Self {
+            dir: root.join(key),
+            cipher: None,
+        }
+    }
+
+    /// Encrypt stored days with `cipher`; plain entries are still read and get encrypted.
+    pub fn encrypted(mut self, cipher: CacheCipher) -> Self {
+        self.cipher = Some(cipher);
+        self
+    }
+
+    pub fn path(&self, date: NaiveDate) -> PathBuf {
+        self.dir.join(format!("{}.bin", date.format("%Y-%m-%d")))
+    }
+
+    /// Delete every cached day of the account, returning how many there were.
+    pub fn clear(&self) -> Result<usize> {
+        let entries = match std::fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+            Err(e) => return Err(e.into()),
+        };
+        let days = entries
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.path().is_file())
+            .count();
+        std::fs::remove_dir_all(&self.dir)?;
+        Ok(days)
+    }
+
+    /// Location of the JSON files written by earlier versions.
+    fn legacy_path(&self, date: NaiveDate) -> PathBuf {
+        self.path(date).with_extension("json")
+    }
+
+    /// Cached heartbeats for a date; unreadable or corrupt entries count as a miss.
+    ///
+    /// A legacy JSON entry is migrated to the binary format on first read, and a plain entry
+    /// of an encrypted cache is encrypted. Encrypted entries without the key count as a miss.
+    pub fn load(&self, date: NaiveDate) -> Option<CachedDay> {
+        if let Ok(bytes) = std::fs::read(self.path(date)) {
+            if is_sealed(&bytes) {
+                return decode_day(&self.cipher.as_ref()?.open(&bytes)?);
+            }
+            let day = decode_day(&bytes)?;
+            if self.cipher.is_some() {
+                let _ = self.store(date, &day);
+            }
+            return Some(day);
+        }
+        let legacy_path = self.legacy_path(date);
+        let text = std::fs::read_to_string(&legacy_path).ok()?;
+        let day: CachedDay = serde_json::from_str(&text).ok()?;
+        if self.store(date, &day).is_ok() {
+            let _ = std::fs::remove_file(legacy_path);
+        }
+        Some(day)
+    }
+
+    pub fn store(&self, date: NaiveDate, day: &CachedDay) -> Result<()> {
+        std::fs::create_dir_all(&self.dir)?;
+        let path = self.path(date);
+        let temp_path = path.with_extension("tmp");
+        let bytes = match &self.cipher {
+            Some(cipher) => cipher.seal(&encode_day(day)?)?,
+            None => encode_day(day)?,
+        };
+        std::fs::write(&temp_path, bytes)?;
+        std::fs::rename(&temp_path, &path)?;
+        Ok(())
+    }
+}
+
+/// How long recent days are trusted, from the `cache` section of the config.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CachePolicy {
+    /// Minutes a fetch of a day that is not final yet (today, and the revalidated days) is
+    /// served from the cache before refetching; 0 refetches on every run.
+    pub ttl_minutes: f64,
+    /// Days before today that are still refetched, for servers that receive heartbeats late
+    /// (offline editors, other timezones); older days are final once fetched.
+    pub revalidate_days: u32,
+}
+
+impl Default for CachePolicy {
+    fn default() -> Self {
+        Self {
+            ttl_minutes: 0.0,
+            revalidate_days: 1,
+        }
+    }
+}
+
+/// Serves complete days from the cache and merges only new heartbeats for recent ones.
+pub struct CachedSource<'a, S: ?Sized> {
+    inner: &'a S,
+    cache: HeartbeatCache,
+    today: NaiveDate,
+    now: i64,
+    policy: CachePolicy,
+    refresh: BTreeSet<NaiveDate>,
+}
+
+impl<'a, S: HeartbeatSource + ?Sized> CachedSource<'a, S> {
+    pub fn new(inner: &'a S, cache: HeartbeatCache, today: NaiveDate) -> Self {
+        Self {
+            inner,
+            cache,
+            today,
+            now: Utc::now().timestamp(),
+            policy: CachePolicy::default(),
+            refresh: BTreeSet::new(),
+        }
+    }
+
+    pub fn policy(mut self, policy: CachePolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Refetch these dates in full, replacing what is cached.
+    pub fn refresh(mut self, dates: impl IntoIterator<Item = NaiveDate>) -> Self {
+        self.refresh.extend(dates);
+        self
+    }
+
+    /// A date is final once `revalidate_days` more days are over. The default of one leaves
+    /// room for a server that buckets `?date=` in a timezone behind ours.
+    fn is_final(&self, date: NaiveDate) -> bool {
+        date.checked_add_days(Days::new(self.policy.revalidate_days as u64))
+            .is_some_and(|last| last < self.today)
+    }
+
+    fn is_fresh(&self, day: &CachedDay) -> bool {
+        let ttl = (self.policy.ttl_minutes * 60.0) as i64;
+        ttl > 0 && day.fetched_at.is_some_and(|at| self.now - at < ttl)
+    }
+}
+
+impl<S: HeartbeatSource + ?Sized> HeartbeatSource for CachedSource<'_, S> {
+    fn fetch_heartbeats(&self, date: NaiveDate) -> Result<Vec<RawHeartbeat>> {
+        let mut day = if self.refresh.contains(&date) {
+            CachedDay::default()
+        } else {
+            self.cache.load(date).unwrap_or_default()
+        };
+        if day.complete || self.is_fresh(&day) {
+            return Ok(day.heartbeats);
+        }
+        let fetched = self.inner.fetch_heartbeats_since(date, day.last_seen)?;
+        day.merge(fetched);
+        day.complete = self.is_final(date);
+        day.fetched_at = Some(self.now);
+        // The cache is an optimization; a failed write must not fail the analysis.
+        let _ = self.cache.store(date, &day);
+        Ok(day.heartbeats)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+
+    fn hb(time: f64, project: &str) -> RawHeartbeat {
+        RawHeartbeat {
+            time: Some(time),
+            project: Some(project.to_string()),
+            ..Default::default()
+        }
+    }
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 2, day).unwrap()
+    }
+
+    /// Records each `(date, since)` request and serves the given heartbeats.
+    struct FakeSource {
+        heartbeats: Vec<RawHeartbeat>,
+        calls: RefCell<Vec<(NaiveDate, Option<f64>)>>,
+    }
+
+    impl HeartbeatSource for FakeSource {
+        fn fetch_heartbeats(&self, date: NaiveDate) -> Result<Vec<RawHeartbeat>> {
+            self.calls.borrow_mut().push((date, None));
+            Ok(self.heartbeats.clone())
+        }
+
+        fn fetch_heartbeats_since(
+            &self,
+            date: NaiveDate,
+            since: Option<f64>,
+        ) -> Result<Vec<RawHeartbeat>> {
+            self.calls.borrow_mut().push((date, since));
+            Ok(self.heartbeats.clone())
+        }
+    }
+
+    #[test]
+    fn cache_key_sanitizes() {
+        assert_eq!(
+            cache_key("me", "https://wakapi.dev/"),
+            "me@wakapi.dev".to_string()
+        );
+        assert_eq!(cache_key("a/b", "http://h:3000"), "a_b@h_3000");
+    }
+
+    #[test]
+    fn merge_keeps_only_newer() {
+        let mut day = CachedDay::default();
+        assert_eq!(day.merge(vec![hb(100.0, "a"), hb(200.0, "a")]), 2);
+        assert_eq!(day.last_seen, Some(200.0));
+        assert_eq!(
+            day.merge(vec![hb(150.0, "a"), hb(200.0, "a"), hb(300.0, "b")]),
+            1
+        );
+        assert_eq!(day.heartbeats.len(), 3);
+        assert_eq!(day.last_seen, Some(300.0));
+    }
+
+    #[test]
+    fn store_and_load_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = HeartbeatCache::new(dir.path(), "me@host");
+        let day = CachedDay {
+            last_seen: Some(100.0),
+            complete: true,
+            fetched_at: None,
+            heartbeats: vec![hb(100.0, "a")],
+        };
+        cache.store(date(1), &day).unwrap();
+        assert_eq!(cache.load(date(1)), Some(day));
+        assert_eq!(cache.load(date(2)), None);
+    }
+
+    #[test]
+    fn clear_removes_the_account_only() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = HeartbeatCache::new(dir.path(), "me@host");
+        let other = HeartbeatCache::new(dir.path(), "other@host");
+        for day in [date(1), date(2)] {
+            cache.store(day, &CachedDay::default()).unwrap();
+        }
+        other.store(date(1), &CachedDay::default()).unwrap();
+        assert_eq!(cache.clear().unwrap(), 2);
+        assert_eq!(cache.load(date(1)), None);
+        assert_eq!(cache.clear().unwrap(), 0);
+        assert!(other.load(date(1)).is_some());
+    }
+
+    #[test]
+    fn load_corrupt_is_miss() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = HeartbeatCache::new(dir.path(), "me@host");
+        std::fs::create_dir_all(cache.path(date(1)).parent().unwrap()).unwrap();
+        std::fs::write(cache.path(date(1)), "not binary").unwrap();
+        assert_eq!(cache.load(date(1)), None);
+    }
+
+    #[test]
+    fn load_other_format_version_is_miss() {
+        let day = CachedDay {
+            heartbeats: vec![hb(100.0, "a")],
+            ..Default::default()
+        };
+        let mut bytes = encode_day(&day).unwrap();
+        bytes[MAGIC.len()] = FORMAT_VERSION + 1;
+        assert_eq!(decode_day(&bytes), None);
+    }
+
+    #[test]
+    fn binary_roundtrip_with_missing_fields() {
+        let day = CachedDay {
+            last_seen: Some(300.5),
+            complete: false,
+            fetched_at: None,
+            heartbeats: vec![
+                hb(100.0, "a"),
+                RawHeartbeat {
+                    time: Some(200.25),
+                    project: None,
+                    ..Default::default()
+                },
+                hb(300.5, "b"),
+                hb(301.0, "a"),
+            ],
+        };
+        assert_eq!(decode_day(&encode_day(&day).unwrap()), Some(day));
+    }
+
+    #[test]
+    fn binary_smaller_than_json() {
+        let day = CachedDay {
+            last_seen: None,
+            complete: true,
+            fetched_at: None,
+            heartbeats: (0..1000)
+                .map(|i| hb(1_770_000_000.0 + i as f64 * 30.0, "some-long-project-name"))
+                .collect(),
+        };
+        let binary = encode_day(&day).unwrap().len();
+        let json = serde_json::to_vec(&day).unwrap().len();
+        assert!(binary * 3 < json, "binary {binary} vs json {json}");
+    }
+
+    #[test]
+    fn load_migrates_legacy_json() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = HeartbeatCache::new(dir.path(), "me@host");
+        let day = CachedDay {
+            last_seen: Some(100.0),
+            complete: true,
+            fetched_at: None,
+            heartbeats: vec![hb(100.0, "a")],
+        };
+        let legacy = cache.legacy_path(date(1));
+        std::fs::create_dir_all(legacy.parent().unwrap()).unwrap();
+        std::fs::write(&legacy, serde_json::to_vec(&day).unwrap()).unwrap();
+
+        assert_eq!(cache.load(date(1)), Some(day.clone()));
+        assert!(!legacy.exists());
+        assert!(cache.path(date(1)).exists());
+        assert_eq!(cache.load(date(1)), Some(day));
+    }
+
+    #[test]
+    fn encrypted_store_load_and_migration() {
+        let dir = tempfile::tempdir().unwrap();
+        let day = CachedDay {
+            last_seen: Some(100.0),
+            complete: true,
+            fetched_at: None,
+            heartbeats: vec![hb(100.0, "secret-project")],
+        };
+        let plain = HeartbeatCache::new(dir.path(), "me@host");
+        plain.store(date(1), &day).unwrap();
+        let cache = plain.clone().encrypted(CacheCipher::new(&[3; 32]));
+
+        assert_eq!(cache.load(date(1)), Some(day.clone()));
+        let bytes = std::fs::read(cache.path(date(1))).unwrap();
+        assert!(is_sealed(&bytes));
+        assert!(!bytes.windows(6).any(|w| w == b"secret"));
+        assert_eq!(cache.load(date(1)), Some(day));
+        assert_eq!(plain.load(date(1)), None);
+        let other = plain.encrypted(CacheCipher::new(&[4; 32]));
+        assert_eq!(other.load(date(1)), None);
+    }
+
+    #[test]
+    fn cached_source_serves_complete_days_from_disk() {
+        let dir = tempfile::tempdir().unwrap();
+        let inner = FakeSource {
+            heartbeats: vec![hb(100.0, "a")],
+            calls: RefCell::new(vec![]),
+        };
+        let source = CachedSource::new(&inner, HeartbeatCache::new(dir.path(), "k"), date(10));
+
+        assert_eq!(source.fetch_heartbeats(date(1)).unwrap().len(), 1);
+        assert_eq!(source.fetch_heartbeats(date(1)).unwrap().len(), 1);
+        assert_eq!(inner.calls.borrow().len(), 1);
+    }
+
+    #[test]
+    fn cached_source_fetches_deltas_for_recent_days() {
+        let dir = tempfile::tempdir().unwrap();
+        let inner = FakeSource {
+            heartbeats: vec![hb(100.0, "a"), hb(200.0, "a")],
+            calls: RefCell::new(vec![]),
+        };
+        let source = CachedSource::new(&inner, HeartbeatCache::new(dir.path(), "k"), date(10));
+
+        assert_eq!(source.fetch_heartbeats(date(9)).unwrap().len(), 2);
+        assert_eq!(source.fetch_heartbeats(date(9)).unwrap().len(), 2);
+        assert_eq!(
+            *inner.calls.borrow(),
+            vec![(date(9), None), (date(9), Some(200.0))]
+        );
+    }
+
+    #[test]
+    fn cached_source_policy_and_refresh() {
+        let dir = tempfile::tempdir().unwrap();
+        let inner = FakeSource {
+            heartbeats: vec![hb(100.0, "a")],
+            calls: RefCell::new(vec![]),
+        };
+        let cache = HeartbeatCache::new(dir.path(), "k");
+        let policy = CachePolicy {
+            ttl_minutes: 10.0,
+            revalidate_days: 3,
+        };
+        let source = CachedSource::new(&inner, cache.clone(), date(10)).policy(policy);
+
+        // Within the TTL a recent day is served from the cache.
+        source.fetch_heartbeats(date(10)).unwrap();
+        source.fetch_heartbeats(date(10)).unwrap();
+        assert_eq!(inner.calls.borrow().len(), 1);
+        // Three days back is still revalidated, four is final.
+        assert!(!cache.load(date(10)).unwrap().complete);
+        source.fetch_heartbeats(date(7)).unwrap();
+        source.fetch_heartbeats(date(6)).unwrap();
+        assert!(!cache.load(date(7)).unwrap().complete);
+        assert!(cache.load(date(6)).unwrap().complete);
+
+        let refreshing = CachedSource::new(&inner, cache, date(10)).refresh([date(6)]);
+        refreshing.fetch_heartbeats(date(6)).unwrap();
+        assert_eq!(inner.calls.borrow().last(), Some(&(date(6), None)));
+        assert_eq!(inner.calls.borrow().len(), 4);
+    }
+}
@@ -0,0 +1,55 @@
+//! Heartbeat categories (`coding`, `debugging`, `code reviewing`, `browsing`, `meeting`, ...).
+
+use crate::core::RawHeartbeat;
+
+/// Category of heartbeats that do not report one, as on the server.
+pub const CODING: &str = "coding";
+
+/// Lowercased category of a heartbeat, [`CODING`] when it has none.
+pub fn category_of(hb: &RawHeartbeat) -> String {
+    hb.category
+        .as_deref()
+        .map(str::trim)
+        .filter(|category| !category.is_empty())
+        .unwrap_or(CODING)
+        .to_lowercase()
+}
+
+/// Whether a heartbeat's category is one of `categories` (any case); all pass when empty.
+pub fn category_allowed(hb: &RawHeartbeat, categories: &[String]) -> bool {
+    if categories.is_empty() {
+        return true;
+    }
+    let category = category_of(hb);
+    categories
+        .iter()
+        .any(|allowed| allowed.trim().eq_ignore_ascii_case(&category))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hb(category: Option<&str>) -> RawHeartbeat {
+        RawHeartbeat {
+            category: category.map(Into::into),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn missing_category_is_coding() {
+        assert_eq!(category_of(&hb(None)), "coding");
+        assert_eq!(category_of(&hb(Some(" "))), "coding");
+        assert_eq!(category_of(&hb(Some("Code Reviewing"))), "code reviewing");
+    }
+
+    #[test]
+    fn restricts_to_listed_categories() {
+        let engineering = vec!["coding".to_string(), "Debugging".to_string()];
+        assert!(category_allowed(&hb(None), &engineering));
+        assert!(category_allowed(&hb(Some("debugging")), &engineering));
+        assert!(!category_allowed(&hb(Some("meeting")), &engineering));
+        assert!(category_allowed(&hb(Some("meeting")), &[]));
+    }
+}
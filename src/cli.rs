@@ -1,18 +1,74 @@
-use std::io::IsTerminal;
+use std::io::{IsTerminal, Write};
+use std::path::{Path, PathBuf};
 
-use clap::{Args, Parser, Subcommand};
+use chrono::{Duration, NaiveDate, NaiveTime};
+use clap::{Args, Parser, Subcommand, ValueEnum};
 use indicatif::{ProgressBar, ProgressFinish, ProgressStyle};
 
-use crate::client::{encode_api_key, WakapiClient, DEFAULT_BASE_URL};
+use crate::analysis::{Analysis, HeartbeatSource, MemorySource, MergedSource, Report};
+use crate::annotations::{normalize_id, state_path, Annotations};
+use crate::archive::{load_signing_key, parse_public_key, Archive, ArchiveSettings};
+use crate::billing::billing_totals;
+use crate::browsing::TOP_DOMAINS;
+use crate::cache::{cache_key, cache_root, CachedSource, HeartbeatCache};
+use crate::client::{encode_api_key, Provider, RetryPolicy, Source, WakapiClient};
+use crate::compare::compare;
 use crate::config::{
-    config_path, load_config, load_config_from, mask_secret, save_config_to, Config,
+    config_path, load_config, load_config_from, mask_secret, save_config_to, Config, Profile,
 };
+use crate::conflicts::ConflictPolicy;
 use crate::core::{
-    build_sessions, filter_sessions, group_heartbeats_by_local_date, iter_dates, month_last_day,
-    parse_month, week_range, DaySessions, RawHeartbeat, DEFAULT_MAX_GAP_SECONDS,
+    clamp_to_today, iter_dates, local_timestamp, parse_clock, parse_date, parse_span, week_range,
+    DaySessions, GapEstimator, Period, RawHeartbeat, DEFAULT_MAX_GAP_SECONDS,
 };
+use crate::corrections::{corrections_path, Corrections};
+use crate::digest::{digest_text, week_start, Digest};
+use crate::durations::duration_sessions;
+use crate::encryption::CacheCipher;
+use crate::entities::TOP_ENTITIES;
 use crate::error::{Result, WakalyzeError};
-use crate::format::build_lines;
+use crate::format::{
+    annotation_suffix, build_calendar_lines, build_chart_lines, build_compare_lines,
+    build_conflict_lines, build_data_quality_lines, build_domain_lines, build_entity_lines,
+    build_focus_lines, build_goal_lines, build_group_lines, build_heartbeat_lines,
+    build_heatmap_lines, build_lines_with, build_machine_lines, build_meeting_lines,
+    build_month_lines, build_project_lines, build_punch_card_lines, build_session_table_lines,
+    build_stats_lines, build_switch_lines, build_trace_lines, build_velocity_lines,
+    build_watch_lines, build_weekday_lines, chart_rows, format_duration, format_time, LineOptions,
+    Precision, HEATMAP_THRESHOLDS,
+};
+use crate::goals::{goal_status, Goal, GoalPeriod, GoalStatus};
+use crate::harvest::{harvest_rows, to_harvest_csv, HarvestMapping};
+use crate::i18n::Lang;
+use crate::input::load_heartbeats;
+use crate::inspect::{trace_at, trace_sessions, SessionTrace};
+use crate::invoice::{
+    convert_items, items_currency, line_items, load_fx_rates, render_invoice, Invoice, LineItems,
+    Markup,
+};
+use crate::json::{json_schema, to_json, to_ndjson_lines, JsonReport};
+use crate::manual::{manual_path, parse_time_range, ManualEntries, ManualEntry};
+use crate::meetings::{load_meetings, MeetingMode};
+use crate::plugin::run_plugin;
+use crate::rounding::{Rounding, RoundingMode, RoundingScope};
+use crate::sessions::session_rows;
+use crate::slack::{post_slack, slack_text};
+use crate::standup::standup_lines;
+use crate::stats::FocusFormula;
+use crate::summaries::summary_sessions;
+use crate::summary::{group_totals, GroupBy};
+use crate::theme::{Role, Theme};
+use crate::timesheet::{timesheet_entries, to_timesheet_csv, to_timesheet_xml};
+use crate::timewarrior::timew_commands;
+use crate::tui::{self, Dashboard};
+use crate::update;
+use crate::velocity::{
+    commit_times, merged_pr_times, parse_remote, remote_url, velocity_rows, Forge,
+};
+use crate::verify::{diff_days, diff_line};
+use crate::warm::{parse_period, warm, WarmOptions, WarmOutcome, WarmSummary};
+use crate::webhook::{parse_header, post_json};
+use crate::zone::{self, parse_timezone};
 
 #[derive(Parser)]
 #[command(
@@ -21,6 +77,15 @@ use crate::format::build_lines;
     about = "List Wakapi working hours per day"
 )]
 pub struct Cli {
+    /// Config profile to use (default: the config's default_profile)
+    #[arg(long, global = true)]
+    pub profile: Option<String>,
+
+    /// Time zone of day boundaries and displayed times, e.g. Asia/Tokyo (default: the
+    /// config's timezone, else the system's)
+    #[arg(long, global = true)]
+    pub timezone: Option<String>,
+
     #[command(subcommand)]
     pub command: Commands,
 }
@@ -34,269 +99,2557 @@ pub enum Commands {
         #[command(subcommand)]
         action: ConfigAction,
     },
+    /// Weekly or monthly hour targets: progress, time left, and the daily pace needed
+    Goals {
+        #[command(subcommand)]
+        action: GoalsAction,
+    },
+    /// Manage the local heartbeat cache
+    Cache {
+        #[command(subcommand)]
+        action: CacheAction,
+    },
+    /// Print the JSON Schema of `--format json` output
+    Schema,
+    /// Show the raw heartbeats behind the sessions of a day
+    Inspect(InspectArgs),
+    /// Print the heartbeats the server returns for a date, unprocessed
+    Heartbeats(HeartbeatsArgs),
+    /// List the sessions of a month/week with stable IDs (--format text, json, or ndjson)
+    Sessions(AnalyzeArgs),
+    /// Add a note or tags to a session (shown in reports and exports)
+    Tag(TagArgs),
+    /// Record time without heartbeats (calls, whiteboard sessions), merged into every analysis
+    Add(AddArgs),
+    /// Summarize a month/week for Slack (posted with --post-slack, printed otherwise)
+    Report(ReportArgs),
+    /// Invoice the billable time of a month/week (rates and clients from the config)
+    Invoice(InvoiceArgs),
+    /// Summarize a week in a few sentences for a weekly report email
+    Digest(DigestArgs),
+    /// Write the heartbeats, settings, and report of a month/week to one self-contained file
+    /// (`archive verify <file>` checks one)
+    Archive(ArchiveArgs),
+    /// Check the checksum, day hashes, and signature of an archive
+    #[command(name = "archive-verify", hide = true)]
+    ArchiveVerify(ArchiveVerifyArgs),
+    /// Refetch a month/week and report differences from the cache (or --from-archive)
+    Verify(AnalyzeArgs),
+    /// Browse months interactively: daily totals, projects, and sessions, with filters
+    Tui(AnalyzeArgs),
+    /// Total and per-project time of two periods side by side, with the change between them
+    Compare(CompareArgs),
+    /// Keep today's sessions and running total on screen, refetched every --interval seconds
+    Watch(WatchArgs),
+    /// Hours per commit (and per merged pull request) of the configured repositories, by month
+    Velocity(VelocityArgs),
+    /// Replace this binary with the latest release (checksum-verified)
+    SelfUpdate(SelfUpdateArgs),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// Human-readable session list
+    Text,
+    /// Single JSON document (see `wakalyze schema`)
+    Json,
+    /// One JSON record per session, then a summary record
+    Ndjson,
+    /// Harvest timesheet import CSV (see --harvest-map)
+    Harvest,
+    /// Timesheet XML laid out by the `timesheet` section of the config
+    TimesheetXml,
+    /// Timesheet CSV laid out by the `timesheet` section of the config
+    TimesheetCsv,
+    /// Terse per-day bullets for a daily standup (see --branches)
+    Standup,
+    /// `timew track` commands, one per session, to replay into Timewarrior
+    Timew,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum View {
+    /// Sessions listed per day
+    List,
+    /// Calendar grid with one row per week and the day totals
+    Calendar,
+    /// Contribution-style heatmap with a shade per day, for a month or a year
+    Heatmap,
+}
+
+/// The server to fetch from and how, shared by the commands that fetch heartbeats.
+#[derive(Args)]
+pub struct ConnectionArgs {
+    /// Wakapi user (or env WAKAPI_USER)
+    #[arg(long)]
+    pub user: Option<String>,
+
+    /// Wakapi base URL (or env WAKAPI_BASE_URL)
+    #[arg(long)]
+    pub base_url: Option<String>,
+
+    /// Server software, wakapi or wakatime (default: detected from the base URL)
+    #[arg(long, value_enum)]
+    pub provider: Option<Provider>,
+
+    /// HTTP request timeout in seconds
+    #[arg(long, default_value_t = 15.0)]
+    pub timeout: f64,
+
+    /// Further attempts after a timeout, connection error, 5xx, or 429 response
+    #[arg(long, default_value_t = 3)]
+    pub retries: u32,
+
+    /// Seconds before the first retry, doubled for each further one (a 429's Retry-After
+    /// wins)
+    #[arg(long, default_value_t = 1.0)]
+    pub retry_delay: f64,
+}
+
+impl ConnectionArgs {
+    /// The resolved account, then each of the config's `servers`.
+    fn connect(&self) -> Result<Vec<Server>> {
+        connect(
+            self.user.as_deref(),
+            self.base_url.as_deref(),
+            self.provider,
+            RetryPolicy::new(self.retries, self.retry_delay),
+            self.timeout,
+        )
+    }
+}
+
+#[derive(Args)]
+pub struct AnalyzeArgs {
+    /// Month as YYYY/MM (also YYYY-MM, YYYYMM, "Feb 2026", current, or last), a year as YYYY,
+    /// or today, yesterday, this-week, last-week, this-month, or last-month
+    #[arg(required_unless_present_any = ["week_of", "from"])]
+    pub month: Option<String>,
+
+    /// Week of month (1-6)
+    pub week: Option<u32>,
+
+    /// The week (Sunday to Saturday) containing this YYYY-MM-DD date, or `this` / `last`,
+    /// instead of a month
+    #[arg(long, conflicts_with = "month")]
+    pub week_of: Option<String>,
+
+    /// First day (YYYY-MM-DD) of an arbitrary range instead of a month, e.g. a sprint
+    #[arg(long, requires = "to", conflicts_with_all = ["month", "week_of"])]
+    pub from: Option<String>,
+
+    /// Last day (YYYY-MM-DD) of the --from range, inclusive
+    #[arg(long, requires = "from")]
+    pub to: Option<String>,
+
+    /// Filter by project substring (comma-separated terms = OR)
+    #[arg(short, long, visible_alias = "project")]
+    pub filter: Option<String>,
+
+    /// Leave out sessions whose project contains any of these terms (comma-separated)
+    #[arg(long)]
+    pub exclude: Option<String>,
+
+    /// Filter by project with a regular expression, e.g. '^client-(acme|globex)$'
+    #[arg(long)]
+    pub filter_regex: Option<String>,
+
+    /// Keep only sessions on a branch matching this glob, e.g. 'feature/*'
+    #[arg(long)]
+    pub branch: Option<String>,
+
+    #[command(flatten)]
+    pub connection: ConnectionArgs,
+
+    /// What the sessions are built from
+    #[arg(long, value_enum, default_value_t = Source::Heartbeats)]
+    pub source: Source,
+
+    /// Max gap in minutes between heartbeats to treat as continuous work
+    #[arg(long, default_value_t = DEFAULT_MAX_GAP_SECONDS as f64 / 60.0)]
+    pub max_gap_minutes: f64,
+
+    /// Output format
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    pub format: OutputFormat,
+
+    /// Layout of the text output
+    #[arg(long, value_enum, default_value_t = View::List)]
+    pub view: View,
+
+    /// POST the JSON report to this URL after the analysis
+    #[arg(long)]
+    pub post_url: Option<String>,
+
+    /// Extra header for --post-url, as `Name: value` (repeatable)
+    #[arg(long = "post-header", requires = "post_url")]
+    pub post_headers: Vec<String>,
+
+    /// JSON file mapping Wakapi projects to Harvest client/project/task
+    #[arg(long)]
+    pub harvest_map: Option<PathBuf>,
+
+    /// Round exported durations to this many minutes
+    #[arg(long)]
+    pub round_minutes: Option<u32>,
+
+    /// Round every session (or each day's total, see --round-scope) to this increment before
+    /// it is shown or summed, e.g. 15m; exports then use the rounded sessions as they are
+    #[arg(long, conflicts_with = "round_minutes")]
+    pub round: Option<String>,
+
+    /// Rounding direction for --round and --round-minutes
+    #[arg(long, value_enum, default_value_t = RoundingMode::Up)]
+    pub round_mode: RoundingMode,
+
+    /// Whether --round rounds every session or each day's total
+    #[arg(long, value_enum, default_value_t = RoundingScope::Session)]
+    pub round_scope: RoundingScope,
+
+    /// Day totals from which the heatmap's three darker shades start (e.g. 1h,3h,5h)
+    #[arg(long, value_delimiter = ',')]
+    pub heatmap_thresholds: Vec<String>,
+
+    /// Holidays to mark in the calendar view (comma-separated YYYY-MM-DD)
+    #[arg(long, value_delimiter = ',')]
+    pub holidays: Vec<NaiveDate>,
+
+    /// Append per-project totals with their share of the period (text output)
+    #[arg(long)]
+    pub projects: bool,
+
+    /// Draw a proportional bar next to each project total (implies --projects)
+    #[arg(long)]
+    pub bars: bool,
+
+    /// Append the period's time totalled by this session attribute (text output)
+    #[arg(long, value_enum, visible_alias = "group-by")]
+    pub by: Option<GroupBy>,
+
+    /// Append the time per billing code of the config's `billing_rules` (text output)
+    #[arg(long)]
+    pub billing: bool,
+
+    /// Append the top domains of `browsing` heartbeats, which are not counted as coding time
+    /// (text output)
+    #[arg(long)]
+    pub domains: bool,
+
+    /// Append the files worked on most, with the time estimated for each (text output; all
+    /// of them in JSON)
+    #[arg(long)]
+    pub entities: bool,
+
+    /// Append session statistics per day and for the period (text output)
+    #[arg(long)]
+    pub stats: bool,
+
+    /// Append project switches per day and the period average (text output)
+    #[arg(long)]
+    pub switches: bool,
+
+    /// Append a focus score (0-100) per day and the period average (text output)
+    #[arg(long)]
+    pub focus: bool,
+
+    /// Minimum session length in minutes that counts as deep work in the focus score
+    #[arg(long, default_value_t = FocusFormula::default().deep_seconds as f64 / 60.0)]
+    pub deep_minutes: f64,
+
+    /// Focus points deducted per project switch
+    #[arg(long, default_value_t = FocusFormula::default().switch_penalty)]
+    pub switch_penalty: f64,
+
+    /// Append a weekday × hour punch card of heartbeat activity (text output)
+    #[arg(long)]
+    pub punch_card: bool,
+
+    /// Append a bar chart of the hours per day, and per group with --by (text output)
+    #[arg(long)]
+    pub chart: bool,
+
+    /// Mark the longest session of each day with `*` (and color on terminals)
+    #[arg(long)]
+    pub highlight_longest: bool,
+
+    /// Show each session's dominant language next to its project
+    #[arg(long)]
+    pub languages: bool,
+
+    /// Fold sessions shorter than this (e.g. 3m, 90s) into one `misc` line per day (text
+    /// output; totals are unchanged)
+    #[arg(long)]
+    pub collapse_below: Option<String>,
+
+    /// Resolution of session and total durations in the text output
+    #[arg(long, value_enum, default_value_t = Precision::Minutes)]
+    pub precision: Precision,
+
+    /// Add a total after each day and one for the period
+    #[arg(long)]
+    pub totals: bool,
+
+    /// Break the --totals down by project
+    #[arg(long)]
+    pub project_totals: bool,
+
+    /// Language of the text labels (default: `lang` in the config, else en)
+    #[arg(long, value_enum)]
+    pub lang: Option<Lang>,
+
+    /// List days without sessions as `(no activity)` instead of leaving them out (text output)
+    #[arg(long)]
+    pub show_empty_days: bool,
+
+    /// List the sessions of every day of a year instead of monthly totals (text output)
+    #[arg(long)]
+    pub daily: bool,
+
+    /// List the branches worked on next to each project (standup output)
+    #[arg(long)]
+    pub branches: bool,
+
+    /// Append the average time per weekday over the period (text output)
+    #[arg(long)]
+    pub weekdays: bool,
+
+    /// Append windows where several machines sent heartbeats at once (text output)
+    #[arg(long)]
+    pub machines: bool,
+
+    /// Count overlapping time from several machines once, keeping the busiest machine
+    #[arg(long)]
+    pub dedupe_machines: bool,
+
+    /// Local time at which a day starts (HH:MM); earlier heartbeats count towards the previous day
+    #[arg(long)]
+    pub day_boundary: Option<String>,
+
+    /// UTC offset in which the server buckets `?date=` (e.g. +00:00); fetches only the server
+    /// days overlapping the period instead of one extra day on each side
+    #[arg(long)]
+    pub fetch_tz: Option<String>,
+
+    /// Also fetch the days after today when the period is still in progress
+    #[arg(long)]
+    pub include_future: bool,
+
+    /// Also ignore heartbeats whose file matches these globs, on top of `ignore_entities`
+    /// (comma-separated, e.g. '**/vendor/**,**/target/**')
+    #[arg(long, value_delimiter = ',')]
+    pub exclude_entity: Vec<String>,
+
+    /// Count only heartbeats of these categories, e.g. coding,debugging (no category counts as
+    /// coding)
+    #[arg(long, value_delimiter = ',')]
+    pub categories: Vec<String>,
+
+    /// Calendar (.ics) whose meetings are compared with the sessions
+    #[arg(long)]
+    pub meetings: Option<PathBuf>,
+
+    /// Whether session time during meetings is only reported or taken off the sessions
+    #[arg(long, value_enum, default_value_t = MeetingMode::Report)]
+    pub meetings_mode: MeetingMode,
+
+    /// Count the fetched heartbeats that were dropped or are suspect, by reason
+    #[arg(long)]
+    pub show_data_quality: bool,
+
+    /// Leave Saturdays and Sundays out of the fetch, the days, and the averages
+    #[arg(long)]
+    pub skip_weekends: bool,
+
+    /// Keep weekends even when `skip_weekends` is set in the config or archive
+    #[arg(long, conflicts_with = "skip_weekends")]
+    pub include_weekends: bool,
+
+    /// Which project wins when heartbeats with the same timestamp disagree
+    #[arg(long, value_enum, default_value_t = ConflictPolicy::Neighbors)]
+    pub conflict_policy: ConflictPolicy,
+
+    /// Append the timestamps where heartbeats disagreed on the project (text output)
+    #[arg(long)]
+    pub report_conflicts: bool,
+
+    /// Fail if the built sessions overlap, are out of order, or count more than their span
+    #[arg(long)]
+    pub verify: bool,
+
+    /// Refetch these dates in full instead of trusting the cache (comma-separated YYYY-MM-DD)
+    #[arg(long, value_delimiter = ',')]
+    pub refresh: Vec<NaiveDate>,
+
+    /// Fetch every day from the server, neither reading nor writing the cache
+    #[arg(long, conflicts_with = "refresh")]
+    pub no_cache: bool,
+
+    /// Analyze the heartbeats, manual entries, corrections, and project rules of an archive
+    /// instead of the server and config
+    #[arg(long)]
+    pub from_archive: Option<PathBuf>,
+
+    /// Analyze heartbeats exported as JSON instead of fetching them (`-` reads stdin; repeatable)
+    #[arg(long, conflicts_with = "from_archive")]
+    pub input: Vec<PathBuf>,
+}
+
+impl AnalyzeArgs {
+    /// Dates to refetch in full, or `None` with `--no-cache`.
+    fn cache_refresh(&self) -> Option<&[NaiveDate]> {
+        (!self.no_cache).then_some(self.refresh.as_slice())
+    }
+}
+
+#[derive(Args)]
+pub struct ReportArgs {
+    #[command(flatten)]
+    pub analyze: AnalyzeArgs,
+
+    /// Slack incoming webhook URL to post the summary to
+    #[arg(long)]
+    pub post_slack: Option<String>,
+
+    /// Target hours for the period, shown as goal progress
+    #[arg(long)]
+    pub goal_hours: Option<f64>,
+}
+
+#[derive(Args)]
+pub struct InvoiceArgs {
+    #[command(flatten)]
+    pub analyze: AnalyzeArgs,
+
+    /// Invoice number (default: INV-<period start as YYYYMMDD>)
+    #[arg(long)]
+    pub number: Option<String>,
+
+    /// Line item granularity
+    #[arg(long, value_enum, default_value_t = LineItems::Project)]
+    pub items: LineItems,
+
+    /// Invoice markup
+    #[arg(long, value_enum, default_value_t = Markup::Markdown)]
+    pub markup: Markup,
+
+    /// Template file with {{placeholder}} fields (overrides the config and built-in template)
+    #[arg(long)]
+    pub template: Option<PathBuf>,
+
+    /// Currency to convert every line item into (e.g. EUR); with --rate, the rate's currency
+    #[arg(long)]
+    pub currency: Option<String>,
+
+    /// Hourly rate of every line item, instead of the configured rates
+    #[arg(long)]
+    pub rate: Option<f64>,
+
+    /// Exchange rates for --currency: a JSON file or http(s) URL like {"base": "EUR", "rates": {...}}
+    #[arg(long, value_name = "FILE|URL", requires = "currency")]
+    pub fx_rates: Option<String>,
+}
+
+#[derive(Args)]
+pub struct ArchiveArgs {
+    #[command(flatten)]
+    pub analyze: AnalyzeArgs,
+
+    /// File to write the archive to
+    #[arg(long, short)]
+    pub output: PathBuf,
+
+    /// Sign the archive with the Ed25519 key whose 32-byte seed this file holds in hex
+    #[arg(long)]
+    pub sign_key: Option<PathBuf>,
+}
+
+#[derive(Args)]
+pub struct ArchiveVerifyArgs {
+    /// Archive written by `wakalyze archive`
+    pub file: PathBuf,
+
+    /// Require a signature by this public key (64 hex digits)
+    #[arg(long)]
+    pub public_key: Option<String>,
+}
+
+#[derive(Args)]
+pub struct DigestArgs {
+    /// `last`, `this`, or a YYYY-MM-DD date in the week (weeks start on Sunday)
+    #[arg(long, default_value = "last")]
+    pub week: String,
+
+    /// Filter by project substring (comma-separated terms = OR)
+    #[arg(short, long)]
+    pub filter: Option<String>,
+
+    /// Leave out sessions whose project contains any of these terms (comma-separated)
+    #[arg(long)]
+    pub exclude: Option<String>,
+
+    /// Filter by project with a regular expression, e.g. '^client-(acme|globex)$'
+    #[arg(long)]
+    pub filter_regex: Option<String>,
+
+    /// Target hours for the week, shown as goal status
+    #[arg(long)]
+    pub goal_hours: Option<f64>,
+
+    /// Sessions at least this many minutes long are called out
+    #[arg(long, default_value_t = 120.0)]
+    pub long_minutes: f64,
+
+    #[command(flatten)]
+    pub connection: ConnectionArgs,
+
+    /// Max gap in minutes between heartbeats to treat as continuous work
+    #[arg(long, default_value_t = DEFAULT_MAX_GAP_SECONDS as f64 / 60.0)]
+    pub max_gap_minutes: f64,
+}
+
+#[derive(Args)]
+pub struct CompareArgs {
+    /// The period compared against, as the month argument of `analyze` (e.g. 2026/01,
+    /// last-month, or 2025)
+    pub before: String,
+
+    /// The period compared with it
+    pub after: String,
+
+    /// Filter by project substring (comma-separated terms = OR)
+    #[arg(short, long)]
+    pub filter: Option<String>,
+
+    /// Leave out sessions whose project contains any of these terms (comma-separated)
+    #[arg(long)]
+    pub exclude: Option<String>,
+
+    /// Filter by project with a regular expression, e.g. '^client-(acme|globex)$'
+    #[arg(long)]
+    pub filter_regex: Option<String>,
+
+    /// Output format (text or json)
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    pub format: OutputFormat,
+
+    /// Compare heartbeats exported as JSON instead of fetching them (`-` reads stdin;
+    /// repeatable)
+    #[arg(long)]
+    pub input: Vec<PathBuf>,
+
+    #[command(flatten)]
+    pub connection: ConnectionArgs,
+
+    /// Max gap in minutes between heartbeats to treat as continuous work
+    #[arg(long, default_value_t = DEFAULT_MAX_GAP_SECONDS as f64 / 60.0)]
+    pub max_gap_minutes: f64,
+}
+
+#[derive(Args)]
+pub struct WatchArgs {
+    /// Seconds between refreshes
+    #[arg(long, default_value_t = 300)]
+    pub interval: u64,
+
+    /// Filter by project substring (comma-separated terms = OR)
+    #[arg(short, long)]
+    pub filter: Option<String>,
+
+    /// Leave out sessions whose project contains any of these terms (comma-separated)
+    #[arg(long)]
+    pub exclude: Option<String>,
+
+    /// Filter by project with a regular expression, e.g. '^client-(acme|globex)$'
+    #[arg(long)]
+    pub filter_regex: Option<String>,
+
+    #[command(flatten)]
+    pub connection: ConnectionArgs,
+
+    /// Max gap in minutes between heartbeats to treat as continuous work
+    #[arg(long, default_value_t = DEFAULT_MAX_GAP_SECONDS as f64 / 60.0)]
+    pub max_gap_minutes: f64,
+}
+
+#[derive(Args)]
+pub struct VelocityArgs {
+    /// Year (YYYY) or month (YYYY/MM)
+    pub period: String,
+
+    /// Only the commits of this author (name or email pattern; default: the checkout's
+    /// `user.email`)
+    #[arg(long)]
+    pub author: Option<String>,
+
+    /// Also count the pull requests merged in the period (GitHub with env GITHUB_TOKEN,
+    /// GitLab with env GITLAB_TOKEN)
+    #[arg(long)]
+    pub pull_requests: bool,
+
+    /// Output format (text or json)
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    pub format: OutputFormat,
+
+    #[command(flatten)]
+    pub connection: ConnectionArgs,
+
+    /// Max gap in minutes between heartbeats to treat as continuous work
+    #[arg(long, default_value_t = DEFAULT_MAX_GAP_SECONDS as f64 / 60.0)]
+    pub max_gap_minutes: f64,
 }
 
-#[derive(Args)]
-pub struct AnalyzeArgs {
-    /// Month in YYYY/MM format
-    pub month: String,
+#[derive(Args)]
+pub struct SelfUpdateArgs {
+    /// Only report whether a newer release is available
+    #[arg(long)]
+    pub check: bool,
+
+    /// HTTP request timeout in seconds
+    #[arg(long, default_value_t = 60.0)]
+    pub timeout: f64,
+}
+
+#[derive(Args)]
+pub struct InspectArgs {
+    /// Date in YYYY-MM-DD format
+    pub date: String,
+
+    /// Only the session in progress at this local time (HH:MM)
+    #[arg(long, conflicts_with = "session")]
+    pub at: Option<String>,
+
+    /// Only the Nth session of the day (1-based)
+    #[arg(long)]
+    pub session: Option<usize>,
+
+    #[command(flatten)]
+    pub connection: ConnectionArgs,
+
+    /// Max gap in minutes between heartbeats to treat as continuous work
+    #[arg(long, default_value_t = DEFAULT_MAX_GAP_SECONDS as f64 / 60.0)]
+    pub max_gap_minutes: f64,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum HeartbeatsFormat {
+    /// Aligned columns: local time, project, language, entity
+    #[default]
+    Table,
+    /// JSON array in the shape of the Wakapi payload
+    Json,
+}
+
+#[derive(Args)]
+pub struct HeartbeatsArgs {
+    /// Date in YYYY-MM-DD format, as the server buckets `?date=`
+    pub date: String,
+
+    /// Output format
+    #[arg(long, value_enum, default_value_t = HeartbeatsFormat::Table)]
+    pub format: HeartbeatsFormat,
+
+    #[command(flatten)]
+    pub connection: ConnectionArgs,
+}
+
+#[derive(Args)]
+pub struct TagArgs {
+    /// Session ID from `wakalyze sessions`
+    pub id: String,
+
+    /// Note to attach, replacing the current one
+    #[arg(long, conflicts_with = "clear_note")]
+    pub note: Option<String>,
+
+    /// Remove the note
+    #[arg(long)]
+    pub clear_note: bool,
+
+    /// Tag to add (repeatable)
+    #[arg(long = "tag", value_name = "TAG")]
+    pub tags: Vec<String>,
+
+    /// Tag to remove (repeatable)
+    #[arg(long, value_name = "TAG")]
+    pub untag: Vec<String>,
+}
+
+#[derive(Args)]
+pub struct AddArgs {
+    /// Date in YYYY-MM-DD format
+    pub date: String,
+
+    /// Local time range, HH:MM-HH:MM
+    pub range: String,
+
+    /// Project to book the time on
+    #[arg(long)]
+    pub project: String,
+
+    /// What the time was spent on
+    #[arg(long)]
+    pub note: Option<String>,
+}
+
+#[derive(Subcommand)]
+pub enum ConfigAction {
+    /// Print config file path
+    Path,
+    /// Show stored config values
+    Show,
+    /// Set stored config values
+    Set(ConfigSetArgs),
+}
+
+#[derive(Subcommand)]
+pub enum CacheAction {
+    /// Fetch every day of a period not yet cached, logging one line per day (for cron)
+    Warm(WarmArgs),
+    /// Delete the cached heartbeats of the account and the config's other servers
+    Clear(CacheClearArgs),
+}
+
+#[derive(Subcommand)]
+pub enum GoalsAction {
+    /// Progress of every goal in the config over the current week or month
+    Status(GoalsStatusArgs),
+    /// Add or replace the goal of a period and project (--hours 0 removes it)
+    Set(GoalsSetArgs),
+}
+
+#[derive(Args)]
+pub struct GoalsSetArgs {
+    /// Whether the hours are per week (Sunday to Saturday) or per calendar month
+    #[arg(long, value_enum)]
+    pub per: GoalPeriod,
+
+    /// Target hours
+    #[arg(long)]
+    pub hours: f64,
+
+    /// Only count this project (after renames); all projects if not given
+    #[arg(long)]
+    pub project: Option<String>,
+}
+
+#[derive(Args)]
+pub struct GoalsStatusArgs {
+    /// Output format (text or json)
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    pub format: OutputFormat,
+
+    /// Count heartbeats exported as JSON instead of fetching them (`-` reads stdin;
+    /// repeatable)
+    #[arg(long)]
+    pub input: Vec<PathBuf>,
+
+    #[command(flatten)]
+    pub connection: ConnectionArgs,
+
+    /// Max gap in minutes between heartbeats to treat as continuous work
+    #[arg(long, default_value_t = DEFAULT_MAX_GAP_SECONDS as f64 / 60.0)]
+    pub max_gap_minutes: f64,
+}
+
+#[derive(Args)]
+pub struct CacheClearArgs {
+    #[command(flatten)]
+    pub connection: ConnectionArgs,
+
+    /// Delete the cache of every account
+    #[arg(long, conflicts_with_all = ["user", "base_url"])]
+    pub all: bool,
+}
+
+#[derive(Args)]
+pub struct WarmArgs {
+    /// Year (YYYY) or month (YYYY/MM)
+    pub period: String,
+
+    /// Days fetched at once
+    #[arg(long, default_value_t = 4)]
+    pub concurrency: usize,
+
+    /// Maximum requests per second over all workers
+    #[arg(long, default_value_t = 4.0)]
+    pub max_rps: f64,
+
+    /// `--retries` and `--retry-delay` apply per day rather than per request
+    #[command(flatten)]
+    pub connection: ConnectionArgs,
+}
+
+#[derive(Args)]
+pub struct ConfigSetArgs {
+    /// Wakapi API token
+    #[arg(long)]
+    pub key: Option<String>,
+
+    /// Wakapi user
+    #[arg(long)]
+    pub user: Option<String>,
+
+    /// Wakapi base URL
+    #[arg(long)]
+    pub base_url: Option<String>,
+
+    /// Server software (wakapi or wakatime)
+    #[arg(long, value_enum)]
+    pub provider: Option<Provider>,
+
+    /// Remove stored key
+    #[arg(long)]
+    pub clear_key: bool,
+
+    /// Remove stored user
+    #[arg(long)]
+    pub clear_user: bool,
+
+    /// Remove stored base URL
+    #[arg(long)]
+    pub clear_base_url: bool,
+
+    /// Remove stored provider (detect it from the base URL again)
+    #[arg(long)]
+    pub clear_provider: bool,
+
+    /// Profile used when --profile is not given
+    #[arg(long)]
+    pub default_profile: Option<String>,
+
+    /// Use the top-level account again when --profile is not given
+    #[arg(long, conflicts_with = "default_profile")]
+    pub clear_default_profile: bool,
+
+    /// Time zone used when --timezone is not given (e.g. Asia/Tokyo)
+    #[arg(long)]
+    pub timezone: Option<String>,
+
+    /// Remove stored time zone (use the system's again)
+    #[arg(long, conflicts_with = "timezone")]
+    pub clear_timezone: bool,
+}
+
+fn non_empty(s: &str) -> Option<&str> {
+    let trimmed = s.trim();
+    (!trimmed.is_empty()).then_some(trimmed)
+}
+
+pub fn resolve_basic_auth(config: &Config) -> Result<String> {
+    if let Some(key) = config.key.as_deref().and_then(non_empty) {
+        return Ok(encode_api_key(key));
+    }
+    if let Some(key) = resolve_from_env("WAKAPI_KEY") {
+        return Ok(encode_api_key(&key));
+    }
+    Err(WakalyzeError::MissingAuth)
+}
+
+fn resolve_from_env(env_var: &str) -> Option<String> {
+    std::env::var(env_var)
+        .ok()
+        .as_deref()
+        .and_then(non_empty)
+        .map(str::to_owned)
+}
+
+fn resolve_field(arg: Option<&str>, config_val: Option<&str>, env_var: &str) -> Option<String> {
+    arg.and_then(non_empty)
+        .or_else(|| config_val.and_then(non_empty))
+        .map(str::to_owned)
+        .or_else(|| resolve_from_env(env_var))
+}
+
+fn resolve_user(args_user: Option<&str>, config: &Config) -> Result<String> {
+    resolve_field(args_user, config.user.as_deref(), "WAKAPI_USER")
+        .ok_or(WakalyzeError::MissingUser)
+}
+
+/// The base URL of `--base-url`, the config, or env; else the default of the provider.
+fn resolve_base_url(
+    args_url: Option<&str>,
+    args_provider: Option<Provider>,
+    config: &Config,
+) -> String {
+    resolve_field(args_url, config.base_url.as_deref(), "WAKAPI_BASE_URL").unwrap_or_else(|| {
+        args_provider
+            .or(config.provider)
+            .unwrap_or_default()
+            .default_base_url()
+            .to_string()
+    })
+}
+
+fn resolve_provider(args_provider: Option<Provider>, base_url: &str, config: &Config) -> Provider {
+    args_provider
+        .or(config.provider)
+        .unwrap_or_else(|| Provider::detect(base_url))
+}
+
+fn update_field(
+    target: &mut Option<String>,
+    value: Option<&str>,
+    clear: bool,
+    label: &str,
+) -> Result<bool> {
+    if clear && value.is_some() {
+        return Err(WakalyzeError::ConflictingFlags(format!(
+            "cannot use --{label} and --clear-{label} together"
+        )));
+    }
+    if clear {
+        *target = None;
+        return Ok(true);
+    }
+    match value {
+        None => Ok(false),
+        Some(v) => {
+            *target = non_empty(v).map(str::to_owned);
+            Ok(true)
+        }
+    }
+}
+
+pub fn handle_config(action: ConfigAction, profile: Option<&str>) -> Result<()> {
+    handle_config_with_path(action, &config_path(), profile)
+}
+
+/// The `user`, `base_url`, `provider`, and `key` lines of `config show`.
+fn account_lines(account: &Profile) -> Vec<String> {
+    let provider = match account.provider {
+        Some(Provider::Wakapi) => "wakapi",
+        Some(Provider::Wakatime) => "wakatime",
+        None => "(detected from base_url)",
+    };
+    let key_display = account
+        .key
+        .as_deref()
+        .map(mask_secret)
+        .unwrap_or_else(|| "(unset)".to_string());
+    vec![
+        format!("user: {}", account.user.as_deref().unwrap_or("(unset)")),
+        format!(
+            "base_url: {}",
+            account.base_url.as_deref().unwrap_or("(unset)")
+        ),
+        format!("provider: {provider}"),
+        format!("key: {key_display}"),
+    ]
+}
+
+/// `config` works on the top-level account, or on profile `profile` (created by `set`).
+pub fn handle_config_with_path(
+    action: ConfigAction,
+    path: &std::path::Path,
+    profile: Option<&str>,
+) -> Result<()> {
+    match action {
+        ConfigAction::Path => {
+            println!("{}", path.display());
+            Ok(())
+        }
+        ConfigAction::Show => {
+            let config = load_config_from(path);
+            println!("path: {}", path.display());
+            if let Some(name) = profile {
+                let account = config
+                    .profiles
+                    .get(name)
+                    .ok_or_else(|| WakalyzeError::UnknownProfile(name.to_string()))?;
+                println!("profile: {name}");
+                for line in account_lines(account) {
+                    println!("{line}");
+                }
+                return Ok(());
+            }
+            let top = Profile {
+                key: config.key.clone(),
+                user: config.user.clone(),
+                base_url: config.base_url.clone(),
+                provider: config.provider,
+            };
+            for line in account_lines(&top) {
+                println!("{line}");
+            }
+            if let Some(name) = &config.default_profile {
+                println!("default_profile: {name}");
+            }
+            if let Some(timezone) = &config.timezone {
+                println!("timezone: {timezone}");
+            }
+            for (name, account) in &config.profiles {
+                println!("profile {name}:");
+                for line in account_lines(account) {
+                    println!("  {line}");
+                }
+            }
+            Ok(())
+        }
+        ConfigAction::Set(args) => {
+            let mut config = load_config_from(path);
+            let mut updated = false;
+            let (key, user, base_url, provider) = match profile {
+                Some(name) => {
+                    let account = config.profiles.entry(name.to_string()).or_default();
+                    (
+                        &mut account.key,
+                        &mut account.user,
+                        &mut account.base_url,
+                        &mut account.provider,
+                    )
+                }
+                None => (
+                    &mut config.key,
+                    &mut config.user,
+                    &mut config.base_url,
+                    &mut config.provider,
+                ),
+            };
+            updated |= update_field(key, args.key.as_deref(), args.clear_key, "key")?;
+            updated |= update_field(user, args.user.as_deref(), args.clear_user, "user")?;
+            updated |= update_field(
+                base_url,
+                args.base_url.as_deref(),
+                args.clear_base_url,
+                "base-url",
+            )?;
+            if args.clear_provider && args.provider.is_some() {
+                return Err(WakalyzeError::ConflictingFlags(
+                    "cannot use --provider and --clear-provider together".to_string(),
+                ));
+            }
+            if args.provider.is_some() || args.clear_provider {
+                *provider = args.provider;
+                updated = true;
+            }
+            if let Some(name) = args.default_profile.as_deref().and_then(non_empty) {
+                if !config.profiles.contains_key(name) {
+                    return Err(WakalyzeError::UnknownProfile(name.to_string()));
+                }
+                config.default_profile = Some(name.to_string());
+                updated = true;
+            }
+            if args.clear_default_profile {
+                config.default_profile = None;
+                updated = true;
+            }
+            if let Some(timezone) = args.timezone.as_deref().and_then(non_empty) {
+                config.timezone = Some(parse_timezone(timezone)?.name().to_string());
+                updated = true;
+            }
+            if args.clear_timezone {
+                config.timezone = None;
+                updated = true;
+            }
+            if !updated {
+                return Err(WakalyzeError::NothingToUpdate);
+            }
+            save_config_to(path, &config)?;
+            Ok(())
+        }
+    }
+}
+
+pub fn handle_analyze(args: AnalyzeArgs) -> Result<()> {
+    let headers = args
+        .post_headers
+        .iter()
+        .map(|h| parse_header(h))
+        .collect::<Result<Vec<_>>>()?;
+    let (label, report) = run_analysis(&args)?;
+    print_report(&args, &label, &report)?;
+    if let Some(url) = args.post_url.as_deref() {
+        post_json(
+            url,
+            &JsonReport::new(&label, &report),
+            &headers,
+            args.connection.timeout,
+        )?;
+    }
+    Ok(())
+}
+
+fn print_report(args: &AnalyzeArgs, label: &str, report: &Report) -> Result<()> {
+    match args.format {
+        OutputFormat::Text => {
+            let options = LineOptions {
+                highlight_longest: args.highlight_longest,
+                theme: theme(std::io::stdout().is_terminal())?,
+                languages: args.languages,
+                lang: output_lang(args),
+                collapse_below: args
+                    .collapse_below
+                    .as_deref()
+                    .map(parse_span)
+                    .transpose()?
+                    .unwrap_or(0),
+                precision: args.precision,
+                totals: args.totals || args.project_totals,
+                project_totals: args.project_totals,
+            };
+            let mut lines = match args.view {
+                View::List if is_year(args) && !args.daily => {
+                    let days = if args.show_empty_days {
+                        report.all_days()
+                    } else {
+                        report.days.clone()
+                    };
+                    build_month_lines(&days, label, &options)
+                }
+                View::List if args.show_empty_days => {
+                    build_lines_with(&report.all_days(), label, &options)
+                }
+                View::List => build_lines_with(&report.days, label, &options),
+                View::Calendar => build_calendar_lines(
+                    label,
+                    report.start,
+                    report.end,
+                    &report.summary.days,
+                    &args.holidays,
+                    &options.theme,
+                    options.lang,
+                ),
+                View::Heatmap => build_heatmap_lines(
+                    label,
+                    report.start,
+                    report.end,
+                    &report.summary.days,
+                    heatmap_thresholds(&args.heatmap_thresholds)?,
+                    options.lang,
+                ),
+            };
+            if args.projects || args.bars {
+                lines.push(String::new());
+                lines.extend(build_project_lines(
+                    &report.summary.projects,
+                    args.bars,
+                    args.precision,
+                ));
+            }
+            if let Some(by) = args.by {
+                lines.push(String::new());
+                lines.extend(build_group_lines(
+                    by.label(),
+                    &group_totals(&report.days, by, &load_config().clients),
+                    args.bars,
+                    args.precision,
+                ));
+            }
+            if args.billing {
+                lines.push(String::new());
+                lines.extend(build_group_lines(
+                    "billing codes",
+                    &billing_totals(&report.days, &load_config().billing_rules),
+                    args.bars,
+                    args.precision,
+                ));
+            }
+            if args.domains {
+                lines.push(String::new());
+                lines.extend(build_domain_lines(&report.domains, TOP_DOMAINS));
+            }
+            if args.entities {
+                lines.push(String::new());
+                lines.extend(build_entity_lines(&report.entities, TOP_ENTITIES));
+            }
+            if args.meetings.is_some() {
+                lines.push(String::new());
+                lines.extend(build_meeting_lines(
+                    &report.meeting_overlaps,
+                    args.meetings_mode == MeetingMode::Exclude,
+                ));
+            }
+            if args.show_data_quality {
+                lines.push(String::new());
+                lines.extend(build_data_quality_lines(&report.sanitized));
+            }
+            if args.stats {
+                lines.push(String::new());
+                lines.extend(build_stats_lines(&report.summary));
+            }
+            if args.switches {
+                lines.push(String::new());
+                lines.extend(build_switch_lines(&report.summary));
+            }
+            if args.focus {
+                lines.push(String::new());
+                lines.extend(build_focus_lines(&report.summary));
+            }
+            if args.weekdays {
+                lines.push(String::new());
+                lines.extend(build_weekday_lines(&report.summary.weekdays, options.lang));
+            }
+            if args.punch_card {
+                lines.push(String::new());
+                lines.extend(build_punch_card_lines(&report.punch_card, options.lang));
+            }
+            if args.chart {
+                let width = terminal_width();
+                let by_month = is_year(args) && !args.daily;
+                lines.push(String::new());
+                lines.extend(build_chart_lines(
+                    "chart",
+                    &chart_rows(&report.all_days(), by_month, options.lang),
+                    width,
+                ));
+                if let Some(by) = args.by {
+                    let rows: Vec<(String, i64)> =
+                        group_totals(&report.days, by, &load_config().clients)
+                            .into_iter()
+                            .map(|g| (g.name.unwrap_or_else(|| "unknown".into()), g.seconds))
+                            .collect();
+                    lines.push(String::new());
+                    lines.extend(build_chart_lines(by.label(), &rows, width));
+                }
+            }
+            if args.machines {
+                lines.push(String::new());
+                lines.extend(build_machine_lines(&report.machine_overlaps));
+            }
+            if args.report_conflicts {
+                lines.push(String::new());
+                lines.extend(build_conflict_lines(&report.project_conflicts));
+            }
+            if !report.corrections.is_empty() {
+                lines.push(String::new());
+                lines.push("corrections".to_string());
+                lines.extend(
+                    report
+                        .corrections
+                        .iter()
+                        .map(|c| format!("- {}", c.summary())),
+                );
+            }
+            for line in lines {
+                println!("{line}");
+            }
+        }
+        OutputFormat::Json => println!("{}", to_json(label, report)?),
+        OutputFormat::Ndjson => {
+            for line in to_ndjson_lines(label, report)? {
+                println!("{line}");
+            }
+        }
+        OutputFormat::Harvest => {
+            let mapping = match &args.harvest_map {
+                Some(path) => HarvestMapping::load(path)?,
+                None => HarvestMapping::default(),
+            };
+            let rows = harvest_rows(&report.days, &mapping, rounding(args).as_ref());
+            for line in to_harvest_csv(&rows) {
+                println!("{line}");
+            }
+        }
+        OutputFormat::Standup => {
+            for line in standup_lines(&report.days, zone::today(), args.branches) {
+                println!("{line}");
+            }
+        }
+        OutputFormat::Timew => {
+            for line in timew_commands(&report.days) {
+                println!("{line}");
+            }
+        }
+        OutputFormat::TimesheetXml | OutputFormat::TimesheetCsv => {
+            let layout = load_config().timesheet.unwrap_or_default();
+            let entries = timesheet_entries(&report.days, &layout, rounding(args).as_ref());
+            let lines = if args.format == OutputFormat::TimesheetXml {
+                to_timesheet_xml(&entries, &layout)
+            } else {
+                to_timesheet_csv(&entries, &layout)
+            };
+            for line in lines {
+                println!("{line}");
+            }
+        }
+    }
+    Ok(())
+}
+
+/// `--heatmap-thresholds`: three increasing day totals, or the defaults if none are given.
+fn heatmap_thresholds(values: &[String]) -> Result<[i64; 3]> {
+    if values.is_empty() {
+        return Ok(HEATMAP_THRESHOLDS);
+    }
+    let invalid = || WakalyzeError::InvalidThresholds(values.join(","));
+    let spans = values
+        .iter()
+        .map(|value| parse_span(value))
+        .collect::<Result<Vec<_>>>()?;
+    let thresholds: [i64; 3] = spans.try_into().map_err(|_| invalid())?;
+    if thresholds[0] <= 0 || thresholds.windows(2).any(|pair| pair[0] >= pair[1]) {
+        return Err(invalid());
+    }
+    Ok(thresholds)
+}
+
+/// Columns of the terminal, else `$COLUMNS`, else 80.
+fn terminal_width() -> usize {
+    terminal_size::terminal_size()
+        .map(|(terminal_size::Width(width), _)| usize::from(width))
+        .or_else(|| std::env::var("COLUMNS").ok()?.parse().ok())
+        .unwrap_or(80)
+}
+
+fn rounding(args: &AnalyzeArgs) -> Option<Rounding> {
+    args.round_minutes
+        .map(|minutes| Rounding::new(i64::from(minutes) * 60, args.round_mode))
+}
+
+pub fn handle_schema() -> Result<()> {
+    println!("{}", serde_json::to_string_pretty(&json_schema())?);
+    Ok(())
+}
+
+/// Run the analysis and pipe the result to an external `wakalyze-<name>` plugin.
+pub fn handle_plugin(plugin: &Path, args: AnalyzeArgs) -> Result<()> {
+    let (label, report) = run_analysis(&args)?;
+    run_plugin(plugin, &JsonReport::new(&label, &report))
+}
+
+pub fn handle_report(args: ReportArgs) -> Result<()> {
+    let (label, report) = run_analysis(&args.analyze)?;
+    let goal = args.goal_hours.map(|hours| (hours * 3600.0) as i64);
+    let text = slack_text(&label, &report, goal);
+    match args.post_slack.as_deref() {
+        Some(url) => post_slack(url, &text, args.analyze.connection.timeout),
+        None => {
+            println!("{text}");
+            Ok(())
+        }
+    }
+}
+
+pub fn handle_invoice(args: InvoiceArgs) -> Result<()> {
+    let (_, report) = run_analysis(&args.analyze)?;
+    let mut config = load_config().invoice.unwrap_or_default();
+    if let Some(rate) = args.rate {
+        config = config.with_rate(rate, args.currency.as_deref());
+    }
+    let mut items = line_items(
+        &report.days,
+        &config,
+        args.items,
+        rounding(&args.analyze).as_ref(),
+    );
+    if let Some(currency) = args.currency.as_deref() {
+        let fx = args
+            .fx_rates
+            .as_deref()
+            .map(|source| load_fx_rates(source, args.analyze.connection.timeout))
+            .transpose()?;
+        convert_items(&mut items, currency, fx.as_ref())?;
+    }
+    let currency = match args.currency.as_deref() {
+        Some(currency) => currency,
+        None => items_currency(&items, &config)?,
+    };
+    let template = match args.template.as_ref().or(config.template.as_ref()) {
+        Some(path) => std::fs::read_to_string(path)?,
+        None => args.markup.default_template().to_string(),
+    };
+    let number = args
+        .number
+        .unwrap_or_else(|| report.start.format("INV-%Y%m%d").to_string());
+    let invoice = Invoice {
+        number: &number,
+        issued: zone::today(),
+        start: report.start,
+        end: report.end,
+        items: &items,
+        currency,
+        config: &config,
+    };
+    print!("{}", render_invoice(&template, &invoice, args.markup));
+    Ok(())
+}
+
+/// Fetch the period, then write its heartbeats, settings, and report to one file.
+pub fn handle_archive(args: ArchiveArgs) -> Result<()> {
+    let analyze = &args.analyze;
+    let (label, start, end) = analysis_period(analyze)?;
+    let config = load_config();
+    let settings = analysis_settings(analyze, &config, None)?;
+    let analysis = configure(analyze, settings.clone(), start, end)?;
+
+    let servers = analyze.connection.connect()?;
+    let heartbeats = fetch_with_progress(&analysis, &servers, analyze.cache_refresh(), |source| {
+        analysis
+            .fetch_dates()
+            .into_iter()
+            .map(|date| source.fetch_heartbeats(date))
+            .collect::<Result<Vec<_>>>()
+    })?
+    .concat();
+    // Analyze what is archived rather than the server, so a replay gives the same report.
+    let mut report = analysis.run(&MemorySource::new(heartbeats.clone()))?;
+    let user = resolve_user(analyze.connection.user.as_deref(), &config)?;
+    Annotations::load(&state_path())?.apply(&user, &mut report.days);
+    warn_sanitized(&report);
+
+    let base_url = resolve_base_url(
+        analyze.connection.base_url.as_deref(),
+        analyze.connection.provider,
+        &config,
+    );
+    let mut archive = Archive::new(&user, &base_url, &label, &report, settings, heartbeats)?;
+    if let Some(path) = args.sign_key.as_deref() {
+        archive.sign(&load_signing_key(path)?);
+    }
+    archive.save(&args.output)?;
+    eprintln!(
+        "archived {label}: {} heartbeats, {} ({})",
+        archive.heartbeats.len(),
+        format_duration(report.summary.seconds),
+        archive.checksum
+    );
+    Ok(())
+}
+
+pub fn handle_archive_verify(args: ArchiveVerifyArgs) -> Result<()> {
+    let expected = args
+        .public_key
+        .as_deref()
+        .map(parse_public_key)
+        .transpose()?;
+    let archive = Archive::read(&args.file)?;
+    let check = archive.check(expected.as_ref())?;
+    let status = |ok: bool| if ok { "ok" } else { "MISMATCH" };
+    println!(
+        "checksum: {} ({})",
+        status(check.checksum_ok),
+        archive.checksum
+    );
+    if archive.day_hashes.is_empty() {
+        println!("day hashes: none (archive written before they were added)");
+    } else if check.altered_days.is_empty() {
+        println!("day hashes: ok ({} days)", archive.day_hashes.len());
+    } else {
+        for date in &check.altered_days {
+            println!("day hashes: {date} altered");
+        }
+    }
+    match (check.signature_ok, &archive.signature) {
+        (None, _) => println!("signature: none"),
+        (Some(ok), Some(signature)) => println!(
+            "signature: {} (key {})",
+            if ok { "ok" } else { "INVALID" },
+            signature.public_key
+        ),
+        (Some(_), None) => println!("signature: missing"),
+    }
+    if check.is_ok() {
+        Ok(())
+    } else {
+        Err(WakalyzeError::InvalidArchive(format!(
+            "{}: failed verification",
+            args.file.display()
+        )))
+    }
+}
+
+/// Compare the cached (or archived) heartbeats of the period with what the server returns now.
+pub fn handle_verify(args: AnalyzeArgs) -> Result<()> {
+    let (label, start, end) = analysis_period(&args)?;
+    let config = load_config();
+    let archive = args
+        .from_archive
+        .as_deref()
+        .map(Archive::load)
+        .transpose()?;
+    let analysis = configure(
+        &args,
+        analysis_settings(&args, &config, archive.as_ref())?,
+        start,
+        end,
+    )?;
+    let servers = args.connection.connect()?;
+
+    let mut dates = analysis.fetch_dates();
+    let local = match &archive {
+        Some(archive) => archive.heartbeats.clone(),
+        None => {
+            let requested = dates.len();
+            dates.retain(|&date| servers.iter().all(|(_, cache)| cache.load(date).is_some()));
+            if dates.len() < requested {
+                eprintln!(
+                    "skipping {} days that are not cached",
+                    requested - dates.len()
+                );
+            }
+            let cached = MergedSource::new(
+                servers
+                    .iter()
+                    .map(|(_, cache)| {
+                        move |date| -> Result<Vec<RawHeartbeat>> {
+                            Ok(cache
+                                .load(date)
+                                .map(|day| day.heartbeats)
+                                .unwrap_or_default())
+                        }
+                    })
+                    .collect(),
+            );
+            dates
+                .iter()
+                .map(|&date| cached.fetch_heartbeats(date))
+                .collect::<Result<Vec<_>>>()?
+                .concat()
+        }
+    };
+    let server = merged_servers(&servers);
+    let pb = progress_bar(dates.len());
+    let fresh = dates
+        .iter()
+        .map(|&date| {
+            let heartbeats = server.fetch_heartbeats(date);
+            pb.inc(1);
+            heartbeats
+        })
+        .collect::<Result<Vec<_>>>()?
+        .concat();
+    pb.finish_and_clear();
+
+    let local_report = analysis.run(&MemorySource::new(local.clone()))?;
+    let fresh_report = analysis.run(&MemorySource::new(fresh.clone()))?;
+    let diffs = diff_days(&local, &fresh, &local_report, &fresh_report);
+    for diff in &diffs {
+        println!("{}", diff_line(diff));
+    }
+    if diffs.is_empty() {
+        println!("{label}: {} heartbeats match the server", fresh.len());
+        return Ok(());
+    }
+    println!(
+        "{label}: {} days differ, {} -> {}",
+        diffs.len(),
+        format_duration(local_report.summary.seconds),
+        format_duration(fresh_report.summary.seconds)
+    );
+    Err(WakalyzeError::DataChanged(diffs.len()))
+}
+
+pub fn handle_goals(action: GoalsAction) -> Result<()> {
+    match action {
+        GoalsAction::Status(args) => handle_goals_status(args),
+        GoalsAction::Set(args) => handle_goals_set(args, &config_path()),
+    }
+}
+
+fn handle_goals_set(args: GoalsSetArgs, path: &std::path::Path) -> Result<()> {
+    if args.hours.is_nan() || args.hours < 0.0 {
+        return Err(WakalyzeError::InvalidGoalHours(args.hours));
+    }
+    let mut config = load_config_from(path);
+    config
+        .goals
+        .retain(|goal| !(goal.period == args.per && goal.project == args.project));
+    let period = match args.per {
+        GoalPeriod::Week => "week",
+        GoalPeriod::Month => "month",
+    };
+    let project = args.project.as_deref().unwrap_or("all projects");
+    if args.hours > 0.0 {
+        config.goals.push(Goal {
+            period: args.per,
+            hours: args.hours,
+            project: args.project.clone(),
+        });
+        println!("goal set: {}h per {period} on {project}", args.hours);
+    } else {
+        println!("goal removed: per {period} on {project}");
+    }
+    save_config_to(path, &config)
+}
+
+fn handle_goals_status(args: GoalsStatusArgs) -> Result<()> {
+    if !matches!(args.format, OutputFormat::Text | OutputFormat::Json) {
+        return Err(WakalyzeError::ConflictingFlags(
+            "goals status supports --format text or json".to_string(),
+        ));
+    }
+    let config = load_config();
+    if config.goals.is_empty() {
+        eprintln!("no goals in the config; add one with `wakalyze goals set`");
+        return Ok(());
+    }
+    let max_gap_seconds = (args.max_gap_minutes * 60.0) as i64;
+    if max_gap_seconds <= 0 {
+        return Err(WakalyzeError::InvalidMaxGap);
+    }
+    let today = zone::today();
+    let start = config
+        .goals
+        .iter()
+        .map(|goal| goal.period.range(today).0)
+        .min()
+        .unwrap_or(today);
+
+    let settings = ArchiveSettings {
+        max_gap_seconds,
+        ..configured_settings(&config)?
+    };
+    let analysis = settings.analysis(start, today)?;
+    let report = if args.input.is_empty() {
+        let servers = args.connection.connect()?;
+        fetch_with_progress(&analysis, &servers, Some(&[]), |source| {
+            analysis.run(source)
+        })?
+    } else {
+        analysis.run(&MemorySource::new(load_heartbeats(&args.input)?))?
+    };
+    warn_sanitized(&report);
+
+    let statuses: Vec<GoalStatus> = config
+        .goals
+        .iter()
+        .map(|goal| goal_status(goal, &report.days, today, settings.skip_weekends))
+        .collect();
+    match args.format {
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&statuses)?),
+        _ => {
+            for line in build_goal_lines(&statuses) {
+                println!("{line}");
+            }
+        }
+    }
+    Ok(())
+}
+
+pub fn handle_cache(action: CacheAction) -> Result<()> {
+    match action {
+        CacheAction::Warm(args) => handle_warm(args),
+        CacheAction::Clear(args) => handle_cache_clear(args),
+    }
+}
+
+fn handle_cache_clear(args: CacheClearArgs) -> Result<()> {
+    let root = cache_root();
+    if args.all {
+        match std::fs::remove_dir_all(&root) {
+            Ok(()) => println!("removed {}", root.display()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => println!("nothing cached"),
+            Err(e) => return Err(e.into()),
+        }
+        return Ok(());
+    }
+    let config = load_config();
+    let user = resolve_user(args.connection.user.as_deref(), &config)?;
+    let main = cache_key(
+        &user,
+        &resolve_base_url(
+            args.connection.base_url.as_deref(),
+            args.connection.provider,
+            &config,
+        ),
+    );
+    let others = config
+        .servers
+        .iter()
+        .map(|server| cache_key(server.user.as_deref().unwrap_or(&user), &server.base_url));
+    for account in std::iter::once(main).chain(others) {
+        let days = HeartbeatCache::new(&root, &account).clear()?;
+        println!("{account}: removed {days} cached days");
+    }
+    Ok(())
+}
+
+fn handle_warm(args: WarmArgs) -> Result<()> {
+    let today = zone::today();
+    let (start, end) = parse_period(&args.period)?;
+    let (start, end) = clamp_to_today(start, end, today, false)?;
+    let connection = &args.connection;
+    let servers = connect(
+        connection.user.as_deref(),
+        connection.base_url.as_deref(),
+        connection.provider,
+        // `warm` retries failed days itself, `--retries` times.
+        RetryPolicy::default(),
+        connection.timeout,
+    )?;
+    let options = WarmOptions {
+        concurrency: args.concurrency,
+        retries: connection.retries,
+        backoff: std::time::Duration::from_secs_f64(connection.retry_delay.max(0.0)),
+        min_interval: if args.max_rps > 0.0 {
+            std::time::Duration::from_secs_f64(1.0 / args.max_rps)
+        } else {
+            std::time::Duration::ZERO
+        },
+    };
+    let dates = iter_dates(start, end);
+    let mut summary = WarmSummary::default();
+    for (client, cache) in servers {
+        eprintln!(
+            "warming {} days from {start} to {end} on {}",
+            dates.len(),
+            client.base_url()
+        );
+        let server = warm(
+            &client,
+            cache,
+            &dates,
+            today,
+            options,
+            |date, outcome| match outcome {
+                WarmOutcome::Cached => eprintln!("{date} cached"),
+                WarmOutcome::Fetched {
+                    heartbeats,
+                    attempts,
+                } if *attempts > 1 => {
+                    eprintln!("{date} fetched {heartbeats} heartbeats after {attempts} attempts")
+                }
+                WarmOutcome::Fetched { heartbeats, .. } => {
+                    eprintln!("{date} fetched {heartbeats} heartbeats")
+                }
+                WarmOutcome::Failed(error) => eprintln!("{date} failed: {error}"),
+            },
+        );
+        summary.cached += server.cached;
+        summary.fetched += server.fetched;
+        summary.failed += server.failed;
+    }
+    eprintln!(
+        "done: {} fetched, {} already cached, {} failed",
+        summary.fetched, summary.cached, summary.failed
+    );
+    if summary.failed > 0 {
+        return Err(WakalyzeError::WarmFailed(summary.failed));
+    }
+    Ok(())
+}
+
+/// Analyze the digest week together with the week before it, for the deltas.
+pub fn handle_digest(args: DigestArgs) -> Result<()> {
+    let today = zone::today();
+    let start = week_start(&args.week, today)?;
+    let (_, end) = clamp_to_today(start, start + Duration::days(6), today, false)?;
+    let previous_start = start - Duration::days(7);
+    let max_gap_seconds = (args.max_gap_minutes * 60.0) as i64;
+    if max_gap_seconds <= 0 {
+        return Err(WakalyzeError::InvalidMaxGap);
+    }
+
+    let servers = args.connection.connect()?;
+    let settings = ArchiveSettings {
+        max_gap_seconds,
+        filter: args.filter.clone(),
+        exclude: args.exclude.clone(),
+        filter_regex: args.filter_regex.clone(),
+        ..configured_settings(&load_config())?
+    };
+    let analysis = settings.analysis(previous_start, end)?;
+    let report = fetch_with_progress(&analysis, &servers, Some(&[]), |source| {
+        analysis.run(source)
+    })?;
+    warn_sanitized(&report);
+
+    let (previous, days) = report
+        .days
+        .split_at(report.days.partition_point(|d| d.date < start));
+    let text = digest_text(&Digest {
+        start,
+        end: start + Duration::days(6),
+        days,
+        previous,
+        goal_seconds: args.goal_hours.map(|hours| (hours * 3600.0) as i64),
+        long_seconds: (args.long_minutes * 60.0) as i64,
+    });
+    println!("{text}");
+    Ok(())
+}
+
+/// Analyze both periods and print the change from the first to the second.
+pub fn handle_compare(args: CompareArgs) -> Result<()> {
+    if !matches!(args.format, OutputFormat::Text | OutputFormat::Json) {
+        return Err(WakalyzeError::ConflictingFlags(
+            "compare supports --format text or json".to_string(),
+        ));
+    }
+    let today = zone::today();
+    let before = Period::parse(&args.before)?;
+    let after = Period::parse(&args.after)?;
+    let max_gap_seconds = (args.max_gap_minutes * 60.0) as i64;
+    if max_gap_seconds <= 0 {
+        return Err(WakalyzeError::InvalidMaxGap);
+    }
+
+    let settings = ArchiveSettings {
+        max_gap_seconds,
+        filter: args.filter.clone(),
+        exclude: args.exclude.clone(),
+        filter_regex: args.filter_regex.clone(),
+        ..configured_settings(&load_config())?
+    };
+    let servers = if args.input.is_empty() {
+        args.connection.connect()?
+    } else {
+        Vec::new()
+    };
+    let heartbeats = if args.input.is_empty() {
+        None
+    } else {
+        Some(MemorySource::new(load_heartbeats(&args.input)?))
+    };
+    let mut days = Vec::new();
+    for period in [before, after] {
+        let (start, end) = clamp_to_today(period.first_day(), period.last_day(), today, false)?;
+        let analysis = settings.analysis(start, end)?;
+        let report = match &heartbeats {
+            Some(source) => analysis.run(source)?,
+            None => fetch_with_progress(&analysis, &servers, Some(&[]), |source| {
+                analysis.run(source)
+            })?,
+        };
+        warn_sanitized(&report);
+        days.push(report.days);
+    }
+
+    let comparison = compare(&before.label(), &days[0], &after.label(), &days[1]);
+    match args.format {
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&comparison)?),
+        _ => {
+            for line in build_compare_lines(&comparison) {
+                println!("{line}");
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Refetch today every `--interval` seconds and redraw its sessions until interrupted. A
+/// failed refresh keeps the last sessions on screen and shows the error below them.
+pub fn handle_watch(args: WatchArgs) -> Result<()> {
+    if args.interval == 0 {
+        return Err(WakalyzeError::InvalidInterval);
+    }
+    let max_gap_seconds = (args.max_gap_minutes * 60.0) as i64;
+    if max_gap_seconds <= 0 {
+        return Err(WakalyzeError::InvalidMaxGap);
+    }
+    let servers = args.connection.connect()?;
+    let settings = ArchiveSettings {
+        max_gap_seconds,
+        filter: args.filter.clone(),
+        exclude: args.exclude.clone(),
+        filter_regex: args.filter_regex.clone(),
+        ..configured_settings(&load_config())?
+    };
+    let terminal = std::io::stdout().is_terminal();
+    let options = LineOptions {
+        theme: theme(terminal)?,
+        lang: load_config().lang.unwrap_or_default(),
+        ..Default::default()
+    };
+    let mut shown = Vec::new();
+    loop {
+        let today = zone::today();
+        let refreshed = settings.analysis(today, today).and_then(|analysis| {
+            fetch_with_progress(&analysis, &servers, Some(&[today]), |source| {
+                analysis.run(source)
+            })
+        });
+        let now = chrono::Utc::now().timestamp();
+        let status = match refreshed {
+            Ok(report) => {
+                let day = report.all_days().into_iter().next().unwrap_or(DaySessions {
+                    date: today,
+                    sessions: Vec::new(),
+                });
+                shown = build_watch_lines(&day, &today.to_string(), now, max_gap_seconds, &options);
+                format!("updated {}", format_time(now))
+            }
+            Err(e) => format!("error: {e}"),
+        };
+        if terminal {
+            // Clear the screen and move to its top left.
+            print!("\x1b[2J\x1b[H");
+        } else {
+            println!();
+        }
+        for line in &shown {
+            println!("{line}");
+        }
+        println!();
+        println!("{status}, next in {}s (Ctrl-C to stop)", args.interval);
+        std::io::stdout().flush()?;
+        std::thread::sleep(std::time::Duration::from_secs(args.interval));
+    }
+}
+
+pub fn handle_velocity(args: VelocityArgs) -> Result<()> {
+    if !matches!(args.format, OutputFormat::Text | OutputFormat::Json) {
+        return Err(WakalyzeError::ConflictingFlags(
+            "velocity supports --format text or json".to_string(),
+        ));
+    }
+    let (start, end) = parse_period(&args.period)?;
+    let (start, end) = clamp_to_today(start, end, zone::today(), false)?;
+    let max_gap_seconds = (args.max_gap_minutes * 60.0) as i64;
+    if max_gap_seconds <= 0 {
+        return Err(WakalyzeError::InvalidMaxGap);
+    }
+    let config = load_config();
+    if config.repositories.is_empty() {
+        eprintln!("no repositories in the config; map projects to checkouts under `repositories`");
+        return Ok(());
+    }
+
+    let servers = args.connection.connect()?;
+    let settings = ArchiveSettings {
+        max_gap_seconds,
+        ..configured_settings(&config)?
+    };
+    let analysis = settings.analysis(start, end)?;
+    let report = fetch_with_progress(&analysis, &servers, Some(&[]), |source| {
+        analysis.run(source)
+    })?;
+    warn_sanitized(&report);
 
-    /// Week of month (1-6)
-    pub week: Option<u32>,
+    let mut rows = Vec::new();
+    for (project, path) in &config.repositories {
+        let commits = commit_times(path, start, end, args.author.as_deref())?;
+        let pull_requests = if args.pull_requests {
+            merged_pull_requests(path, start, end, args.connection.timeout)?
+        } else {
+            None
+        };
+        rows.extend(velocity_rows(
+            &report.days,
+            project,
+            &commits,
+            pull_requests.as_deref(),
+        ));
+    }
+    match args.format {
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&rows)?),
+        _ => {
+            for line in build_velocity_lines(&rows) {
+                println!("{line}");
+            }
+        }
+    }
+    Ok(())
+}
 
-    /// Filter by project substring (comma-separated terms = OR)
-    #[arg(short, long)]
-    pub filter: Option<String>,
+pub fn handle_self_update(args: SelfUpdateArgs) -> Result<()> {
+    let current = env!("CARGO_PKG_VERSION");
+    let release = update::latest_release(update::LATEST_RELEASE_URL, args.timeout)?;
+    let latest = release.version();
+    if !update::is_newer(current, latest) {
+        println!("wakalyze {current} is up to date");
+        return Ok(());
+    }
+    if args.check {
+        println!("wakalyze {latest} is available (installed: {current})");
+        return Ok(());
+    }
+    let target = update::target().ok_or_else(|| {
+        WakalyzeError::SelfUpdate(format!(
+            "no release binary for {} {}",
+            std::env::consts::OS,
+            std::env::consts::ARCH
+        ))
+    })?;
+    let archive = update::download(&release, target, args.timeout)?;
+    update::install(&archive, &std::env::current_exe()?)?;
+    println!("updated wakalyze {current} -> {latest}");
+    Ok(())
+}
 
-    /// Wakapi user (or env WAKAPI_USER)
-    #[arg(long)]
-    pub user: Option<String>,
+/// Merge times of the checkout's pull requests, or `None` (with a warning) when its remote is
+/// not on GitHub or GitLab or no token is set.
+fn merged_pull_requests(
+    path: &Path,
+    start: NaiveDate,
+    end: NaiveDate,
+    timeout: f64,
+) -> Result<Option<Vec<i64>>> {
+    let Some(forge) = remote_url(path).as_deref().and_then(parse_remote) else {
+        eprintln!(
+            "{}: origin is not on GitHub or GitLab; pull requests not counted",
+            path.display()
+        );
+        return Ok(None);
+    };
+    let variable = match forge {
+        Forge::GitHub { .. } => "GITHUB_TOKEN",
+        Forge::GitLab { .. } => "GITLAB_TOKEN",
+    };
+    let Some(token) = std::env::var(variable).ok().filter(|t| !t.is_empty()) else {
+        eprintln!("{}: set {variable} to count pull requests", path.display());
+        return Ok(None);
+    };
+    merged_pr_times(&forge, &token, start, end, timeout).map(Some)
+}
 
-    /// Wakapi base URL (or env WAKAPI_BASE_URL)
-    #[arg(long)]
-    pub base_url: Option<String>,
+pub fn handle_inspect(args: InspectArgs) -> Result<()> {
+    let date = parse_date(&args.date)?;
+    if date > zone::today() {
+        return Err(WakalyzeError::FuturePeriod(date));
+    }
+    let at = args.at.as_deref().map(parse_clock).transpose()?;
+    let max_gap_seconds = (args.max_gap_minutes * 60.0) as i64;
+    if max_gap_seconds <= 0 {
+        return Err(WakalyzeError::InvalidMaxGap);
+    }
 
-    /// HTTP request timeout in seconds
-    #[arg(long, default_value_t = 15.0)]
-    pub timeout: f64,
+    let servers = args.connection.connect()?;
+    let analysis = Analysis::range(date, date).max_gap(max_gap_seconds);
+    let heartbeats = fetch_with_progress(&analysis, &servers, Some(&[]), |source| {
+        analysis.heartbeats(source)
+    })?;
+    let traces = trace_sessions(&heartbeats, max_gap_seconds, &GapEstimator);
 
-    /// Max gap in minutes between heartbeats to treat as continuous work
-    #[arg(long, default_value_t = DEFAULT_MAX_GAP_SECONDS as f64 / 60.0)]
-    pub max_gap_minutes: f64,
-}
+    let selected: Vec<SessionTrace> = match (at, args.session) {
+        (Some(at), _) => {
+            let timestamp = local_timestamp(date, at).ok_or(WakalyzeError::InvalidTime)?;
+            let trace = trace_at(&traces, timestamp).ok_or_else(|| {
+                WakalyzeError::SessionNotFound(format!("at {}", at.format("%H:%M")))
+            })?;
+            vec![trace.clone()]
+        }
+        (None, Some(index)) => {
+            let trace = traces
+                .iter()
+                .find(|t| t.index == index)
+                .ok_or_else(|| WakalyzeError::SessionNotFound(format!("#{index}")))?;
+            vec![trace.clone()]
+        }
+        (None, None) => traces,
+    };
 
-#[derive(Subcommand)]
-pub enum ConfigAction {
-    /// Print config file path
-    Path,
-    /// Show stored config values
-    Show,
-    /// Set stored config values
-    Set(ConfigSetArgs),
+    for line in build_trace_lines(date, &selected, max_gap_seconds) {
+        println!("{line}");
+    }
+    Ok(())
 }
 
-#[derive(Args)]
-pub struct ConfigSetArgs {
-    /// Wakapi API token
-    #[arg(long)]
-    pub key: Option<String>,
+/// Open the dashboard on the period of `args`; the arrow keys load the other months.
+pub fn handle_tui(mut args: AnalyzeArgs) -> Result<()> {
+    let (label, report) = run_analysis(&args)?;
+    let dashboard = Dashboard::new(label, report, output_lang(&args));
+    tui::run(dashboard, &mut |month| {
+        args.month = Some(month.format("%Y/%m").to_string());
+        args.week = None;
+        args.week_of = None;
+        args.from = None;
+        args.to = None;
+        run_analysis(&args)
+    })
+}
 
-    /// Wakapi user
-    #[arg(long)]
-    pub user: Option<String>,
+pub fn handle_sessions(args: AnalyzeArgs) -> Result<()> {
+    if !matches!(
+        args.format,
+        OutputFormat::Text | OutputFormat::Json | OutputFormat::Ndjson
+    ) {
+        return Err(WakalyzeError::ConflictingFlags(
+            "sessions supports --format text, json, or ndjson".to_string(),
+        ));
+    }
+    let (_, report, user) = run_analysis_for_user(&args)?;
+    let rows = session_rows(&user, &report.days);
+    match args.format {
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&rows)?),
+        OutputFormat::Ndjson => {
+            for row in &rows {
+                println!("{}", serde_json::to_string(row)?);
+            }
+        }
+        _ => {
+            for line in build_session_table_lines(&rows) {
+                println!("{line}");
+            }
+        }
+    }
+    Ok(())
+}
 
-    /// Wakapi base URL
-    #[arg(long)]
-    pub base_url: Option<String>,
+/// Update the annotation of a session, then print it; without options only prints it.
+pub fn handle_tag(args: TagArgs) -> Result<()> {
+    let id = normalize_id(&args.id)?;
+    let path = state_path();
+    let mut annotations = Annotations::load(&path)?;
+    let mut annotation = annotations.get(&id);
+    let before = annotation.clone();
+    if let Some(note) = args.note {
+        annotation.note = Some(note);
+    }
+    if args.clear_note {
+        annotation.note = None;
+    }
+    annotation.tags.extend(args.tags);
+    for tag in &args.untag {
+        annotation.tags.remove(tag);
+    }
+    if annotation != before {
+        annotations.set(&id, annotation.clone());
+        annotations.save(&path)?;
+    }
 
-    /// Remove stored key
-    #[arg(long)]
-    pub clear_key: bool,
+    let tags: Vec<&String> = annotation.tags.iter().collect();
+    let suffix = annotation_suffix(annotation.note.as_deref(), &tags);
+    if suffix.is_empty() {
+        println!("{id}: no note or tags");
+    } else {
+        println!("{id}:{suffix}");
+    }
+    Ok(())
+}
 
-    /// Remove stored user
-    #[arg(long)]
-    pub clear_user: bool,
+pub fn handle_add(args: AddArgs) -> Result<()> {
+    let date = parse_date(&args.date)?;
+    let (start, end) = parse_time_range(&args.range)?;
+    let added = format!(
+        "added {date} {}-{} {}",
+        start.format("%H:%M"),
+        end.format("%H:%M"),
+        args.project
+    );
+    let path = manual_path();
+    let mut manual = ManualEntries::load(&path)?;
+    manual.entries.push(ManualEntry {
+        date,
+        start,
+        end,
+        project: args.project,
+        note: args.note,
+    });
+    manual.save(&path)?;
+    println!("{added}");
+    Ok(())
+}
 
-    /// Remove stored base URL
-    #[arg(long)]
-    pub clear_base_url: bool,
+/// Fetched straight from the servers, bypassing the cache, so what is printed is exactly
+/// what they stored.
+pub fn handle_heartbeats(args: HeartbeatsArgs) -> Result<()> {
+    let date = parse_date(&args.date)?;
+    if date > zone::today() {
+        return Err(WakalyzeError::FuturePeriod(date));
+    }
+    let servers = args.connection.connect()?;
+    let heartbeats = merged_servers(&servers).fetch_heartbeats(date)?;
+    match args.format {
+        HeartbeatsFormat::Table => {
+            for line in build_heartbeat_lines(&heartbeats) {
+                println!("{line}");
+            }
+        }
+        HeartbeatsFormat::Json => println!("{}", serde_json::to_string_pretty(&heartbeats)?),
+    }
+    Ok(())
 }
 
-fn non_empty(s: &str) -> Option<&str> {
-    let trimmed = s.trim();
-    (!trimmed.is_empty()).then_some(trimmed)
+/// Runs a [`HeartbeatSource`] while advancing a progress bar per fetched date.
+struct ProgressSource<'a, S> {
+    inner: &'a S,
+    progress: &'a ProgressBar,
 }
 
-pub fn resolve_basic_auth(config: &Config) -> Result<String> {
-    if let Some(key) = config.key.as_deref().and_then(non_empty) {
-        return Ok(encode_api_key(key));
+impl<S: HeartbeatSource> HeartbeatSource for ProgressSource<'_, S> {
+    fn fetch_heartbeats(&self, date: NaiveDate) -> Result<Vec<RawHeartbeat>> {
+        let heartbeats = self.inner.fetch_heartbeats(date)?;
+        self.progress.inc(1);
+        Ok(heartbeats)
     }
-    if let Some(key) = resolve_from_env("WAKAPI_KEY") {
-        return Ok(encode_api_key(&key));
+
+    fn for_each_heartbeat(
+        &self,
+        date: NaiveDate,
+        sink: &mut dyn FnMut(RawHeartbeat),
+    ) -> Result<()> {
+        self.inner.for_each_heartbeat(date, sink)?;
+        self.progress.inc(1);
+        Ok(())
     }
-    Err(WakalyzeError::MissingAuth)
 }
 
-fn resolve_from_env(env_var: &str) -> Option<String> {
-    std::env::var(env_var)
-        .ok()
-        .as_deref()
-        .and_then(non_empty)
-        .map(str::to_owned)
+/// The config's theme for output to a terminal without `NO_COLOR`, else no colors.
+fn theme(terminal: bool) -> Result<Theme> {
+    if !terminal || std::env::var_os("NO_COLOR").is_some() {
+        return Ok(Theme::default());
+    }
+    Theme::from_config(&load_config().theme.unwrap_or_default())
 }
 
-fn resolve_field(arg: Option<&str>, config_val: Option<&str>, env_var: &str) -> Option<String> {
-    arg.and_then(non_empty)
-        .or_else(|| config_val.and_then(non_empty))
-        .map(str::to_owned)
-        .or_else(|| resolve_from_env(env_var))
+fn progress_bar(len: usize) -> ProgressBar {
+    if !std::io::stderr().is_terminal() {
+        return ProgressBar::hidden();
+    }
+    let pb = ProgressBar::new(len as u64).with_finish(ProgressFinish::AndClear);
+    pb.set_style(
+        ProgressStyle::with_template(
+            "{spinner} Loading heartbeats {bar:30} {percent}% [{elapsed}]",
+        )
+        .unwrap(),
+    );
+    pb
 }
 
-fn resolve_user(args_user: Option<&str>, config: &Config) -> Result<String> {
-    resolve_field(args_user, config.user.as_deref(), "WAKAPI_USER")
-        .ok_or(WakalyzeError::MissingUser)
+/// Client and on-disk cache of one account.
+type Server = (WakapiClient, HeartbeatCache);
+
+/// The resolved account, then each of the config's `servers`.
+fn connect(
+    user: Option<&str>,
+    base_url: Option<&str>,
+    provider: Option<Provider>,
+    retry: RetryPolicy,
+    timeout: f64,
+) -> Result<Vec<Server>> {
+    let config = load_config();
+    let user = resolve_user(user, &config)?;
+    let main_url = resolve_base_url(base_url, provider, &config);
+    let main = (
+        resolve_provider(provider, &main_url, &config),
+        main_url,
+        user.clone(),
+        resolve_basic_auth(&config)?,
+    );
+    let others = config.servers.iter().map(|server| {
+        (
+            server
+                .provider
+                .unwrap_or_else(|| Provider::detect(&server.base_url)),
+            server.base_url.clone(),
+            server.user.clone().unwrap_or_else(|| user.clone()),
+            encode_api_key(&server.key),
+        )
+    });
+    std::iter::once(main)
+        .chain(others)
+        .map(|(provider, base_url, user, auth)| {
+            let client = WakapiClient::new(&base_url, &user, &auth, timeout)
+                .provider(provider)
+                .retry(retry);
+            let account = cache_key(&user, &base_url);
+            let mut cache = HeartbeatCache::new(&cache_root(), &account);
+            if let Some(mode) = config.cache_encryption {
+                cache = cache.encrypted(CacheCipher::resolve(mode, &account)?);
+            }
+            Ok((client, cache))
+        })
+        .collect()
 }
 
-fn resolve_base_url(args_url: Option<&str>, config: &Config) -> String {
-    resolve_field(args_url, config.base_url.as_deref(), "WAKAPI_BASE_URL")
-        .unwrap_or_else(|| DEFAULT_BASE_URL.to_string())
+/// The heartbeats of every server, straight from the network.
+fn merged_servers(servers: &[Server]) -> impl HeartbeatSource + '_ {
+    MergedSource::new(
+        servers
+            .iter()
+            .map(|(client, _)| move |date| client.fetch_heartbeats(date))
+            .collect(),
+    )
 }
 
-fn update_field(
-    target: &mut Option<String>,
-    value: Option<&str>,
-    clear: bool,
-    label: &str,
-) -> Result<bool> {
-    if clear && value.is_some() {
-        return Err(WakalyzeError::ConflictingFlags(format!(
-            "cannot use --{label} and --clear-{label} together"
-        )));
-    }
-    if clear {
-        *target = None;
-        return Ok(true);
-    }
-    match value {
-        None => Ok(false),
-        Some(v) => {
-            *target = non_empty(v).map(str::to_owned);
-            Ok(true)
-        }
-    }
+/// Serve `fetch` from the caches in front of `servers`, merged, with a progress bar over the
+/// dates `analysis` fetches. `refresh` of `None` bypasses the caches.
+fn fetch_with_progress<T>(
+    analysis: &Analysis,
+    servers: &[Server],
+    refresh: Option<&[NaiveDate]>,
+    fetch: impl FnOnce(&dyn HeartbeatSource) -> Result<T>,
+) -> Result<T> {
+    let pb = progress_bar(analysis.fetch_dates().len());
+    let Some(refresh) = refresh else {
+        let result = fetch(&ProgressSource {
+            inner: &merged_servers(servers),
+            progress: &pb,
+        })?;
+        pb.finish_and_clear();
+        return Ok(result);
+    };
+    let today = zone::today();
+    let policy = load_config().cache.unwrap_or_default();
+    let source = MergedSource::new(
+        servers
+            .iter()
+            .map(|(client, cache)| {
+                CachedSource::new(client, cache.clone(), today)
+                    .policy(policy)
+                    .refresh(refresh.iter().copied())
+            })
+            .collect(),
+    );
+    let result = fetch(&ProgressSource {
+        inner: &source,
+        progress: &pb,
+    })?;
+    pb.finish_and_clear();
+    Ok(result)
 }
 
-pub fn handle_config(action: ConfigAction) -> Result<()> {
-    handle_config_with_path(action, &config_path())
+fn run_analysis(args: &AnalyzeArgs) -> Result<(String, Report)> {
+    let (label, report, _) = run_analysis_for_user(args)?;
+    Ok((label, report))
 }
 
-pub fn handle_config_with_path(action: ConfigAction, path: &std::path::Path) -> Result<()> {
-    match action {
-        ConfigAction::Path => {
-            println!("{}", path.display());
-            Ok(())
+/// [`run_analysis`], with the user the report belongs to: the archive's, else the resolved
+/// one, which may be empty for `--input`.
+fn run_analysis_for_user(args: &AnalyzeArgs) -> Result<(String, Report, String)> {
+    let (label, start, end) = analysis_period(args)?;
+    let config = load_config();
+    let archive = args
+        .from_archive
+        .as_deref()
+        .map(Archive::load)
+        .transpose()?;
+    let analysis = configure(
+        args,
+        analysis_settings(args, &config, archive.as_ref())?,
+        start,
+        end,
+    )?;
+
+    let summaries = args.source == Source::Summaries && archive.is_none() && args.input.is_empty();
+    if summaries && !summaries_suffice(args) {
+        eprintln!("note: this output needs session times, so it is built from the heartbeats");
+    }
+    let (mut report, user) = match &archive {
+        Some(archive) => (analysis.run(&archive.source())?, archive.user.clone()),
+        None if !args.input.is_empty() => {
+            let source = MemorySource::new(load_heartbeats(&args.input)?);
+            let user = resolve_user(args.connection.user.as_deref(), &config).unwrap_or_default();
+            (analysis.run(&source)?, user)
         }
-        ConfigAction::Show => {
-            let config = load_config_from(path);
-            println!("path: {}", path.display());
-            println!("user: {}", config.user.as_deref().unwrap_or("(unset)"));
-            println!(
-                "base_url: {}",
-                config.base_url.as_deref().unwrap_or("(unset)")
-            );
-            let key_display = config
-                .key
+        None if args.source == Source::Durations => {
+            let servers = args.connection.connect()?;
+            let dates = analysis.fetch_dates();
+            let pb = progress_bar(dates.len());
+            let mut blocks = Vec::new();
+            for date in dates {
+                for (client, _) in &servers {
+                    blocks.extend(client.fetch_durations(date)?);
+                }
+                pb.inc(1);
+            }
+            pb.finish_and_clear();
+            let day_start = args
+                .day_boundary
                 .as_deref()
-                .map(mask_secret)
-                .unwrap_or_else(|| "(unset)".to_string());
-            println!("key: {key_display}");
-            Ok(())
+                .map(parse_clock)
+                .transpose()?
+                .unwrap_or(NaiveTime::MIN);
+            let report =
+                analysis.run_sessions(duration_sessions(blocks, day_start), "durations")?;
+            (
+                report,
+                resolve_user(args.connection.user.as_deref(), &config)?,
+            )
         }
-        ConfigAction::Set(args) => {
-            let mut config = load_config_from(path);
-            let mut updated = false;
-            updated |= update_field(&mut config.key, args.key.as_deref(), args.clear_key, "key")?;
-            updated |= update_field(
-                &mut config.user,
-                args.user.as_deref(),
-                args.clear_user,
-                "user",
-            )?;
-            updated |= update_field(
-                &mut config.base_url,
-                args.base_url.as_deref(),
-                args.clear_base_url,
-                "base-url",
-            )?;
-            if !updated {
-                return Err(WakalyzeError::NothingToUpdate);
+        None if summaries && summaries_suffice(args) => {
+            let servers = args.connection.connect()?;
+            let mut days = Vec::new();
+            for (client, _) in &servers {
+                days.extend(client.fetch_summaries(start, end)?);
             }
-            save_config_to(path, &config)?;
-            Ok(())
+            let report = analysis.run_sessions(summary_sessions(days), "summaries")?;
+            (
+                report,
+                resolve_user(args.connection.user.as_deref(), &config)?,
+            )
         }
-    }
+        None => {
+            let servers = args.connection.connect()?;
+            let report =
+                fetch_with_progress(&analysis, &servers, args.cache_refresh(), |source| {
+                    analysis.run(source)
+                })?;
+            (
+                report,
+                resolve_user(args.connection.user.as_deref(), &config)?,
+            )
+        }
+    };
+    Annotations::load(&state_path())?.apply(&user, &mut report.days);
+    warn_sanitized(&report);
+    Ok((label, report, user))
 }
 
-pub fn handle_analyze(args: AnalyzeArgs) -> Result<()> {
-    let config = load_config();
-    let base_url = resolve_base_url(args.base_url.as_deref(), &config);
+/// `--lang`, else the config's `lang`.
+fn output_lang(args: &AnalyzeArgs) -> Lang {
+    args.lang.or(load_config().lang).unwrap_or_default()
+}
 
-    let first_day = parse_month(&args.month)?;
-    let (start, end, label) = if let Some(week) = args.week {
-        let (s, e) = week_range(first_day, week)?;
-        (s, e, format!("{} week {week}", first_day.format("%Y/%m")))
-    } else {
-        let last = month_last_day(first_day);
-        (first_day, last, first_day.format("%Y/%m").to_string())
+/// Whether the output of `args` shows no more than the time per project and day, which
+/// `--source summaries` reports without fetching the heartbeats.
+fn summaries_suffice(args: &AnalyzeArgs) -> bool {
+    let totals_only = match args.view {
+        View::Calendar | View::Heatmap => true,
+        View::List => is_year(args) && !args.daily,
     };
+    let needs_heartbeats = args
+        .by
+        .is_some_and(|by| !matches!(by, GroupBy::Project | GroupBy::Client))
+        || args.branch.is_some()
+        || args.day_boundary.is_some()
+        || !args.categories.is_empty()
+        || !args.exclude_entity.is_empty()
+        || args.billing
+        || args.domains
+        || args.entities
+        || args.stats
+        || args.switches
+        || args.focus
+        || args.punch_card
+        || args.machines
+        || args.dedupe_machines
+        || args.meetings.is_some()
+        || args.show_data_quality
+        || args.report_conflicts
+        || args.verify;
+    args.format == OutputFormat::Text && totals_only && !needs_heartbeats
+}
 
-    let user = resolve_user(args.user.as_deref(), &config)?;
-    let auth = resolve_basic_auth(&config)?;
+/// Whether `args` cover a whole year, which the text output lists by month.
+fn is_year(args: &AnalyzeArgs) -> bool {
+    args.week_of.is_none()
+        && args.from.is_none()
+        && matches!(
+            args.month.as_deref().map(Period::parse),
+            Some(Ok(Period::Year(_)))
+        )
+}
 
-    let max_gap_seconds = (args.max_gap_minutes * 60.0) as i64;
-    if max_gap_seconds <= 0 {
-        return Err(WakalyzeError::InvalidMaxGap);
+/// Label and first and last day of the year, month, or week in `args`.
+fn analysis_period(args: &AnalyzeArgs) -> Result<(String, NaiveDate, NaiveDate)> {
+    let today = zone::today();
+    if let Some(spec) = args.week_of.as_deref() {
+        let start = week_start(spec, today)?;
+        let end = start + Duration::days(6);
+        let label = format!("{start} ~ {end}");
+        let (start, end) = clamp_to_today(start, end, today, args.include_future)?;
+        return Ok((label, start, end));
     }
-
-    let client = WakapiClient::new(&base_url, &user, &auth, args.timeout);
-
-    // Expand fetch range by ±1 day to capture heartbeats near timezone boundaries
-    let fetch_start = start.pred_opt().unwrap_or(start);
-    let fetch_end = end.succ_opt().unwrap_or(end);
-    let fetch_dates = iter_dates(fetch_start, fetch_end);
-
-    let is_terminal = std::io::stderr().is_terminal();
-    let pb = if is_terminal {
-        let pb = ProgressBar::new(fetch_dates.len() as u64).with_finish(ProgressFinish::AndClear);
-        pb.set_style(
-            ProgressStyle::with_template(
-                "{spinner} Loading heartbeats {bar:30} {percent}% [{elapsed}]",
-            )
-            .unwrap(),
-        );
-        pb
-    } else {
-        ProgressBar::hidden()
+    if let (Some(from), Some(to)) = (args.from.as_deref(), args.to.as_deref()) {
+        let (start, end) = (parse_date(from)?, parse_date(to)?);
+        if end < start {
+            return Err(WakalyzeError::InvalidRange(start, end));
+        }
+        let label = format!("{start} ~ {end}");
+        let (start, end) = clamp_to_today(start, end, today, args.include_future)?;
+        return Ok((label, start, end));
+    }
+    let period = Period::parse(args.month.as_deref().unwrap_or_default())?;
+    let (start, end, label) = match (period, args.week) {
+        (Period::Month(first_day), Some(week)) => {
+            let (s, e) = week_range(first_day, week)?;
+            let week = output_lang(args).bundle().week(week);
+            (s, e, format!("{} {week}", period.label()))
+        }
+        (Period::Year(_), Some(_)) => {
+            return Err(WakalyzeError::ConflictingFlags(
+                "a week number needs a month, not a year".to_string(),
+            ))
+        }
+        (Period::Days(..), Some(_)) => {
+            return Err(WakalyzeError::ConflictingFlags(
+                "a week number needs a month, not a day or week".to_string(),
+            ))
+        }
+        (period, None) => (period.first_day(), period.last_day(), period.label()),
     };
+    let (start, end) = clamp_to_today(start, end, today, args.include_future)?;
+    Ok((label, start, end))
+}
 
-    let mut all_heartbeats: Vec<RawHeartbeat> = Vec::new();
-    for date in &fetch_dates {
-        let heartbeats = client.fetch_heartbeats(*date)?;
-        all_heartbeats.extend(heartbeats);
-        pb.inc(1);
+/// The flags in `args` with the manual entries, corrections, and project rules of the config,
+/// or of `archive` when re-analyzing one.
+fn analysis_settings(
+    args: &AnalyzeArgs,
+    config: &Config,
+    archive: Option<&Archive>,
+) -> Result<ArchiveSettings> {
+    let recorded = match archive {
+        Some(archive) => archive.settings.clone(),
+        None => configured_settings(config)?,
+    };
+    let mut settings = ArchiveSettings {
+        max_gap_seconds: (args.max_gap_minutes * 60.0) as i64,
+        filter: args.filter.clone(),
+        exclude: args.exclude.clone(),
+        filter_regex: args.filter_regex.clone(),
+        branch: args.branch.clone(),
+        day_boundary: args.day_boundary.clone(),
+        fetch_tz: args.fetch_tz.clone(),
+        dedupe_machines: args.dedupe_machines,
+        conflict_policy: args.conflict_policy,
+        categories: args.categories.clone(),
+        skip_weekends: (recorded.skip_weekends || args.skip_weekends) && !args.include_weekends,
+        ..recorded
+    };
+    settings
+        .ignore
+        .entities
+        .extend(args.exclude_entity.iter().cloned());
+    if let Some(path) = &args.meetings {
+        settings.meetings = load_meetings(path)?;
+        settings.meeting_mode = args.meetings_mode;
     }
-    pb.finish_and_clear();
+    Ok(settings)
+}
 
-    // Deduplicate heartbeats that may appear in adjacent day fetches
-    all_heartbeats.sort_by(|a, b| {
-        a.time
-            .partial_cmp(&b.time)
-            .unwrap_or(std::cmp::Ordering::Equal)
-    });
-    all_heartbeats.dedup_by(|a, b| a.time == b.time && a.project == b.project);
-
-    // Regroup by local date and filter to the requested range
-    let grouped = group_heartbeats_by_local_date(all_heartbeats);
-    let days: Vec<DaySessions> = grouped
-        .into_iter()
-        .filter(|(date, _)| *date >= start && *date <= end)
-        .map(|(date, hbs)| DaySessions {
-            date,
-            sessions: build_sessions(&hbs, max_gap_seconds),
-        })
-        .collect();
+/// The analysis `settings` describe, plus the flags of `args` that only affect the output.
+fn configure(
+    args: &AnalyzeArgs,
+    settings: ArchiveSettings,
+    start: NaiveDate,
+    end: NaiveDate,
+) -> Result<Analysis> {
+    let analysis = settings
+        .analysis(start, end)?
+        .verify(args.verify)
+        .entities(args.entities)
+        .focus(FocusFormula {
+            deep_seconds: (args.deep_minutes * 60.0) as i64,
+            switch_penalty: args.switch_penalty,
+        });
+    Ok(match args.round.as_deref() {
+        Some(increment) => analysis.rounding(
+            Rounding::new(parse_span(increment)?, args.round_mode),
+            args.round_scope,
+        ),
+        None => analysis,
+    })
+}
 
-    let days = filter_sessions(&days, args.filter.as_deref());
+/// Default settings with the manual entries, corrections, and project rules every report
+/// applies.
+fn configured_settings(config: &Config) -> Result<ArchiveSettings> {
+    Ok(ArchiveSettings {
+        manual_entries: ManualEntries::load(&manual_path())?.entries,
+        corrections: Corrections::load(&corrections_path())?.corrections,
+        ignore: config.ignore_rules(),
+        projects: config.project_map(),
+        skip_weekends: config.skip_weekends,
+        ..Default::default()
+    })
+}
 
-    for line in build_lines(&days, &label) {
-        println!("{line}");
+fn warn_sanitized(report: &Report) {
+    let sanitized = &report.sanitized;
+    let warning = theme(std::io::stderr().is_terminal())
+        .unwrap_or_default()
+        .paint(Role::Warning, "warning:");
+    if sanitized.normalized > 0 {
+        eprintln!(
+            "{warning} {} heartbeat timestamps were not in seconds and have been normalized",
+            sanitized.normalized
+        );
+    }
+    if sanitized.dropped > 0 {
+        eprintln!(
+            "{warning} dropped {} heartbeats dated before 2000 or in the future",
+            sanitized.dropped
+        );
     }
-    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::client::DEFAULT_BASE_URL;
     use serial_test::serial;
 
     #[test]
@@ -369,14 +2722,88 @@ mod tests {
             key: None,
             user: Some("testuser".into()),
             base_url: None,
+            provider: Some(Provider::Wakatime),
             clear_key: false,
             clear_user: false,
             clear_base_url: false,
+            clear_provider: false,
+            default_profile: None,
+            clear_default_profile: false,
+            timezone: None,
+            clear_timezone: false,
         };
-        handle_config_with_path(ConfigAction::Set(set_args), &path).unwrap();
+        handle_config_with_path(ConfigAction::Set(set_args), &path, None).unwrap();
 
         let loaded = load_config_from(&path);
         assert_eq!(loaded.user.as_deref(), Some("testuser"));
+        assert_eq!(loaded.provider, Some(Provider::Wakatime));
+    }
+
+    #[test]
+    fn goals_set_replaces_and_removes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("wakalyze").join("config.json");
+        let set = |hours: f64, project: Option<&str>| {
+            let args = GoalsSetArgs {
+                per: GoalPeriod::Week,
+                hours,
+                project: project.map(str::to_string),
+            };
+            handle_goals_set(args, &path)
+        };
+        set(20.0, None).unwrap();
+        set(8.0, Some("api")).unwrap();
+        set(25.0, None).unwrap();
+        let goals: Vec<_> = load_config_from(&path)
+            .goals
+            .into_iter()
+            .map(|goal| (goal.project, goal.hours))
+            .collect();
+        assert_eq!(goals, [(Some("api".to_string()), 8.0), (None, 25.0)]);
+
+        set(0.0, Some("api")).unwrap();
+        assert_eq!(load_config_from(&path).goals.len(), 1);
+        assert!(matches!(
+            set(-1.0, None),
+            Err(WakalyzeError::InvalidGoalHours(_))
+        ));
+    }
+
+    #[test]
+    fn config_set_profile() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("wakalyze").join("config.json");
+        let set_args = |user: Option<&str>, default_profile: Option<&str>| ConfigSetArgs {
+            key: None,
+            user: user.map(str::to_string),
+            base_url: None,
+            provider: None,
+            clear_key: false,
+            clear_user: false,
+            clear_base_url: false,
+            clear_provider: false,
+            default_profile: default_profile.map(str::to_string),
+            clear_default_profile: false,
+            timezone: None,
+            clear_timezone: false,
+        };
+        assert!(matches!(
+            handle_config_with_path(ConfigAction::Set(set_args(None, Some("work"))), &path, None),
+            Err(WakalyzeError::UnknownProfile(_))
+        ));
+        handle_config_with_path(
+            ConfigAction::Set(set_args(Some("me.work"), Some("work"))),
+            &path,
+            Some("work"),
+        )
+        .unwrap();
+        handle_config_with_path(ConfigAction::Set(set_args(Some("me"), None)), &path, None)
+            .unwrap();
+
+        let loaded = load_config_from(&path);
+        assert_eq!(loaded.user.as_deref(), Some("me"));
+        assert_eq!(loaded.profiles["work"].user.as_deref(), Some("me.work"));
+        assert_eq!(loaded.default_profile.as_deref(), Some("work"));
     }
 
     #[test]
@@ -387,11 +2814,17 @@ mod tests {
             key: None,
             user: None,
             base_url: None,
+            provider: None,
             clear_key: false,
             clear_user: false,
             clear_base_url: false,
+            clear_provider: false,
+            default_profile: None,
+            clear_default_profile: false,
+            timezone: None,
+            clear_timezone: false,
         };
-        let result = handle_config_with_path(ConfigAction::Set(set_args), &path);
+        let result = handle_config_with_path(ConfigAction::Set(set_args), &path, None);
         assert!(result.is_err());
     }
 
@@ -427,7 +2860,7 @@ mod tests {
             base_url: Some("https://cfg.example.com".into()),
             ..Default::default()
         };
-        let result = resolve_base_url(None, &config);
+        let result = resolve_base_url(None, None, &config);
         std::env::remove_var("WAKAPI_BASE_URL");
         assert_eq!(result, "https://cfg.example.com");
     }
@@ -455,14 +2888,38 @@ mod tests {
             base_url: Some("https://cfg.example.com".into()),
             ..Default::default()
         };
-        let result = resolve_base_url(Some("https://arg.example.com"), &config);
+        let result = resolve_base_url(Some("https://arg.example.com"), None, &config);
         assert_eq!(result, "https://arg.example.com");
     }
 
     #[test]
     fn resolve_base_url_default_fallback() {
         std::env::remove_var("WAKAPI_BASE_URL");
-        let result = resolve_base_url(None, &Config::default());
+        let result = resolve_base_url(None, None, &Config::default());
         assert_eq!(result, DEFAULT_BASE_URL);
     }
+
+    #[test]
+    #[serial]
+    fn resolve_base_url_and_provider_for_wakatime() {
+        std::env::remove_var("WAKAPI_BASE_URL");
+        let config = Config {
+            provider: Some(Provider::Wakatime),
+            ..Default::default()
+        };
+        let url = resolve_base_url(None, None, &config);
+        assert_eq!(url, "https://wakatime.com");
+        assert_eq!(
+            resolve_provider(None, "https://wakapi.dev", &config),
+            Provider::Wakatime
+        );
+        assert_eq!(
+            resolve_provider(None, "https://wakatime.com", &Config::default()),
+            Provider::Wakatime
+        );
+        assert_eq!(
+            resolve_provider(Some(Provider::Wakapi), &url, &config),
+            Provider::Wakapi
+        );
+    }
 }
@@ -1,34 +1,194 @@
-use base64::Engine;
-use chrono::NaiveDate;
+use std::fmt;
+use std::io::{BufReader, Read};
+use std::thread::sleep;
+use std::time::{Duration, SystemTime};
 
-use serde::Deserialize;
+use base64::Engine;
+use chrono::{DateTime, NaiveDate, Utc};
+use clap::ValueEnum;
+use serde::de::{DeserializeSeed, Deserializer, IgnoredAny, MapAccess, SeqAccess, Visitor};
+use serde::{Deserialize, Serialize};
 
+use crate::analysis::HeartbeatSource;
 use crate::core::RawHeartbeat;
-use crate::error::Result;
+use crate::durations::{DurationBlock, DurationsResponse};
+use crate::error::{Result, WakalyzeError};
+use crate::summaries::{SummariesResponse, SummaryDay};
+
+/// Visits `{"data": [...]}` and hands each heartbeat to a sink as soon as it is parsed.
+struct ResponseVisitor<'a, F>(&'a mut F);
+
+impl<'de, F: FnMut(RawHeartbeat)> Visitor<'de> for ResponseVisitor<'_, F> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a heartbeats response object")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> std::result::Result<(), A::Error> {
+        while let Some(key) = map.next_key::<String>()? {
+            if key == "data" {
+                map.next_value_seed(DataSeed(&mut *self.0))?;
+            } else {
+                map.next_value::<IgnoredAny>()?;
+            }
+        }
+        Ok(())
+    }
+}
+
+struct DataSeed<'a, F>(&'a mut F);
+
+impl<'de, F: FnMut(RawHeartbeat)> DeserializeSeed<'de> for DataSeed<'_, F> {
+    type Value = ();
 
-#[derive(Deserialize)]
-struct HeartbeatsResponse {
-    #[serde(default)]
-    data: Vec<RawHeartbeat>,
+    fn deserialize<D: Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> std::result::Result<(), D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+impl<'de, F: FnMut(RawHeartbeat)> Visitor<'de> for DataSeed<'_, F> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a list of heartbeats")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> std::result::Result<(), A::Error> {
+        while let Some(heartbeat) = seq.next_element::<RawHeartbeat>()? {
+            (self.0)(heartbeat);
+        }
+        Ok(())
+    }
+}
+
+/// Stream-parse a heartbeats response body without buffering it.
+pub fn read_heartbeats<R: Read>(reader: R, mut sink: impl FnMut(RawHeartbeat)) -> Result<()> {
+    let mut deserializer = serde_json::Deserializer::from_reader(BufReader::new(reader));
+    deserializer
+        .deserialize_map(ResponseVisitor(&mut sink))
+        .and_then(|()| deserializer.end())
+        .map_err(WakalyzeError::InvalidResponse)
 }
 
 pub const DEFAULT_BASE_URL: &str = "https://wakapi.dev";
 
+pub const WAKATIME_BASE_URL: &str = "https://wakatime.com";
+
+/// The server software behind a base URL.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Provider {
+    /// Wakapi, through its WakaTime-compatible API
+    #[default]
+    Wakapi,
+    /// WakaTime.com, for the account of the API key
+    Wakatime,
+}
+
+impl Provider {
+    /// WakaTime for wakatime.com and its subdomains, Wakapi otherwise.
+    pub fn detect(base_url: &str) -> Self {
+        let host = base_url
+            .split_once("://")
+            .map_or(base_url, |(_, rest)| rest)
+            .split(['/', ':'])
+            .next()
+            .unwrap_or_default()
+            .to_lowercase();
+        if host == "wakatime.com" || host.ends_with(".wakatime.com") {
+            Provider::Wakatime
+        } else {
+            Provider::Wakapi
+        }
+    }
+
+    pub fn default_base_url(self) -> &'static str {
+        match self {
+            Provider::Wakapi => DEFAULT_BASE_URL,
+            Provider::Wakatime => WAKATIME_BASE_URL,
+        }
+    }
+}
+
+/// What the sessions are built from.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum Source {
+    /// The raw heartbeats of every day, split into sessions at --max-gap-minutes
+    #[default]
+    Heartbeats,
+    /// The time per project and day, in one request for the whole range; used when only
+    /// totals are shown, heartbeats otherwise
+    Summaries,
+    /// The work blocks the server built, one request per day, so sessions match its web UI
+    Durations,
+}
+
 pub fn encode_api_key(key: &str) -> String {
     let encoded = base64::engine::general_purpose::STANDARD.encode(key.as_bytes());
     format!("Basic {encoded}")
 }
 
+/// Retries of requests that failed with a timeout, a connection error, a 5xx, or a 429.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct RetryPolicy {
+    /// Further attempts after the first; none by default.
+    pub retries: u32,
+    /// Wait before the first retry, doubled for each further one, plus up to half of it again
+    /// at random so parallel fetches do not retry in lockstep.
+    pub delay: Duration,
+}
+
+impl RetryPolicy {
+    pub fn new(retries: u32, delay_secs: f64) -> Self {
+        Self {
+            retries,
+            delay: Duration::from_secs_f64(delay_secs.max(0.0)),
+        }
+    }
+
+    fn backoff(&self, attempt: u32) -> Duration {
+        let base = self.delay.saturating_mul(2u32.saturating_pow(attempt));
+        let nanos = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default()
+            .subsec_nanos();
+        base + (base / 2).mul_f64(f64::from(nanos % 1000) / 1000.0)
+    }
+}
+
+/// Longest `Retry-After` waited for; a 429 asking for more fails instead of hanging the command.
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(60);
+
+/// The wait a `Retry-After` header asks for: seconds, or an HTTP date.
+fn retry_after(value: &str, now: DateTime<Utc>) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let at = DateTime::parse_from_rfc2822(value).ok()?;
+    Some((at.with_timezone(&Utc) - now).to_std().unwrap_or_default())
+}
+
 pub struct WakapiClient {
     base_url: String,
     user: String,
     auth: String,
+    provider: Provider,
+    retry: RetryPolicy,
     client: reqwest::blocking::Client,
 }
 
 impl WakapiClient {
     pub fn new(base_url: &str, user: &str, auth: &str, timeout_secs: f64) -> Self {
+        // Month-long fetches are megabytes of JSON that compress well.
         let client = reqwest::blocking::Client::builder()
+            .gzip(true)
+            .brotli(true)
+            .deflate(true)
             .timeout(std::time::Duration::from_secs_f64(timeout_secs))
             .build()
             .expect("failed to build HTTP client");
@@ -36,25 +196,167 @@ impl WakapiClient {
             base_url: base_url.trim_end_matches('/').to_string(),
             user: user.to_string(),
             auth: auth.to_string(),
+            provider: Provider::Wakapi,
+            retry: RetryPolicy::default(),
             client,
         }
     }
 
+    /// Retry transient failures as `retry` says.
+    pub fn retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Talk to `provider` instead of Wakapi.
+    pub fn provider(mut self, provider: Provider) -> Self {
+        self.provider = provider;
+        self
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
     pub fn fetch_heartbeats(&self, date: NaiveDate) -> Result<Vec<RawHeartbeat>> {
-        let url = format!(
-            "{}/api/compat/wakatime/v1/users/{}/heartbeats?date={}",
-            self.base_url,
-            self.user,
-            date.format("%Y-%m-%d"),
+        let mut heartbeats = Vec::new();
+        self.fetch_heartbeats_with(date, |hb| heartbeats.push(hb))?;
+        Ok(heartbeats)
+    }
+
+    /// Fetch the heartbeats for a date, handing each one to `sink` while the body streams in.
+    pub fn fetch_heartbeats_with(
+        &self,
+        date: NaiveDate,
+        sink: impl FnMut(RawHeartbeat),
+    ) -> Result<()> {
+        // WakaTime serves the key's own account as `current`; `user` only names the cache.
+        let url = match self.provider {
+            Provider::Wakapi => format!(
+                "{}/api/compat/wakatime/v1/users/{}/heartbeats?date={}",
+                self.base_url,
+                self.user,
+                date.format("%Y-%m-%d"),
+            ),
+            Provider::Wakatime => format!(
+                "{}/api/v1/users/current/heartbeats?date={}",
+                self.base_url,
+                date.format("%Y-%m-%d"),
+            ),
+        };
+        read_heartbeats(self.send(&url)?, sink)
+    }
+
+    /// The time per project on each day from `start` to `end`, from one summaries request.
+    pub fn fetch_summaries(&self, start: NaiveDate, end: NaiveDate) -> Result<Vec<SummaryDay>> {
+        let range = format!(
+            "start={}&end={}",
+            start.format("%Y-%m-%d"),
+            end.format("%Y-%m-%d")
         );
-        let resp = self
-            .client
-            .get(&url)
-            .header("Authorization", &self.auth)
-            .send()?;
-        resp.error_for_status_ref()?;
-        let payload: HeartbeatsResponse = resp.json()?;
-        Ok(payload.data)
+        let url = match self.provider {
+            Provider::Wakapi => format!(
+                "{}/api/compat/wakatime/v1/users/{}/summaries?{range}",
+                self.base_url, self.user,
+            ),
+            Provider::Wakatime => {
+                format!("{}/api/v1/users/current/summaries?{range}", self.base_url)
+            }
+        };
+        let response: SummariesResponse = serde_json::from_reader(BufReader::new(self.send(&url)?))
+            .map_err(WakalyzeError::InvalidResponse)?;
+        Ok(response.data)
+    }
+
+    /// The work blocks the server joined the heartbeats of `date` into.
+    pub fn fetch_durations(&self, date: NaiveDate) -> Result<Vec<DurationBlock>> {
+        let date = date.format("%Y-%m-%d");
+        let url = match self.provider {
+            Provider::Wakapi => format!(
+                "{}/api/compat/wakatime/v1/users/{}/durations?date={date}",
+                self.base_url, self.user,
+            ),
+            Provider::Wakatime => {
+                format!(
+                    "{}/api/v1/users/current/durations?date={date}",
+                    self.base_url
+                )
+            }
+        };
+        let response: DurationsResponse = serde_json::from_reader(BufReader::new(self.send(&url)?))
+            .map_err(WakalyzeError::InvalidResponse)?;
+        Ok(response.data)
+    }
+
+    /// GET `url`, retried per the [`RetryPolicy`]; a 429 waits as long as `Retry-After` asks,
+    /// up to [`MAX_RETRY_AFTER`].
+    fn send(&self, url: &str) -> Result<reqwest::blocking::Response> {
+        let mut attempt = 0;
+        loop {
+            let result = self
+                .client
+                .get(url)
+                .header("Authorization", &self.auth)
+                .send();
+            let wait = match &result {
+                Ok(resp) if resp.status() == reqwest::StatusCode::TOO_MANY_REQUESTS => {
+                    match resp
+                        .headers()
+                        .get(reqwest::header::RETRY_AFTER)
+                        .and_then(|value| value.to_str().ok())
+                        .and_then(|value| retry_after(value, Utc::now()))
+                    {
+                        Some(wait) if wait > MAX_RETRY_AFTER => None,
+                        Some(wait) => Some(wait),
+                        None => Some(self.retry.backoff(attempt)),
+                    }
+                }
+                Ok(resp) if resp.status().is_server_error() => Some(self.retry.backoff(attempt)),
+                Err(e) if e.is_timeout() || e.is_connect() => Some(self.retry.backoff(attempt)),
+                _ => None,
+            };
+            match wait {
+                Some(wait) if attempt < self.retry.retries => {
+                    sleep(wait);
+                    attempt += 1;
+                }
+                _ => {
+                    let resp = result?;
+                    resp.error_for_status_ref()?;
+                    return Ok(resp);
+                }
+            }
+        }
+    }
+}
+
+impl HeartbeatSource for WakapiClient {
+    fn fetch_heartbeats(&self, date: NaiveDate) -> Result<Vec<RawHeartbeat>> {
+        WakapiClient::fetch_heartbeats(self, date)
+    }
+
+    fn for_each_heartbeat(
+        &self,
+        date: NaiveDate,
+        sink: &mut dyn FnMut(RawHeartbeat),
+    ) -> Result<()> {
+        self.fetch_heartbeats_with(date, sink)
+    }
+
+    fn fetch_heartbeats_since(
+        &self,
+        date: NaiveDate,
+        since: Option<f64>,
+    ) -> Result<Vec<RawHeartbeat>> {
+        // The heartbeats endpoint has no "since" parameter, so older heartbeats are
+        // dropped while streaming instead of being collected.
+        let mut heartbeats = Vec::new();
+        self.fetch_heartbeats_with(date, |hb| {
+            if since.is_none_or(|since| hb.time.is_some_and(|t| t > since)) {
+                heartbeats.push(hb);
+            }
+        })?;
+        Ok(heartbeats)
     }
 }
 
@@ -77,6 +379,155 @@ mod tests {
         assert_eq!(encode_api_key(""), "Basic ");
     }
 
+    #[test]
+    fn providers_of_base_urls() {
+        assert_eq!(Provider::detect("https://wakatime.com"), Provider::Wakatime);
+        assert_eq!(
+            Provider::detect("https://api.wakatime.com:443/"),
+            Provider::Wakatime
+        );
+        assert_eq!(Provider::detect("https://wakapi.dev"), Provider::Wakapi);
+        assert_eq!(
+            Provider::detect("https://notwakatime.com"),
+            Provider::Wakapi
+        );
+    }
+
+    #[test]
+    fn fetch_heartbeats_from_wakatime() {
+        let mut server = mockito::Server::new();
+        let mock = server
+            .mock("GET", "/api/v1/users/current/heartbeats?date=2026-02-01")
+            .match_header("Authorization", "Basic abc")
+            .with_body(r#"{"data":[{"time":100,"project":"foo"}],"timezone":"UTC"}"#)
+            .create();
+
+        let client =
+            WakapiClient::new(&server.url(), "me", "Basic abc", 15.0).provider(Provider::Wakatime);
+        let result = client
+            .fetch_heartbeats(NaiveDate::from_ymd_opt(2026, 2, 1).unwrap())
+            .unwrap();
+
+        assert_eq!(result.len(), 1);
+        mock.assert();
+    }
+
+    #[test]
+    fn fetch_summaries_of_a_range() {
+        let mut server = mockito::Server::new();
+        let mock = server
+            .mock(
+                "GET",
+                "/api/compat/wakatime/v1/users/me/summaries?start=2026-02-01&end=2026-02-28",
+            )
+            .match_header("Authorization", "Basic abc")
+            .with_body(
+                r#"{"data":[{"range":{"date":"2026-02-02"},"projects":[{"name":"api","total_seconds":3600}]}]}"#,
+            )
+            .create();
+
+        let client = WakapiClient::new(&server.url(), "me", "Basic abc", 15.0);
+        let days = client
+            .fetch_summaries(
+                NaiveDate::from_ymd_opt(2026, 2, 1).unwrap(),
+                NaiveDate::from_ymd_opt(2026, 2, 28).unwrap(),
+            )
+            .unwrap();
+
+        assert_eq!(days.len(), 1);
+        assert_eq!(days[0].projects[0].name, "api");
+        mock.assert();
+    }
+
+    #[test]
+    fn fetch_durations_of_a_day() {
+        let mut server = mockito::Server::new();
+        let mock = server
+            .mock(
+                "GET",
+                "/api/compat/wakatime/v1/users/me/durations?date=2026-02-02",
+            )
+            .match_header("Authorization", "Basic abc")
+            .with_body(r#"{"data":[{"project":"api","time":1770022800.5,"duration":1800}]}"#)
+            .create();
+
+        let client = WakapiClient::new(&server.url(), "me", "Basic abc", 15.0);
+        let blocks = client
+            .fetch_durations(NaiveDate::from_ymd_opt(2026, 2, 2).unwrap())
+            .unwrap();
+
+        assert_eq!(blocks.len(), 1);
+        assert_eq!(blocks[0].project.as_deref(), Some("api"));
+        assert_eq!(blocks[0].duration, 1800.0);
+        mock.assert();
+    }
+
+    #[test]
+    fn retries_server_errors_then_gives_up() {
+        let mut server = mockito::Server::new();
+        let mock = server
+            .mock(
+                "GET",
+                "/api/compat/wakatime/v1/users/me/heartbeats?date=2026-02-01",
+            )
+            .with_status(503)
+            .expect(3)
+            .create();
+
+        let client = WakapiClient::new(&server.url(), "me", "Basic abc", 15.0)
+            .retry(RetryPolicy::new(2, 0.0));
+        let result = client.fetch_heartbeats(NaiveDate::from_ymd_opt(2026, 2, 1).unwrap());
+        assert!(matches!(result, Err(WakalyzeError::Http(_))));
+        mock.assert();
+    }
+
+    #[test]
+    fn long_retry_after_fails_without_waiting() {
+        let mut server = mockito::Server::new();
+        let mock = server
+            .mock(
+                "GET",
+                "/api/compat/wakatime/v1/users/me/heartbeats?date=2026-02-01",
+            )
+            .with_status(429)
+            .with_header("retry-after", "86400")
+            .expect(1)
+            .create();
+
+        let client = WakapiClient::new(&server.url(), "me", "Basic abc", 15.0)
+            .retry(RetryPolicy::new(3, 0.0));
+        let result = client.fetch_heartbeats(NaiveDate::from_ymd_opt(2026, 2, 1).unwrap());
+        assert!(matches!(result, Err(WakalyzeError::Http(_))));
+        mock.assert();
+    }
+
+    #[test]
+    fn retry_after_seconds_or_date() {
+        let now = DateTime::parse_from_rfc3339("2026-02-01T12:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        assert_eq!(retry_after("7", now), Some(Duration::from_secs(7)));
+        assert_eq!(
+            retry_after("Sun, 01 Feb 2026 12:00:30 GMT", now),
+            Some(Duration::from_secs(30))
+        );
+        assert_eq!(
+            retry_after("Sun, 01 Feb 2026 11:00:00 GMT", now),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(retry_after("soon", now), None);
+    }
+
+    #[test]
+    fn backoff_doubles_with_jitter() {
+        let policy = RetryPolicy::new(3, 1.0);
+        for attempt in 0..3 {
+            let base = Duration::from_secs(1 << attempt);
+            let wait = policy.backoff(attempt);
+            assert!(wait >= base && wait <= base + base / 2, "{wait:?}");
+        }
+    }
+
     #[test]
     fn fetch_heartbeats_returns_data() {
         let mut server = mockito::Server::new();
@@ -101,6 +552,60 @@ mod tests {
         mock.assert();
     }
 
+    #[test]
+    fn for_each_heartbeat_hands_over_each_heartbeat() {
+        let mut server = mockito::Server::new();
+        server
+            .mock(
+                "GET",
+                "/api/compat/wakatime/v1/users/me/heartbeats?date=2026-02-01",
+            )
+            .with_body(r#"{"data":[{"time":100,"project":"foo"},{"time":200}],"end":"x"}"#)
+            .create();
+
+        let client = WakapiClient::new(&server.url(), "me", "Basic abc", 15.0);
+        let mut times = Vec::new();
+        HeartbeatSource::for_each_heartbeat(
+            &client,
+            NaiveDate::from_ymd_opt(2026, 2, 1).unwrap(),
+            &mut |hb| times.push(hb.time),
+        )
+        .unwrap();
+
+        assert_eq!(times, [Some(100.0), Some(200.0)]);
+    }
+
+    #[test]
+    fn fetch_heartbeats_accepts_gzip() {
+        // `{"data":[{"time":100,"project":"foo"}]}`, gzipped
+        const BODY: &[u8] = &[
+            0x1f, 0x8b, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x02, 0x03, 0xab, 0x56, 0x4a, 0x49,
+            0x2c, 0x49, 0x54, 0xb2, 0x8a, 0xae, 0x56, 0x2a, 0xc9, 0xcc, 0x4d, 0x55, 0xb2, 0x32,
+            0x34, 0x30, 0xd0, 0x51, 0x2a, 0x28, 0xca, 0xcf, 0x4a, 0x4d, 0x2e, 0x51, 0xb2, 0x52,
+            0x4a, 0xcb, 0xcf, 0x57, 0xaa, 0x8d, 0xad, 0x05, 0x00, 0xf1, 0xe2, 0xc8, 0x4f, 0x27,
+            0x00, 0x00, 0x00,
+        ];
+        let mut server = mockito::Server::new();
+        let mock = server
+            .mock(
+                "GET",
+                "/api/compat/wakatime/v1/users/me/heartbeats?date=2026-02-01",
+            )
+            .match_header("Accept-Encoding", mockito::Matcher::Regex("gzip".into()))
+            .with_body(BODY)
+            .with_header("content-type", "application/json")
+            .with_header("content-encoding", "gzip")
+            .create();
+
+        let client = WakapiClient::new(&server.url(), "me", "Basic abc", 15.0);
+        let result = client
+            .fetch_heartbeats(NaiveDate::from_ymd_opt(2026, 2, 1).unwrap())
+            .unwrap();
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].project.as_deref(), Some("foo"));
+        mock.assert();
+    }
+
     #[test]
     fn fetch_heartbeats_missing_data() {
         let mut server = mockito::Server::new();
@@ -139,6 +644,20 @@ mod tests {
         mock.assert();
     }
 
+    #[test]
+    fn read_heartbeats_streams_to_sink() {
+        let body = r#"{"meta":{"n":[1,2]},"data":[{"time":1,"project":"a","extra":{"x":1}},{"time":2}],"total":2}"#;
+        let mut times = Vec::new();
+        read_heartbeats(body.as_bytes(), |hb| times.push(hb.time)).unwrap();
+        assert_eq!(times, vec![Some(1.0), Some(2.0)]);
+    }
+
+    #[test]
+    fn read_heartbeats_rejects_trailing_garbage() {
+        let result = read_heartbeats(r#"{"data":[]} x"#.as_bytes(), |_| {});
+        assert!(matches!(result, Err(WakalyzeError::InvalidResponse(_))));
+    }
+
     #[test]
     fn fetch_heartbeats_strips_trailing_slash() {
         let mut server = mockito::Server::new();
@@ -0,0 +1,75 @@
+//! Clients by project, for reports that bill several repositories to one client (e.g.
+//! `acme-*` to Acme).
+
+use serde::{Deserialize, Serialize};
+
+use crate::ignore::glob_match;
+
+/// Sessions of a project matching any glob of `projects` are totalled under `client`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClientRule {
+    pub client: String,
+    pub projects: Vec<String>,
+}
+
+/// Client of the first rule with a glob matching `project`, if any.
+pub fn client_of<'a>(rules: &'a [ClientRule], project: &str) -> Option<&'a str> {
+    rules
+        .iter()
+        .find(|rule| rule.projects.iter().any(|p| glob_match(p, project)))
+        .map(|rule| rule.client.as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{DaySessions, Session};
+    use crate::summary::{group_totals, GroupBy};
+    use chrono::NaiveDate;
+
+    fn session(seconds: i64, project: Option<&str>) -> Session {
+        Session {
+            seconds,
+            project: project.map(Into::into),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn time_per_client() {
+        let rules: Vec<ClientRule> = serde_json::from_str(
+            r#"[
+                { "client": "Globex", "projects": ["globex-*"] },
+                { "client": "Acme", "projects": ["acme-*", "billing-portal"] },
+                { "client": "Acme mobile", "projects": ["acme-ios"] }
+            ]"#,
+        )
+        .unwrap();
+        assert_eq!(client_of(&rules, "acme-web"), Some("Acme"));
+        assert_eq!(client_of(&rules, "acme-ios"), Some("Acme"));
+        assert_eq!(client_of(&rules, "oss"), None);
+
+        let days = vec![DaySessions {
+            date: NaiveDate::from_ymd_opt(2026, 2, 2).unwrap(),
+            sessions: vec![
+                session(600, Some("acme-api")),
+                session(300, Some("billing-portal")),
+                session(1200, Some("globex-web")),
+                session(100, Some("oss")),
+                session(50, None),
+            ],
+        }];
+        let totals: Vec<_> = group_totals(&days, GroupBy::Client, &rules)
+            .into_iter()
+            .map(|t| (t.name, t.seconds))
+            .collect();
+        assert_eq!(
+            totals,
+            vec![
+                (Some("Globex".to_string()), 1200),
+                (Some("Acme".to_string()), 900),
+                (None, 150),
+            ]
+        );
+    }
+}
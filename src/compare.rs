@@ -0,0 +1,138 @@
+//! `wakalyze compare`: the total and per-project time of two periods side by side, with the
+//! change from the first to the second.
+
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::Serialize;
+
+use crate::core::DaySessions;
+
+/// One project's time in both periods; `project` is `None` for the total row.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CompareRow {
+    pub project: Option<String>,
+    pub before_seconds: i64,
+    pub after_seconds: i64,
+    pub delta_seconds: i64,
+    /// Change relative to the first period; `None` when the project is new in the second.
+    pub change_percent: Option<f64>,
+}
+
+impl CompareRow {
+    fn new(project: Option<String>, before_seconds: i64, after_seconds: i64) -> Self {
+        let delta_seconds = after_seconds - before_seconds;
+        Self {
+            project,
+            before_seconds,
+            after_seconds,
+            delta_seconds,
+            change_percent: (before_seconds > 0)
+                .then(|| delta_seconds as f64 * 100.0 / before_seconds as f64),
+        }
+    }
+}
+
+/// The two periods compared.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Comparison {
+    pub before: String,
+    pub after: String,
+    pub total: CompareRow,
+    /// Most time in the second period first, then in the first.
+    pub projects: Vec<CompareRow>,
+}
+
+fn project_seconds(days: &[DaySessions]) -> BTreeMap<Option<String>, i64> {
+    let mut totals = BTreeMap::new();
+    for session in days.iter().flat_map(|day| &day.sessions) {
+        *totals
+            .entry(session.project.as_deref().map(str::to_owned))
+            .or_default() += session.seconds;
+    }
+    totals
+}
+
+/// Compare the sessions of the period labelled `before` with those labelled `after`.
+pub fn compare(
+    before: &str,
+    before_days: &[DaySessions],
+    after: &str,
+    after_days: &[DaySessions],
+) -> Comparison {
+    let before_totals = project_seconds(before_days);
+    let after_totals = project_seconds(after_days);
+    let mut projects: Vec<CompareRow> = before_totals
+        .keys()
+        .chain(after_totals.keys())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .map(|project| {
+            CompareRow::new(
+                project.clone(),
+                before_totals.get(project).copied().unwrap_or_default(),
+                after_totals.get(project).copied().unwrap_or_default(),
+            )
+        })
+        .collect();
+    projects.sort_by_key(|p| Reverse((p.after_seconds, p.before_seconds)));
+    Comparison {
+        before: before.to_string(),
+        after: after.to_string(),
+        total: CompareRow::new(
+            None,
+            before_totals.values().sum(),
+            after_totals.values().sum(),
+        ),
+        projects,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Session;
+    use chrono::NaiveDate;
+
+    fn day(sessions: &[(i64, &str)]) -> DaySessions {
+        DaySessions {
+            date: NaiveDate::from_ymd_opt(2026, 2, 2).unwrap(),
+            sessions: sessions
+                .iter()
+                .map(|&(seconds, project)| Session {
+                    seconds,
+                    project: Some(project.into()),
+                    ..Default::default()
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn deltas_per_project_and_in_total() {
+        let before = [day(&[(3600, "api"), (1800, "docs")]), day(&[(3600, "api")])];
+        let after = [day(&[(5400, "api"), (900, "web")])];
+        let comparison = compare("2026/01", &before, "2026/02", &after);
+
+        assert_eq!(comparison.total, CompareRow::new(None, 9000, 6300));
+        let rows: Vec<_> = comparison
+            .projects
+            .iter()
+            .map(|row| {
+                (
+                    row.project.as_deref().unwrap(),
+                    row.delta_seconds,
+                    row.change_percent,
+                )
+            })
+            .collect();
+        assert_eq!(
+            rows,
+            [
+                ("api", -1800, Some(-25.0)),
+                ("web", 900, None),
+                ("docs", -1800, Some(-100.0)),
+            ]
+        );
+    }
+}
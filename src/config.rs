@@ -1,10 +1,25 @@
-use std::path::PathBuf;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
 
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 
-use crate::error::Result;
+use crate::billing::BillingRule;
+use crate::cache::CachePolicy;
+use crate::client::Provider;
+use crate::clients::ClientRule;
+use crate::encryption::CacheEncryption;
+use crate::error::{Result, WakalyzeError};
+use crate::goals::Goal;
+use crate::i18n::Lang;
+use crate::ignore::IgnoreRules;
+use crate::invoice::InvoiceConfig;
+use crate::projects::{MergeRule, ProjectMap};
+use crate::theme::ThemeConfig;
+use crate::timesheet::TimesheetLayout;
 
-#[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq)]
 pub struct Config {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub key: Option<String>,
@@ -12,6 +27,129 @@ pub struct Config {
     pub user: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub base_url: Option<String>,
+    /// Server software; detected from `base_url` if unset.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub provider: Option<Provider>,
+    /// Layout of `--format timesheet-xml` / `timesheet-csv`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timesheet: Option<TimesheetLayout>,
+    /// Rates, billable projects, and client details of `wakalyze invoice`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub invoice: Option<InvoiceConfig>,
+    /// Globs of projects whose heartbeats are ignored (e.g. `scratch*`).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ignore_projects: Vec<String>,
+    /// Globs of entities whose heartbeats are ignored (e.g. `**/node_modules/**`).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ignore_entities: Vec<String>,
+    /// Exact project renames, checked before `project_rules`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub project_aliases: BTreeMap<String, String>,
+    /// Glob project renames, first match wins (e.g. `acme-*` to `Acme`).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub project_rules: Vec<MergeRule>,
+    /// How long recent days are served from the cache.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cache: Option<CachePolicy>,
+    /// Where the key of the encrypted heartbeat cache comes from; plain cache if unset.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cache_encryption: Option<CacheEncryption>,
+    /// Leave Saturdays and Sundays out of every analysis unless `--include-weekends` is given.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub skip_weekends: bool,
+    /// Further servers the same person reports to; their heartbeats are merged into those of
+    /// the main `base_url`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub servers: Vec<ServerConfig>,
+    /// Branch globs mapped to billing codes, first match wins.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub billing_rules: Vec<BillingRule>,
+    /// Project globs per client, first match wins, for `--group-by client`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub clients: Vec<ClientRule>,
+    /// Local checkouts of projects, for the commits of `wakalyze velocity`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub repositories: BTreeMap<String, PathBuf>,
+    /// Weekly or monthly hour targets shown by `wakalyze goals status`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub goals: Vec<Goal>,
+    /// Language of the text labels when `--lang` is not given.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lang: Option<Lang>,
+    /// Colors of the text output; the `dark` preset if unset.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub theme: Option<ThemeConfig>,
+    /// Named accounts chosen with `--profile`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, Profile>,
+    /// Profile used when `--profile` is not given.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_profile: Option<String>,
+    /// IANA time zone of day boundaries and displayed times when `--timezone` is not given;
+    /// the system's if unset.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timezone: Option<String>,
+}
+
+/// An account in `profiles`. It replaces the top-level `key`, `user`, `base_url`, and
+/// `provider` as a whole; the other settings are shared by all profiles.
+#[derive(Debug, Default, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Profile {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_url: Option<String>,
+    /// Server software; detected from `base_url` if unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider: Option<Provider>,
+}
+
+/// Another Wakapi server in `servers`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ServerConfig {
+    pub base_url: String,
+    pub key: String,
+    /// User on that server, if it differs from the main one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+    /// Server software; detected from `base_url` if unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider: Option<Provider>,
+}
+
+impl Config {
+    pub fn ignore_rules(&self) -> IgnoreRules {
+        IgnoreRules {
+            projects: self.ignore_projects.clone(),
+            entities: self.ignore_entities.clone(),
+        }
+    }
+
+    pub fn project_map(&self) -> ProjectMap {
+        ProjectMap {
+            aliases: self.project_aliases.clone(),
+            rules: self.project_rules.clone(),
+        }
+    }
+
+    /// The config with the account of profile `name`, or of `default_profile` if `None`.
+    pub fn with_profile(mut self, name: Option<&str>) -> Result<Config> {
+        let Some(name) = name.or(self.default_profile.as_deref()) else {
+            return Ok(self);
+        };
+        let profile = self
+            .profiles
+            .get(name)
+            .cloned()
+            .ok_or_else(|| WakalyzeError::UnknownProfile(name.to_string()))?;
+        self.key = profile.key;
+        self.user = profile.user;
+        self.base_url = profile.base_url;
+        self.provider = profile.provider;
+        Ok(self)
+    }
 }
 
 pub fn config_path() -> PathBuf {
@@ -27,6 +165,43 @@ pub fn config_path() -> PathBuf {
         .join("config.json")
 }
 
+/// Directory of local state that is not configuration (annotations, manual entries).
+pub fn state_dir() -> PathBuf {
+    if let Ok(xdg) = std::env::var("XDG_STATE_HOME") {
+        if !xdg.is_empty() {
+            return PathBuf::from(xdg).join("wakalyze");
+        }
+    }
+    dirs::home_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(".local")
+        .join("state")
+        .join("wakalyze")
+}
+
+/// A state file, or the default if it does not exist yet.
+pub fn load_state<T: DeserializeOwned + Default>(path: &Path) -> Result<T> {
+    match std::fs::read_to_string(path) {
+        Ok(text) => Ok(serde_json::from_str(&text)?),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(T::default()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Replace a state file atomically.
+pub fn save_state<T: Serialize>(path: &Path, state: &T) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let temp_path = path.with_extension("tmp");
+    std::fs::write(
+        &temp_path,
+        format!("{}\n", serde_json::to_string_pretty(state)?),
+    )?;
+    std::fs::rename(&temp_path, path)?;
+    Ok(())
+}
+
 pub fn load_config_from(path: &std::path::Path) -> Config {
     let text = match std::fs::read_to_string(path) {
         Ok(t) => t,
@@ -50,15 +225,74 @@ pub fn load_config_from(path: &std::path::Path) -> Config {
         }
     };
 
+    fn parsed<T: DeserializeOwned + Default>(value: Option<&serde_json::Value>) -> T {
+        value
+            .and_then(|v| serde_json::from_value(v.clone()).ok())
+            .unwrap_or_default()
+    }
+
     Config {
         key: str_field("key"),
         user: str_field("user"),
         base_url: str_field("base_url"),
+        timesheet: obj
+            .get("timesheet")
+            .and_then(|v| serde_json::from_value(v.clone()).ok()),
+        invoice: obj
+            .get("invoice")
+            .and_then(|v| serde_json::from_value(v.clone()).ok()),
+        ignore_projects: parsed(obj.get("ignore_projects")),
+        ignore_entities: parsed(obj.get("ignore_entities")),
+        project_aliases: parsed(obj.get("project_aliases")),
+        project_rules: parsed(obj.get("project_rules")),
+        cache: obj
+            .get("cache")
+            .and_then(|v| serde_json::from_value(v.clone()).ok()),
+        cache_encryption: obj
+            .get("cache_encryption")
+            .and_then(|v| serde_json::from_value(v.clone()).ok()),
+        skip_weekends: obj
+            .get("skip_weekends")
+            .and_then(serde_json::Value::as_bool)
+            .unwrap_or_default(),
+        servers: parsed(obj.get("servers")),
+        billing_rules: parsed(obj.get("billing_rules")),
+        clients: parsed(obj.get("clients")),
+        repositories: parsed(obj.get("repositories")),
+        goals: parsed(obj.get("goals")),
+        lang: obj
+            .get("lang")
+            .and_then(|v| serde_json::from_value(v.clone()).ok()),
+        theme: obj
+            .get("theme")
+            .and_then(|v| serde_json::from_value(v.clone()).ok()),
+        provider: obj
+            .get("provider")
+            .and_then(|v| serde_json::from_value(v.clone()).ok()),
+        profiles: parsed(obj.get("profiles")),
+        default_profile: str_field("default_profile"),
+        timezone: str_field("timezone"),
     }
 }
 
+static PROFILE: OnceLock<Option<String>> = OnceLock::new();
+
+/// Read the account of profile `name` (or of `default_profile`) in every later `load_config`.
+pub fn select_profile(name: Option<String>) -> Result<()> {
+    load_config_from(&config_path()).with_profile(name.as_deref())?;
+    PROFILE.set(name).ok();
+    Ok(())
+}
+
+/// The stored config with the account of the selected profile. A profile that does not exist
+/// is reported by `select_profile`; here it leaves the top-level account in place.
 pub fn load_config() -> Config {
-    load_config_from(&config_path())
+    let config = load_config_from(&config_path());
+    let name = PROFILE.get().cloned().flatten();
+    config
+        .clone()
+        .with_profile(name.as_deref())
+        .unwrap_or(config)
 }
 
 pub fn save_config_to(path: &std::path::Path, config: &Config) -> Result<()> {
@@ -99,6 +333,7 @@ pub fn mask_secret(value: &str) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::goals::GoalPeriod;
 
     #[test]
     fn config_path_xdg() {
@@ -118,11 +353,43 @@ mod tests {
             key: Some("tok".into()),
             user: Some("me".into()),
             base_url: None,
+            timesheet: None,
+            invoice: None,
+            ignore_projects: vec!["scratch*".into()],
+            ignore_entities: vec![],
+            project_aliases: BTreeMap::new(),
+            project_rules: vec![MergeRule {
+                pattern: "acme-*".into(),
+                regex: None,
+                project: "Acme".into(),
+            }],
+            skip_weekends: true,
+            servers: vec![ServerConfig {
+                base_url: "https://wakatime.com".into(),
+                key: "other".into(),
+                user: None,
+                provider: None,
+            }],
+            lang: Some(Lang::Ja),
+            goals: vec![Goal {
+                period: GoalPeriod::Week,
+                hours: 27.5,
+                project: Some("api".into()),
+            }],
+            clients: vec![ClientRule {
+                client: "Acme".into(),
+                projects: vec!["acme-*".into()],
+            }],
+            timezone: Some("Asia/Tokyo".into()),
+            ..Default::default()
         };
         save_config_to(&path, &config).unwrap();
         let loaded = load_config_from(&path);
         assert_eq!(loaded.key.as_deref(), Some("tok"));
         assert_eq!(loaded.user.as_deref(), Some("me"));
+        assert_eq!(loaded.ignore_projects, vec!["scratch*"]);
+        assert_eq!(loaded.project_map().resolve("acme-api"), "Acme");
+        assert_eq!(loaded, config);
     }
 
     #[test]
@@ -161,6 +428,51 @@ mod tests {
         assert!(loaded.user.is_none());
     }
 
+    #[test]
+    fn load_timesheet_layout() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.json");
+        std::fs::write(
+            &path,
+            r#"{"timesheet":{"root":"TimeSheet","constants":{"EmployeeID":"E42"}}}"#,
+        )
+        .unwrap();
+        let layout = load_config_from(&path).timesheet.unwrap();
+        assert_eq!(layout.root, "TimeSheet");
+        assert_eq!(layout.entry, "Entry");
+        assert_eq!(layout.constants["EmployeeID"], "E42");
+    }
+
+    #[test]
+    fn load_profiles() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.json");
+        std::fs::write(
+            &path,
+            r#"{"user":"me","key":"home","default_profile":"work","skip_weekends":true,
+                "profiles":{"work":{"user":"me.work","base_url":"https://wakapi.work.example"}}}"#,
+        )
+        .unwrap();
+        let config = load_config_from(&path);
+
+        let work = config.clone().with_profile(None).unwrap();
+        assert_eq!(work.user.as_deref(), Some("me.work"));
+        assert_eq!(
+            work.base_url.as_deref(),
+            Some("https://wakapi.work.example")
+        );
+        assert_eq!(work.key, None);
+        assert!(work.skip_weekends);
+
+        let mut top = config.clone();
+        top.default_profile = None;
+        assert_eq!(top.clone().with_profile(None).unwrap(), top);
+        assert!(matches!(
+            config.with_profile(Some("home")),
+            Err(WakalyzeError::UnknownProfile(name)) if name == "home"
+        ));
+    }
+
     #[test]
     fn mask_secret_empty() {
         assert_eq!(mask_secret(""), "");
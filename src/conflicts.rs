@@ -0,0 +1,200 @@
+//! Heartbeats that share a timestamp but disagree on the project, e.g. from two editors.
+
+use std::collections::BTreeSet;
+
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+use crate::core::{iso8601, RawHeartbeat};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum ConflictPolicy {
+    /// The project of the surrounding heartbeats; ties as `non-empty`
+    #[default]
+    Neighbors,
+    /// Any heartbeat with a project over one without, then the first project by name
+    NonEmpty,
+    /// Whichever heartbeat came first in the fetched data
+    First,
+}
+
+/// Distinct projects seen at one timestamp and the one the policy picked.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProjectConflict {
+    pub time: i64,
+    /// In name order; `None` for heartbeats without a project.
+    pub projects: Vec<Option<String>>,
+    pub chosen: Option<String>,
+}
+
+impl Serialize for ProjectConflict {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+        let mut state = serializer.serialize_struct("ProjectConflict", 4)?;
+        state.serialize_field("time", &self.time)?;
+        state.serialize_field("time_iso", &iso8601(self.time))?;
+        state.serialize_field("projects", &self.projects)?;
+        state.serialize_field("chosen", &self.chosen)?;
+        state.end()
+    }
+}
+
+fn project_of(hb: &RawHeartbeat) -> Option<&str> {
+    hb.project.as_deref().filter(|p| !p.trim().is_empty())
+}
+
+fn second_of(hb: &RawHeartbeat) -> Option<i64> {
+    hb.time.map(|t| t as i64)
+}
+
+/// Prefer a project over none, then name order.
+fn non_empty_choice<'a>(candidates: &BTreeSet<Option<&'a str>>) -> Option<&'a str> {
+    candidates.iter().flatten().next().copied()
+}
+
+/// Give every heartbeat of a conflicting timestamp (same second) the project chosen by
+/// `policy`, so session building no longer depends on which one came first.
+/// `heartbeats` must be sorted by time.
+pub fn resolve_conflicts(
+    heartbeats: &mut [RawHeartbeat],
+    policy: ConflictPolicy,
+) -> Vec<ProjectConflict> {
+    // Runs of heartbeats in the same second, as index ranges.
+    let mut groups: Vec<(usize, usize)> = Vec::new();
+    let mut start = 0;
+    while start < heartbeats.len() {
+        let second = second_of(&heartbeats[start]);
+        let len = heartbeats[start..]
+            .iter()
+            .take_while(|hb| second_of(hb) == second)
+            .count();
+        groups.push((start, start + len));
+        start += len;
+    }
+
+    let mut conflicts = Vec::new();
+    // Project of the group before, once there is one.
+    let mut previous: Option<Option<String>> = None;
+    for (g, &(start, end)) in groups.iter().enumerate() {
+        let group = &heartbeats[start..end];
+        let candidates: BTreeSet<Option<&str>> = group.iter().map(project_of).collect();
+        let Some(time) = second_of(&group[0]).filter(|_| candidates.len() > 1) else {
+            previous = Some(project_of(&group[group.len() - 1]).map(str::to_owned));
+            continue;
+        };
+
+        let chosen = match policy {
+            ConflictPolicy::First => project_of(&group[0]),
+            ConflictPolicy::NonEmpty => non_empty_choice(&candidates),
+            ConflictPolicy::Neighbors => {
+                let next: BTreeSet<Option<&str>> = groups
+                    .get(g + 1)
+                    .map(|&(s, e)| heartbeats[s..e].iter().map(project_of).collect())
+                    .unwrap_or_default();
+                let score = |candidate: &Option<&str>| {
+                    usize::from(
+                        previous
+                            .as_ref()
+                            .is_some_and(|p| p.as_deref() == *candidate),
+                    ) + usize::from(next.contains(candidate))
+                };
+                let best = candidates.iter().map(score).max().unwrap_or(0);
+                let tied: BTreeSet<Option<&str>> = candidates
+                    .iter()
+                    .filter(|c| score(c) == best)
+                    .copied()
+                    .collect();
+                if tied.len() == 1 {
+                    tied.into_iter().next().flatten()
+                } else {
+                    non_empty_choice(&tied)
+                }
+            }
+        }
+        .map(str::to_owned);
+
+        conflicts.push(ProjectConflict {
+            time,
+            projects: candidates.iter().map(|p| p.map(str::to_owned)).collect(),
+            chosen: chosen.clone(),
+        });
+        for hb in &mut heartbeats[start..end] {
+            hb.project = chosen.clone();
+        }
+        previous = Some(chosen);
+    }
+    conflicts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hb(time: f64, project: Option<&str>) -> RawHeartbeat {
+        RawHeartbeat {
+            time: Some(time),
+            project: project.map(str::to_string),
+            ..Default::default()
+        }
+    }
+
+    fn projects(heartbeats: &[RawHeartbeat]) -> Vec<Option<&str>> {
+        heartbeats.iter().map(|hb| hb.project.as_deref()).collect()
+    }
+
+    #[test]
+    fn neighbors_pick_the_surrounding_project() {
+        let mut heartbeats = vec![
+            hb(100.0, Some("api")),
+            hb(200.0, Some("web")),
+            hb(200.2, Some("api")),
+            hb(300.0, Some("api")),
+        ];
+        let conflicts = resolve_conflicts(&mut heartbeats, ConflictPolicy::Neighbors);
+        assert_eq!(
+            conflicts,
+            vec![ProjectConflict {
+                time: 200,
+                projects: vec![Some("api".into()), Some("web".into())],
+                chosen: Some("api".into()),
+            }]
+        );
+        assert_eq!(
+            projects(&heartbeats),
+            vec![Some("api"), Some("api"), Some("api"), Some("api")]
+        );
+    }
+
+    #[test]
+    fn neighbors_tie_falls_back_to_non_empty() {
+        let mut heartbeats = vec![hb(200.0, None), hb(200.0, Some("web"))];
+        resolve_conflicts(&mut heartbeats, ConflictPolicy::Neighbors);
+        assert_eq!(projects(&heartbeats), vec![Some("web"), Some("web")]);
+    }
+
+    #[test]
+    fn non_empty_and_first_policies() {
+        let input = vec![
+            hb(200.0, None),
+            hb(200.0, Some("web")),
+            hb(200.0, Some("api")),
+        ];
+
+        let mut heartbeats = input.clone();
+        resolve_conflicts(&mut heartbeats, ConflictPolicy::NonEmpty);
+        assert_eq!(projects(&heartbeats), vec![Some("api"); 3]);
+
+        let mut heartbeats = input;
+        let conflicts = resolve_conflicts(&mut heartbeats, ConflictPolicy::First);
+        assert_eq!(conflicts[0].chosen, None);
+        assert_eq!(conflicts[0].projects.len(), 3);
+        assert_eq!(projects(&heartbeats), vec![None; 3]);
+    }
+
+    #[test]
+    fn agreeing_duplicates_are_not_conflicts() {
+        let mut heartbeats = vec![hb(200.0, Some("api")), hb(200.0, Some("api"))];
+        assert!(resolve_conflicts(&mut heartbeats, ConflictPolicy::Neighbors).is_empty());
+    }
+}
@@ -1,49 +1,305 @@
-use std::collections::{BTreeMap, BTreeSet};
-
-use chrono::{Datelike, Local, NaiveDate, TimeZone};
-use serde::Deserialize;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::sync::Arc;
+
+use chrono::{
+    DateTime, Datelike, Duration, FixedOffset, LocalResult, NaiveDate, NaiveTime, SecondsFormat,
+    Timelike, Weekday,
+};
+use rayon::prelude::*;
+use regex::Regex;
+use serde::ser::SerializeStruct;
+use serde::{Deserialize, Serialize, Serializer};
 
 use crate::error::{Result, WakalyzeError};
+use crate::ignore::{glob_match, IgnoreRules};
+use crate::projects::ProjectMap;
+use crate::zone;
 
 pub const DEFAULT_MAX_GAP_SECONDS: i64 = 15 * 60;
 
-#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct RawHeartbeat {
     pub time: Option<f64>,
     pub project: Option<String>,
+    /// Machine that sent the heartbeat (`machine_name_id` in the Wakapi payload).
+    #[serde(default, rename = "machine_name_id", alias = "machine")]
+    pub machine: Option<String>,
+    /// File (or domain/app) the heartbeat was recorded for.
+    #[serde(default)]
+    pub entity: Option<String>,
+    #[serde(default)]
+    pub language: Option<String>,
+    /// Editor or IDE the heartbeat was sent from, e.g. `vscode`; left out of the JSON when
+    /// unset so archives written before it keep their checksums.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub editor: Option<String>,
+    /// VCS branch, when the editor plugin reports one.
+    #[serde(default)]
+    pub branch: Option<String>,
+    #[serde(default)]
+    pub operating_system: Option<String>,
+    /// Kind of activity, e.g. `coding`, `debugging`, or `browsing`.
+    #[serde(default)]
+    pub category: Option<String>,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct HeartbeatEntry {
     pub time: i64,
-    pub project: Option<String>,
+    pub project: Option<Arc<str>>,
+    pub language: Option<Arc<str>>,
+    pub editor: Option<Arc<str>>,
+    pub branch: Option<Arc<str>>,
+    pub operating_system: Option<Arc<str>>,
+    /// Lowercased; `None` when the heartbeat has no category.
+    pub category: Option<Arc<str>>,
+}
+
+/// Number of a session's heartbeats recorded in one language.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LanguageShare {
+    pub language: Arc<str>,
+    pub heartbeats: usize,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
 pub struct Session {
     pub start: i64,
     pub end: i64,
     pub seconds: i64,
-    pub project: Option<String>,
+    pub project: Option<Arc<str>>,
+    /// Languages seen in the session, most heartbeats first.
+    pub languages: Vec<LanguageShare>,
+    /// Editor with the most heartbeats in the session, if any were reported.
+    pub editor: Option<Arc<str>>,
+    /// Branch with the most heartbeats in the session, if any were reported.
+    pub branch: Option<Arc<str>>,
+    /// Operating system with the most heartbeats in the session, if any were reported.
+    pub operating_system: Option<Arc<str>>,
+    /// Category with the most heartbeats in the session, if any were reported.
+    pub category: Option<Arc<str>>,
+    /// Heartbeats the session was built from; 0 for manual entries.
+    pub heartbeats: usize,
+    /// Local annotation added with `wakalyze tag`.
+    pub note: Option<String>,
+    pub tags: Vec<String>,
+    /// Entered with `wakalyze add` rather than built from heartbeats.
+    pub manual: bool,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+impl Session {
+    pub fn dominant_language(&self) -> Option<&str> {
+        self.languages.first().map(|share| &*share.language)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct DaySessions {
     pub date: NaiveDate,
     pub sessions: Vec<Session>,
 }
 
+impl Serialize for Session {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("Session", 18)?;
+        state.serialize_field("start", &self.start)?;
+        state.serialize_field("start_iso", &iso8601(self.start))?;
+        state.serialize_field("start_local", &local_iso8601(self.start))?;
+        state.serialize_field("end", &self.end)?;
+        state.serialize_field("end_iso", &iso8601(self.end))?;
+        state.serialize_field("end_local", &local_iso8601(self.end))?;
+        state.serialize_field("seconds", &self.seconds)?;
+        state.serialize_field("heartbeats", &self.heartbeats)?;
+        state.serialize_field("project", &self.project)?;
+        state.serialize_field("language", &self.dominant_language())?;
+        state.serialize_field("languages", &self.languages)?;
+        state.serialize_field("editor", &self.editor)?;
+        state.serialize_field("branch", &self.branch)?;
+        state.serialize_field("operating_system", &self.operating_system)?;
+        state.serialize_field("category", &self.category)?;
+        state.serialize_field("note", &self.note)?;
+        state.serialize_field("tags", &self.tags)?;
+        state.serialize_field("manual", &self.manual)?;
+        state.end()
+    }
+}
+
+/// Render an epoch timestamp as an RFC 3339 / ISO-8601 UTC string.
+pub fn iso8601(timestamp: i64) -> String {
+    DateTime::from_timestamp(timestamp, 0)
+        .map(|dt| dt.to_rfc3339_opts(SecondsFormat::Secs, true))
+        .unwrap_or_default()
+}
+
+/// Render an epoch timestamp as an RFC 3339 string with the UTC offset of the selected zone.
+pub fn local_iso8601(timestamp: i64) -> String {
+    zone::at(timestamp)
+        .single()
+        .map(|dt| dt.to_rfc3339_opts(SecondsFormat::Secs, true))
+        .unwrap_or_default()
+}
+
+/// First day of a month given as `YYYY/MM`, `YYYY-MM`, `YYYYMM`, `Feb 2026`, `current`, or
+/// `last`, see [`parse_month_on`].
 pub fn parse_month(value: &str) -> Result<NaiveDate> {
-    let (year_str, month_str) = value.split_once('/').ok_or(WakalyzeError::InvalidMonth)?;
+    parse_month_on(value, zone::today())
+}
+
+/// Like [`parse_month`], with `current` and `last` relative to `today`. The month may lack
+/// its leading zero, and names may be abbreviated or in full, in any case.
+pub fn parse_month_on(value: &str, today: NaiveDate) -> Result<NaiveDate> {
+    let value = value.trim();
+    let this_month = today.with_day(1).ok_or(WakalyzeError::InvalidMonth)?;
+    match value.to_lowercase().as_str() {
+        "current" | "this" => return Ok(this_month),
+        "last" => {
+            return this_month
+                .pred_opt()
+                .and_then(|day| day.with_day(1))
+                .ok_or(WakalyzeError::InvalidMonth)
+        }
+        _ => {}
+    }
+    let numeric = match value.split_once(['/', '-']) {
+        Some(parts) => Some(parts),
+        None if value.len() == 6 && value.bytes().all(|b| b.is_ascii_digit()) => {
+            Some(value.split_at(4))
+        }
+        None => None,
+    };
+    if let Some((year_str, month_str)) = numeric {
+        if year_str.len() != 4 || !(1..=2).contains(&month_str.len()) {
+            return Err(WakalyzeError::InvalidMonth);
+        }
+        let year: i32 = year_str.parse().map_err(|_| WakalyzeError::InvalidMonth)?;
+        let month: u32 = month_str.parse().map_err(|_| WakalyzeError::InvalidMonth)?;
+        return NaiveDate::from_ymd_opt(year, month, 1).ok_or(WakalyzeError::InvalidMonth);
+    }
+    NaiveDate::parse_from_str(&format!("1 {value}"), "%d %B %Y")
+        .map_err(|_| WakalyzeError::InvalidMonth)
+}
+
+/// The month, whole year, or run of days an analysis covers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Period {
+    /// First day of the month.
+    Month(NaiveDate),
+    Year(i32),
+    /// First and last day, from `today`, `yesterday`, `this-week`, or `last-week`.
+    Days(NaiveDate, NaiveDate),
+}
+
+impl Period {
+    /// A year as `YYYY`, a relative keyword, else a month as in [`parse_month`].
+    pub fn parse(value: &str) -> Result<Period> {
+        Period::parse_on(value, zone::today())
+    }
+
+    /// Like [`Period::parse`], with the relative keywords resolved against `today`. Weeks
+    /// start on Sunday as in [`week_range`].
+    pub fn parse_on(value: &str, today: NaiveDate) -> Result<Period> {
+        let value = value.trim();
+        if value.len() == 4 && value.bytes().all(|b| b.is_ascii_digit()) {
+            let year = value.parse().map_err(|_| WakalyzeError::InvalidMonth)?;
+            NaiveDate::from_ymd_opt(year, 1, 1).ok_or(WakalyzeError::InvalidMonth)?;
+            return Ok(Period::Year(year));
+        }
+        let sunday = today - Duration::days(today.weekday().num_days_from_sunday() as i64);
+        match value.to_lowercase().as_str() {
+            "today" => Ok(Period::Days(today, today)),
+            "yesterday" => {
+                let yesterday = today.pred_opt().ok_or(WakalyzeError::InvalidMonth)?;
+                Ok(Period::Days(yesterday, yesterday))
+            }
+            "this-week" => Ok(Period::Days(sunday, sunday + Duration::days(6))),
+            "last-week" => Ok(Period::Days(
+                sunday - Duration::days(7),
+                sunday - Duration::days(1),
+            )),
+            "this-month" => parse_month_on("current", today).map(Period::Month),
+            "last-month" => parse_month_on("last", today).map(Period::Month),
+            _ => parse_month_on(value, today).map(Period::Month),
+        }
+    }
 
-    if year_str.len() != 4 || month_str.len() != 2 {
-        return Err(WakalyzeError::InvalidMonth);
+    pub fn first_day(self) -> NaiveDate {
+        match self {
+            Period::Month(first_day) => first_day,
+            Period::Year(year) => NaiveDate::from_ymd_opt(year, 1, 1).unwrap(),
+            Period::Days(first_day, _) => first_day,
+        }
     }
 
-    let year: i32 = year_str.parse().map_err(|_| WakalyzeError::InvalidMonth)?;
-    let month: u32 = month_str.parse().map_err(|_| WakalyzeError::InvalidMonth)?;
+    pub fn last_day(self) -> NaiveDate {
+        match self {
+            Period::Month(first_day) => month_last_day(first_day),
+            Period::Year(year) => NaiveDate::from_ymd_opt(year, 12, 31).unwrap(),
+            Period::Days(_, last_day) => last_day,
+        }
+    }
 
-    NaiveDate::from_ymd_opt(year, month, 1).ok_or(WakalyzeError::InvalidMonth)
+    /// `2026/02`, `2026`, `2026-02-03`, or `2026-02-01 ~ 2026-02-07`.
+    pub fn label(self) -> String {
+        match self {
+            Period::Month(first_day) => first_day.format("%Y/%m").to_string(),
+            Period::Year(year) => year.to_string(),
+            Period::Days(first_day, last_day) if first_day == last_day => first_day.to_string(),
+            Period::Days(first_day, last_day) => format!("{first_day} ~ {last_day}"),
+        }
+    }
+}
+
+pub fn parse_date(value: &str) -> Result<NaiveDate> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d").map_err(|_| WakalyzeError::InvalidDate)
+}
+
+pub fn parse_clock(value: &str) -> Result<NaiveTime> {
+    NaiveTime::parse_from_str(value, "%H:%M").map_err(|_| WakalyzeError::InvalidTime)
+}
+
+/// Seconds of a length such as `90s`, `3m`, or `1h`; a bare number is minutes.
+pub fn parse_span(value: &str) -> Result<i64> {
+    let invalid = || WakalyzeError::InvalidSpan(value.to_string());
+    let trimmed = value.trim();
+    let (number, unit) = trimmed.split_at(
+        trimmed
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(trimmed.len()),
+    );
+    let number: i64 = number.parse().map_err(|_| invalid())?;
+    let unit = match unit {
+        "s" => 1,
+        "m" | "" => 60,
+        "h" => 3600,
+        _ => return Err(invalid()),
+    };
+    Ok(number * unit)
+}
+
+/// `UTC`/`Z` or a `±HH:MM` (also `±HHMM`, `±HH`) offset from UTC.
+pub fn parse_utc_offset(value: &str) -> Result<FixedOffset> {
+    if value.eq_ignore_ascii_case("utc") || value == "Z" {
+        return Ok(FixedOffset::east_opt(0).expect("zero offset"));
+    }
+    let invalid = || WakalyzeError::InvalidOffset(value.to_string());
+    let (sign, rest) = if let Some(rest) = value.strip_prefix('+') {
+        (1, rest)
+    } else if let Some(rest) = value.strip_prefix('-') {
+        (-1, rest)
+    } else {
+        return Err(invalid());
+    };
+    let digits = rest.replace(':', "");
+    if !matches!(digits.len(), 2 | 4) || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(invalid());
+    }
+    let hours: i32 = digits[..2].parse().map_err(|_| invalid())?;
+    let minutes: i32 = digits[2..].parse().unwrap_or(0);
+    if minutes >= 60 {
+        return Err(invalid());
+    }
+    FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60)).ok_or_else(invalid)
 }
 
 pub fn month_last_day(first_day: NaiveDate) -> NaiveDate {
@@ -65,6 +321,10 @@ pub fn iter_dates(start: NaiveDate, end: NaiveDate) -> Vec<NaiveDate> {
     .collect()
 }
 
+pub fn is_weekend(date: NaiveDate) -> bool {
+    matches!(date.weekday(), Weekday::Sat | Weekday::Sun)
+}
+
 pub fn week_range(first_day: NaiveDate, week: u32) -> Result<(NaiveDate, NaiveDate)> {
     if !(1..=6).contains(&week) {
         return Err(WakalyzeError::InvalidWeek);
@@ -80,6 +340,24 @@ pub fn week_range(first_day: NaiveDate, week: u32) -> Result<(NaiveDate, NaiveDa
     Ok((start, end))
 }
 
+/// Limit a period to the days up to `today`: a period starting after today is an error, and one
+/// in progress ends today unless `include_future` is set.
+pub fn clamp_to_today(
+    start: NaiveDate,
+    end: NaiveDate,
+    today: NaiveDate,
+    include_future: bool,
+) -> Result<(NaiveDate, NaiveDate)> {
+    if start > today {
+        return Err(WakalyzeError::FuturePeriod(start));
+    }
+    if include_future {
+        Ok((start, end))
+    } else {
+        Ok((start, end.min(today)))
+    }
+}
+
 pub fn estimate_seconds(times: &[i64], max_gap: i64) -> i64 {
     let unique: BTreeSet<i64> = times.iter().copied().collect();
     let sorted: Vec<i64> = unique.into_iter().collect();
@@ -93,119 +371,626 @@ pub fn estimate_seconds(times: &[i64], max_gap: i64) -> i64 {
     total
 }
 
+/// Turns the heartbeat timestamps of one session into an estimated number of seconds worked.
+pub trait Estimator: Send + Sync {
+    fn estimate(&self, times: &[i64], max_gap: i64) -> i64;
+
+    /// Name recorded in reports, so consumers know how time was estimated.
+    fn name(&self) -> &str {
+        "custom"
+    }
+}
+
+/// The default estimator: sums gaps between consecutive heartbeats up to `max_gap`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GapEstimator;
+
+impl Estimator for GapEstimator {
+    fn estimate(&self, times: &[i64], max_gap: i64) -> i64 {
+        estimate_seconds(times, max_gap)
+    }
+
+    fn name(&self) -> &str {
+        "gap"
+    }
+}
+
+impl<F> Estimator for F
+where
+    F: Fn(&[i64], i64) -> i64 + Send + Sync,
+{
+    fn estimate(&self, times: &[i64], max_gap: i64) -> i64 {
+        self(times, max_gap)
+    }
+}
+
+/// Pool of shared project names, so repeated names cost one allocation.
+#[derive(Debug, Default)]
+pub struct Interner {
+    names: HashSet<Arc<str>>,
+}
+
+impl Interner {
+    pub fn intern(&mut self, name: &str) -> Arc<str> {
+        if let Some(existing) = self.names.get(name) {
+            return Arc::clone(existing);
+        }
+        let name: Arc<str> = Arc::from(name);
+        self.names.insert(Arc::clone(&name));
+        name
+    }
+}
+
+/// 2000-01-01T00:00:00Z, the default lower bound of [`Sanitizer`].
+pub const MIN_PLAUSIBLE_TIME: i64 = 946_684_800;
+
+/// Heartbeat times at or above this (year 5138 in seconds) are taken to be milli-, micro-, or
+/// nanoseconds.
+const SCALED_TIME_THRESHOLD: f64 = 1e11;
+
+/// Plausibility check of heartbeat timestamps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sanitizer {
+    /// Earlier timestamps are dropped.
+    pub min_time: i64,
+    /// Later timestamps are dropped.
+    pub max_time: i64,
+}
+
+impl Sanitizer {
+    /// From 2000-01-01 to one day past `now`, for heartbeats fetched at `now`.
+    pub fn at(now: i64) -> Self {
+        Self {
+            min_time: MIN_PLAUSIBLE_TIME,
+            max_time: now + 86_400,
+        }
+    }
+
+    /// Only scales timestamps down to seconds, without bounds.
+    pub fn units_only() -> Self {
+        Self {
+            min_time: i64::MIN,
+            max_time: i64::MAX,
+        }
+    }
+
+    /// The time in seconds, scaled down from milli-/micro-/nanoseconds if needed, and whether
+    /// it was scaled; `None` when it is out of bounds.
+    pub fn sanitize(&self, time: f64) -> Option<(f64, bool)> {
+        self.check(time).ok()
+    }
+
+    /// Like [`Self::sanitize`], with the reason a time is out of bounds.
+    pub fn check(&self, time: f64) -> std::result::Result<(f64, bool), Implausible> {
+        if !time.is_finite() {
+            return Err(Implausible::NotFinite);
+        }
+        let mut seconds = time;
+        let mut scaled = false;
+        while seconds.abs() >= SCALED_TIME_THRESHOLD {
+            seconds /= 1000.0;
+            scaled = true;
+        }
+        if seconds < self.min_time as f64 {
+            Err(Implausible::TooEarly)
+        } else if seconds > self.max_time as f64 {
+            Err(Implausible::Future)
+        } else {
+            Ok((seconds, scaled))
+        }
+    }
+}
+
+/// Why a [`Sanitizer`] rejected a timestamp.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Implausible {
+    /// NaN or infinite.
+    NotFinite,
+    /// Before [`Sanitizer::min_time`], e.g. before the epoch.
+    TooEarly,
+    /// After [`Sanitizer::max_time`].
+    Future,
+}
+
+/// What a [`Sanitizer`] changed, and what else about the fetched heartbeats is suspect.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct SanitizeStats {
+    /// Timestamps scaled down to seconds.
+    pub normalized: usize,
+    /// Heartbeats dropped for an implausible timestamp: `not_finite + too_early + future`.
+    pub dropped: usize,
+    pub not_finite: usize,
+    pub too_early: usize,
+    pub future: usize,
+    /// Heartbeats fetched, including the neighbouring days of the range.
+    pub fetched: usize,
+    /// Heartbeats without a time, which no session counts.
+    pub missing_time: usize,
+    /// Repeats of a heartbeat with the same time, project, and machine, dropped.
+    pub duplicates: usize,
+    /// Heartbeats without a project, counted as `unknown`.
+    pub empty_project: usize,
+}
+
+impl SanitizeStats {
+    fn record(&mut self, reason: Implausible) {
+        self.dropped += 1;
+        match reason {
+            Implausible::NotFinite => self.not_finite += 1,
+            Implausible::TooEarly => self.too_early += 1,
+            Implausible::Future => self.future += 1,
+        }
+    }
+}
+
+/// Normalize the times of `heartbeats` in place and drop the implausible ones. Heartbeats
+/// without a time are kept.
+pub fn sanitize_heartbeats(
+    heartbeats: &mut Vec<RawHeartbeat>,
+    sanitizer: &Sanitizer,
+) -> SanitizeStats {
+    let mut stats = SanitizeStats::default();
+    heartbeats.retain_mut(|hb| {
+        let Some(time) = hb.time else {
+            stats.missing_time += 1;
+            return true;
+        };
+        match sanitizer.check(time) {
+            Ok((seconds, scaled)) => {
+                hb.time = Some(seconds);
+                stats.normalized += usize::from(scaled);
+                stats.empty_project +=
+                    usize::from(hb.project.as_deref().is_none_or(|p| p.trim().is_empty()));
+                true
+            }
+            Err(reason) => {
+                stats.record(reason);
+                false
+            }
+        }
+    });
+    stats
+}
+
+/// Session entries of the heartbeats with a time, sorted; millisecond (and finer) timestamps
+/// are scaled to seconds, see [`extract_entries_with`] to also drop implausible ones.
 pub fn extract_entries(heartbeats: &[RawHeartbeat]) -> Vec<HeartbeatEntry> {
+    extract_entries_with(heartbeats, &Sanitizer::units_only(), &EntryRules::default()).0
+}
+
+/// Which heartbeats become session entries, and under which project.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EntryRules {
+    pub ignore: IgnoreRules,
+    pub projects: ProjectMap,
+}
+
+/// Like [`extract_entries`] with the timestamps checked by `sanitizer`, skipping the
+/// heartbeats that `rules` ignore (by their original project) and renaming projects.
+pub fn extract_entries_with(
+    heartbeats: &[RawHeartbeat],
+    sanitizer: &Sanitizer,
+    rules: &EntryRules,
+) -> (Vec<HeartbeatEntry>, SanitizeStats) {
+    let mut interner = Interner::default();
+    let mut stats = SanitizeStats::default();
     let mut entries: Vec<HeartbeatEntry> = heartbeats
         .iter()
         .filter_map(|hb| {
-            let time = hb.time? as i64;
+            if rules
+                .ignore
+                .ignores(hb.project.as_deref(), hb.entity.as_deref())
+            {
+                return None;
+            }
+            let (time, scaled) = match sanitizer.check(hb.time?) {
+                Ok(checked) => checked,
+                Err(reason) => {
+                    stats.record(reason);
+                    return None;
+                }
+            };
+            stats.normalized += usize::from(scaled);
             let project = hb
                 .project
                 .as_deref()
                 .filter(|p| !p.trim().is_empty())
-                .map(String::from);
-            Some(HeartbeatEntry { time, project })
+                .map(|p| interner.intern(rules.projects.resolve(p)));
+            let language = hb
+                .language
+                .as_deref()
+                .filter(|l| !l.trim().is_empty())
+                .map(|l| interner.intern(l));
+            let editor = hb
+                .editor
+                .as_deref()
+                .filter(|e| !e.trim().is_empty())
+                .map(|e| interner.intern(e));
+            let branch = hb
+                .branch
+                .as_deref()
+                .filter(|b| !b.trim().is_empty())
+                .map(|b| interner.intern(b));
+            let operating_system = hb
+                .operating_system
+                .as_deref()
+                .filter(|os| !os.trim().is_empty())
+                .map(|os| interner.intern(os));
+            let category = hb
+                .category
+                .as_deref()
+                .map(str::trim)
+                .filter(|c| !c.is_empty())
+                .map(|c| interner.intern(&c.to_lowercase()));
+            Some(HeartbeatEntry {
+                time: time as i64,
+                project,
+                language,
+                editor,
+                branch,
+                operating_system,
+                category,
+            })
         })
         .collect();
     entries.sort_by_key(|e| e.time);
-    entries
+    (entries, stats)
+}
+
+pub fn local_date(timestamp: i64) -> Option<NaiveDate> {
+    day_of(timestamp, NaiveTime::MIN)
+}
+
+/// Local date a timestamp counts towards when days start at `day_start`: earlier heartbeats
+/// still belong to the previous day.
+pub fn day_of(timestamp: i64, day_start: NaiveTime) -> Option<NaiveDate> {
+    let shift = Duration::seconds(i64::from(day_start.num_seconds_from_midnight()));
+    zone::at(timestamp)
+        .single()
+        .map(|dt| (dt.naive_local() - shift).date())
+}
+
+/// Timestamp of a local wall-clock time. An ambiguous time (clocks set back) resolves to its
+/// earlier instant, a time skipped by clocks going forward to the first minute after the gap.
+pub fn local_timestamp(date: NaiveDate, time: NaiveTime) -> Option<i64> {
+    let naive = date.and_time(time);
+    match zone::from_local(&naive) {
+        LocalResult::Single(dt) | LocalResult::Ambiguous(dt, _) => Some(dt.timestamp()),
+        LocalResult::None => (1..=180).find_map(|minutes| {
+            zone::from_local(&(naive + Duration::minutes(minutes)))
+                .earliest()
+                .map(|dt| dt.timestamp())
+        }),
+    }
+}
+
+/// How far the local clocks move on `date`: positive when they go forward (a 23-hour day),
+/// negative when they go back, 0 on ordinary days.
+pub fn dst_shift_seconds(date: NaiveDate) -> i64 {
+    let midnight = |d: NaiveDate| local_timestamp(d, NaiveTime::MIN);
+    match (midnight(date), date.succ_opt().and_then(midnight)) {
+        (Some(start), Some(end)) => 86_400 - (end - start),
+        _ => 0,
+    }
 }
 
 pub fn group_heartbeats_by_local_date(
     heartbeats: Vec<RawHeartbeat>,
+) -> BTreeMap<NaiveDate, Vec<RawHeartbeat>> {
+    group_heartbeats_by_day(heartbeats, NaiveTime::MIN)
+}
+
+/// Like [`group_heartbeats_by_local_date`] with days starting at `day_start`, see [`day_of`].
+pub fn group_heartbeats_by_day(
+    heartbeats: Vec<RawHeartbeat>,
+    day_start: NaiveTime,
 ) -> BTreeMap<NaiveDate, Vec<RawHeartbeat>> {
     let mut map: BTreeMap<NaiveDate, Vec<RawHeartbeat>> = BTreeMap::new();
     for hb in heartbeats {
-        if let Some(ts) = hb.time {
-            if let Some(dt) = Local.timestamp_opt(ts as i64, 0).single() {
-                map.entry(dt.date_naive()).or_default().push(hb);
-            }
+        if let Some(date) = hb.time.and_then(|ts| day_of(ts as i64, day_start)) {
+            map.entry(date).or_default().push(hb);
         }
     }
     map
 }
 
 pub fn build_sessions(heartbeats: &[RawHeartbeat], max_gap: i64) -> Vec<Session> {
+    build_sessions_with(heartbeats, max_gap, &GapEstimator)
+}
+
+pub fn build_sessions_with(
+    heartbeats: &[RawHeartbeat],
+    max_gap: i64,
+    estimator: &dyn Estimator,
+) -> Vec<Session> {
     let entries = extract_entries(heartbeats);
-    if entries.is_empty() {
-        return Vec::new();
-    }
+    session_runs(&entries, max_gap)
+        .iter()
+        .map(|run| make_session(run, None, None, max_gap, estimator))
+        .collect()
+}
 
-    let mut sessions = Vec::new();
-    let mut current_times = vec![entries[0].time];
-    let mut current_project = entries[0].project.clone();
-    let mut prev_time = entries[0].time;
+/// Sessions of heartbeats from any number of days, split where a new day starts (see
+/// [`day_of`]). A session running past a day start ends there and continues from it on the
+/// next day, so the time across the boundary is counted, each side on its own day.
+/// Heartbeats are taken as `rules` say.
+pub fn build_day_sessions(
+    heartbeats: &[RawHeartbeat],
+    max_gap: i64,
+    estimator: &dyn Estimator,
+    day_start: NaiveTime,
+    rules: &EntryRules,
+) -> Vec<DaySessions> {
+    let (entries, _) = extract_entries_with(heartbeats, &Sanitizer::units_only(), rules);
+    // Runs are independent; an indexed parallel collect keeps them in time order. The worker
+    // threads do not see a zone scoped to this one.
+    let zone = zone::selected();
+    let runs: Vec<Vec<(NaiveDate, Session)>> = session_runs(&entries, max_gap)
+        .into_par_iter()
+        .map(|run| zone::scoped(zone, || split_run(&run, max_gap, estimator, day_start)))
+        .collect();
+    let mut days: BTreeMap<NaiveDate, Vec<Session>> = BTreeMap::new();
+    for (date, session) in runs.into_iter().flatten() {
+        days.entry(date).or_default().push(session);
+    }
+    days.into_iter()
+        .map(|(date, sessions)| DaySessions { date, sessions })
+        .collect()
+}
 
-    for entry in &entries[1..] {
-        if entry.time == prev_time {
+/// Sessions of one run, one per day it touches, each cut at the day boundaries it crosses.
+fn split_run(
+    run: &[&HeartbeatEntry],
+    max_gap: i64,
+    estimator: &dyn Estimator,
+    day_start: NaiveTime,
+) -> Vec<(NaiveDate, Session)> {
+    let mut pieces: Vec<(NaiveDate, Vec<&HeartbeatEntry>)> = Vec::new();
+    for &entry in run {
+        let Some(date) = day_of(entry.time, day_start) else {
             continue;
+        };
+        match pieces.last_mut() {
+            Some((last, piece)) if *last == date => piece.push(entry),
+            _ => pieces.push((date, vec![entry])),
         }
-        let gap = entry.time - prev_time;
-        if gap <= max_gap && entry.project == current_project {
-            current_times.push(entry.time);
-        } else {
-            sessions.push(make_session(
-                &current_times,
-                current_project.as_deref(),
-                max_gap,
+    }
+    let count = pieces.len();
+    pieces
+        .iter()
+        .enumerate()
+        .map(|(i, (date, piece))| {
+            let from = (i > 0).then(|| local_timestamp(*date, day_start)).flatten();
+            let until = (i + 1 < count)
+                .then(|| local_timestamp(date.succ_opt()?, day_start))
+                .flatten();
+            (*date, make_session(piece, from, until, max_gap, estimator))
+        })
+        .collect()
+}
+
+/// Check what every report relies on: sessions are ordered and do not overlap, none counts
+/// more than its own start-to-end span, and so the total never exceeds the wall-clock span.
+pub fn verify_sessions<'a>(sessions: impl IntoIterator<Item = &'a Session>) -> Result<()> {
+    let violation = |message: String| Err(WakalyzeError::InvariantViolated(message));
+    let mut previous: Option<&Session> = None;
+    let mut first_start: Option<i64> = None;
+    let mut total: i64 = 0;
+    for session in sessions {
+        let span = session.end - session.start;
+        if span < 0 {
+            return violation(format!(
+                "session at {} ends before it starts",
+                session.start
+            ));
+        }
+        if session.seconds < 0 || session.seconds > span {
+            return violation(format!(
+                "session at {} counts {}s over a {span}s span",
+                session.start, session.seconds
             ));
-            current_times = vec![entry.time];
-            current_project = entry.project.clone();
         }
-        prev_time = entry.time;
+        if let Some(prev) = previous {
+            if session.start < prev.end {
+                return violation(format!(
+                    "session at {} starts before the previous one ends at {}",
+                    session.start, prev.end
+                ));
+            }
+        }
+        let first_start = *first_start.get_or_insert(session.start);
+        total += session.seconds;
+        if total > session.end - first_start {
+            return violation(format!(
+                "{total}s counted within {}s of wall-clock time",
+                session.end - first_start
+            ));
+        }
+        previous = Some(session);
     }
+    Ok(())
+}
 
-    sessions.push(make_session(
-        &current_times,
-        current_project.as_deref(),
-        max_gap,
-    ));
-    sessions
+/// Heartbeats grouped into sessions: same project, gaps of at most `max_gap`. Heartbeats
+/// repeating the previous timestamp are skipped.
+fn session_runs(entries: &[HeartbeatEntry], max_gap: i64) -> Vec<Vec<&HeartbeatEntry>> {
+    let mut runs: Vec<Vec<&HeartbeatEntry>> = Vec::new();
+    let mut previous: Option<&HeartbeatEntry> = None;
+    for entry in entries {
+        match previous {
+            Some(prev) if entry.time == prev.time => continue,
+            Some(prev) if entry.time - prev.time <= max_gap && entry.project == prev.project => {
+                runs.last_mut().expect("run of previous entry").push(entry);
+            }
+            _ => runs.push(vec![entry]),
+        }
+        previous = Some(entry);
+    }
+    runs
 }
 
-fn make_session(times: &[i64], project: Option<&str>, max_gap: i64) -> Session {
+/// Session of `entries`, optionally starting at `from` and running until `until` when it
+/// continues from/into another day.
+fn make_session(
+    entries: &[&HeartbeatEntry],
+    from: Option<i64>,
+    until: Option<i64>,
+    max_gap: i64,
+    estimator: &dyn Estimator,
+) -> Session {
+    let times: Vec<i64> = from
+        .filter(|&from| from < entries[0].time)
+        .into_iter()
+        .chain(entries.iter().map(|e| e.time))
+        .chain(until)
+        .collect();
+    let languages: Vec<Option<&Arc<str>>> = entries.iter().map(|e| e.language.as_ref()).collect();
+    let editors: Vec<Option<&Arc<str>>> = entries.iter().map(|e| e.editor.as_ref()).collect();
+    let branches: Vec<Option<&Arc<str>>> = entries.iter().map(|e| e.branch.as_ref()).collect();
+    let systems: Vec<Option<&Arc<str>>> = entries
+        .iter()
+        .map(|e| e.operating_system.as_ref())
+        .collect();
+    let categories: Vec<Option<&Arc<str>>> = entries.iter().map(|e| e.category.as_ref()).collect();
     Session {
         start: times[0],
         end: *times.last().unwrap(),
-        seconds: estimate_seconds(times, max_gap),
-        project: project.map(str::to_owned),
+        seconds: estimator.estimate(&times, max_gap),
+        project: entries[0].project.clone(),
+        languages: language_shares(&languages),
+        editor: name_counts(&editors)
+            .into_iter()
+            .next()
+            .map(|(editor, _)| editor),
+        branch: name_counts(&branches)
+            .into_iter()
+            .next()
+            .map(|(branch, _)| branch),
+        operating_system: name_counts(&systems).into_iter().next().map(|(os, _)| os),
+        category: name_counts(&categories)
+            .into_iter()
+            .next()
+            .map(|(category, _)| category),
+        heartbeats: entries.len(),
+        ..Default::default()
     }
 }
 
-pub fn filter_sessions(days: &[DaySessions], filter: Option<&str>) -> Vec<DaySessions> {
-    let term = match filter {
-        Some(t) if !t.is_empty() => t,
-        _ => return days.to_vec(),
-    };
+/// Heartbeat count per name, most first; ties in name order.
+fn name_counts(names: &[Option<&Arc<str>>]) -> Vec<(Arc<str>, usize)> {
+    let mut counts: BTreeMap<&Arc<str>, usize> = BTreeMap::new();
+    for name in names.iter().flatten() {
+        *counts.entry(name).or_default() += 1;
+    }
+    let mut counts: Vec<(Arc<str>, usize)> = counts
+        .into_iter()
+        .map(|(name, count)| (Arc::clone(name), count))
+        .collect();
+    counts.sort_by_key(|c| Reverse(c.1));
+    counts
+}
 
-    let needles: Vec<String> = term
+fn language_shares(languages: &[Option<&Arc<str>>]) -> Vec<LanguageShare> {
+    name_counts(languages)
+        .into_iter()
+        .map(|(language, heartbeats)| LanguageShare {
+            language,
+            heartbeats,
+        })
+        .collect()
+}
+
+/// Lowercased, trimmed terms of a comma-separated `--filter`; empty when nothing filters.
+pub fn filter_needles(filter: Option<&str>) -> Vec<String> {
+    filter
+        .unwrap_or("")
         .split(',')
         .map(|s| s.trim().to_lowercase())
         .filter(|s| !s.is_empty())
-        .collect();
+        .collect()
+}
 
+/// Whether `project` contains any of `needles` (case-insensitive); true without needles.
+pub fn project_matches(project: Option<&str>, needles: &[String]) -> bool {
     if needles.is_empty() {
-        return days.to_vec();
-    }
-
-    days.iter()
-        .filter_map(|day| {
-            let sessions: Vec<Session> = day
-                .sessions
-                .iter()
-                .filter(|s| {
-                    let proj = s.project.as_deref().unwrap_or("").to_lowercase();
-                    needles.iter().any(|needle| proj.contains(needle.as_str()))
-                })
-                .cloned()
-                .collect();
-            if sessions.is_empty() {
-                None
-            } else {
-                Some(DaySessions {
-                    date: day.date,
-                    sessions,
-                })
-            }
+        return true;
+    }
+    let proj = project.unwrap_or("").to_lowercase();
+    needles.iter().any(|needle| proj.contains(needle.as_str()))
+}
+
+/// Keep sessions whose project matches any comma-separated term (case-insensitive substring).
+///
+/// Filters in place: surviving days and sessions are moved, not cloned.
+pub fn filter_sessions(days: Vec<DaySessions>, filter: Option<&str>) -> Vec<DaySessions> {
+    let needles = filter_needles(filter);
+    if needles.is_empty() {
+        return days;
+    }
+
+    days.into_iter()
+        .filter_map(|mut day| {
+            day.sessions
+                .retain(|s| project_matches(s.project.as_deref(), &needles));
+            (!day.sessions.is_empty()).then_some(day)
+        })
+        .collect()
+}
+
+/// A `--filter-regex` pattern, matched against whole or partial project names.
+pub fn parse_project_regex(pattern: &str) -> Result<Regex> {
+    Regex::new(pattern).map_err(|e| WakalyzeError::InvalidRegex(format!("{pattern}: {e}")))
+}
+
+/// Keep sessions whose project matches `regex` (a session without one matches as `""`).
+pub fn filter_sessions_regex(days: Vec<DaySessions>, regex: Option<&Regex>) -> Vec<DaySessions> {
+    let Some(regex) = regex else {
+        return days;
+    };
+
+    days.into_iter()
+        .filter_map(|mut day| {
+            day.sessions
+                .retain(|s| regex.is_match(s.project.as_deref().unwrap_or("")));
+            (!day.sessions.is_empty()).then_some(day)
+        })
+        .collect()
+}
+
+/// Keep sessions whose branch (the one with the most heartbeats) matches a glob `pattern`,
+/// such as `feature/*`; sessions without a branch are dropped.
+pub fn filter_sessions_by_branch(
+    days: Vec<DaySessions>,
+    pattern: Option<&str>,
+) -> Vec<DaySessions> {
+    let Some(pattern) = pattern else {
+        return days;
+    };
+
+    days.into_iter()
+        .filter_map(|mut day| {
+            day.sessions.retain(|s| {
+                s.branch
+                    .as_deref()
+                    .is_some_and(|branch| glob_match(pattern, branch))
+            });
+            (!day.sessions.is_empty()).then_some(day)
+        })
+        .collect()
+}
+
+/// Drop sessions whose project matches any comma-separated term (case-insensitive substring).
+pub fn exclude_sessions(days: Vec<DaySessions>, exclude: Option<&str>) -> Vec<DaySessions> {
+    let needles = filter_needles(exclude);
+    if needles.is_empty() {
+        return days;
+    }
+
+    days.into_iter()
+        .filter_map(|mut day| {
+            day.sessions
+                .retain(|s| !project_matches(s.project.as_deref(), &needles));
+            (!day.sessions.is_empty()).then_some(day)
         })
         .collect()
 }
@@ -213,6 +998,7 @@ pub fn filter_sessions(days: &[DaySessions], filter: Option<&str>) -> Vec<DaySes
 #[cfg(test)]
 mod tests {
     use super::*;
+    use chrono::{Local, TimeZone};
 
     const GAP: i64 = DEFAULT_MAX_GAP_SECONDS;
 
@@ -220,6 +1006,7 @@ mod tests {
         RawHeartbeat {
             time: Some(time),
             project: Some(project.to_string()),
+            ..Default::default()
         }
     }
 
@@ -232,13 +1019,27 @@ mod tests {
     }
 
     #[test]
-    fn parse_month_invalid_format() {
-        assert!(parse_month("2026-02").is_err());
+    fn parse_month_other_forms() {
+        let today = NaiveDate::from_ymd_opt(2026, 1, 20).unwrap();
+        let month = |year, month| NaiveDate::from_ymd_opt(year, month, 1).unwrap();
+        for (value, expected) in [
+            ("2026-02", month(2026, 2)),
+            ("202602", month(2026, 2)),
+            ("2026/2", month(2026, 2)),
+            ("Feb 2026", month(2026, 2)),
+            ("february 2026", month(2026, 2)),
+            ("current", month(2026, 1)),
+            ("last", month(2025, 12)),
+        ] {
+            assert_eq!(parse_month_on(value, today).unwrap(), expected, "{value}");
+        }
     }
 
     #[test]
-    fn parse_month_no_leading_zero() {
-        assert!(parse_month("2026/2").is_err());
+    fn parse_month_invalid_format() {
+        for value in ["2026", "26/02", "2026/002", "20262", "Febr 2026", "next"] {
+            assert!(parse_month(value).is_err(), "{value}");
+        }
     }
 
     #[test]
@@ -247,6 +1048,152 @@ mod tests {
         assert!(parse_month("2026/13").is_err());
     }
 
+    #[test]
+    fn parse_period_year_or_month() {
+        let year = Period::parse("2026").unwrap();
+        assert_eq!(year, Period::Year(2026));
+        assert_eq!(
+            year.first_day(),
+            NaiveDate::from_ymd_opt(2026, 1, 1).unwrap()
+        );
+        assert_eq!(
+            year.last_day(),
+            NaiveDate::from_ymd_opt(2026, 12, 31).unwrap()
+        );
+        assert_eq!(year.label(), "2026");
+        let month = Period::parse("202602").unwrap();
+        assert_eq!(
+            month.last_day(),
+            NaiveDate::from_ymd_opt(2026, 2, 28).unwrap()
+        );
+        assert_eq!(month.label(), "2026/02");
+        assert!(Period::parse("20x6").is_err());
+    }
+
+    #[test]
+    fn parse_period_relative_keywords() {
+        // 2026-02-12 is a Thursday.
+        let today = NaiveDate::from_ymd_opt(2026, 2, 12).unwrap();
+        let date = |month, day| NaiveDate::from_ymd_opt(2026, month, day).unwrap();
+        for (value, first, last) in [
+            ("today", date(2, 12), date(2, 12)),
+            ("Yesterday", date(2, 11), date(2, 11)),
+            ("this-week", date(2, 8), date(2, 14)),
+            ("last-week", date(2, 1), date(2, 7)),
+            ("this-month", date(2, 1), date(2, 28)),
+            ("last-month", date(1, 1), date(1, 31)),
+        ] {
+            let period = Period::parse_on(value, today).unwrap();
+            assert_eq!(
+                (period.first_day(), period.last_day()),
+                (first, last),
+                "{value}"
+            );
+        }
+        assert_eq!(
+            Period::parse_on("today", today).unwrap().label(),
+            "2026-02-12"
+        );
+        assert_eq!(
+            Period::parse_on("last-week", today).unwrap().label(),
+            "2026-02-01 ~ 2026-02-07"
+        );
+        assert!(Period::parse_on("next-week", today).is_err());
+    }
+
+    #[test]
+    fn parse_date_and_clock() {
+        assert_eq!(
+            parse_date("2026-02-03").unwrap(),
+            NaiveDate::from_ymd_opt(2026, 2, 3).unwrap()
+        );
+        assert!(matches!(
+            parse_date("2026/02/03"),
+            Err(WakalyzeError::InvalidDate)
+        ));
+        assert_eq!(
+            parse_clock("14:30").unwrap(),
+            NaiveTime::from_hms_opt(14, 30, 0).unwrap()
+        );
+        assert!(matches!(
+            parse_clock("25:00"),
+            Err(WakalyzeError::InvalidTime)
+        ));
+    }
+
+    #[test]
+    fn local_timestamp_roundtrips_local_date() {
+        let date = NaiveDate::from_ymd_opt(2026, 2, 3).unwrap();
+        let ts = local_timestamp(date, NaiveTime::from_hms_opt(14, 30, 0).unwrap()).unwrap();
+        assert_eq!(local_date(ts), Some(date));
+        let midnight = local_timestamp(date, NaiveTime::MIN).unwrap();
+        assert_eq!(ts - midnight, 14 * 3600 + 30 * 60);
+        assert_eq!(dst_shift_seconds(date), 0);
+    }
+
+    #[test]
+    fn clamp_to_today_trims_current_and_rejects_future_periods() {
+        let date = |d| NaiveDate::from_ymd_opt(2026, 2, d).unwrap();
+        assert_eq!(
+            clamp_to_today(date(1), date(28), date(10), false).unwrap(),
+            (date(1), date(10))
+        );
+        assert_eq!(
+            clamp_to_today(date(1), date(28), date(10), true).unwrap(),
+            (date(1), date(28))
+        );
+        assert_eq!(
+            clamp_to_today(date(1), date(7), date(10), false).unwrap(),
+            (date(1), date(7))
+        );
+        assert!(matches!(
+            clamp_to_today(date(11), date(17), date(10), true),
+            Err(WakalyzeError::FuturePeriod(d)) if d == date(11)
+        ));
+    }
+
+    #[test]
+    fn parse_spans() {
+        assert_eq!(parse_span("90s").unwrap(), 90);
+        assert_eq!(parse_span("3m").unwrap(), 180);
+        assert_eq!(parse_span(" 2h").unwrap(), 7200);
+        assert_eq!(parse_span("5").unwrap(), 300);
+        for invalid in ["", "m", "3d", "1.5m", "-3m"] {
+            assert!(
+                matches!(parse_span(invalid), Err(WakalyzeError::InvalidSpan(_))),
+                "{invalid}"
+            );
+        }
+    }
+
+    #[test]
+    fn parse_utc_offsets() {
+        let east = |seconds| FixedOffset::east_opt(seconds).unwrap();
+        assert_eq!(parse_utc_offset("UTC").unwrap(), east(0));
+        assert_eq!(parse_utc_offset("+09:00").unwrap(), east(9 * 3600));
+        assert_eq!(parse_utc_offset("-0530").unwrap(), east(-(5 * 3600 + 1800)));
+        assert_eq!(parse_utc_offset("+02").unwrap(), east(2 * 3600));
+        for invalid in ["09:00", "+9", "+09:60", "+25:00", "Asia/Tokyo"] {
+            assert!(
+                matches!(
+                    parse_utc_offset(invalid),
+                    Err(WakalyzeError::InvalidOffset(_))
+                ),
+                "{invalid}"
+            );
+        }
+    }
+
+    #[test]
+    fn day_of_moves_early_hours_to_previous_day() {
+        let date = NaiveDate::from_ymd_opt(2026, 2, 3).unwrap();
+        let at = |h| local_timestamp(date, NaiveTime::from_hms_opt(h, 30, 0).unwrap()).unwrap();
+        let boundary = NaiveTime::from_hms_opt(4, 0, 0).unwrap();
+        assert_eq!(day_of(at(3), boundary), date.pred_opt());
+        assert_eq!(day_of(at(4), boundary), Some(date));
+        assert_eq!(day_of(at(3), NaiveTime::MIN), Some(date));
+    }
+
     #[test]
     fn month_last_day_january() {
         let first = NaiveDate::from_ymd_opt(2026, 1, 1).unwrap();
@@ -414,11 +1361,21 @@ mod tests {
             vec![
                 HeartbeatEntry {
                     time: 100,
-                    project: Some("bar".into())
+                    project: Some("bar".into()),
+                    language: None,
+                    editor: None,
+                    branch: None,
+                    operating_system: None,
+                    category: None
                 },
                 HeartbeatEntry {
                     time: 200,
-                    project: Some("foo".into())
+                    project: Some("foo".into()),
+                    language: None,
+                    editor: None,
+                    branch: None,
+                    operating_system: None,
+                    category: None
                 },
             ]
         );
@@ -429,6 +1386,7 @@ mod tests {
         let heartbeats = vec![RawHeartbeat {
             time: None,
             project: Some("foo".into()),
+            ..Default::default()
         }];
         assert_eq!(extract_entries(&heartbeats), Vec::<HeartbeatEntry>::new());
     }
@@ -441,23 +1399,86 @@ mod tests {
             result,
             vec![HeartbeatEntry {
                 time: 100,
-                project: Some("foo".into())
+                project: Some("foo".into()),
+                language: None,
+                editor: None,
+                branch: None,
+                operating_system: None,
+                category: None
             }]
         );
     }
 
+    #[test]
+    fn extract_entries_normalizes_milliseconds() {
+        let heartbeats = vec![hb(1_770_000_000_500.0, "foo"), hb(1_770_000_001.0, "foo")];
+        let times: Vec<i64> = extract_entries(&heartbeats)
+            .iter()
+            .map(|e| e.time)
+            .collect();
+        assert_eq!(times, vec![1_770_000_000, 1_770_000_001]);
+    }
+
+    #[test]
+    fn extract_entries_with_drops_implausible_times() {
+        let sanitizer = Sanitizer::at(1_800_000_000 - 86_400);
+        let heartbeats = vec![
+            hb(1_770_000_000_000_000.0, "micros"),
+            hb(100.0, "epoch"),
+            hb(1_900_000_000.0, "future"),
+            hb(f64::NAN, "nan"),
+        ];
+        let (entries, stats) =
+            extract_entries_with(&heartbeats, &sanitizer, &EntryRules::default());
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].time, 1_770_000_000);
+        assert_eq!(
+            stats,
+            SanitizeStats {
+                normalized: 1,
+                dropped: 3,
+                not_finite: 1,
+                too_early: 1,
+                future: 1,
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn sanitize_heartbeats_rewrites_times_in_place() {
+        let mut heartbeats = vec![
+            hb(1_770_000_000_250.0, "ms"),
+            hb(5.0, "old"),
+            RawHeartbeat::default(),
+        ];
+        let stats = sanitize_heartbeats(&mut heartbeats, &Sanitizer::at(1_770_000_000));
+        assert_eq!(stats.normalized, 1);
+        assert_eq!(stats.dropped, 1);
+        assert_eq!(stats.too_early, 1);
+        assert_eq!(stats.missing_time, 1);
+        assert_eq!(heartbeats.len(), 2);
+        assert_eq!(heartbeats[0].time, Some(1_770_000_000.25));
+    }
+
     #[test]
     fn extract_entries_empty_project_becomes_none() {
         let heartbeats = vec![RawHeartbeat {
             time: Some(100.0),
             project: Some("  ".into()),
+            ..Default::default()
         }];
         let result = extract_entries(&heartbeats);
         assert_eq!(
             result,
             vec![HeartbeatEntry {
                 time: 100,
-                project: None
+                project: None,
+                language: None,
+                editor: None,
+                branch: None,
+                operating_system: None,
+                category: None
             }]
         );
     }
@@ -467,17 +1488,42 @@ mod tests {
         let heartbeats = vec![RawHeartbeat {
             time: Some(100.0),
             project: None,
+            ..Default::default()
         }];
         let result = extract_entries(&heartbeats);
         assert_eq!(
             result,
             vec![HeartbeatEntry {
                 time: 100,
-                project: None
+                project: None,
+                language: None,
+                editor: None,
+                branch: None,
+                operating_system: None,
+                category: None
             }]
         );
     }
 
+    #[test]
+    fn extract_entries_interns_project_names() {
+        let heartbeats = vec![hb(100.0, "foo"), hb(200.0, "foo")];
+        let result = extract_entries(&heartbeats);
+        let first = result[0].project.as_ref().unwrap();
+        let second = result[1].project.as_ref().unwrap();
+        assert!(Arc::ptr_eq(first, second));
+    }
+
+    #[test]
+    fn interner_reuses_names() {
+        let mut interner = Interner::default();
+        let a = interner.intern("foo");
+        let b = interner.intern("foo");
+        let c = interner.intern("bar");
+        assert!(Arc::ptr_eq(&a, &b));
+        assert!(!Arc::ptr_eq(&a, &c));
+    }
+
     #[test]
     fn build_sessions_empty() {
         assert_eq!(build_sessions(&[], GAP), Vec::<Session>::new());
@@ -530,6 +1576,84 @@ mod tests {
         assert_eq!(sessions[0].seconds, 300);
     }
 
+    #[test]
+    fn build_sessions_counts_languages() {
+        let in_language = |time: f64, language: Option<&str>| RawHeartbeat {
+            language: language.map(str::to_string),
+            ..hb(time, "foo")
+        };
+        let heartbeats = vec![
+            in_language(1000.0, Some("Rust")),
+            in_language(1100.0, Some("TOML")),
+            in_language(1200.0, Some("Rust")),
+            in_language(1300.0, None),
+        ];
+        let sessions = build_sessions(&heartbeats, GAP);
+        assert_eq!(sessions.len(), 1);
+        let languages: Vec<_> = sessions[0]
+            .languages
+            .iter()
+            .map(|l| (&*l.language, l.heartbeats))
+            .collect();
+        assert_eq!(languages, vec![("Rust", 2), ("TOML", 1)]);
+        assert_eq!(sessions[0].dominant_language(), Some("Rust"));
+    }
+
+    #[test]
+    fn build_sessions_picks_dominant_editor() {
+        let heartbeats: Vec<RawHeartbeat> = serde_json::from_str(
+            r#"[{"time":1000,"project":"foo","editor":"vim"},
+                {"time":1100,"project":"foo","editor":"vscode"},
+                {"time":1200,"project":"foo","editor":"vscode"},
+                {"time":1300,"project":"foo","editor":" "}]"#,
+        )
+        .unwrap();
+        let sessions = build_sessions(&heartbeats, GAP);
+        assert_eq!(sessions[0].editor.as_deref(), Some("vscode"));
+    }
+
+    #[test]
+    fn build_sessions_with_custom_estimator() {
+        let heartbeats = vec![hb(1000.0, "foo"), hb(1300.0, "foo")];
+        let flat = |times: &[i64], _max_gap: i64| times.len() as i64 * 60;
+        let sessions = build_sessions_with(&heartbeats, GAP, &flat);
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].seconds, 120);
+    }
+
+    #[test]
+    fn build_day_sessions_splits_at_midnight() {
+        let date = NaiveDate::from_ymd_opt(2026, 2, 3).unwrap();
+        let next = date.succ_opt().unwrap();
+        let midnight = local_timestamp(next, NaiveTime::MIN).unwrap();
+        let heartbeats = vec![
+            hb((midnight - 300) as f64, "foo"),
+            hb((midnight + 120) as f64, "foo"),
+            hb((midnight + 240) as f64, "foo"),
+        ];
+        let days = build_day_sessions(
+            &heartbeats,
+            GAP,
+            &GapEstimator,
+            NaiveTime::MIN,
+            &EntryRules::default(),
+        );
+        assert_eq!(days.len(), 2);
+        assert_eq!(days[0].date, date);
+        assert_eq!(days[0].sessions[0].start, midnight - 300);
+        assert_eq!(days[0].sessions[0].end, midnight);
+        assert_eq!(days[0].sessions[0].seconds, 300);
+        assert_eq!(days[1].date, next);
+        assert_eq!(days[1].sessions[0].start, midnight);
+        assert_eq!(days[1].sessions[0].seconds, 240);
+        let total: i64 = days
+            .iter()
+            .flat_map(|d| &d.sessions)
+            .map(|s| s.seconds)
+            .sum();
+        assert_eq!(total, build_sessions(&heartbeats, GAP)[0].seconds);
+    }
+
     #[test]
     fn filter_sessions_none_returns_all() {
         let days = vec![DaySessions {
@@ -539,9 +1663,10 @@ mod tests {
                 end: 2,
                 seconds: 1,
                 project: Some("foo".into()),
+                ..Default::default()
             }],
         }];
-        assert_eq!(filter_sessions(&days, None), days);
+        assert_eq!(filter_sessions(days.clone(), None), days);
     }
 
     #[test]
@@ -553,9 +1678,10 @@ mod tests {
                 end: 2,
                 seconds: 1,
                 project: Some("foo".into()),
+                ..Default::default()
             }],
         }];
-        assert_eq!(filter_sessions(&days, Some("")), days);
+        assert_eq!(filter_sessions(days.clone(), Some("")), days);
     }
 
     #[test]
@@ -568,16 +1694,18 @@ mod tests {
                     end: 2,
                     seconds: 1,
                     project: Some("my-project".into()),
+                    ..Default::default()
                 },
                 Session {
                     start: 3,
                     end: 4,
                     seconds: 1,
                     project: Some("other".into()),
+                    ..Default::default()
                 },
             ],
         }];
-        let result = filter_sessions(&days, Some("proj"));
+        let result = filter_sessions(days, Some("proj"));
         assert_eq!(result.len(), 1);
         assert_eq!(result[0].sessions.len(), 1);
         assert_eq!(result[0].sessions[0].project, Some("my-project".into()));
@@ -592,9 +1720,10 @@ mod tests {
                 end: 2,
                 seconds: 1,
                 project: Some("MyProject".into()),
+                ..Default::default()
             }],
         }];
-        let result = filter_sessions(&days, Some("myproject"));
+        let result = filter_sessions(days, Some("myproject"));
         assert_eq!(result.len(), 1);
     }
 
@@ -608,22 +1737,25 @@ mod tests {
                     end: 2,
                     seconds: 1,
                     project: Some("foo".into()),
+                    ..Default::default()
                 },
                 Session {
                     start: 3,
                     end: 4,
                     seconds: 1,
                     project: Some("bar".into()),
+                    ..Default::default()
                 },
                 Session {
                     start: 5,
                     end: 6,
                     seconds: 1,
                     project: Some("baz".into()),
+                    ..Default::default()
                 },
             ],
         }];
-        let result = filter_sessions(&days, Some("foo,bar"));
+        let result = filter_sessions(days, Some("foo,bar"));
         assert_eq!(result.len(), 1);
         let projects: Vec<_> = result[0]
             .sessions
@@ -642,9 +1774,10 @@ mod tests {
                 end: 2,
                 seconds: 1,
                 project: Some("bar".into()),
+                ..Default::default()
             }],
         }];
-        let result = filter_sessions(&days, Some(" , bar , "));
+        let result = filter_sessions(days, Some(" , bar , "));
         assert_eq!(result.len(), 1);
     }
 
@@ -657,12 +1790,127 @@ mod tests {
                 end: 2,
                 seconds: 1,
                 project: Some("foo".into()),
+                ..Default::default()
             }],
         }];
-        let result = filter_sessions(&days, Some("bar"));
+        let result = filter_sessions(days, Some("bar"));
         assert!(result.is_empty());
     }
 
+    #[test]
+    fn filter_sessions_regex_matches_pattern() {
+        let session = |project: &str| Session {
+            start: 1,
+            end: 2,
+            seconds: 1,
+            project: Some(project.into()),
+            ..Default::default()
+        };
+        let days = vec![DaySessions {
+            date: NaiveDate::from_ymd_opt(2026, 2, 1).unwrap(),
+            sessions: vec![
+                session("client-acme"),
+                session("client-acme-old"),
+                session("client-globex"),
+            ],
+        }];
+        assert_eq!(filter_sessions_regex(days.clone(), None), days);
+        let regex = parse_project_regex("^client-(acme|globex)$").unwrap();
+        let result = filter_sessions_regex(days, Some(&regex));
+        assert_eq!(
+            result[0].sessions,
+            vec![session("client-acme"), session("client-globex")]
+        );
+        assert!(matches!(
+            parse_project_regex("client-(acme"),
+            Err(WakalyzeError::InvalidRegex(_))
+        ));
+    }
+
+    #[test]
+    fn filter_sessions_by_branch_glob() {
+        let on = |branch: Option<&str>| Session {
+            start: 1,
+            end: 2,
+            seconds: 1,
+            branch: branch.map(Into::into),
+            ..Default::default()
+        };
+        let days = vec![DaySessions {
+            date: NaiveDate::from_ymd_opt(2026, 2, 1).unwrap(),
+            sessions: vec![on(Some("feature/login")), on(Some("main")), on(None)],
+        }];
+        assert_eq!(filter_sessions_by_branch(days.clone(), None), days);
+        let result = filter_sessions_by_branch(days, Some("feature/*"));
+        assert_eq!(result[0].sessions, vec![on(Some("feature/login"))]);
+    }
+
+    #[test]
+    fn exclude_sessions_drops_matching_projects() {
+        let session = |project: &str| Session {
+            start: 1,
+            end: 2,
+            seconds: 1,
+            project: Some(project.into()),
+            ..Default::default()
+        };
+        let days = vec![
+            DaySessions {
+                date: NaiveDate::from_ymd_opt(2026, 2, 1).unwrap(),
+                sessions: vec![session("api"), session("Scratch-1")],
+            },
+            DaySessions {
+                date: NaiveDate::from_ymd_opt(2026, 2, 2).unwrap(),
+                sessions: vec![session("sandbox")],
+            },
+        ];
+        assert_eq!(exclude_sessions(days.clone(), Some(" , ")), days);
+        let result = exclude_sessions(days, Some("scratch,sandbox"));
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].sessions, vec![session("api")]);
+    }
+
+    #[test]
+    fn iso8601_epoch() {
+        assert_eq!(iso8601(0), "1970-01-01T00:00:00Z");
+    }
+
+    #[test]
+    fn session_serializes_epochs_and_iso() {
+        let session = Session {
+            start: 1_770_000_000,
+            end: 1_770_000_600,
+            seconds: 600,
+            project: Some("foo".into()),
+            ..Default::default()
+        };
+        let value = serde_json::to_value(&session).unwrap();
+        assert_eq!(value["start"], 1_770_000_000);
+        assert_eq!(value["start_iso"], "2026-02-02T02:40:00Z");
+        assert_eq!(value["end_iso"], "2026-02-02T02:50:00Z");
+        let start_local = value["start_local"].as_str().unwrap();
+        assert_eq!(
+            DateTime::parse_from_rfc3339(start_local)
+                .unwrap()
+                .timestamp(),
+            1_770_000_000
+        );
+        assert!(value["language"].is_null());
+        assert_eq!(value["seconds"], 600);
+        assert_eq!(value["project"], "foo");
+    }
+
+    #[test]
+    fn day_sessions_serializes_date() {
+        let day = DaySessions {
+            date: NaiveDate::from_ymd_opt(2026, 2, 1).unwrap(),
+            sessions: vec![],
+        };
+        let value = serde_json::to_value(&day).unwrap();
+        assert_eq!(value["date"], "2026-02-01");
+        assert!(value["sessions"].as_array().unwrap().is_empty());
+    }
+
     #[test]
     fn group_heartbeats_by_local_date_basic() {
         // Two heartbeats 25 hours apart → should land on different local dates
@@ -686,6 +1934,7 @@ mod tests {
         let heartbeats = vec![RawHeartbeat {
             time: None,
             project: Some("proj".into()),
+            ..Default::default()
         }];
         let grouped = group_heartbeats_by_local_date(heartbeats);
         assert!(grouped.is_empty());
@@ -696,4 +1945,76 @@ mod tests {
         let grouped = group_heartbeats_by_local_date(vec![]);
         assert!(grouped.is_empty());
     }
+
+    #[test]
+    fn verify_sessions_rejects_overlap_and_overcounting() {
+        let session = |start, end, seconds| Session {
+            start,
+            end,
+            seconds,
+            ..Default::default()
+        };
+        assert!(verify_sessions(&[session(0, 100, 100), session(100, 200, 50)]).is_ok());
+        assert!(matches!(
+            verify_sessions(&[session(0, 100, 100), session(50, 200, 50)]),
+            Err(WakalyzeError::InvariantViolated(_))
+        ));
+        assert!(verify_sessions(&[session(0, 100, 101)]).is_err());
+        assert!(verify_sessions(&[session(100, 0, 0)]).is_err());
+    }
+
+    mod properties {
+        use super::*;
+        use proptest::prelude::*;
+
+        const BASE: f64 = 1_770_000_000.0;
+
+        fn heartbeats() -> impl Strategy<Value = Vec<RawHeartbeat>> {
+            prop::collection::vec(
+                (
+                    0..3 * 86_400u32,
+                    prop::sample::select(vec!["api", "web", ""]),
+                ),
+                0..200,
+            )
+            .prop_map(|beats| {
+                beats
+                    .into_iter()
+                    .map(|(offset, project)| hb(BASE + f64::from(offset), project))
+                    .collect()
+            })
+        }
+
+        proptest! {
+            #[test]
+            fn build_sessions_keeps_invariants(
+                heartbeats in heartbeats(),
+                max_gap in 1..4 * 3600i64,
+            ) {
+                let sessions = build_sessions(&heartbeats, max_gap);
+                prop_assert!(verify_sessions(&sessions).is_ok(), "{:?}", verify_sessions(&sessions));
+            }
+
+            #[test]
+            fn build_day_sessions_keeps_invariants(
+                heartbeats in heartbeats(),
+                max_gap in 1..4 * 3600i64,
+                boundary_hour in 0..24u32,
+            ) {
+                let day_start = NaiveTime::from_hms_opt(boundary_hour, 0, 0).unwrap();
+                let days = build_day_sessions(
+                    &heartbeats,
+                    max_gap,
+                    &GapEstimator,
+                    day_start,
+                    &EntryRules::default(),
+                );
+                let sessions = days.iter().flat_map(|day| &day.sessions);
+                prop_assert!(verify_sessions(sessions.clone()).is_ok());
+                let split: i64 = sessions.map(|s| s.seconds).sum();
+                let whole: i64 = build_sessions(&heartbeats, max_gap).iter().map(|s| s.seconds).sum();
+                prop_assert_eq!(split, whole);
+            }
+        }
+    }
 }
@@ -0,0 +1,313 @@
+//! Non-destructive fixes applied to the built sessions, e.g. for a week an editor reported
+//! under the wrong project.
+//!
+//! Rules come from `corrections.json` next to the config file:
+//!
+//! ```json
+//! { "corrections": [
+//!     { "rule": "reassign", "project": "scratch", "to": "clientx", "date": "2026-02-02", "until": "2026-02-08" },
+//!     { "rule": "drop", "from": "2026-02-03T12:00:00", "to": "2026-02-03T13:30:00" },
+//!     { "rule": "cap", "date": "2026-02-04", "hours": 8 }
+//! ] }
+//! ```
+
+use std::path::{Path, PathBuf};
+
+use chrono::{NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+
+use crate::config::{config_path, load_state};
+use crate::core::{local_timestamp, DaySessions};
+use crate::error::Result;
+use crate::format::format_duration;
+
+pub fn corrections_path() -> PathBuf {
+    config_path().with_file_name("corrections.json")
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "rule", rename_all = "kebab-case")]
+pub enum Correction {
+    /// Move the sessions of `project` to `to` on `date`, or from `date` through `until`.
+    Reassign {
+        project: String,
+        to: String,
+        date: NaiveDate,
+        #[serde(default)]
+        until: Option<NaiveDate>,
+    },
+    /// Remove the sessions starting in the local time window `from`..`to`.
+    Drop {
+        from: NaiveDateTime,
+        to: NaiveDateTime,
+    },
+    /// Limit a day to `hours`, trimming its latest sessions first.
+    Cap { date: NaiveDate, hours: f64 },
+}
+
+impl Correction {
+    fn describe(&self) -> String {
+        match self {
+            Correction::Reassign {
+                project,
+                to,
+                date,
+                until,
+            } => match until {
+                Some(until) => format!("reassign {project} to {to} from {date} through {until}"),
+                None => format!("reassign {project} to {to} on {date}"),
+            },
+            Correction::Drop { from, to } => format!(
+                "drop sessions starting {} - {}",
+                from.format("%Y-%m-%d %H:%M"),
+                to.format("%Y-%m-%d %H:%M")
+            ),
+            Correction::Cap { date, hours } => format!("cap {date} at {hours}h"),
+        }
+    }
+
+    /// Apply the rule; the number of sessions it changed and the seconds they moved or lost.
+    fn apply(&self, days: &mut [DaySessions]) -> (usize, i64) {
+        let mut sessions = 0;
+        let mut seconds = 0;
+        match self {
+            Correction::Reassign {
+                project,
+                to,
+                date,
+                until,
+            } => {
+                let last = until.unwrap_or(*date);
+                for day in days.iter_mut() {
+                    if day.date < *date || day.date > last {
+                        continue;
+                    }
+                    for session in &mut day.sessions {
+                        if session.project.as_deref() == Some(project.as_str()) {
+                            session.project = Some(to.as_str().into());
+                            sessions += 1;
+                            seconds += session.seconds;
+                        }
+                    }
+                }
+            }
+            Correction::Drop { from, to } => {
+                let (Some(from), Some(to)) = (
+                    local_timestamp(from.date(), from.time()),
+                    local_timestamp(to.date(), to.time()),
+                ) else {
+                    return (0, 0);
+                };
+                for day in days.iter_mut() {
+                    day.sessions.retain(|session| {
+                        let dropped = session.start >= from && session.start < to;
+                        if dropped {
+                            sessions += 1;
+                            seconds += session.seconds;
+                        }
+                        !dropped
+                    });
+                }
+            }
+            Correction::Cap { date, hours } => {
+                let Some(day) = days.iter_mut().find(|day| day.date == *date) else {
+                    return (0, 0);
+                };
+                let cap = (hours * 3600.0) as i64;
+                let mut excess = day.sessions.iter().map(|s| s.seconds).sum::<i64>() - cap;
+                for session in day.sessions.iter_mut().rev() {
+                    if excess <= 0 {
+                        break;
+                    }
+                    let trimmed = excess.min(session.seconds);
+                    session.seconds -= trimmed;
+                    excess -= trimmed;
+                    sessions += 1;
+                    seconds += trimmed;
+                }
+                day.sessions.retain(|session| session.seconds > 0);
+            }
+        }
+        (sessions, seconds)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Corrections {
+    pub corrections: Vec<Correction>,
+}
+
+impl Corrections {
+    /// Rules in `path`; none if the file does not exist.
+    pub fn load(path: &Path) -> Result<Self> {
+        load_state(path)
+    }
+}
+
+/// Audit record of a correction that changed the report.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AppliedCorrection {
+    pub description: String,
+    /// Sessions reassigned, dropped, or trimmed.
+    pub sessions: usize,
+    /// Time reassigned, dropped, or trimmed.
+    pub seconds: i64,
+}
+
+impl AppliedCorrection {
+    pub fn summary(&self) -> String {
+        format!(
+            "{}: {} sessions, {}",
+            self.description,
+            self.sessions,
+            format_duration(self.seconds)
+        )
+    }
+}
+
+/// Apply `corrections` in order, returning those that changed something.
+pub fn apply_corrections(
+    days: &mut Vec<DaySessions>,
+    corrections: &[Correction],
+) -> Vec<AppliedCorrection> {
+    let mut applied = Vec::new();
+    for correction in corrections {
+        let (sessions, seconds) = correction.apply(days);
+        if sessions > 0 {
+            applied.push(AppliedCorrection {
+                description: correction.describe(),
+                sessions,
+                seconds,
+            });
+        }
+    }
+    days.retain(|day| !day.sessions.is_empty());
+    applied
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Session;
+    use chrono::NaiveTime;
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 2, day).unwrap()
+    }
+
+    fn session(day: u32, hour: u32, seconds: i64, project: &str) -> Session {
+        let start =
+            local_timestamp(date(day), NaiveTime::from_hms_opt(hour, 0, 0).unwrap()).unwrap();
+        Session {
+            start,
+            end: start + seconds,
+            seconds,
+            project: Some(project.into()),
+            ..Default::default()
+        }
+    }
+
+    fn days() -> Vec<DaySessions> {
+        vec![
+            DaySessions {
+                date: date(2),
+                sessions: vec![session(2, 9, 3600, "scratch"), session(2, 12, 1800, "api")],
+            },
+            DaySessions {
+                date: date(3),
+                sessions: vec![
+                    session(3, 9, 4 * 3600, "api"),
+                    session(3, 14, 3 * 3600, "scratch"),
+                    session(3, 18, 2 * 3600, "api"),
+                ],
+            },
+        ]
+    }
+
+    #[test]
+    fn parse_rules() {
+        let corrections: Corrections = serde_json::from_str(
+            r#"{ "corrections": [
+                { "rule": "reassign", "project": "a", "to": "b", "date": "2026-02-02" },
+                { "rule": "drop", "from": "2026-02-03T12:00:00", "to": "2026-02-03T13:00:00" },
+                { "rule": "cap", "date": "2026-02-04", "hours": 8 }
+            ] }"#,
+        )
+        .unwrap();
+        assert_eq!(corrections.corrections.len(), 3);
+        assert_eq!(
+            corrections.corrections[2],
+            Correction::Cap {
+                date: date(4),
+                hours: 8.0
+            }
+        );
+    }
+
+    #[test]
+    fn reassign_over_a_date_range() {
+        let mut days = days();
+        let applied = apply_corrections(
+            &mut days,
+            &[Correction::Reassign {
+                project: "scratch".into(),
+                to: "clientx".into(),
+                date: date(2),
+                until: Some(date(3)),
+            }],
+        );
+        assert_eq!(
+            applied,
+            vec![AppliedCorrection {
+                description: "reassign scratch to clientx from 2026-02-02 through 2026-02-03"
+                    .into(),
+                sessions: 2,
+                seconds: 4 * 3600,
+            }]
+        );
+        assert_eq!(days[1].sessions[1].project.as_deref(), Some("clientx"));
+    }
+
+    #[test]
+    fn drop_sessions_starting_in_window_and_empty_days() {
+        let mut days = days();
+        let window = |hour| date(2).and_hms_opt(hour, 0, 0).unwrap();
+        let applied = apply_corrections(
+            &mut days,
+            &[Correction::Drop {
+                from: window(8),
+                to: window(13),
+            }],
+        );
+        assert_eq!(applied[0].sessions, 2);
+        assert_eq!(days.len(), 1);
+        assert_eq!(days[0].date, date(3));
+    }
+
+    #[test]
+    fn cap_trims_latest_sessions_first() {
+        let mut days = days();
+        let applied = apply_corrections(
+            &mut days,
+            &[
+                Correction::Cap {
+                    date: date(3),
+                    hours: 5.0,
+                },
+                Correction::Cap {
+                    date: date(2),
+                    hours: 8.0,
+                },
+            ],
+        );
+        assert_eq!(applied.len(), 1);
+        assert_eq!(applied[0].seconds, 4 * 3600);
+        assert_eq!(
+            applied[0].summary(),
+            "cap 2026-02-03 at 5h: 2 sessions, 4h00m"
+        );
+        let seconds: Vec<i64> = days[1].sessions.iter().map(|s| s.seconds).collect();
+        assert_eq!(seconds, vec![4 * 3600, 3600]);
+    }
+}
@@ -0,0 +1,220 @@
+//! `wakalyze digest`: a few sentences on one week, written for a weekly report email.
+
+use chrono::{Datelike, Duration, NaiveDate};
+use std::cmp::Reverse;
+
+use crate::core::{parse_date, DaySessions, Session};
+use crate::error::{Result, WakalyzeError};
+use crate::format::format_duration;
+use crate::summary::project_totals;
+
+/// How many projects the digest names.
+pub const TOP_PROJECTS: usize = 3;
+/// How many long sessions the digest names.
+pub const LONG_SESSIONS: usize = 3;
+
+/// First day (Sunday, as in `week_range`) of the week `spec` names: `last`, `this`, or any
+/// YYYY-MM-DD date in it.
+pub fn week_start(spec: &str, today: NaiveDate) -> Result<NaiveDate> {
+    let date = match spec {
+        "this" => today,
+        "last" => today - Duration::days(7),
+        _ => parse_date(spec).map_err(|_| WakalyzeError::InvalidWeekSpec(spec.to_string()))?,
+    };
+    Ok(date - Duration::days(date.weekday().num_days_from_sunday() as i64))
+}
+
+/// The inputs of [`digest_text`].
+pub struct Digest<'a> {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    pub days: &'a [DaySessions],
+    /// The week before, for the deltas.
+    pub previous: &'a [DaySessions],
+    pub goal_seconds: Option<i64>,
+    /// Sessions at least this long are called out.
+    pub long_seconds: i64,
+}
+
+fn total(days: &[DaySessions]) -> i64 {
+    days.iter()
+        .flat_map(|day| &day.sessions)
+        .map(|s| s.seconds)
+        .sum()
+}
+
+/// `+1h05m` / `-0h20m`.
+fn signed_duration(seconds: i64) -> String {
+    let sign = if seconds < 0 { '-' } else { '+' };
+    format!("{sign}{}", format_duration(seconds.abs()))
+}
+
+/// `a`, `a and b`, `a, b and c`.
+fn join_prose(items: &[String]) -> String {
+    match items {
+        [] => String::new(),
+        [only] => only.clone(),
+        [init @ .., last] => format!("{} and {last}", init.join(", ")),
+    }
+}
+
+fn project_name(session: &Session) -> &str {
+    session.project.as_deref().unwrap_or("unknown")
+}
+
+/// Totals, top projects with their change vs the week before, long sessions, and goal status.
+pub fn digest_text(digest: &Digest) -> String {
+    let seconds = total(digest.days);
+    let previous_seconds = total(digest.previous);
+    let active_days = digest
+        .days
+        .iter()
+        .filter(|day| day.sessions.iter().any(|s| s.seconds > 0))
+        .count();
+    let mut sentences = vec![format!(
+        "Week of {} - {}: {} over {active_days} {} ({} vs the week before).",
+        digest.start.format("%b %-d"),
+        digest.end.format("%b %-d, %Y"),
+        format_duration(seconds),
+        if active_days == 1 { "day" } else { "days" },
+        signed_duration(seconds - previous_seconds)
+    )];
+
+    let previous = project_totals(digest.previous);
+    let projects: Vec<String> = project_totals(digest.days)
+        .iter()
+        .filter(|total| total.seconds > 0)
+        .take(TOP_PROJECTS)
+        .map(|total| {
+            let before = previous
+                .iter()
+                .find(|p| p.project == total.project)
+                .map_or(0, |p| p.seconds);
+            let change = if before == 0 {
+                "new".to_string()
+            } else {
+                signed_duration(total.seconds - before)
+            };
+            format!(
+                "{} ({}, {change})",
+                total.project.as_deref().unwrap_or("unknown"),
+                format_duration(total.seconds)
+            )
+        })
+        .collect();
+    if !projects.is_empty() {
+        sentences.push(format!("Most time went to {}.", join_prose(&projects)));
+    }
+
+    let mut long: Vec<(NaiveDate, &Session)> = digest
+        .days
+        .iter()
+        .flat_map(|day| day.sessions.iter().map(move |s| (day.date, s)))
+        .filter(|(_, s)| s.seconds >= digest.long_seconds)
+        .collect();
+    long.sort_by_key(|(_, s)| Reverse(s.seconds));
+    let long: Vec<String> = long
+        .iter()
+        .take(LONG_SESSIONS)
+        .map(|(date, session)| {
+            format!(
+                "{} on {} ({})",
+                format_duration(session.seconds),
+                project_name(session),
+                date.format("%A")
+            )
+        })
+        .collect();
+    if !long.is_empty() {
+        sentences.push(format!("Longest sessions: {}.", join_prose(&long)));
+    }
+
+    if let Some(goal) = digest.goal_seconds.filter(|g| *g > 0) {
+        let percent = seconds as f64 * 100.0 / goal as f64;
+        let status = if seconds >= goal {
+            "reached".to_string()
+        } else {
+            format!("{} short", format_duration(goal - seconds))
+        };
+        sentences.push(format!(
+            "Goal of {}: {percent:.0}%, {status}.",
+            format_duration(goal)
+        ));
+    }
+    sentences.join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 2, day).unwrap()
+    }
+
+    fn day(day: u32, sessions: &[(i64, &str)]) -> DaySessions {
+        DaySessions {
+            date: date(day),
+            sessions: sessions
+                .iter()
+                .map(|&(seconds, project)| Session {
+                    seconds,
+                    project: Some(project.into()),
+                    ..Default::default()
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn week_start_is_the_sunday() {
+        let today = date(12);
+        assert_eq!(week_start("this", today).unwrap(), date(8));
+        assert_eq!(week_start("last", today).unwrap(), date(1));
+        assert_eq!(week_start("2026-02-04", today).unwrap(), date(1));
+        assert!(matches!(
+            week_start("next", today),
+            Err(WakalyzeError::InvalidWeekSpec(_))
+        ));
+    }
+
+    #[test]
+    fn prose_with_deltas_long_sessions_and_goal() {
+        let previous = [day(2, &[(3 * 3600, "api"), (2 * 3600, "infra")])];
+        let days = [
+            day(9, &[(3 * 3600, "api"), (1800, "infra")]),
+            day(10, &[(2 * 3600 + 900, "api"), (3600, "docs")]),
+        ];
+        let text = digest_text(&Digest {
+            start: date(8),
+            end: date(14),
+            days: &days,
+            previous: &previous,
+            goal_seconds: Some(9 * 3600),
+            long_seconds: 2 * 3600,
+        });
+        assert_eq!(
+            text,
+            "Week of Feb 8 - Feb 14, 2026: 6h45m over 2 days (+1h45m vs the week before). \
+             Most time went to api (5h15m, +2h15m), docs (1h00m, new) and infra (0h30m, -1h30m). \
+             Longest sessions: 3h00m on api (Monday) and 2h15m on api (Tuesday). \
+             Goal of 9h00m: 75%, 2h15m short."
+        );
+    }
+
+    #[test]
+    fn quiet_week_has_only_the_total() {
+        let text = digest_text(&Digest {
+            start: date(8),
+            end: date(14),
+            days: &[],
+            previous: &[],
+            goal_seconds: None,
+            long_seconds: 7200,
+        });
+        assert_eq!(
+            text,
+            "Week of Feb 8 - Feb 14, 2026: 0h00m over 0 days (+0h00m vs the week before)."
+        );
+    }
+}
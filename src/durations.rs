@@ -0,0 +1,108 @@
+//! Work blocks from the durations endpoint, for `--source durations`.
+//!
+//! The server already joined the heartbeats into blocks, so the sessions match the ones its
+//! web UI shows rather than those of `--max-gap-minutes`.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use chrono::{NaiveDate, NaiveTime};
+use serde::Deserialize;
+
+use crate::core::{day_of, DaySessions, Session};
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct DurationsResponse {
+    #[serde(default)]
+    pub data: Vec<DurationBlock>,
+}
+
+/// One block of a durations response: `duration` seconds of work from `time` on.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct DurationBlock {
+    pub time: f64,
+    pub duration: f64,
+    #[serde(default)]
+    pub project: Option<String>,
+    #[serde(default)]
+    pub branch: Option<String>,
+}
+
+/// One session per block, on the day it starts given `day_start`. Blocks repeated by
+/// neighbouring dates or several servers are kept once.
+pub fn duration_sessions(
+    blocks: impl IntoIterator<Item = DurationBlock>,
+    day_start: NaiveTime,
+) -> Vec<DaySessions> {
+    let mut days: BTreeMap<NaiveDate, Vec<Session>> = BTreeMap::new();
+    for block in blocks {
+        let start = block.time.floor() as i64;
+        let seconds = block.duration.round() as i64;
+        if seconds <= 0 {
+            continue;
+        }
+        let Some(date) = day_of(start, day_start) else {
+            continue;
+        };
+        days.entry(date).or_default().push(Session {
+            start,
+            end: start + seconds,
+            seconds,
+            project: block.project.filter(|p| !p.is_empty()).map(Arc::from),
+            branch: block.branch.filter(|b| !b.is_empty()).map(Arc::from),
+            ..Default::default()
+        });
+    }
+    days.into_iter()
+        .map(|(date, mut sessions)| {
+            sessions.sort_by(|a, b| (a.start, &a.project).cmp(&(b.start, &b.project)));
+            sessions.dedup();
+            DaySessions { date, sessions }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Local, TimeZone};
+
+    fn at(day: u32, hour: u32) -> f64 {
+        Local
+            .with_ymd_and_hms(2026, 2, day, hour, 0, 0)
+            .unwrap()
+            .timestamp() as f64
+    }
+
+    #[test]
+    fn blocks_become_sessions_of_the_day_they_start() {
+        let response: DurationsResponse = serde_json::from_str(&format!(
+            r#"{{"data":[
+                {{"project":"api","time":{},"duration":1800.4,"branch":"main"}},
+                {{"project":"web","time":{},"duration":600}},
+                {{"project":"web","time":{},"duration":0}}
+            ],"timezone":"UTC"}}"#,
+            at(2, 9),
+            at(3, 2),
+            at(3, 5),
+        ))
+        .unwrap();
+        let repeated = response.data[0].clone();
+        let days = duration_sessions(
+            response.data.into_iter().chain([repeated]),
+            NaiveTime::from_hms_opt(4, 0, 0).unwrap(),
+        );
+
+        // The 02:00 block counts towards the day before with a 04:00 day boundary.
+        assert_eq!(days.len(), 1);
+        assert_eq!(days[0].date, NaiveDate::from_ymd_opt(2026, 2, 2).unwrap());
+        let sessions: Vec<_> = days[0]
+            .sessions
+            .iter()
+            .map(|s| (s.project.as_deref().unwrap(), s.seconds))
+            .collect();
+        assert_eq!(sessions, [("api", 1800), ("web", 600)]);
+        assert_eq!(days[0].sessions[0].branch.as_deref(), Some("main"));
+        assert_eq!(days[0].sessions[0].end, at(2, 9) as i64 + 1800);
+    }
+}
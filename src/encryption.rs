@@ -0,0 +1,207 @@
+//! Encryption of the heartbeat cache at rest, for entities (file paths) that should not sit in
+//! plain text on shared machines. Enabled with `cache_encryption` in the config:
+//!
+//! - `"keyring"`: a random key kept in the OS keyring, created on first use. Without the
+//!   `keyring` feature the key is kept in a file next to the config file instead.
+//! - `"passphrase"`: a key derived from `WAKALYZE_CACHE_PASSPHRASE` with Argon2.
+
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Result, WakalyzeError};
+
+pub const PASSPHRASE_ENV: &str = "WAKALYZE_CACHE_PASSPHRASE";
+
+#[cfg(feature = "keyring")]
+const KEYRING_SERVICE: &str = "wakalyze-cache";
+
+/// Prefix of encrypted cache files; plain ones start with the cache's own magic.
+const SEALED_MAGIC: &[u8; 4] = b"WKZE";
+
+const NONCE_LEN: usize = 24;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CacheEncryption {
+    Keyring,
+    Passphrase,
+}
+
+/// XChaCha20-Poly1305 with the key of one account's cache.
+#[derive(Clone)]
+pub struct CacheCipher {
+    cipher: XChaCha20Poly1305,
+}
+
+impl std::fmt::Debug for CacheCipher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("CacheCipher(..)")
+    }
+}
+
+fn key_error(reason: impl std::fmt::Display) -> WakalyzeError {
+    WakalyzeError::CacheKey(reason.to_string())
+}
+
+impl CacheCipher {
+    pub fn new(key: &[u8; 32]) -> Self {
+        Self {
+            cipher: XChaCha20Poly1305::new(key.into()),
+        }
+    }
+
+    /// Key derived from `passphrase`, salted with the cache `account` so accounts differ.
+    pub fn from_passphrase(passphrase: &str, account: &str) -> Result<Self> {
+        let mut key = [0u8; 32];
+        argon2::Argon2::default()
+            .hash_password_into(
+                passphrase.as_bytes(),
+                format!("wakalyze-cache:{account}").as_bytes(),
+                &mut key,
+            )
+            .map_err(key_error)?;
+        Ok(Self::new(&key))
+    }
+
+    /// The key of `account`'s cache from the source `mode` names.
+    pub fn resolve(mode: CacheEncryption, account: &str) -> Result<Self> {
+        match mode {
+            CacheEncryption::Passphrase => {
+                let passphrase = std::env::var(PASSPHRASE_ENV)
+                    .ok()
+                    .filter(|p| !p.is_empty())
+                    .ok_or_else(|| key_error(format!("set {PASSPHRASE_ENV}")))?;
+                Self::from_passphrase(&passphrase, account)
+            }
+            CacheEncryption::Keyring => {
+                let hex = stored_key(account)?;
+                let key: Vec<u8> = (0..hex.len())
+                    .step_by(2)
+                    .filter_map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+                    .collect();
+                let key: [u8; 32] = key
+                    .try_into()
+                    .map_err(|_| key_error("stored cache key is not a 32-byte hex key"))?;
+                Ok(Self::new(&key))
+            }
+        }
+    }
+
+    pub fn seal(&self, plain: &[u8]) -> Result<Vec<u8>> {
+        let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .cipher
+            .encrypt(&nonce, plain)
+            .map_err(|_| key_error("encryption failed"))?;
+        let mut bytes = SEALED_MAGIC.to_vec();
+        bytes.extend_from_slice(&nonce);
+        bytes.extend_from_slice(&ciphertext);
+        Ok(bytes)
+    }
+
+    /// The plain bytes; `None` for data sealed with another key or corrupted.
+    pub fn open(&self, bytes: &[u8]) -> Option<Vec<u8>> {
+        let body = bytes.strip_prefix(SEALED_MAGIC)?;
+        if body.len() < NONCE_LEN {
+            return None;
+        }
+        let (nonce, ciphertext) = body.split_at(NONCE_LEN);
+        self.cipher
+            .decrypt(XNonce::from_slice(nonce), ciphertext)
+            .ok()
+    }
+}
+
+fn new_hex_key() -> String {
+    let key = XChaCha20Poly1305::generate_key(&mut OsRng);
+    key.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// The hex key of `account` in the OS keyring, created on first use.
+#[cfg(feature = "keyring")]
+fn stored_key(account: &str) -> Result<String> {
+    let entry = keyring::Entry::new(KEYRING_SERVICE, account).map_err(key_error)?;
+    match entry.get_password() {
+        Ok(hex) => Ok(hex),
+        Err(keyring::Error::NoEntry) => {
+            let hex = new_hex_key();
+            entry.set_password(&hex).map_err(key_error)?;
+            Ok(hex)
+        }
+        Err(e) => Err(key_error(e)),
+    }
+}
+
+/// The hex key of `account` in `cache-<account>.key` next to the config file, created on
+/// first use.
+#[cfg(not(feature = "keyring"))]
+fn stored_key(account: &str) -> Result<String> {
+    let path = crate::config::config_path().with_file_name(format!("cache-{account}.key"));
+    file_key(&path)
+}
+
+#[cfg_attr(feature = "keyring", allow(dead_code))]
+fn file_key(path: &std::path::Path) -> Result<String> {
+    match std::fs::read_to_string(path) {
+        Ok(hex) => Ok(hex.trim().to_string()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            let hex = new_hex_key();
+            let temp_path = path.with_extension("tmp");
+            std::fs::write(&temp_path, format!("{hex}\n"))?;
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+                std::fs::set_permissions(&temp_path, std::fs::Permissions::from_mode(0o600))?;
+            }
+            std::fs::rename(&temp_path, path)?;
+            Ok(hex)
+        }
+        Err(e) => Err(key_error(e)),
+    }
+}
+
+pub fn is_sealed(bytes: &[u8]) -> bool {
+    bytes.starts_with(SEALED_MAGIC)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seal_and_open() {
+        let cipher = CacheCipher::new(&[1; 32]);
+        let sealed = cipher.seal(b"/home/me/secret/plan.md").unwrap();
+        assert!(is_sealed(&sealed));
+        assert!(!sealed.windows(6).any(|w| w == b"secret"));
+        assert_eq!(
+            cipher.open(&sealed).as_deref(),
+            Some(&b"/home/me/secret/plan.md"[..])
+        );
+        assert_eq!(CacheCipher::new(&[2; 32]).open(&sealed), None);
+        assert_eq!(cipher.open(b"WKZEshort"), None);
+    }
+
+    #[test]
+    fn passphrase_keys_differ_per_account() {
+        let a = CacheCipher::from_passphrase("hunter2", "me@wakapi.dev").unwrap();
+        let again = CacheCipher::from_passphrase("hunter2", "me@wakapi.dev").unwrap();
+        let other = CacheCipher::from_passphrase("hunter2", "you@wakapi.dev").unwrap();
+        let sealed = a.seal(b"x").unwrap();
+        assert!(again.open(&sealed).is_some());
+        assert!(other.open(&sealed).is_none());
+    }
+
+    #[test]
+    fn file_key_is_created_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("wakalyze").join("cache-me.key");
+        let key = file_key(&path).unwrap();
+        assert_eq!(key.len(), 64);
+        assert_eq!(file_key(&path).unwrap(), key);
+    }
+}
@@ -0,0 +1,97 @@
+//! Time per file (or other entity) of the coding heartbeats, for `--entities`.
+
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use crate::core::RawHeartbeat;
+use crate::projects::ProjectMap;
+use crate::summary::percent_of;
+
+/// Entities listed by `--entities`.
+pub const TOP_ENTITIES: usize = 15;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EntityTotal {
+    pub entity: Option<String>,
+    /// Project of the entity's first heartbeat, after renames.
+    pub project: Option<String>,
+    pub seconds: i64,
+    /// Share of the time of all entities, 0–100.
+    pub percent: f64,
+}
+
+/// Time per entity, most first; ties in name order. Each gap of at most `max_gap` seconds
+/// between two heartbeats counts towards the entity of the earlier one, so switching files
+/// within a session splits its time between them. Projects are renamed by `projects`.
+pub fn entity_totals<'a>(
+    heartbeats: impl IntoIterator<Item = &'a RawHeartbeat>,
+    max_gap: i64,
+    projects: &'a ProjectMap,
+) -> Vec<EntityTotal> {
+    let mut timed: Vec<(i64, &RawHeartbeat)> = heartbeats
+        .into_iter()
+        .filter_map(|hb| Some((hb.time? as i64, hb)))
+        .collect();
+    timed.sort_by_key(|(time, _)| *time);
+    let mut totals: BTreeMap<Option<&str>, (Option<&str>, i64)> = BTreeMap::new();
+    for pair in timed.windows(2) {
+        let [(time, hb), (next, _)] = pair else {
+            continue;
+        };
+        let entity = hb.entity.as_deref().filter(|e| !e.trim().is_empty());
+        let project = hb.project.as_deref().map(|p| projects.resolve(p));
+        let total = totals.entry(entity).or_insert((project, 0));
+        if next - time <= max_gap {
+            total.1 += next - time;
+        }
+    }
+    let total: i64 = totals.values().map(|(_, seconds)| seconds).sum();
+    let mut entities: Vec<EntityTotal> = totals
+        .into_iter()
+        .filter(|(_, (_, seconds))| *seconds > 0)
+        .map(|(entity, (project, seconds))| EntityTotal {
+            entity: entity.map(str::to_owned),
+            project: project.map(str::to_owned),
+            seconds,
+            percent: percent_of(seconds, total),
+        })
+        .collect();
+    entities.sort_by_key(|e| Reverse(e.seconds));
+    entities
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hb(time: f64, entity: &str) -> RawHeartbeat {
+        RawHeartbeat {
+            time: Some(time),
+            project: Some("api".into()),
+            entity: Some(entity.into()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn splits_gaps_between_entities() {
+        let heartbeats = vec![
+            hb(0.0, "src/main.rs"),
+            hb(120.0, "src/lib.rs"),
+            hb(180.0, "src/main.rs"),
+            hb(240.0, "src/main.rs"),
+            // After a break longer than the max gap.
+            hb(5000.0, "README.md"),
+        ];
+        let totals = entity_totals(&heartbeats, 900, &ProjectMap::default());
+        let seconds: Vec<_> = totals
+            .iter()
+            .map(|t| (t.entity.as_deref().unwrap(), t.seconds))
+            .collect();
+        assert_eq!(seconds, [("src/main.rs", 180), ("src/lib.rs", 60)]);
+        assert_eq!(totals[0].project.as_deref(), Some("api"));
+        assert_eq!(totals[0].percent, 75.0);
+    }
+}
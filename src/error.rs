@@ -1,13 +1,59 @@
 use std::io;
 
+use chrono::NaiveDate;
+
 #[derive(Debug, thiserror::Error)]
 pub enum WakalyzeError {
-    #[error("month must be in YYYY/MM format")]
+    #[error(
+        "month must be YYYY/MM, YYYY-MM, YYYYMM, a name and year (Feb 2026), `current`, or `last`"
+    )]
     InvalidMonth,
 
+    #[error("period must be in YYYY or YYYY/MM format: {0}")]
+    InvalidPeriod(String),
+
+    #[error("date must be in YYYY-MM-DD format")]
+    InvalidDate,
+
+    #[error("time must be in HH:MM format")]
+    InvalidTime,
+
+    #[error("time range must be HH:MM-HH:MM with the end after the start: {0}")]
+    InvalidTimeRange(String),
+
+    #[error("length must be a number with s, m, or h (e.g. 3m): {0}")]
+    InvalidSpan(String),
+
+    #[error("heatmap thresholds must be three increasing lengths like 1h,3h,5h: {0}")]
+    InvalidThresholds(String),
+
+    #[error("goal hours must not be negative: {0}")]
+    InvalidGoalHours(f64),
+
+    #[error("--interval must be at least 1 second")]
+    InvalidInterval,
+
+    #[error("UTC offset must be UTC or in ±HH:MM format: {0}")]
+    InvalidOffset(String),
+
+    #[error("no session {0}")]
+    SessionNotFound(String),
+
+    #[error("session ID must be 12 hex digits as listed by `wakalyze sessions`: {0}")]
+    InvalidSessionId(String),
+
+    #[error("{0} is in the future; nothing has been recorded yet")]
+    FuturePeriod(NaiveDate),
+
+    #[error("--to {1} is before --from {0}")]
+    InvalidRange(NaiveDate, NaiveDate),
+
     #[error("week must be between 1 and 6")]
     InvalidWeek,
 
+    #[error("week must be `last`, `this`, or a YYYY-MM-DD date in it: {0}")]
+    InvalidWeekSpec(String),
+
     #[error("week is out of range for the month")]
     WeekOutOfRange(u32),
 
@@ -17,6 +63,9 @@ pub enum WakalyzeError {
     #[error("missing user: use --user, set WAKAPI_USER, or run `wakalyze config set --user`")]
     MissingUser,
 
+    #[error("session invariant violated: {0}")]
+    InvariantViolated(String),
+
     #[error("--max-gap-minutes must be greater than 0")]
     InvalidMaxGap,
 
@@ -26,9 +75,66 @@ pub enum WakalyzeError {
     #[error("nothing to update: provide --key/--user/--base-url")]
     NothingToUpdate,
 
+    #[error("no profile {0}: create it with `wakalyze config set --profile {0} ...`")]
+    UnknownProfile(String),
+
+    #[error("header must be in `Name: value` format: {0}")]
+    InvalidHeader(String),
+
+    #[error("{0} days could not be fetched")]
+    WarmFailed(usize),
+
+    #[error("{0} days differ from the server")]
+    DataChanged(usize),
+
+    #[error("cache encryption key unavailable: {0}")]
+    CacheKey(String),
+
+    #[error("key must be 64 hex digits: {0}")]
+    InvalidKey(String),
+
+    #[error("invalid archive {0}")]
+    InvalidArchive(String),
+
+    #[error("invalid --filter-regex {0}")]
+    InvalidRegex(String),
+
+    #[error("unknown time zone {0}: use an IANA name such as Asia/Tokyo or UTC")]
+    InvalidTimezone(String),
+
+    #[error("invalid heartbeat export {0}")]
+    InvalidInput(String),
+
+    #[error("invalid calendar {0}")]
+    InvalidCalendar(String),
+
+    #[error("git failed in {0}: {1}")]
+    Git(String, String),
+
+    #[error("unknown color {0}: use names like `bold bright blue` or SGR codes like `38;5;214`")]
+    InvalidColor(String),
+
+    #[error("invalid exchange rates {0}")]
+    InvalidFxRates(String),
+
+    #[error("no exchange rate from {0} to {1}: pass --fx-rates with both currencies")]
+    MissingFxRate(String, String),
+
+    #[error("line items are billed in {0}: convert them into one with --currency")]
+    MixedCurrencies(String),
+
+    #[error("self-update failed: {0}")]
+    SelfUpdate(String),
+
+    #[error("plugin {0} failed: {1}")]
+    PluginFailed(String, String),
+
     #[error("HTTP error: {0}")]
     Http(#[from] reqwest::Error),
 
+    #[error("invalid API response: {0}")]
+    InvalidResponse(serde_json::Error),
+
     #[error("config I/O error: {0}")]
     ConfigIo(#[from] io::Error),
 
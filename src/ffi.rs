@@ -0,0 +1,174 @@
+//! C ABI over the analysis pipeline, for editor plugins and other non-Rust tools.
+//!
+//! Build the shared library with:
+//!
+//! ```sh
+//! cargo rustc --release --lib --features ffi --crate-type cdylib
+//! ```
+//!
+//! See `include/wakalyze.h` for the C declarations.
+
+use std::ffi::{c_char, CStr, CString};
+
+use chrono::NaiveDate;
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::analysis::{Analysis, MemorySource};
+use crate::core::RawHeartbeat;
+use crate::error::Result;
+use crate::json::JsonReport;
+use crate::zone::{self, parse_timezone};
+
+#[derive(Deserialize)]
+struct AnalyzeRequest {
+    start: NaiveDate,
+    end: NaiveDate,
+    #[serde(default)]
+    heartbeats: Vec<RawHeartbeat>,
+    #[serde(default)]
+    filter: Option<String>,
+    #[serde(default)]
+    exclude: Option<String>,
+    #[serde(default)]
+    max_gap_seconds: Option<i64>,
+    #[serde(default)]
+    label: Option<String>,
+    #[serde(default)]
+    timezone: Option<String>,
+}
+
+/// Analyze a JSON request and return the `--format json` document.
+///
+/// The request holds `start`/`end` dates (`YYYY-MM-DD`), the raw `heartbeats` (as returned by
+/// the Wakapi heartbeats API), and optional `filter`, `exclude`, `max_gap_seconds`, `label`,
+/// and IANA `timezone`.
+pub fn analyze_json(input: &str) -> Result<String> {
+    let request: AnalyzeRequest = serde_json::from_str(input)?;
+    let timezone = request
+        .timezone
+        .as_deref()
+        .map(parse_timezone)
+        .transpose()?;
+    let mut analysis = Analysis::range(request.start, request.end);
+    if let Some(timezone) = timezone {
+        analysis = analysis.timezone(timezone);
+    }
+    if let Some(filter) = request.filter {
+        analysis = analysis.filter(filter);
+    }
+    if let Some(exclude) = request.exclude {
+        analysis = analysis.exclude(exclude);
+    }
+    if let Some(max_gap) = request.max_gap_seconds {
+        analysis = analysis.max_gap(max_gap);
+    }
+    let report = analysis.run(&MemorySource::new(request.heartbeats))?;
+    let label = request
+        .label
+        .unwrap_or_else(|| format!("{} ~ {}", request.start, request.end));
+    let json = zone::scoped(timezone, || JsonReport::new(&label, &report));
+    Ok(serde_json::to_string(&json)?)
+}
+
+fn into_c_string(value: String) -> *mut c_char {
+    // serde_json escapes NUL, so JSON output never contains an interior NUL byte.
+    CString::new(value)
+        .expect("JSON output has no interior NUL")
+        .into_raw()
+}
+
+fn error_json(message: impl std::fmt::Display) -> String {
+    json!({ "error": message.to_string() }).to_string()
+}
+
+/// Analyze a NUL-terminated UTF-8 JSON request (see [`analyze_json`]).
+///
+/// Returns a newly allocated JSON string: the report on success, or `{"error": "..."}`.
+/// Never returns null. The result must be released with [`wakalyze_free_string`].
+///
+/// # Safety
+///
+/// `input` must be null or point to a valid NUL-terminated string that stays alive for the
+/// duration of the call.
+#[no_mangle]
+pub unsafe extern "C" fn wakalyze_analyze_json(input: *const c_char) -> *mut c_char {
+    if input.is_null() {
+        return into_c_string(error_json("input is null"));
+    }
+    let output = match CStr::from_ptr(input).to_str() {
+        Ok(text) => analyze_json(text).unwrap_or_else(error_json),
+        Err(e) => error_json(e),
+    };
+    into_c_string(output)
+}
+
+/// Release a string returned by [`wakalyze_analyze_json`].
+///
+/// # Safety
+///
+/// `value` must be null or a pointer returned by this library that has not been freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn wakalyze_free_string(value: *mut c_char) {
+    if !value.is_null() {
+        drop(CString::from_raw(value));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Local, TimeZone};
+
+    fn local_ts(hour: u32, min: u32) -> i64 {
+        Local
+            .with_ymd_and_hms(2026, 2, 1, hour, min, 0)
+            .unwrap()
+            .timestamp()
+    }
+
+    fn call(input: &str) -> serde_json::Value {
+        let input = CString::new(input).unwrap();
+        unsafe {
+            let output = wakalyze_analyze_json(input.as_ptr());
+            let text = CStr::from_ptr(output).to_str().unwrap().to_owned();
+            wakalyze_free_string(output);
+            serde_json::from_str(&text).unwrap()
+        }
+    }
+
+    #[test]
+    fn analyze_json_builds_report() {
+        let input = json!({
+            "start": "2026-02-01",
+            "end": "2026-02-01",
+            "heartbeats": [
+                { "time": local_ts(9, 0), "project": "foo" },
+                { "time": local_ts(9, 10), "project": "foo" }
+            ]
+        });
+        let value = call(&input.to_string());
+        assert_eq!(value["label"], "2026-02-01 ~ 2026-02-01");
+        assert_eq!(value["summary"]["seconds"], 600);
+        assert_eq!(value["days"][0]["sessions"][0]["project"], "foo");
+    }
+
+    #[test]
+    fn analyze_json_reports_errors_as_json() {
+        let value = call("not json");
+        assert!(value["error"].is_string());
+
+        let value = call(r#"{"start":"2026-02-01","end":"2026-02-01","max_gap_seconds":0}"#);
+        assert!(value["error"].as_str().unwrap().contains("max-gap"));
+    }
+
+    #[test]
+    fn analyze_json_null_input() {
+        unsafe {
+            let output = wakalyze_analyze_json(std::ptr::null());
+            let text = CStr::from_ptr(output).to_str().unwrap().to_owned();
+            wakalyze_free_string(output);
+            assert!(text.contains("null"));
+        }
+    }
+}
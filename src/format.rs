@@ -1,6 +1,30 @@
-use chrono::{Datelike, Local, NaiveDate, TimeZone};
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
 
-use crate::core::DaySessions;
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+use clap::ValueEnum;
+
+use crate::browsing::DomainTotal;
+use crate::compare::{CompareRow, Comparison};
+use crate::conflicts::ProjectConflict;
+use crate::core::{
+    dst_shift_seconds, is_weekend, local_date, DaySessions, RawHeartbeat, SanitizeStats, Session,
+};
+use crate::entities::EntityTotal;
+use crate::goals::{GoalPeriod, GoalStatus};
+use crate::i18n::Lang;
+use crate::inspect::SessionTrace;
+use crate::machines::MachineOverlap;
+use crate::meetings::MeetingOverlap;
+use crate::punchcard::PunchCard;
+use crate::sessions::SessionRow;
+use crate::stats::SessionStats;
+use crate::summary::{DaySummary, GroupTotal, PeriodSummary, ProjectTotal, WeekdayAverage};
+use crate::theme::{Role, Theme};
+use crate::velocity::VelocityRow;
+use crate::zone;
+
+const BAR_WIDTH: usize = 20;
 
 pub fn format_duration(seconds: i64) -> String {
     let hours = seconds / 3600;
@@ -8,44 +32,994 @@ pub fn format_duration(seconds: i64) -> String {
     format!("{hours}h{minutes:02}m")
 }
 
+/// Resolution of the durations in the text output.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum Precision {
+    /// `1h04m`, leftover seconds dropped
+    #[default]
+    Minutes,
+    /// `1h04m32s`
+    Seconds,
+}
+
+impl Precision {
+    pub fn format(self, seconds: i64) -> String {
+        match self {
+            Precision::Minutes => format_duration(seconds),
+            Precision::Seconds => format!("{}{:02}s", format_duration(seconds), seconds % 60),
+        }
+    }
+}
+
+/// 12-hour local clock time; `?` for timestamps outside chrono's range.
+fn format_clock(timestamp: i64, pattern: &str) -> String {
+    match zone::at(timestamp).earliest() {
+        Some(dt) => {
+            let formatted = dt.format(pattern).to_string();
+            formatted.trim_start_matches('0').to_lowercase()
+        }
+        None => "?".to_string(),
+    }
+}
+
 pub fn format_time(timestamp: i64) -> String {
-    let dt = Local
-        .timestamp_opt(timestamp, 0)
-        .single()
-        .expect("valid timestamp");
-    let formatted = dt.format("%I:%M%p").to_string();
-    formatted.trim_start_matches('0').to_lowercase()
+    format_clock(timestamp, "%I:%M%p")
+}
+
+/// Like [`format_time`] but with seconds, for auditing individual heartbeats.
+pub fn format_time_seconds(timestamp: i64) -> String {
+    format_clock(timestamp, "%I:%M:%S%p")
+}
+
+/// ` (DST +1h)` after days on which the clocks change, empty otherwise.
+pub fn dst_marker(date: NaiveDate) -> String {
+    match dst_shift_seconds(date) {
+        0 => String::new(),
+        shift => format!(
+            " (DST {}{})",
+            if shift > 0 { "+" } else { "-" },
+            format_duration(shift.abs())
+        ),
+    }
+}
+
+/// Gap between heartbeats: `45s`, `2m05s`, or `1h05m`.
+pub fn format_gap(seconds: i64) -> String {
+    if seconds < 60 {
+        format!("{seconds}s")
+    } else if seconds < 3600 {
+        format!("{}m{:02}s", seconds / 60, seconds % 60)
+    } else {
+        format_duration(seconds)
+    }
+}
+
+/// Quote a CSV field when it contains a separator, quote, or line break.
+pub fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Escape text for XML/HTML element content and attribute values.
+pub fn xml_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
 }
 
 pub fn format_date_short(date: NaiveDate) -> String {
     format!("{}/{}", date.month(), date.day())
 }
 
+/// Presentation switches for [`build_lines_with`].
+#[derive(Debug, Clone, Default)]
+pub struct LineOptions {
+    /// Mark the longest session of each day with a trailing `*`.
+    pub highlight_longest: bool,
+    /// ANSI colors (only for terminals); none by default.
+    pub theme: Theme,
+    /// Show each session's dominant language after its project.
+    pub languages: bool,
+    /// Language of the labels.
+    pub lang: Lang,
+    /// Fold sessions shorter than this many seconds into one `misc` line per day.
+    pub collapse_below: i64,
+    pub precision: Precision,
+    /// Add a total after each day and one for the period.
+    pub totals: bool,
+    /// Break those totals down by project.
+    pub project_totals: bool,
+}
+
 pub fn build_lines(days: &[DaySessions], label: &str) -> Vec<String> {
+    build_lines_with(days, label, &LineOptions::default())
+}
+
+pub fn build_lines_with(days: &[DaySessions], label: &str, options: &LineOptions) -> Vec<String> {
     let mut lines = vec![label.to_string()];
     for (index, day) in days.iter().enumerate() {
         if index > 0 {
             lines.push(String::new());
         }
-        lines.push(format!("- {}", format_date_short(day.date)));
-        for session in &day.sessions {
-            let project = session.project.as_deref().unwrap_or("unknown");
+        if day.sessions.is_empty() {
             lines.push(format!(
+                "- {}{}  ({})",
+                format_date_short(day.date),
+                dst_marker(day.date),
+                options.lang.bundle().no_activity
+            ));
+            continue;
+        }
+        let date = options
+            .theme
+            .paint(Role::Date, &format_date_short(day.date));
+        lines.push(format!("- {date}{}", dst_marker(day.date)));
+        let (shown, collapsed): (Vec<&Session>, Vec<&Session>) = day
+            .sessions
+            .iter()
+            .partition(|s| s.manual || s.seconds >= options.collapse_below);
+        let longest = if options.highlight_longest {
+            longest_session_index(&shown)
+        } else {
+            None
+        };
+        for (i, session) in shown.into_iter().enumerate() {
+            let project = session.project.as_deref().unwrap_or("unknown");
+            // A highlighted line is colored as a whole.
+            let project = if longest == Some(i) {
+                project.to_string()
+            } else {
+                options.theme.paint(Role::Project, project)
+            };
+            let mut line = format!(
                 "  - {} ~ {} ({}) {}",
                 format_time(session.start),
                 format_time(session.end),
-                format_duration(session.seconds),
+                options.precision.format(session.seconds),
                 project,
+            );
+            if options.languages {
+                if let Some(language) = session.dominant_language() {
+                    line.push_str(&format!(" [{language}]"));
+                }
+            }
+            if session.manual {
+                line.push_str(" [manual]");
+            }
+            line.push_str(&annotation_suffix(session.note.as_deref(), &session.tags));
+            if longest == Some(i) {
+                line.push_str(" *");
+                line = options.theme.paint(Role::Highlight, &line);
+            }
+            lines.push(line);
+        }
+        if !collapsed.is_empty() {
+            let seconds: i64 = collapsed.iter().map(|s| s.seconds).sum();
+            lines.push(format!(
+                "  - misc: {} sessions ({})",
+                collapsed.len(),
+                options.precision.format(seconds)
             ));
         }
+        if options.totals {
+            let sessions: Vec<&Session> = day.sessions.iter().collect();
+            push_total(&mut lines, "  ", &sessions, options, None);
+        }
+    }
+    if options.totals {
+        let sessions: Vec<&Session> = days.iter().flat_map(|d| &d.sessions).collect();
+        let active = days.iter().filter(|d| !d.sessions.is_empty()).count();
+        lines.push(String::new());
+        push_total(&mut lines, "", &sessions, options, Some(active));
+    }
+    lines
+}
+
+/// Today's sessions and their total, for `wakalyze watch`. The last session counts as
+/// running while `now` is within `max_gap_seconds` of its end.
+pub fn build_watch_lines(
+    day: &DaySessions,
+    label: &str,
+    now: i64,
+    max_gap_seconds: i64,
+    options: &LineOptions,
+) -> Vec<String> {
+    let mut lines = build_lines_with(std::slice::from_ref(day), label, options);
+    let total: i64 = day.sessions.iter().map(|s| s.seconds).sum();
+    lines.push(String::new());
+    lines.push(format!("total {}", options.precision.format(total)));
+    let running = day
+        .sessions
+        .iter()
+        .filter(|s| !s.manual)
+        .max_by_key(|s| s.end)
+        .filter(|s| now - s.end <= max_gap_seconds);
+    if let Some(session) = running {
+        lines.push(format!(
+            "running: {} since {}",
+            session.project.as_deref().unwrap_or("unknown"),
+            format_time(session.start)
+        ));
     }
     lines
 }
 
+/// Monthly totals of a year: each month with sessions (or an empty day, from
+/// [`Report::all_days`](crate::analysis::Report::all_days)) and its total, then the period total.
+pub fn build_month_lines(days: &[DaySessions], label: &str, options: &LineOptions) -> Vec<String> {
+    let mut months: BTreeMap<(i32, u32), Vec<&DaySessions>> = BTreeMap::new();
+    for day in days {
+        months
+            .entry((day.date.year(), day.date.month()))
+            .or_default()
+            .push(day);
+    }
+    let mut lines = vec![label.to_string()];
+    for ((year, month), days) in &months {
+        let month = format!("{year}/{month:02}");
+        let sessions: Vec<&Session> = days.iter().flat_map(|d| &d.sessions).collect();
+        if sessions.is_empty() {
+            lines.push(format!(
+                "- {month}  ({})",
+                options.lang.bundle().no_activity
+            ));
+            continue;
+        }
+        lines.push(format!("- {}", options.theme.paint(Role::Date, &month)));
+        let active = days.iter().filter(|d| !d.sessions.is_empty()).count();
+        push_total(&mut lines, "  ", &sessions, options, Some(active));
+    }
+    let sessions: Vec<&Session> = days.iter().flat_map(|d| &d.sessions).collect();
+    let active = days.iter().filter(|d| !d.sessions.is_empty()).count();
+    lines.push(String::new());
+    push_total(&mut lines, "", &sessions, options, Some(active));
+    lines
+}
+
+/// `{indent}total 5h10m`, with the number of active days for the period total and the
+/// projects under it with [`LineOptions::project_totals`].
+fn push_total(
+    lines: &mut Vec<String>,
+    indent: &str,
+    sessions: &[&Session],
+    options: &LineOptions,
+    days: Option<usize>,
+) {
+    let bundle = options.lang.bundle();
+    let total: i64 = sessions.iter().map(|s| s.seconds).sum();
+    let mut line = format!(
+        "{indent}{} {}",
+        bundle.total,
+        options.precision.format(total)
+    );
+    if let Some(days) = days {
+        line.push_str(&format!(" ({})", bundle.days(days)));
+    }
+    lines.push(options.theme.paint(Role::Total, &line));
+    if options.project_totals {
+        let mut projects: BTreeMap<&str, i64> = BTreeMap::new();
+        for session in sessions {
+            *projects
+                .entry(session.project.as_deref().unwrap_or("unknown"))
+                .or_default() += session.seconds;
+        }
+        let mut projects: Vec<(&str, i64)> = projects.into_iter().collect();
+        projects.sort_by_key(|(_, seconds)| Reverse(*seconds));
+        for (project, seconds) in projects {
+            lines.push(format!(
+                "{indent}  {project} {}",
+                options.precision.format(seconds)
+            ));
+        }
+    }
+}
+
+/// ` #tag #tag (note)` for an annotated session, empty otherwise.
+pub fn annotation_suffix<S: AsRef<str>>(note: Option<&str>, tags: &[S]) -> String {
+    let mut suffix = String::new();
+    for tag in tags {
+        suffix.push_str(" #");
+        suffix.push_str(tag.as_ref());
+    }
+    if let Some(note) = note {
+        suffix.push_str(&format!(" ({note})"));
+    }
+    suffix
+}
+
+/// Index of the longest session; the earliest one wins a tie.
+fn longest_session_index(sessions: &[&Session]) -> Option<usize> {
+    sessions
+        .iter()
+        .enumerate()
+        .rev()
+        .max_by_key(|(_, s)| s.seconds)
+        .map(|(i, _)| i)
+}
+
+/// Proportional bar of `BAR_WIDTH` cells for a 0–100 percentage.
+pub fn format_bar(percent: f64) -> String {
+    let cells = ((percent.clamp(0.0, 100.0) / 100.0) * BAR_WIDTH as f64).round() as usize;
+    format!("{}{}", "█".repeat(cells), "░".repeat(BAR_WIDTH - cells))
+}
+
+pub fn build_project_lines(
+    projects: &[ProjectTotal],
+    bars: bool,
+    precision: Precision,
+) -> Vec<String> {
+    let mut lines = vec!["projects".to_string()];
+    for total in projects {
+        let project = total.project.as_deref().unwrap_or("unknown");
+        let bar = if bars {
+            format!(" {}", format_bar(total.percent))
+        } else {
+            String::new()
+        };
+        lines.push(format!(
+            "- {} {:>5.1}%{bar} {project}",
+            precision.format(total.seconds),
+            total.percent,
+        ));
+    }
+    lines
+}
+
+/// Totals of `--by`, headed by `label`.
+pub fn build_group_lines(
+    label: &str,
+    groups: &[GroupTotal],
+    bars: bool,
+    precision: Precision,
+) -> Vec<String> {
+    let mut lines = vec![label.to_string()];
+    for total in groups {
+        let name = total.name.as_deref().unwrap_or("unknown");
+        let bar = if bars {
+            format!(" {}", format_bar(total.percent))
+        } else {
+            String::new()
+        };
+        lines.push(format!(
+            "- {} {:>5.1}%{bar} {name}",
+            precision.format(total.seconds),
+            total.percent,
+        ));
+    }
+    lines
+}
+
+/// The `limit` domains with the most browsing time, under the browsing total.
+pub fn build_domain_lines(domains: &[DomainTotal], limit: usize) -> Vec<String> {
+    let total: i64 = domains.iter().map(|d| d.seconds).sum();
+    let mut lines = vec![format!("browsing {}", format_duration(total))];
+    for domain in domains.iter().take(limit) {
+        lines.push(format!(
+            "- {} {:>5.1}% {}",
+            format_duration(domain.seconds),
+            domain.percent,
+            domain.domain.as_deref().unwrap_or("unknown")
+        ));
+    }
+    lines
+}
+
+/// The `limit` files with the most time, under the time of all of them.
+pub fn build_entity_lines(entities: &[EntityTotal], limit: usize) -> Vec<String> {
+    let total: i64 = entities.iter().map(|e| e.seconds).sum();
+    let mut lines = vec![format!("files {}", format_duration(total))];
+    for entity in entities.iter().take(limit) {
+        lines.push(format!(
+            "- {} {:>5.1}% {} ({})",
+            format_duration(entity.seconds),
+            entity.percent,
+            entity.entity.as_deref().unwrap_or("unknown"),
+            entity.project.as_deref().unwrap_or("unknown")
+        ));
+    }
+    lines
+}
+
+/// Partial cells of the `--chart` bars, one to seven eighths.
+const CHART_EIGHTHS: [char; 7] = ['▏', '▎', '▍', '▌', '▋', '▊', '▉'];
+
+/// Bar of `seconds` in eighths of a cell, `max` filling `cells` cells. Any time at all gets
+/// at least a sliver.
+pub fn chart_bar(seconds: i64, max: i64, cells: usize) -> String {
+    if seconds <= 0 || max <= 0 {
+        return String::new();
+    }
+    let eighths = (seconds as f64 / max as f64 * (cells * 8) as f64).round() as usize;
+    let eighths = eighths.clamp(1, cells * 8);
+    let mut bar = "█".repeat(eighths / 8);
+    if !eighths.is_multiple_of(8) {
+        bar.push(CHART_EIGHTHS[eighths % 8 - 1]);
+    }
+    bar
+}
+
+/// Rows of the `--chart` of `days`: one per day (`Mon 2/2`), or per month when `by_month`.
+pub fn chart_rows(days: &[DaySessions], by_month: bool, lang: Lang) -> Vec<(String, i64)> {
+    let mut rows: Vec<(String, i64)> = Vec::new();
+    for day in days {
+        let label = if by_month {
+            format!("{}/{:02}", day.date.year(), day.date.month())
+        } else {
+            format!(
+                "{} {}",
+                lang.bundle().weekday(day.date.weekday()),
+                format_date_short(day.date)
+            )
+        };
+        let seconds: i64 = day.sessions.iter().map(|s| s.seconds).sum();
+        match rows.last_mut() {
+            Some((last, total)) if *last == label => *total += seconds,
+            _ => rows.push((label, seconds)),
+        }
+    }
+    rows
+}
+
+/// `title` and a horizontal bar per row, scaled so the longest fits in `width` columns.
+pub fn build_chart_lines(title: &str, rows: &[(String, i64)], width: usize) -> Vec<String> {
+    let label_width = rows
+        .iter()
+        .map(|(l, _)| display_width(l))
+        .max()
+        .unwrap_or(0);
+    let max = rows.iter().map(|(_, seconds)| *seconds).max().unwrap_or(0);
+    // Label, duration, and the spaces around it.
+    let cells = width.saturating_sub(label_width + 8).max(10);
+    let mut lines = vec![title.to_string()];
+    for (label, seconds) in rows {
+        let line = format!(
+            "{label}{} {:>6} {}",
+            " ".repeat(label_width - display_width(label)),
+            format_duration(*seconds),
+            chart_bar(*seconds, max, cells)
+        );
+        lines.push(line.trim_end().to_string());
+    }
+    lines
+}
+
+pub fn build_meeting_lines(overlaps: &[MeetingOverlap], excluded: bool) -> Vec<String> {
+    let total: i64 = overlaps.iter().map(|o| o.seconds).sum();
+    let verb = if excluded { "excluded" } else { "overlap" };
+    let mut lines = vec![format!("meetings {verb} {}", format_duration(total))];
+    for overlap in overlaps {
+        lines.push(format!(
+            "- {} {}",
+            format_date_short(overlap.date),
+            format_duration(overlap.seconds)
+        ));
+    }
+    lines
+}
+
+/// Counts of the fetched heartbeats by what happened to them; reasons with none are left out.
+pub fn build_data_quality_lines(stats: &SanitizeStats) -> Vec<String> {
+    let mut lines = vec![
+        "data quality".to_string(),
+        format!("- {} heartbeats fetched", stats.fetched),
+    ];
+    let reasons = [
+        (stats.duplicates, "duplicates (dropped)"),
+        (stats.missing_time, "without a time (not counted)"),
+        (
+            stats.not_finite,
+            "with a time that is not a number (dropped)",
+        ),
+        (stats.too_early, "dated before 2000 (dropped)"),
+        (stats.future, "dated in the future (dropped)"),
+        (
+            stats.empty_project,
+            "without a project (counted as unknown)",
+        ),
+        (stats.normalized, "with times not in seconds (normalized)"),
+    ];
+    for (count, reason) in reasons {
+        if count > 0 {
+            lines.push(format!("- {count} {reason}"));
+        }
+    }
+    lines
+}
+
+fn format_stats(stats: &SessionStats) -> String {
+    format!(
+        "{} sessions, avg {}, median {}, p90 {}, longest {}",
+        stats.sessions,
+        format_duration(stats.average_seconds),
+        format_duration(stats.median_seconds),
+        format_duration(stats.p90_seconds),
+        format_duration(stats.longest_seconds),
+    )
+}
+
+pub fn build_stats_lines(summary: &PeriodSummary) -> Vec<String> {
+    let mut lines = vec!["stats".to_string()];
+    for day in &summary.days {
+        lines.push(format!(
+            "- {} {}",
+            format_date_short(day.date),
+            format_stats(&day.stats)
+        ));
+    }
+    lines.push(format!("- period {}", format_stats(&summary.stats)));
+    lines
+}
+
+pub fn build_switch_lines(summary: &PeriodSummary) -> Vec<String> {
+    let mut lines = vec!["switches".to_string()];
+    for day in &summary.days {
+        lines.push(format!(
+            "- {} {}",
+            format_date_short(day.date),
+            day.switches
+        ));
+    }
+    lines.push(format!("- period avg {:.1}/day", summary.average_switches));
+    lines
+}
+
+pub fn build_focus_lines(summary: &PeriodSummary) -> Vec<String> {
+    let mut lines = vec!["focus".to_string()];
+    for day in &summary.days {
+        lines.push(format!(
+            "- {} {:.0}",
+            format_date_short(day.date),
+            day.focus
+        ));
+    }
+    lines.push(format!("- period avg {:.0}", summary.average_focus));
+    lines
+}
+
+const CALENDAR_CELL_WIDTH: usize = 11;
+
+/// Terminal columns of `text`, taking non-ASCII characters (CJK labels) as two wide.
+fn display_width(text: &str) -> usize {
+    text.chars().map(|c| if c.is_ascii() { 1 } else { 2 }).sum()
+}
+
+/// Month/week grid with Sunday-first rows (matching week numbers) and a weekly total.
+///
+/// Weekend cells are wrapped in `( )` and holidays in `[ ]` (also colored by `theme`);
+/// dates outside `start..=end` are left blank.
+pub fn build_calendar_lines(
+    label: &str,
+    start: NaiveDate,
+    end: NaiveDate,
+    days: &[DaySummary],
+    holidays: &[NaiveDate],
+    theme: &Theme,
+    lang: Lang,
+) -> Vec<String> {
+    let bundle = lang.bundle();
+    let totals: BTreeMap<NaiveDate, i64> = days.iter().map(|d| (d.date, d.seconds)).collect();
+    let mut header: String = [
+        Weekday::Sun,
+        Weekday::Mon,
+        Weekday::Tue,
+        Weekday::Wed,
+        Weekday::Thu,
+        Weekday::Fri,
+        Weekday::Sat,
+    ]
+    .into_iter()
+    .map(|weekday| {
+        let name = bundle.weekday(weekday);
+        let padding = CALENDAR_CELL_WIDTH.saturating_sub(display_width(name));
+        format!(
+            "{}{name}{}",
+            " ".repeat(padding / 2),
+            " ".repeat(padding - padding / 2)
+        )
+    })
+    .collect();
+    header.push_str(&format!(" {}", bundle.total));
+    let mut lines = vec![label.to_string(), header];
+
+    let mut week_start = start - Duration::days(start.weekday().num_days_from_sunday() as i64);
+    while week_start <= end {
+        let mut line = String::new();
+        let mut week_seconds = 0;
+        for offset in 0..7 {
+            let date = week_start + Duration::days(offset);
+            if date < start || date > end {
+                line.push_str(&" ".repeat(CALENDAR_CELL_WIDTH));
+                continue;
+            }
+            let seconds = totals.get(&date).copied().unwrap_or(0);
+            week_seconds += seconds;
+            let value = if seconds > 0 {
+                format_duration(seconds)
+            } else {
+                "-".to_string()
+            };
+            let (open, close, role) = if holidays.contains(&date) {
+                ('[', ']', Some(Role::Holiday))
+            } else if is_weekend(date) {
+                ('(', ')', Some(Role::Weekend))
+            } else {
+                (' ', ' ', None)
+            };
+            let cell = format!("{open}{:>2} {value:>6}{close}", date.day());
+            match role {
+                Some(role) => line.push_str(&theme.paint(role, &cell)),
+                None => line.push_str(&cell),
+            }
+        }
+        let total = theme.paint(Role::Total, &format_duration(week_seconds));
+        line.push_str(&format!(" {total}"));
+        lines.push(line);
+        week_start += Duration::days(7);
+    }
+    lines
+}
+
+const PUNCH_LEVELS: [char; 5] = ['·', '░', '▒', '▓', '█'];
+
+/// Default `--heatmap-thresholds`: the day totals from which the three darker shades start.
+pub const HEATMAP_THRESHOLDS: [i64; 3] = [3600, 3 * 3600, 5 * 3600];
+
+/// Contribution-style heatmap: a row per weekday (Sunday first), a column per week, and a
+/// shade per day; any time at all is `░`, from each of `thresholds` on a darker shade.
+pub fn build_heatmap_lines(
+    label: &str,
+    start: NaiveDate,
+    end: NaiveDate,
+    days: &[DaySummary],
+    thresholds: [i64; 3],
+    lang: Lang,
+) -> Vec<String> {
+    let bundle = lang.bundle();
+    let totals: BTreeMap<NaiveDate, i64> = days.iter().map(|d| (d.date, d.seconds)).collect();
+    let first_week = start - Duration::days(start.weekday().num_days_from_sunday() as i64);
+    let weeks: Vec<NaiveDate> = std::iter::successors(Some(first_week), |week| {
+        Some(*week + Duration::days(7)).filter(|next| *next <= end)
+    })
+    .collect();
+
+    // Month names above the week of their first day, where they fit.
+    let mut header = "    ".to_string();
+    let mut column = header.len();
+    for (i, week) in weeks.iter().enumerate() {
+        let first = (0..7)
+            .map(|offset| *week + Duration::days(offset))
+            .find(|date| date.day() == 1 && *date >= start && *date <= end);
+        let target = 4 + 2 * i;
+        if let Some(date) = first.filter(|_| column <= target) {
+            let name = bundle.month(date.month());
+            header.push_str(&" ".repeat(target - column));
+            header.push_str(name);
+            column = target + display_width(name);
+        }
+    }
+    let mut lines = vec![label.to_string(), header.trim_end().to_string()];
+
+    let weekdays = std::iter::successors(Some(Weekday::Sun), |day| Some(day.succ())).take(7);
+    for (row, weekday) in weekdays.enumerate() {
+        let name = bundle.weekday(weekday);
+        let mut line = format!("{name}{}", " ".repeat(4 - display_width(name)));
+        for week in &weeks {
+            let date = *week + Duration::days(row as i64);
+            let cell = if date < start || date > end {
+                ' '
+            } else {
+                match totals.get(&date).copied().unwrap_or(0) {
+                    0 => PUNCH_LEVELS[0],
+                    seconds => {
+                        PUNCH_LEVELS[1 + thresholds.iter().filter(|&&t| seconds >= t).count()]
+                    }
+                }
+            };
+            line.push(cell);
+            line.push(' ');
+        }
+        lines.push(line.trim_end().to_string());
+    }
+    let [low, mid, high] = thresholds.map(format_duration);
+    lines.push(format!(
+        "    {} 0  {} <{low}  {} {low}+  {} {mid}+  {} {high}+",
+        PUNCH_LEVELS[0], PUNCH_LEVELS[1], PUNCH_LEVELS[2], PUNCH_LEVELS[3], PUNCH_LEVELS[4]
+    ));
+    lines
+}
+
+/// Weekday × hour matrix; each cell's shade scales with its share of the busiest cell.
+pub fn build_punch_card_lines(card: &PunchCard, lang: Lang) -> Vec<String> {
+    let max = card.max();
+    let hours: String = (0..24).map(|h| format!("{h:>3}")).collect();
+    let mut lines = vec!["punch card".to_string(), format!("   {hours}")];
+    let weekdays = std::iter::successors(Some(Weekday::Mon), |day| Some(day.succ()));
+    for (weekday, row) in weekdays.zip(&card.counts) {
+        let name = lang.bundle().weekday(weekday);
+        let name = format!(
+            "{name}{}",
+            " ".repeat(3usize.saturating_sub(display_width(name)))
+        );
+        let cells: String = row
+            .iter()
+            .map(|&count| {
+                let level = match count {
+                    0 => 0,
+                    n => (n * 4).div_ceil(max) as usize,
+                };
+                format!("  {}", PUNCH_LEVELS[level])
+            })
+            .collect();
+        lines.push(format!("{name}{cells}"));
+    }
+    lines
+}
+
+pub fn build_weekday_lines(weekdays: &[WeekdayAverage], lang: Lang) -> Vec<String> {
+    let bundle = lang.bundle();
+    let mut lines = vec!["weekdays".to_string()];
+    for average in weekdays {
+        lines.push(format!(
+            "- {} {} {} ({})",
+            bundle.weekday(average.weekday),
+            bundle.average,
+            format_duration(average.average_seconds),
+            bundle.days(average.days)
+        ));
+    }
+    lines
+}
+
+pub fn build_machine_lines(overlaps: &[MachineOverlap]) -> Vec<String> {
+    let mut lines = vec!["machines".to_string()];
+    for overlap in overlaps {
+        let date = local_date(overlap.start).expect("valid timestamp");
+        lines.push(format!(
+            "- {} {} ~ {} ({}) {}",
+            format_date_short(date),
+            format_time(overlap.start),
+            format_time(overlap.end),
+            format_duration(overlap.seconds()),
+            overlap.machines.join(", ")
+        ));
+    }
+    lines
+}
+
+pub fn build_conflict_lines(conflicts: &[ProjectConflict]) -> Vec<String> {
+    let mut lines = vec!["project conflicts".to_string()];
+    for conflict in conflicts {
+        let date = local_date(conflict.time).expect("valid timestamp");
+        let projects: Vec<&str> = conflict
+            .projects
+            .iter()
+            .map(|p| p.as_deref().unwrap_or("unknown"))
+            .collect();
+        lines.push(format!(
+            "- {} {} {} -> {}",
+            format_date_short(date),
+            format_time_seconds(conflict.time),
+            projects.join(", "),
+            conflict.chosen.as_deref().unwrap_or("unknown")
+        ));
+    }
+    lines
+}
+
+/// Sessions with their raw heartbeats; gaps over `max_gap` are marked as not counted.
+pub fn build_trace_lines(date: NaiveDate, traces: &[SessionTrace], max_gap: i64) -> Vec<String> {
+    let mut lines = vec![format_date_short(date)];
+    for trace in traces {
+        let session = &trace.session;
+        lines.push(format!(
+            "- #{} {} ~ {} ({}) {}",
+            trace.index,
+            format_time(session.start),
+            format_time(session.end),
+            format_duration(session.seconds),
+            session.project.as_deref().unwrap_or("unknown"),
+        ));
+        for hb in &trace.heartbeats {
+            let gap = match hb.gap {
+                Some(gap) if gap > max_gap => format!(" +{} (not counted)", format_gap(gap)),
+                Some(gap) => format!(" +{}", format_gap(gap)),
+                None => String::new(),
+            };
+            lines.push(format!(
+                "  - {}{gap} {} {}",
+                format_time_seconds(hb.time),
+                hb.project.as_deref().unwrap_or("unknown"),
+                hb.entity.as_deref().unwrap_or("-"),
+            ));
+        }
+    }
+    lines
+}
+
+/// `header` and `rows` in columns padded to their widest cell; the last column is unpadded.
+fn aligned_lines<const N: usize>(header: [&str; N], rows: Vec<[String; N]>) -> Vec<String> {
+    let header = header.map(str::to_string);
+    let mut widths = [0; N];
+    for row in std::iter::once(&header).chain(&rows) {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    std::iter::once(&header)
+        .chain(&rows)
+        .map(|row| {
+            let mut line = String::new();
+            for (i, (cell, width)) in row.iter().zip(widths).enumerate() {
+                if i + 1 == N {
+                    line.push_str(cell);
+                } else {
+                    line.push_str(&format!("{cell:<width$}  "));
+                }
+            }
+            line
+        })
+        .collect()
+}
+
+/// One row per heartbeat: local time, project, language, and entity. Missing values show
+/// as `-`.
+pub fn build_heartbeat_lines(heartbeats: &[RawHeartbeat]) -> Vec<String> {
+    let text = |value: &Option<String>| value.clone().unwrap_or_else(|| "-".to_string());
+    let rows = heartbeats
+        .iter()
+        .map(|hb| {
+            [
+                hb.time
+                    .map_or_else(|| "-".to_string(), |t| format_time_seconds(t as i64)),
+                text(&hb.project),
+                text(&hb.language),
+                text(&hb.entity),
+            ]
+        })
+        .collect();
+    aligned_lines(["TIME", "PROJECT", "LANGUAGE", "ENTITY"], rows)
+}
+
+/// One row per session with its ID, for `wakalyze sessions`.
+pub fn build_session_table_lines(rows: &[SessionRow]) -> Vec<String> {
+    let rows = rows
+        .iter()
+        .map(|row| {
+            [
+                row.id.clone(),
+                row.date.to_string(),
+                format_time(row.session.start),
+                format_time(row.session.end),
+                format_duration(row.session.seconds),
+                row.session
+                    .project
+                    .as_deref()
+                    .unwrap_or("unknown")
+                    .to_string(),
+            ]
+        })
+        .collect();
+    aligned_lines(["ID", "DATE", "START", "END", "DURATION", "PROJECT"], rows)
+}
+
+/// One row per project and month, for `wakalyze velocity`; ratios without commits or pull
+/// requests show as `-`.
+pub fn build_velocity_lines(rows: &[VelocityRow]) -> Vec<String> {
+    let ratio = |hours: Option<f64>| hours.map_or_else(|| "-".to_string(), |h| format!("{h:.1}h"));
+    let rows = rows
+        .iter()
+        .map(|row| {
+            [
+                row.project.clone(),
+                row.month.format("%Y/%m").to_string(),
+                format_duration(row.seconds),
+                row.commits.to_string(),
+                ratio(row.hours_per_commit()),
+                row.pull_requests
+                    .map_or_else(|| "-".to_string(), |prs| prs.to_string()),
+                ratio(row.hours_per_pull_request()),
+            ]
+        })
+        .collect();
+    aligned_lines(
+        [
+            "PROJECT",
+            "MONTH",
+            "TIME",
+            "COMMITS",
+            "PER COMMIT",
+            "PRS",
+            "PER PR",
+        ],
+        rows,
+    )
+}
+
+/// `+1h05m` / `-0h20m`.
+fn format_signed_duration(seconds: i64) -> String {
+    let sign = if seconds < 0 { '-' } else { '+' };
+    format!("{sign}{}", format_duration(seconds.abs()))
+}
+
+/// One row per project and a total row, for `wakalyze compare`; projects without time in the
+/// first period show `new` instead of a percentage.
+pub fn build_compare_lines(comparison: &Comparison) -> Vec<String> {
+    let row = |row: &CompareRow| {
+        [
+            row.project.as_deref().unwrap_or("unknown").to_string(),
+            format_duration(row.before_seconds),
+            format_duration(row.after_seconds),
+            format_signed_duration(row.delta_seconds),
+            row.change_percent
+                .map_or_else(|| "new".to_string(), |percent| format!("{percent:+.1}%")),
+        ]
+    };
+    let mut rows: Vec<[String; 5]> = comparison.projects.iter().map(row).collect();
+    let mut total = row(&comparison.total);
+    total[0] = "total".to_string();
+    rows.push(total);
+    aligned_lines(
+        [
+            "PROJECT",
+            &comparison.before,
+            &comparison.after,
+            "DELTA",
+            "CHANGE",
+        ],
+        rows,
+    )
+}
+
+/// One row per goal for `wakalyze goals status`: time so far, what is left, the daily pace
+/// needed to meet it by the end of the period, and a progress bar.
+pub fn build_goal_lines(statuses: &[GoalStatus]) -> Vec<String> {
+    let rows = statuses
+        .iter()
+        .map(|status| {
+            let (left, pace) = if status.remaining_seconds == 0 {
+                ("done".to_string(), "-".to_string())
+            } else {
+                (
+                    format_duration(status.remaining_seconds),
+                    status
+                        .daily_pace_seconds
+                        .map_or_else(|| "-".to_string(), format_duration),
+                )
+            };
+            [
+                match status.period {
+                    GoalPeriod::Week => "week".to_string(),
+                    GoalPeriod::Month => "month".to_string(),
+                },
+                status.project.clone().unwrap_or_else(|| "all".to_string()),
+                format_duration(status.seconds),
+                format_duration(status.goal_seconds),
+                left,
+                pace,
+                format!("{} {:.0}%", format_bar(status.percent()), status.percent()),
+            ]
+        })
+        .collect();
+    aligned_lines(
+        [
+            "PERIOD", "PROJECT", "TIME", "GOAL", "LEFT", "PER DAY", "PROGRESS",
+        ],
+        rows,
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::core::Session;
+    use crate::core::{LanguageShare, Session};
+    use crate::theme::Preset;
+    use chrono::{Local, TimeZone};
 
     fn local_timestamp(year: i32, month: u32, day: u32, hour: u32, min: u32) -> i64 {
         let naive = NaiveDate::from_ymd_opt(year, month, day)
@@ -59,6 +1033,107 @@ mod tests {
             .timestamp()
     }
 
+    #[test]
+    fn goal_table_with_pace() {
+        let date = |day| NaiveDate::from_ymd_opt(2026, 2, day).unwrap();
+        let status = |project: Option<&str>, seconds, remaining_seconds, pace| GoalStatus {
+            period: GoalPeriod::Week,
+            project: project.map(str::to_owned),
+            start: date(8),
+            end: date(14),
+            goal_seconds: 20 * 3600,
+            seconds,
+            remaining_seconds,
+            days_left: 4,
+            daily_pace_seconds: pace,
+        };
+        let lines = build_goal_lines(&[
+            status(None, 5 * 3600, 15 * 3600, Some(13_500)),
+            status(Some("api"), 20 * 3600, 0, None),
+        ]);
+        assert_eq!(
+            lines,
+            [
+                "PERIOD  PROJECT  TIME    GOAL    LEFT    PER DAY  PROGRESS".to_string(),
+                format!(
+                    "week    all      5h00m   20h00m  15h00m  3h45m    {} 25%",
+                    format_bar(25.0)
+                ),
+                format!(
+                    "week    api      20h00m  20h00m  done    -        {} 100%",
+                    format_bar(100.0)
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn watch_lines_with_total_and_running_session() {
+        let start = local_timestamp(2026, 2, 3, 9, 0);
+        let day = DaySessions {
+            date: NaiveDate::from_ymd_opt(2026, 2, 3).unwrap(),
+            sessions: vec![Session {
+                start,
+                end: start + 1800,
+                seconds: 1800,
+                project: Some("api".into()),
+                ..Default::default()
+            }],
+        };
+        let options = LineOptions::default();
+        let lines = build_watch_lines(&day, "2026-02-03", start + 2000, 900, &options);
+        assert_eq!(
+            lines,
+            [
+                "2026-02-03",
+                "- 2/3",
+                "  - 9:00am ~ 9:30am (0h30m) api",
+                "",
+                "total 0h30m",
+                "running: api since 9:00am",
+            ]
+        );
+        let later = build_watch_lines(&day, "2026-02-03", start + 3600, 900, &options);
+        assert_eq!(later.last().unwrap(), "total 0h30m");
+    }
+
+    #[test]
+    fn compare_table_with_total() {
+        let day = |sessions: &[(i64, &str)]| DaySessions {
+            date: NaiveDate::from_ymd_opt(2026, 2, 2).unwrap(),
+            sessions: sessions
+                .iter()
+                .map(|&(seconds, project)| Session {
+                    seconds,
+                    project: Some(project.into()),
+                    ..Default::default()
+                })
+                .collect(),
+        };
+        let comparison = crate::compare::compare(
+            "2026/01",
+            &[day(&[(3600, "api")])],
+            "2026/02",
+            &[day(&[(5400, "api"), (900, "web")])],
+        );
+        assert_eq!(
+            build_compare_lines(&comparison),
+            [
+                "PROJECT  2026/01  2026/02  DELTA   CHANGE",
+                "api      1h00m    1h30m    +0h30m  +50.0%",
+                "web      0h00m    0h15m    +0h15m  new",
+                "total    1h00m    1h45m    +0h45m  +75.0%",
+            ]
+        );
+    }
+
+    #[test]
+    fn precision_seconds_keeps_leftover_seconds() {
+        assert_eq!(Precision::Minutes.format(3872), "1h04m");
+        assert_eq!(Precision::Seconds.format(3872), "1h04m32s");
+        assert_eq!(Precision::Seconds.format(45), "0h00m45s");
+    }
+
     #[test]
     fn format_duration_zero() {
         assert_eq!(format_duration(0), "0h00m");
@@ -103,6 +1178,16 @@ mod tests {
         assert_eq!(format_time(ts), "12:00am");
     }
 
+    #[test]
+    fn format_time_out_of_range_does_not_panic() {
+        assert_eq!(format_time(i64::MAX), "?");
+    }
+
+    #[test]
+    fn no_dst_marker_on_ordinary_days() {
+        assert_eq!(dst_marker(NaiveDate::from_ymd_opt(2026, 2, 3).unwrap()), "");
+    }
+
     #[test]
     fn format_date_short_basic() {
         assert_eq!(
@@ -125,6 +1210,525 @@ mod tests {
         assert_eq!(result, vec!["2026/02"]);
     }
 
+    #[test]
+    fn format_bar_scales() {
+        assert_eq!(format_bar(0.0), "░".repeat(20));
+        assert_eq!(format_bar(100.0), "█".repeat(20));
+        assert_eq!(
+            format_bar(50.0),
+            format!("{}{}", "█".repeat(10), "░".repeat(10))
+        );
+        assert_eq!(format_bar(150.0), "█".repeat(20));
+    }
+
+    #[test]
+    fn build_project_lines_with_percent() {
+        let projects = vec![
+            ProjectTotal {
+                project: Some("a".into()),
+                seconds: 5400,
+                percent: 75.0,
+            },
+            ProjectTotal {
+                project: None,
+                seconds: 1800,
+                percent: 25.0,
+            },
+        ];
+        let lines = build_project_lines(&projects, false, Precision::Minutes);
+        assert_eq!(
+            lines,
+            vec!["projects", "- 1h30m  75.0% a", "- 0h30m  25.0% unknown"]
+        );
+
+        let lines = build_project_lines(&projects, true, Precision::Minutes);
+        assert!(lines[1].contains(&"█".repeat(15)));
+        assert!(lines[1].ends_with(" a"));
+    }
+
+    #[test]
+    fn build_stats_lines_per_day_and_period() {
+        let days = vec![DaySessions {
+            date: NaiveDate::from_ymd_opt(2026, 2, 1).unwrap(),
+            sessions: vec![
+                Session {
+                    start: 0,
+                    end: 600,
+                    seconds: 600,
+                    project: None,
+                    ..Default::default()
+                },
+                Session {
+                    start: 1000,
+                    end: 4600,
+                    seconds: 3600,
+                    project: None,
+                    ..Default::default()
+                },
+            ],
+        }];
+        let lines = build_stats_lines(&crate::summary::summarize(&days));
+        assert_eq!(
+            lines,
+            vec![
+                "stats",
+                "- 2/1 2 sessions, avg 0h35m, median 0h35m, p90 1h00m, longest 1h00m",
+                "- period 2 sessions, avg 0h35m, median 0h35m, p90 1h00m, longest 1h00m",
+            ]
+        );
+    }
+
+    fn session_at(hour: u32, seconds: i64) -> Session {
+        let start = local_timestamp(2026, 2, 1, hour, 0);
+        Session {
+            start,
+            end: start + seconds,
+            seconds,
+            project: Some("p".into()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn build_lines_highlight_longest() {
+        let days = vec![DaySessions {
+            date: NaiveDate::from_ymd_opt(2026, 2, 1).unwrap(),
+            sessions: vec![
+                session_at(9, 600),
+                session_at(10, 3600),
+                session_at(12, 3600),
+            ],
+        }];
+        let options = LineOptions {
+            highlight_longest: true,
+            ..Default::default()
+        };
+        let lines = build_lines_with(&days, "x", &options);
+        assert!(!lines[2].ends_with('*'));
+        assert!(lines[3].ends_with(" *"));
+        assert!(!lines[4].ends_with('*'));
+
+        let plain = build_lines(&days, "x");
+        assert!(plain.iter().all(|l| !l.ends_with('*')));
+    }
+
+    #[test]
+    fn build_lines_highlight_longest_color() {
+        let days = vec![DaySessions {
+            date: NaiveDate::from_ymd_opt(2026, 2, 1).unwrap(),
+            sessions: vec![session_at(9, 600)],
+        }];
+        let options = LineOptions {
+            highlight_longest: true,
+            theme: Theme::preset(Preset::Dark),
+            ..Default::default()
+        };
+        let lines = build_lines_with(&days, "x", &options);
+        assert_eq!(lines[1], "- \x1b[1m2/1\x1b[0m");
+        assert!(lines[2].starts_with("\x1b[1;33m"));
+        assert!(lines[2].ends_with("\x1b[0m"));
+    }
+
+    #[test]
+    fn build_lines_collapse_below() {
+        let days = vec![DaySessions {
+            date: NaiveDate::from_ymd_opt(2026, 2, 1).unwrap(),
+            sessions: vec![
+                session_at(9, 60),
+                session_at(10, 3600),
+                session_at(11, 90),
+                session_at(12, 30),
+            ],
+        }];
+        let options = LineOptions {
+            collapse_below: 180,
+            highlight_longest: true,
+            ..Default::default()
+        };
+        let lines = build_lines_with(&days, "x", &options);
+        assert_eq!(lines.len(), 4);
+        assert!(lines[2].contains("(1h00m) p *"));
+        assert_eq!(lines[3], "  - misc: 3 sessions (0h03m)");
+    }
+
+    #[test]
+    fn build_lines_totals_per_day_and_period() {
+        let mut other = session_at(14, 1800);
+        other.project = Some("q".into());
+        let days = vec![
+            DaySessions {
+                date: NaiveDate::from_ymd_opt(2026, 2, 1).unwrap(),
+                sessions: vec![session_at(9, 3600), other],
+            },
+            DaySessions {
+                date: NaiveDate::from_ymd_opt(2026, 2, 2).unwrap(),
+                sessions: vec![session_at(9, 600)],
+            },
+        ];
+        let options = LineOptions {
+            totals: true,
+            ..Default::default()
+        };
+        let lines = build_lines_with(&days, "x", &options);
+        assert_eq!(lines[4], "  total 1h30m");
+        assert_eq!(lines[8..], ["  total 0h10m", "", "total 1h40m (2 days)"]);
+
+        let options = LineOptions {
+            totals: true,
+            project_totals: true,
+            ..Default::default()
+        };
+        let lines = build_lines_with(&days, "x", &options);
+        assert_eq!(lines[4..7], ["  total 1h30m", "    p 1h00m", "    q 0h30m"]);
+        assert_eq!(
+            lines[lines.len() - 3..],
+            ["total 1h40m (2 days)", "  p 1h10m", "  q 0h30m"]
+        );
+    }
+
+    #[test]
+    fn build_entity_lines_lists_top_files() {
+        let entity = |name: &str, seconds: i64, percent: f64| EntityTotal {
+            entity: Some(name.to_string()),
+            project: Some("api".to_string()),
+            seconds,
+            percent,
+        };
+        let entities = [
+            entity("src/main.rs", 3600, 75.0),
+            entity("README.md", 1200, 25.0),
+        ];
+        assert_eq!(
+            build_entity_lines(&entities, 1),
+            ["files 1h20m", "- 1h00m  75.0% src/main.rs (api)"]
+        );
+    }
+
+    #[test]
+    fn chart_bar_uses_eighths() {
+        assert_eq!(chart_bar(0, 3600, 10), "");
+        assert_eq!(chart_bar(3600, 3600, 10), "█".repeat(10));
+        assert_eq!(chart_bar(1800, 3600, 3), "█▌");
+        assert_eq!(chart_bar(1, 3600, 10), "▏");
+    }
+
+    #[test]
+    fn build_chart_lines_scale_to_width() {
+        let days = vec![
+            DaySessions {
+                date: NaiveDate::from_ymd_opt(2026, 2, 2).unwrap(),
+                sessions: vec![session_at(9, 7200)],
+            },
+            DaySessions {
+                date: NaiveDate::from_ymd_opt(2026, 2, 3).unwrap(),
+                sessions: vec![],
+            },
+            DaySessions {
+                date: NaiveDate::from_ymd_opt(2026, 2, 4).unwrap(),
+                sessions: vec![session_at(9, 1800), session_at(14, 1800)],
+            },
+        ];
+        let rows = chart_rows(&days, false, Lang::En);
+        assert_eq!(rows[0], ("Mon 2/2".to_string(), 7200));
+        let lines = build_chart_lines("chart", &rows, 35);
+        assert_eq!(
+            lines,
+            [
+                "chart".to_string(),
+                format!("Mon 2/2  2h00m {}", "█".repeat(20)),
+                "Tue 2/3  0h00m".to_string(),
+                format!("Wed 2/4  1h00m {}", "█".repeat(10)),
+            ]
+        );
+        assert_eq!(
+            chart_rows(&days, true, Lang::En),
+            [("2026/02".to_string(), 10800)]
+        );
+    }
+
+    #[test]
+    fn build_month_lines_totals_per_month() {
+        let day = |month, day, sessions| DaySessions {
+            date: NaiveDate::from_ymd_opt(2026, month, day).unwrap(),
+            sessions,
+        };
+        let days = vec![
+            day(1, 5, vec![session_at(9, 3600)]),
+            day(1, 6, vec![session_at(9, 1800)]),
+            day(2, 1, vec![]),
+            day(3, 2, vec![session_at(9, 600)]),
+        ];
+        let lines = build_month_lines(&days, "2026", &LineOptions::default());
+        assert_eq!(
+            lines,
+            [
+                "2026",
+                "- 2026/01",
+                "  total 1h30m (2 days)",
+                "- 2026/02  (no activity)",
+                "- 2026/03",
+                "  total 0h10m (1 days)",
+                "",
+                "total 1h40m (3 days)",
+            ]
+        );
+    }
+
+    #[test]
+    fn build_lines_dominant_language() {
+        let mut session = session_at(9, 600);
+        session.languages = vec![LanguageShare {
+            language: "Rust".into(),
+            heartbeats: 3,
+        }];
+        let days = vec![DaySessions {
+            date: NaiveDate::from_ymd_opt(2026, 2, 1).unwrap(),
+            sessions: vec![session, session_at(10, 600)],
+        }];
+        let options = LineOptions {
+            languages: true,
+            ..Default::default()
+        };
+        let lines = build_lines_with(&days, "x", &options);
+        assert!(lines[2].ends_with(" p [Rust]"));
+        assert!(lines[3].ends_with(" p"));
+    }
+
+    #[test]
+    fn build_lines_annotations() {
+        let mut session = session_at(9, 600);
+        session.note = Some("pairing with Sam".into());
+        session.tags = vec!["billable".into()];
+        let days = vec![DaySessions {
+            date: NaiveDate::from_ymd_opt(2026, 2, 1).unwrap(),
+            sessions: vec![session],
+        }];
+        let lines = build_lines_with(&days, "x", &LineOptions::default());
+        assert!(lines[2].ends_with(" p #billable (pairing with Sam)"));
+    }
+
+    #[test]
+    fn build_lines_days_without_activity() {
+        let days = vec![
+            DaySessions {
+                date: NaiveDate::from_ymd_opt(2026, 2, 13).unwrap(),
+                sessions: vec![session_at(9, 600)],
+            },
+            DaySessions {
+                date: NaiveDate::from_ymd_opt(2026, 2, 14).unwrap(),
+                sessions: vec![],
+            },
+        ];
+        let lines = build_lines_with(&days, "x", &LineOptions::default());
+        assert_eq!(lines[4], "- 2/14  (no activity)");
+        assert_eq!(lines.len(), 5);
+    }
+
+    #[test]
+    fn build_switch_lines_per_day_and_average() {
+        let on = |project: &str| Session {
+            project: Some(project.into()),
+            ..Default::default()
+        };
+        let days = vec![DaySessions {
+            date: NaiveDate::from_ymd_opt(2026, 2, 1).unwrap(),
+            sessions: vec![on("a"), on("b"), on("a")],
+        }];
+        assert_eq!(
+            build_switch_lines(&crate::summary::summarize(&days)),
+            vec!["switches", "- 2/1 2", "- period avg 2.0/day"]
+        );
+    }
+
+    #[test]
+    fn build_focus_lines_per_day_and_average() {
+        let days = vec![DaySessions {
+            date: NaiveDate::from_ymd_opt(2026, 2, 1).unwrap(),
+            sessions: vec![session_at(9, 3600), session_at(11, 1200)],
+        }];
+        assert_eq!(
+            build_focus_lines(&crate::summary::summarize(&days)),
+            vec!["focus", "- 2/1 75", "- period avg 75"]
+        );
+    }
+
+    #[test]
+    fn build_calendar_lines_grid() {
+        let date = |d: u32| NaiveDate::from_ymd_opt(2026, 2, d).unwrap();
+        let days = crate::summary::summarize(&[DaySessions {
+            date: date(3),
+            sessions: vec![session_at(9, 5400)],
+        }])
+        .days;
+        // Feb 2026 starts on a Sunday and spans exactly four weeks
+        let lines = build_calendar_lines(
+            "2026/02",
+            date(1),
+            date(28),
+            &days,
+            &[date(11)],
+            &Theme::default(),
+            Lang::En,
+        );
+        assert_eq!(lines.len(), 6);
+        assert!(lines[1].starts_with("    Sun    "));
+        assert!(lines[1].ends_with(" total"));
+        assert_eq!(
+            lines[2],
+            "( 1      -)  2      -   3  1h30m   4      -   5      -   6      - ( 7      -) 1h30m"
+        );
+        assert!(lines[3].contains("[11      -]"));
+        assert!(lines[5].starts_with("(22      -)"));
+    }
+
+    #[test]
+    fn build_calendar_lines_blank_outside_range() {
+        let date = |d: u32| NaiveDate::from_ymd_opt(2026, 2, d).unwrap();
+        let lines =
+            build_calendar_lines("x", date(3), date(5), &[], &[], &Theme::default(), Lang::En);
+        assert_eq!(lines.len(), 3);
+        assert!(lines[2].starts_with(&" ".repeat(2 * CALENDAR_CELL_WIDTH)));
+        assert!(lines[2].contains("  3      - "));
+        assert!(lines[2].ends_with(&format!("{} 0h00m", " ".repeat(2 * CALENDAR_CELL_WIDTH))));
+    }
+
+    #[test]
+    fn build_punch_card_lines_shades() {
+        let mut card = PunchCard::default();
+        card.counts[0][9] = 8;
+        card.counts[0][10] = 1;
+        card.counts[4][23] = 4;
+        let lines = build_punch_card_lines(&card, Lang::En);
+        assert_eq!(lines.len(), 9);
+        assert_eq!(lines[0], "punch card");
+        assert!(lines[1].ends_with(" 22 23"));
+        assert!(lines[2].starts_with("Mon  ·"));
+        assert_eq!(lines[2].chars().filter(|c| *c == '█').count(), 1);
+        assert_eq!(lines[2].chars().filter(|c| *c == '░').count(), 1);
+        assert!(lines[6].starts_with("Fri") && lines[6].ends_with("  ▒"));
+        assert!(lines[8].chars().skip(3).all(|c| c == ' ' || c == '·'));
+    }
+
+    #[test]
+    fn build_heatmap_lines_shades_by_threshold() {
+        let date = |d: u32| NaiveDate::from_ymd_opt(2026, 2, d).unwrap();
+        let day = |d: u32, seconds: i64| DaySessions {
+            date: date(d),
+            sessions: vec![session_at(9, seconds)],
+        };
+        let days =
+            crate::summary::summarize(&[day(2, 1800), day(3, 4 * 3600), day(4, 6 * 3600)]).days;
+        let lines = build_heatmap_lines(
+            "2026/02",
+            date(1),
+            date(28),
+            &days,
+            HEATMAP_THRESHOLDS,
+            Lang::En,
+        );
+        assert_eq!(lines.len(), 10);
+        assert_eq!(lines[1], "    Feb");
+        // 2026-02-01 is a Sunday, so the month fills four whole weeks.
+        assert_eq!(lines[2], "Sun · · · ·");
+        assert_eq!(lines[3], "Mon ░ · · ·");
+        assert_eq!(lines[4], "Tue ▓ · · ·");
+        assert_eq!(lines[5], "Wed █ · · ·");
+        assert_eq!(lines[9], "    · 0  ░ <1h00m  ▒ 1h00m+  ▓ 3h00m+  █ 5h00m+");
+    }
+
+    #[test]
+    fn build_weekday_lines_basic() {
+        let weekdays = vec![WeekdayAverage {
+            weekday: chrono::Weekday::Fri,
+            days: 4,
+            average_seconds: 5400,
+        }];
+        assert_eq!(
+            build_weekday_lines(&weekdays, Lang::En),
+            vec!["weekdays", "- Fri avg 1h30m (4 days)"]
+        );
+        assert_eq!(
+            build_weekday_lines(&weekdays, Lang::Ja),
+            vec!["weekdays", "- 金 平均 1h30m (4日)"]
+        );
+    }
+
+    #[test]
+    fn build_machine_lines_basic() {
+        let overlap = MachineOverlap {
+            start: local_timestamp(2026, 2, 1, 9, 0),
+            end: local_timestamp(2026, 2, 1, 9, 30),
+            machines: vec!["desk".into(), "laptop".into()],
+        };
+        assert_eq!(
+            build_machine_lines(&[overlap]),
+            vec!["machines", "- 2/1 9:00am ~ 9:30am (0h30m) desk, laptop"]
+        );
+    }
+
+    #[test]
+    fn build_data_quality_lines_skips_reasons_without_heartbeats() {
+        let stats = SanitizeStats {
+            fetched: 120,
+            duplicates: 4,
+            too_early: 2,
+            dropped: 2,
+            empty_project: 1,
+            ..Default::default()
+        };
+        assert_eq!(
+            build_data_quality_lines(&stats),
+            vec![
+                "data quality",
+                "- 120 heartbeats fetched",
+                "- 4 duplicates (dropped)",
+                "- 2 dated before 2000 (dropped)",
+                "- 1 without a project (counted as unknown)",
+            ]
+        );
+    }
+
+    #[test]
+    fn format_gap_units() {
+        assert_eq!(format_gap(45), "45s");
+        assert_eq!(format_gap(125), "2m05s");
+        assert_eq!(format_gap(3900), "1h05m");
+    }
+
+    #[test]
+    fn build_trace_lines_marks_long_gaps() {
+        use crate::inspect::TracedHeartbeat;
+
+        let start = local_timestamp(2026, 2, 3, 14, 0);
+        let traced = |offset: i64, gap: Option<i64>| TracedHeartbeat {
+            time: start + offset,
+            project: Some("p".into()),
+            entity: Some("main.rs".into()),
+            gap,
+        };
+        let traces = vec![SessionTrace {
+            index: 2,
+            session: Session {
+                start,
+                end: start + 120,
+                seconds: 120,
+                project: Some("p".into()),
+                ..Default::default()
+            },
+            heartbeats: vec![traced(0, Some(1800)), traced(120, Some(120))],
+        }];
+        let date = NaiveDate::from_ymd_opt(2026, 2, 3).unwrap();
+        assert_eq!(
+            build_trace_lines(date, &traces, 900),
+            vec![
+                "2/3",
+                "- #2 2:00pm ~ 2:02pm (0h02m) p",
+                "  - 2:00:00pm +30m00s (not counted) p main.rs",
+                "  - 2:02:00pm +2m00s p main.rs",
+            ]
+        );
+    }
+
     #[test]
     fn build_lines_with_sessions() {
         let ts1 = local_timestamp(2026, 2, 1, 9, 0);
@@ -136,6 +1740,7 @@ mod tests {
                 end: ts2,
                 seconds: 3600,
                 project: Some("myproj".into()),
+                ..Default::default()
             }],
         }];
         let result = build_lines(&days, "2026/02");
@@ -144,4 +1749,52 @@ mod tests {
         assert!(result[2].contains("myproj"));
         assert!(result[2].contains("1h00m"));
     }
+
+    #[test]
+    fn build_heartbeat_lines_aligns_columns() {
+        let heartbeats = vec![
+            RawHeartbeat {
+                time: Some(local_timestamp(2026, 2, 3, 9, 5) as f64),
+                project: Some("wakalyze".into()),
+                language: Some("Rust".into()),
+                entity: Some("src/main.rs".into()),
+                ..Default::default()
+            },
+            RawHeartbeat {
+                time: Some(local_timestamp(2026, 2, 3, 13, 30) as f64),
+                ..Default::default()
+            },
+        ];
+        assert_eq!(
+            build_heartbeat_lines(&heartbeats),
+            vec![
+                "TIME       PROJECT   LANGUAGE  ENTITY",
+                "9:05:00am  wakalyze  Rust      src/main.rs",
+                "1:30:00pm  -         -         -",
+            ]
+        );
+    }
+
+    #[test]
+    fn build_session_table_lines_lists_ids() {
+        let session = Session {
+            start: local_timestamp(2026, 2, 3, 9, 0),
+            end: local_timestamp(2026, 2, 3, 10, 30),
+            seconds: 5400,
+            project: Some("wakalyze".into()),
+            ..Default::default()
+        };
+        let rows = [SessionRow {
+            id: "0123456789ab".into(),
+            date: NaiveDate::from_ymd_opt(2026, 2, 3).unwrap(),
+            session: &session,
+        }];
+        assert_eq!(
+            build_session_table_lines(&rows),
+            vec![
+                "ID            DATE        START   END      DURATION  PROJECT",
+                "0123456789ab  2026-02-03  9:00am  10:30am  1h30m     wakalyze",
+            ]
+        );
+    }
 }
@@ -0,0 +1,162 @@
+//! Weekly and monthly hour targets from the config's `goals`, and how far the current week or
+//! month is along towards them.
+
+use chrono::{Datelike, Duration, NaiveDate};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+use crate::core::{is_weekend, iter_dates, month_last_day, DaySessions};
+use crate::summary::percent_of;
+
+/// What a goal's hours are counted over.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum GoalPeriod {
+    /// Sunday to Saturday
+    Week,
+    /// The calendar month
+    Month,
+}
+
+impl GoalPeriod {
+    /// First and last day of the week or month containing `today`.
+    pub fn range(self, today: NaiveDate) -> (NaiveDate, NaiveDate) {
+        match self {
+            GoalPeriod::Week => {
+                let start = today - Duration::days(today.weekday().num_days_from_sunday() as i64);
+                (start, start + Duration::days(6))
+            }
+            GoalPeriod::Month => {
+                let start = today.with_day(1).unwrap_or(today);
+                (start, month_last_day(start))
+            }
+        }
+    }
+}
+
+/// A target in `goals`: `hours` per week or month, on one project or on all.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Goal {
+    pub period: GoalPeriod,
+    pub hours: f64,
+    /// Project name after renames; all projects if unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project: Option<String>,
+}
+
+/// Progress of a goal on `today`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GoalStatus {
+    pub period: GoalPeriod,
+    pub project: Option<String>,
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    pub goal_seconds: i64,
+    pub seconds: i64,
+    /// 0 once the goal is met.
+    pub remaining_seconds: i64,
+    /// Days from today to the end of the period, today included.
+    pub days_left: i64,
+    /// Time a day needed over `days_left` to meet the goal; `None` once met or over.
+    pub daily_pace_seconds: Option<i64>,
+}
+
+impl GoalStatus {
+    /// Share of the goal reached, 0–100 and beyond.
+    pub fn percent(&self) -> f64 {
+        percent_of(self.seconds, self.goal_seconds)
+    }
+}
+
+/// The status of `goal` for the period containing `today`, from `days` covering it. With
+/// `skip_weekends`, Saturdays and Sundays are not counted as days left.
+pub fn goal_status(
+    goal: &Goal,
+    days: &[DaySessions],
+    today: NaiveDate,
+    skip_weekends: bool,
+) -> GoalStatus {
+    let (start, end) = goal.period.range(today);
+    let seconds: i64 = days
+        .iter()
+        .filter(|day| day.date >= start && day.date <= end)
+        .flat_map(|day| &day.sessions)
+        .filter(|s| goal.project.is_none() || s.project.as_deref() == goal.project.as_deref())
+        .map(|s| s.seconds)
+        .sum();
+    let goal_seconds = (goal.hours * 3600.0).round() as i64;
+    let remaining_seconds = (goal_seconds - seconds).max(0);
+    let days_left = iter_dates(today.max(start), end)
+        .into_iter()
+        .filter(|date| !(skip_weekends && is_weekend(*date)))
+        .count() as i64;
+    GoalStatus {
+        period: goal.period,
+        project: goal.project.clone(),
+        start,
+        end,
+        goal_seconds,
+        seconds,
+        remaining_seconds,
+        days_left,
+        daily_pace_seconds: (remaining_seconds > 0 && days_left > 0)
+            .then(|| (remaining_seconds + days_left - 1) / days_left),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Session;
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 2, day).unwrap()
+    }
+
+    fn day(day: u32, seconds: i64, project: &str) -> DaySessions {
+        DaySessions {
+            date: date(day),
+            sessions: vec![Session {
+                seconds,
+                project: Some(project.into()),
+                ..Default::default()
+            }],
+        }
+    }
+
+    #[test]
+    fn progress_remaining_and_pace() {
+        // 2026-02-11 is a Wednesday of the week 2026-02-08 to 2026-02-14.
+        let days = [
+            day(2, 4 * 3600, "api"),
+            day(9, 3 * 3600, "api"),
+            day(10, 2 * 3600, "web"),
+        ];
+        let week = Goal {
+            period: GoalPeriod::Week,
+            hours: 20.0,
+            project: None,
+        };
+        let status = goal_status(&week, &days, date(11), false);
+        assert_eq!((status.start, status.end), (date(8), date(14)));
+        assert_eq!(status.seconds, 5 * 3600);
+        assert_eq!(status.remaining_seconds, 15 * 3600);
+        assert_eq!(status.days_left, 4);
+        assert_eq!(status.daily_pace_seconds, Some(3 * 3600 + 45 * 60));
+        assert_eq!(status.percent(), 25.0);
+
+        let weekdays = goal_status(&week, &days, date(11), true);
+        assert_eq!(weekdays.days_left, 3);
+        assert_eq!(weekdays.daily_pace_seconds, Some(5 * 3600));
+
+        let month = Goal {
+            period: GoalPeriod::Month,
+            hours: 6.0,
+            project: Some("api".to_string()),
+        };
+        let status = goal_status(&month, &days, date(11), false);
+        assert_eq!(status.seconds, 7 * 3600);
+        assert_eq!(status.remaining_seconds, 0);
+        assert_eq!(status.daily_pace_seconds, None);
+    }
+}
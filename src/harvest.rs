@@ -0,0 +1,219 @@
+//! Harvest timesheet CSV export.
+//!
+//! Rows follow Harvest's time import template (`Date, Client, Project, Task, Notes, Hours`):
+//! one row per day and mapped Harvest project/task, with the rounding applied per row.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+use chrono::NaiveDate;
+use serde::Deserialize;
+
+use crate::core::DaySessions;
+use crate::error::Result;
+use crate::format::csv_field;
+use crate::rounding::Rounding;
+
+pub const DEFAULT_TASK: &str = "Development";
+
+/// Where one Wakapi project goes in Harvest; missing fields fall back to the defaults.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct HarvestTarget {
+    pub client: Option<String>,
+    pub project: Option<String>,
+    pub task: Option<String>,
+}
+
+/// Wakapi project → Harvest client/project/task, loaded from a JSON file:
+///
+/// ```json
+/// { "task": "Development", "projects": { "wakalyze": { "client": "Acme", "project": "CLI" } } }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct HarvestMapping {
+    /// Task for projects without one of their own.
+    pub task: Option<String>,
+    #[serde(default)]
+    pub projects: BTreeMap<String, HarvestTarget>,
+}
+
+impl HarvestMapping {
+    pub fn load(path: &Path) -> Result<Self> {
+        Ok(serde_json::from_str(&std::fs::read_to_string(path)?)?)
+    }
+
+    /// Harvest (client, project, task) of a Wakapi project.
+    fn resolve(&self, project: &str) -> (String, String, String) {
+        let target = self.projects.get(project).cloned().unwrap_or_default();
+        (
+            target.client.unwrap_or_default(),
+            target.project.unwrap_or_else(|| project.to_string()),
+            target
+                .task
+                .or_else(|| self.task.clone())
+                .unwrap_or_else(|| DEFAULT_TASK.to_string()),
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HarvestRow {
+    pub date: NaiveDate,
+    pub client: String,
+    pub project: String,
+    pub task: String,
+    /// Wakapi projects that make up the row, then the notes of its sessions.
+    pub notes: String,
+    /// Rounded duration.
+    pub seconds: i64,
+}
+
+/// Sessions of one day that go into the same row.
+#[derive(Default)]
+struct Entry<'a> {
+    seconds: i64,
+    sources: BTreeSet<&'a str>,
+    /// Session notes from `wakalyze tag`, in time order.
+    notes: Vec<&'a str>,
+}
+
+pub fn harvest_rows(
+    days: &[DaySessions],
+    mapping: &HarvestMapping,
+    rounding: Option<&Rounding>,
+) -> Vec<HarvestRow> {
+    let mut rows = Vec::new();
+    for day in days {
+        let mut entries: BTreeMap<(String, String, String), Entry> = BTreeMap::new();
+        for session in &day.sessions {
+            let project = session.project.as_deref().unwrap_or("unknown");
+            let entry = entries.entry(mapping.resolve(project)).or_default();
+            entry.seconds += session.seconds;
+            entry.sources.insert(project);
+            if let Some(note) = session.note.as_deref() {
+                entry.notes.push(note);
+            }
+        }
+        for ((client, project, task), entry) in entries {
+            let mut notes = entry.sources.into_iter().collect::<Vec<_>>().join(", ");
+            for note in entry.notes {
+                notes.push_str("; ");
+                notes.push_str(note);
+            }
+            let seconds = entry.seconds;
+            rows.push(HarvestRow {
+                date: day.date,
+                client,
+                project,
+                task,
+                notes,
+                seconds: rounding.map_or(seconds, |r| r.apply(seconds)),
+            });
+        }
+    }
+    rows
+}
+
+/// CSV lines, header first; hours are decimal with two places.
+pub fn to_harvest_csv(rows: &[HarvestRow]) -> Vec<String> {
+    let mut lines = vec!["Date,Client,Project,Task,Notes,Hours".to_string()];
+    for row in rows {
+        lines.push(format!(
+            "{},{},{},{},{},{:.2}",
+            row.date,
+            csv_field(&row.client),
+            csv_field(&row.project),
+            csv_field(&row.task),
+            csv_field(&row.notes),
+            row.seconds as f64 / 3600.0,
+        ));
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Session;
+    use crate::rounding::RoundingMode;
+
+    fn session(seconds: i64, project: &str) -> Session {
+        Session {
+            seconds,
+            project: Some(project.into()),
+            ..Default::default()
+        }
+    }
+
+    fn mapping() -> HarvestMapping {
+        serde_json::from_str(
+            r#"{
+                "task": "Coding",
+                "projects": {
+                    "api": { "client": "Acme, Inc.", "project": "Platform" },
+                    "web": { "client": "Acme, Inc.", "project": "Platform", "task": "Frontend" },
+                    "ui": { "client": "Acme, Inc.", "project": "Platform", "task": "Frontend" }
+                }
+            }"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn rows_grouped_by_mapped_target_and_rounded() {
+        let days = vec![DaySessions {
+            date: NaiveDate::from_ymd_opt(2026, 2, 3).unwrap(),
+            sessions: vec![
+                session(1000, "api"),
+                session(600, "web"),
+                session(300, "ui"),
+                session(60, "misc"),
+            ],
+        }];
+        let rounding = Rounding::new(15 * 60, RoundingMode::Up);
+        let rows = harvest_rows(&days, &mapping(), Some(&rounding));
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[0].client, "");
+        assert_eq!(rows[0].project, "misc");
+        assert_eq!(rows[0].task, "Coding");
+        assert_eq!(rows[0].seconds, 900);
+        assert_eq!(rows[1].task, "Coding");
+        assert_eq!(rows[1].seconds, 1800);
+        assert_eq!(rows[2].task, "Frontend");
+        assert_eq!(rows[2].notes, "ui, web");
+        assert_eq!(rows[2].seconds, 900);
+    }
+
+    #[test]
+    fn session_notes_follow_the_projects() {
+        let noted = |note: &str| Session {
+            note: Some(note.into()),
+            ..session(600, "web")
+        };
+        let days = vec![DaySessions {
+            date: NaiveDate::from_ymd_opt(2026, 2, 3).unwrap(),
+            sessions: vec![noted("pairing"), session(300, "ui"), noted("review")],
+        }];
+        let rows = harvest_rows(&days, &mapping(), None);
+        assert_eq!(rows[0].notes, "ui, web; pairing; review");
+    }
+
+    #[test]
+    fn csv_quotes_and_formats_hours() {
+        let rows = vec![HarvestRow {
+            date: NaiveDate::from_ymd_opt(2026, 2, 3).unwrap(),
+            client: "Acme, Inc.".into(),
+            project: "Say \"hi\"".into(),
+            task: DEFAULT_TASK.into(),
+            notes: "api".into(),
+            seconds: 5400,
+        }];
+        assert_eq!(
+            to_harvest_csv(&rows),
+            vec![
+                "Date,Client,Project,Task,Notes,Hours",
+                "2026-02-03,\"Acme, Inc.\",\"Say \"\"hi\"\"\",Development,api,1.50",
+            ]
+        );
+    }
+}
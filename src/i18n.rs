@@ -0,0 +1,99 @@
+//! Labels of the text output in the language of `--lang` (or `lang` in the config).
+
+use chrono::Weekday;
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum Lang {
+    /// English
+    #[default]
+    En,
+    /// Japanese
+    Ja,
+}
+
+/// The strings of one language.
+#[derive(Debug)]
+pub struct Bundle {
+    /// Week of a month in period labels; `{n}` is the week number.
+    week: &'static str,
+    pub total: &'static str,
+    pub no_activity: &'static str,
+    pub average: &'static str,
+    /// Count of days; `{n}` is the number.
+    days: &'static str,
+    /// Abbreviated weekday names, Monday first.
+    weekdays: [&'static str; 7],
+    /// Abbreviated month names, January first.
+    months: [&'static str; 12],
+}
+
+const EN: Bundle = Bundle {
+    week: "week {n}",
+    total: "total",
+    no_activity: "no activity",
+    average: "avg",
+    days: "{n} days",
+    weekdays: ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"],
+    months: [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ],
+};
+
+const JA: Bundle = Bundle {
+    week: "第{n}週",
+    total: "合計",
+    no_activity: "活動なし",
+    average: "平均",
+    days: "{n}日",
+    weekdays: ["月", "火", "水", "木", "金", "土", "日"],
+    months: [
+        "1月", "2月", "3月", "4月", "5月", "6月", "7月", "8月", "9月", "10月", "11月", "12月",
+    ],
+};
+
+impl Lang {
+    pub fn bundle(self) -> &'static Bundle {
+        match self {
+            Lang::En => &EN,
+            Lang::Ja => &JA,
+        }
+    }
+}
+
+impl Bundle {
+    pub fn week(&self, n: u32) -> String {
+        self.week.replace("{n}", &n.to_string())
+    }
+
+    pub fn days(&self, n: usize) -> String {
+        self.days.replace("{n}", &n.to_string())
+    }
+
+    pub fn weekday(&self, weekday: Weekday) -> &'static str {
+        self.weekdays[weekday.num_days_from_monday() as usize]
+    }
+
+    /// Name of `month`, 1–12.
+    pub fn month(&self, month: u32) -> &'static str {
+        self.months[(month as usize).clamp(1, 12) - 1]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bundles_fill_in_numbers_and_weekdays() {
+        assert_eq!(Lang::En.bundle().week(2), "week 2");
+        assert_eq!(Lang::Ja.bundle().week(2), "第2週");
+        assert_eq!(Lang::Ja.bundle().days(3), "3日");
+        assert_eq!(Lang::En.bundle().weekday(Weekday::Sun), "Sun");
+        assert_eq!(Lang::Ja.bundle().weekday(Weekday::Sat), "土");
+        assert_eq!(Lang::En.bundle().month(2), "Feb");
+        assert_eq!(Lang::Ja.bundle().month(12), "12月");
+    }
+}
@@ -0,0 +1,101 @@
+//! Projects and entities (files, domains) whose heartbeats never make it into sessions.
+
+use serde::{Deserialize, Serialize};
+
+/// Whether `text` matches a glob `pattern`: `?` is any character, `*` any run of characters
+/// within a path segment, and `**` any run including `/`.
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    matches_from(&pattern, &text)
+}
+
+fn matches_from(pattern: &[char], text: &[char]) -> bool {
+    match pattern {
+        [] => text.is_empty(),
+        ['*', '*', rest @ ..] => {
+            // `**/` also matches no directory at all.
+            let rest_after_slash = rest.strip_prefix(&['/']).unwrap_or(rest);
+            (0..=text.len()).any(|i| {
+                matches_from(rest, &text[i..]) || matches_from(rest_after_slash, &text[i..])
+            })
+        }
+        ['*', rest @ ..] => (0..=text.len())
+            .take_while(|&i| i == 0 || text[i - 1] != '/')
+            .any(|i| matches_from(rest, &text[i..])),
+        ['?', rest @ ..] => text
+            .split_first()
+            .is_some_and(|(&c, tail)| c != '/' && matches_from(rest, tail)),
+        [p, rest @ ..] => text
+            .split_first()
+            .is_some_and(|(c, tail)| c == p && matches_from(rest, tail)),
+    }
+}
+
+/// The `ignore_projects` and `ignore_entities` globs of the config.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IgnoreRules {
+    pub projects: Vec<String>,
+    pub entities: Vec<String>,
+}
+
+impl IgnoreRules {
+    pub fn is_empty(&self) -> bool {
+        self.projects.is_empty() && self.entities.is_empty()
+    }
+
+    /// Whether a heartbeat with this project and entity is noise. Entities are matched with
+    /// `/` separators, so one pattern covers Windows paths too.
+    pub fn ignores(&self, project: Option<&str>, entity: Option<&str>) -> bool {
+        let project_ignored = project.is_some_and(|project| {
+            self.projects
+                .iter()
+                .any(|pattern| glob_match(pattern, project))
+        });
+        project_ignored
+            || entity.is_some_and(|entity| {
+                let entity = entity.replace('\\', "/");
+                self.entities
+                    .iter()
+                    .any(|pattern| glob_match(pattern, &entity))
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn glob_wildcards() {
+        assert!(glob_match("scratch*", "scratch-2026"));
+        assert!(!glob_match("scratch*", "my-scratch"));
+        assert!(glob_match("?pi", "api"));
+        assert!(glob_match("*.lock", "Cargo.lock"));
+        assert!(!glob_match("*.lock", "vendor/Cargo.lock"));
+        assert!(glob_match("**/*.lock", "vendor/Cargo.lock"));
+        assert!(glob_match("**/*.lock", "Cargo.lock"));
+        assert!(glob_match(
+            "**/node_modules/**",
+            "/home/me/app/node_modules/x/index.js"
+        ));
+        assert!(!glob_match(
+            "**/node_modules/**",
+            "/home/me/app/src/node_modules.rs"
+        ));
+        assert!(glob_match("", ""));
+        assert!(!glob_match("a", ""));
+    }
+
+    #[test]
+    fn ignores_by_project_or_entity() {
+        let rules = IgnoreRules {
+            projects: vec!["scratch*".into()],
+            entities: vec!["**/node_modules/**".into()],
+        };
+        assert!(rules.ignores(Some("scratchpad"), None));
+        assert!(rules.ignores(Some("api"), Some(r"C:\app\node_modules\x.js")));
+        assert!(!rules.ignores(Some("api"), Some("/app/src/main.rs")));
+        assert!(!rules.ignores(None, None));
+    }
+}
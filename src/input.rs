@@ -0,0 +1,104 @@
+//! Heartbeats exported from a server, for `--input` analysis without credentials.
+//!
+//! Accepted are a JSON list of heartbeats, a heartbeats API response (`{"data": [...]}`), and
+//! a WakaTime data dump (`{"days": [{"heartbeats": [...]}, ...]}`).
+
+use std::io::Read;
+use std::path::Path;
+
+use serde::Deserialize;
+
+use crate::core::RawHeartbeat;
+use crate::error::{Result, WakalyzeError};
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Export {
+    List(Vec<RawHeartbeat>),
+    Response { data: Vec<RawHeartbeat> },
+    Dump { days: Vec<DumpDay> },
+}
+
+#[derive(Deserialize)]
+struct DumpDay {
+    #[serde(default)]
+    heartbeats: Vec<RawHeartbeat>,
+}
+
+pub fn parse_heartbeats(text: &str) -> std::result::Result<Vec<RawHeartbeat>, String> {
+    let export: Export = serde_json::from_str(text).map_err(|_| {
+        "expected a list of heartbeats, {\"data\": [...]}, or a WakaTime data dump".to_string()
+    })?;
+    Ok(match export {
+        Export::List(heartbeats) | Export::Response { data: heartbeats } => heartbeats,
+        Export::Dump { days } => days.into_iter().flat_map(|day| day.heartbeats).collect(),
+    })
+}
+
+/// The heartbeats of every file in `paths`, in order; `-` reads stdin.
+pub fn load_heartbeats(paths: &[impl AsRef<Path>]) -> Result<Vec<RawHeartbeat>> {
+    let mut heartbeats = Vec::new();
+    for path in paths {
+        let path = path.as_ref();
+        let text = if path == Path::new("-") {
+            let mut text = String::new();
+            std::io::stdin().read_to_string(&mut text)?;
+            text
+        } else {
+            std::fs::read_to_string(path)?
+        };
+        let parsed = parse_heartbeats(&text).map_err(|reason| {
+            WakalyzeError::InvalidInput(format!("{}: {reason}", path.display()))
+        })?;
+        heartbeats.extend(parsed);
+    }
+    Ok(heartbeats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_every_export_shape() {
+        let expected = vec![
+            RawHeartbeat {
+                time: Some(100.0),
+                project: Some("foo".into()),
+                ..Default::default()
+            },
+            RawHeartbeat {
+                time: Some(200.0),
+                project: Some("bar".into()),
+                ..Default::default()
+            },
+        ];
+        for text in [
+            r#"[{"time":100,"project":"foo"},{"time":200,"project":"bar"}]"#,
+            r#"{"data":[{"time":100,"project":"foo"},{"time":200,"project":"bar"}]}"#,
+            r#"{"user":{},"days":[{"date":"1970-01-01","heartbeats":[{"time":100,"project":"foo"}]},
+                {"date":"1970-01-02","heartbeats":[{"time":200,"project":"bar"}]}]}"#,
+        ] {
+            assert_eq!(parse_heartbeats(text).unwrap(), expected);
+        }
+        assert!(parse_heartbeats(r#"{"data":"not a list"}"#).is_err());
+    }
+
+    #[test]
+    fn loads_files_in_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let first = dir.path().join("a.json");
+        let second = dir.path().join("b.json");
+        std::fs::write(&first, r#"[{"time":100,"project":"foo"}]"#).unwrap();
+        std::fs::write(&second, r#"{"data":[{"time":200,"project":"bar"}]}"#).unwrap();
+        let heartbeats = load_heartbeats(&[first, second.clone()]).unwrap();
+        assert_eq!(heartbeats.len(), 2);
+        assert_eq!(heartbeats[1].project.as_deref(), Some("bar"));
+
+        std::fs::write(&second, "not json").unwrap();
+        assert!(matches!(
+            load_heartbeats(&[second]),
+            Err(WakalyzeError::InvalidInput(_))
+        ));
+    }
+}
@@ -0,0 +1,120 @@
+use serde::Serialize;
+
+use crate::core::{build_sessions_with, Estimator, RawHeartbeat, Session};
+
+/// One raw heartbeat behind a session.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TracedHeartbeat {
+    pub time: i64,
+    pub project: Option<String>,
+    pub entity: Option<String>,
+    /// Seconds since the previous heartbeat of the day; `None` for the first one.
+    pub gap: Option<i64>,
+}
+
+/// A session together with the heartbeats that produced it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SessionTrace {
+    /// 1-based position of the session within the day.
+    pub index: usize,
+    pub session: Session,
+    pub heartbeats: Vec<TracedHeartbeat>,
+}
+
+/// Build the day's sessions and attach each heartbeat to the session covering its time.
+pub fn trace_sessions(
+    heartbeats: &[RawHeartbeat],
+    max_gap: i64,
+    estimator: &dyn Estimator,
+) -> Vec<SessionTrace> {
+    let sessions = build_sessions_with(heartbeats, max_gap, estimator);
+    let mut traces: Vec<SessionTrace> = sessions
+        .into_iter()
+        .enumerate()
+        .map(|(i, session)| SessionTrace {
+            index: i + 1,
+            session,
+            heartbeats: Vec::new(),
+        })
+        .collect();
+
+    let mut timed: Vec<(i64, &RawHeartbeat)> = heartbeats
+        .iter()
+        .filter_map(|hb| Some((hb.time? as i64, hb)))
+        .collect();
+    timed.sort_by_key(|(time, _)| *time);
+
+    // Sessions are disjoint and in time order, so one cursor walks both lists.
+    let mut cursor = 0;
+    let mut previous: Option<i64> = None;
+    for (time, hb) in timed {
+        while cursor < traces.len() && time > traces[cursor].session.end {
+            cursor += 1;
+        }
+        if let Some(trace) = traces.get_mut(cursor) {
+            trace.heartbeats.push(TracedHeartbeat {
+                time,
+                project: hb.project.clone(),
+                entity: hb.entity.clone(),
+                gap: previous.map(|p| time - p),
+            });
+        }
+        previous = Some(time);
+    }
+    traces
+}
+
+/// The session in progress at `timestamp` (first to last heartbeat, inclusive).
+pub fn trace_at(traces: &[SessionTrace], timestamp: i64) -> Option<&SessionTrace> {
+    traces
+        .iter()
+        .find(|t| t.session.start <= timestamp && timestamp <= t.session.end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::GapEstimator;
+
+    fn hb(time: f64, project: &str, entity: &str) -> RawHeartbeat {
+        RawHeartbeat {
+            time: Some(time),
+            project: Some(project.to_string()),
+            entity: Some(entity.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn heartbeats_attached_to_their_session() {
+        let heartbeats = vec![
+            hb(1300.0, "foo", "b.rs"),
+            hb(1000.0, "foo", "a.rs"),
+            hb(5000.0, "foo", "c.rs"),
+            hb(5100.0, "bar", "d.rs"),
+        ];
+        let traces = trace_sessions(&heartbeats, 900, &GapEstimator);
+        assert_eq!(traces.len(), 3);
+
+        assert_eq!(traces[0].index, 1);
+        let first: Vec<_> = traces[0]
+            .heartbeats
+            .iter()
+            .map(|h| (h.time, h.gap))
+            .collect();
+        assert_eq!(first, vec![(1000, None), (1300, Some(300))]);
+        assert_eq!(traces[0].heartbeats[1].entity.as_deref(), Some("b.rs"));
+
+        assert_eq!(traces[1].heartbeats.len(), 1);
+        assert_eq!(traces[1].heartbeats[0].gap, Some(3700));
+        assert_eq!(traces[2].heartbeats[0].project.as_deref(), Some("bar"));
+    }
+
+    #[test]
+    fn trace_at_finds_covering_session() {
+        let heartbeats = vec![hb(1000.0, "foo", "a"), hb(1600.0, "foo", "a")];
+        let traces = trace_sessions(&heartbeats, 900, &GapEstimator);
+        assert_eq!(trace_at(&traces, 1200).map(|t| t.index), Some(1));
+        assert!(trace_at(&traces, 2000).is_none());
+    }
+}
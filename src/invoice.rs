@@ -0,0 +1,563 @@
+//! Invoices built from the analyzed sessions.
+//!
+//! Billable projects, hourly rates, and client details come from the `invoice` section of the
+//! config file; line items are rounded one by one and rendered through a Markdown or HTML
+//! template with `{{placeholder}}` fields. Projects billed in other currencies are converted
+//! into the invoice's with exchange rates from a file or URL.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+
+use chrono::NaiveDate;
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+use crate::core::DaySessions;
+use crate::error::{Result, WakalyzeError};
+use crate::format::xml_escape;
+use crate::rounding::Rounding;
+
+pub const DEFAULT_CURRENCY: &str = "USD";
+
+/// Currencies without minor units, whose amounts are written without decimals.
+const ZERO_DECIMAL_CURRENCIES: [&str; 10] = [
+    "BIF", "CLP", "ISK", "JPY", "KRW", "PYG", "UGX", "VND", "XAF", "XOF",
+];
+
+pub const MARKDOWN_TEMPLATE: &str = "# Invoice {{number}}
+
+Date: {{date}}
+Period: {{period}}
+
+**From**
+{{from}}
+
+**Bill to**
+{{client}}
+
+{{items}}
+
+**Total: {{total}}**
+";
+
+pub const HTML_TEMPLATE: &str = "<!DOCTYPE html>
+<html>
+<head><meta charset=\"utf-8\"><title>Invoice {{number}}</title></head>
+<body>
+<h1>Invoice {{number}}</h1>
+<p>Date: {{date}}<br>Period: {{period}}</p>
+<h2>From</h2>
+<p>{{from}}</p>
+<h2>Bill to</h2>
+<p>{{client}}</p>
+{{items}}
+<p><strong>Total: {{total}}</strong></p>
+</body>
+</html>
+";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct InvoiceProject {
+    /// Hourly rate; falls back to the invoice-wide rate.
+    pub rate: Option<f64>,
+    /// Currency of `rate`; falls back to the invoice-wide currency.
+    pub currency: Option<String>,
+    /// Non-billable projects are left off the invoice.
+    pub billable: bool,
+}
+
+impl Default for InvoiceProject {
+    fn default() -> Self {
+        Self {
+            rate: None,
+            currency: None,
+            billable: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct InvoiceConfig {
+    /// Currency code written after amounts (`USD` by default).
+    pub currency: Option<String>,
+    /// Hourly rate of projects without one of their own.
+    pub rate: Option<f64>,
+    pub projects: BTreeMap<String, InvoiceProject>,
+    /// Issuer address block, one entry per line.
+    pub from: Vec<String>,
+    /// Client address block, one entry per line.
+    pub client: Vec<String>,
+    /// Template file used instead of the built-in one.
+    pub template: Option<PathBuf>,
+}
+
+impl InvoiceConfig {
+    fn project(&self, name: &str) -> InvoiceProject {
+        self.projects.get(name).cloned().unwrap_or_default()
+    }
+
+    pub fn currency(&self) -> &str {
+        self.currency.as_deref().unwrap_or(DEFAULT_CURRENCY)
+    }
+
+    /// The config with every project billed at `rate`, in `currency` or the invoice-wide one.
+    /// Billable flags are kept.
+    pub fn with_rate(mut self, rate: f64, currency: Option<&str>) -> Self {
+        self.rate = Some(rate);
+        if let Some(currency) = currency {
+            self.currency = Some(currency.to_string());
+        }
+        for project in self.projects.values_mut() {
+            project.rate = None;
+            project.currency = None;
+        }
+        self
+    }
+
+    fn project_currency(&self, name: &str) -> String {
+        self.project(name)
+            .currency
+            .unwrap_or_else(|| self.currency().to_string())
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum LineItems {
+    /// One line per billable project
+    #[default]
+    Project,
+    /// One line per day and billable project
+    Day,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum Markup {
+    /// Markdown document
+    #[default]
+    Markdown,
+    /// Standalone HTML page
+    Html,
+}
+
+impl Markup {
+    pub fn default_template(self) -> &'static str {
+        match self {
+            Markup::Markdown => MARKDOWN_TEMPLATE,
+            Markup::Html => HTML_TEMPLATE,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LineItem {
+    pub description: String,
+    /// Rounded duration.
+    pub seconds: i64,
+    pub rate: f64,
+    /// Currency of `rate` and the amount.
+    pub currency: String,
+}
+
+impl LineItem {
+    pub fn hours(&self) -> f64 {
+        self.seconds as f64 / 3600.0
+    }
+
+    pub fn amount(&self) -> f64 {
+        self.hours() * self.rate
+    }
+}
+
+/// Billable time grouped into line items, each rounded on its own.
+pub fn line_items(
+    days: &[DaySessions],
+    config: &InvoiceConfig,
+    per: LineItems,
+    rounding: Option<&Rounding>,
+) -> Vec<LineItem> {
+    let mut totals: BTreeMap<(Option<NaiveDate>, &str), i64> = BTreeMap::new();
+    for day in days {
+        let date = (per == LineItems::Day).then_some(day.date);
+        for session in &day.sessions {
+            let project = session.project.as_deref().unwrap_or("unknown");
+            if config.project(project).billable {
+                *totals.entry((date, project)).or_default() += session.seconds;
+            }
+        }
+    }
+    totals
+        .into_iter()
+        .map(|((date, project), seconds)| LineItem {
+            description: match date {
+                Some(date) => format!("{date} {project}"),
+                None => project.to_string(),
+            },
+            seconds: rounding.map_or(seconds, |r| r.apply(seconds)),
+            rate: config.project(project).rate.or(config.rate).unwrap_or(0.0),
+            currency: config.project_currency(project),
+        })
+        .collect()
+}
+
+/// Exchange rates: one unit of `base` is worth `rates[code]` of each other currency. Reads
+/// the JSON of ECB-based services such as Frankfurter (`{"base": "EUR", "rates": {...}}`).
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct FxRates {
+    pub base: String,
+    pub rates: BTreeMap<String, f64>,
+}
+
+impl FxRates {
+    fn rate(&self, currency: &str) -> Option<f64> {
+        if currency.eq_ignore_ascii_case(&self.base) {
+            return Some(1.0);
+        }
+        self.rates
+            .iter()
+            .find(|(code, _)| code.eq_ignore_ascii_case(currency))
+            .map(|(_, &rate)| rate)
+            .filter(|&rate| rate > 0.0)
+    }
+
+    /// `amount` of `from` in `to`; `None` when either rate is missing.
+    pub fn convert(&self, amount: f64, from: &str, to: &str) -> Option<f64> {
+        Some(amount / self.rate(from)? * self.rate(to)?)
+    }
+}
+
+/// Exchange rates from a JSON file, or from an `http(s)://` URL.
+pub fn load_fx_rates(source: &str, timeout_secs: f64) -> Result<FxRates> {
+    let text = if source.starts_with("http://") || source.starts_with("https://") {
+        reqwest::blocking::Client::builder()
+            .timeout(std::time::Duration::from_secs_f64(timeout_secs))
+            .build()?
+            .get(source)
+            .send()?
+            .error_for_status()?
+            .text()?
+    } else {
+        std::fs::read_to_string(Path::new(source))?
+    };
+    serde_json::from_str(&text).map_err(|e| WakalyzeError::InvalidFxRates(format!("{source}: {e}")))
+}
+
+/// Reprice `items` in `currency`. Items already in it are left alone; the others need `fx`,
+/// and keep their original rate in the description.
+pub fn convert_items(items: &mut [LineItem], currency: &str, fx: Option<&FxRates>) -> Result<()> {
+    for item in items {
+        if item.currency.eq_ignore_ascii_case(currency) {
+            continue;
+        }
+        let missing = || WakalyzeError::MissingFxRate(item.currency.clone(), currency.to_string());
+        let rate = fx
+            .and_then(|fx| fx.convert(item.rate, &item.currency, currency))
+            .ok_or_else(missing)?;
+        item.description = format!(
+            "{} ({}/h)",
+            item.description,
+            format_money(item.rate, &item.currency)
+        );
+        item.rate = rate;
+        item.currency = currency.to_string();
+    }
+    Ok(())
+}
+
+/// The one currency of `items`, or the configured one when there are none; an error when
+/// they are billed in several, which [`convert_items`] has to bring into one first.
+pub fn items_currency<'a>(items: &'a [LineItem], config: &'a InvoiceConfig) -> Result<&'a str> {
+    let currencies: BTreeSet<&str> = items.iter().map(|item| item.currency.as_str()).collect();
+    match currencies.len() {
+        0 => Ok(config.currency()),
+        1 => Ok(currencies.into_iter().next().unwrap_or_default()),
+        _ => Err(WakalyzeError::MixedCurrencies(
+            currencies.into_iter().collect::<Vec<_>>().join(", "),
+        )),
+    }
+}
+
+/// `amount` with thousands separators and the decimals of `currency` (none for JPY, ...).
+pub fn format_amount(amount: f64, currency: &str) -> String {
+    let decimals = if ZERO_DECIMAL_CURRENCIES.contains(&currency.to_uppercase().as_str()) {
+        0
+    } else {
+        2
+    };
+    let text = format!("{:.*}", decimals, amount.abs());
+    let (whole, fraction) = text.split_once('.').unwrap_or((&text, ""));
+    let mut grouped = String::new();
+    for (i, digit) in whole.chars().enumerate() {
+        if i > 0 && (whole.len() - i).is_multiple_of(3) {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+    let sign = if amount < 0.0 && text.chars().any(|c| c != '0' && c != '.') {
+        "-"
+    } else {
+        ""
+    };
+    match fraction {
+        "" => format!("{sign}{grouped}"),
+        fraction => format!("{sign}{grouped}.{fraction}"),
+    }
+}
+
+/// `amount` and its currency code, e.g. `1,250.00 EUR`.
+pub fn format_money(amount: f64, currency: &str) -> String {
+    format!("{} {currency}", format_amount(amount, currency))
+}
+
+pub struct Invoice<'a> {
+    pub number: &'a str,
+    pub issued: NaiveDate,
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    pub items: &'a [LineItem],
+    /// Currency of every item and the total.
+    pub currency: &'a str,
+    pub config: &'a InvoiceConfig,
+}
+
+impl Invoice<'_> {
+    pub fn total(&self) -> f64 {
+        self.items.iter().map(LineItem::amount).sum()
+    }
+}
+
+fn items_table(items: &[LineItem], currency: &str, markup: Markup) -> String {
+    let money = |amount: f64| format_amount(amount, currency);
+    let mut lines = Vec::new();
+    match markup {
+        Markup::Markdown => {
+            lines.push("| Description | Hours | Rate | Amount |".to_string());
+            lines.push("|---|---:|---:|---:|".to_string());
+            for item in items {
+                lines.push(format!(
+                    "| {} | {:.2} | {} | {} |",
+                    item.description.replace('|', "\\|"),
+                    item.hours(),
+                    money(item.rate),
+                    money(item.amount())
+                ));
+            }
+        }
+        Markup::Html => {
+            lines.push("<table>".to_string());
+            lines.push(
+                "<tr><th>Description</th><th>Hours</th><th>Rate</th><th>Amount</th></tr>"
+                    .to_string(),
+            );
+            for item in items {
+                lines.push(format!(
+                    "<tr><td>{}</td><td>{:.2}</td><td>{}</td><td>{}</td></tr>",
+                    xml_escape(&item.description),
+                    item.hours(),
+                    money(item.rate),
+                    money(item.amount())
+                ));
+            }
+            lines.push("</table>".to_string());
+        }
+    }
+    lines.join("\n")
+}
+
+/// Fill the `{{number}}`, `{{date}}`, `{{period}}`, `{{from}}`, `{{client}}`, `{{items}}`,
+/// `{{total}}`, and `{{currency}}` placeholders of `template`.
+pub fn render_invoice(template: &str, invoice: &Invoice, markup: Markup) -> String {
+    let escape = |text: &str| match markup {
+        Markup::Markdown => text.to_string(),
+        Markup::Html => xml_escape(text),
+    };
+    let block = |lines: &[String]| {
+        let lines: Vec<String> = lines.iter().map(|line| escape(line)).collect();
+        match markup {
+            Markup::Markdown => lines.join("  \n"),
+            Markup::Html => lines.join("<br>\n"),
+        }
+    };
+    let currency = invoice.currency;
+    template
+        .replace("{{number}}", &escape(invoice.number))
+        .replace("{{date}}", &invoice.issued.to_string())
+        .replace(
+            "{{period}}",
+            &format!("{} – {}", invoice.start, invoice.end),
+        )
+        .replace("{{from}}", &block(&invoice.config.from))
+        .replace("{{client}}", &block(&invoice.config.client))
+        .replace("{{items}}", &items_table(invoice.items, currency, markup))
+        .replace(
+            "{{total}}",
+            &escape(&format_money(invoice.total(), currency)),
+        )
+        .replace("{{currency}}", &escape(currency))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Session;
+    use crate::rounding::RoundingMode;
+
+    fn config() -> InvoiceConfig {
+        serde_json::from_str(
+            r#"{
+                "currency": "EUR",
+                "rate": 100,
+                "projects": {
+                    "api": { "rate": 120 },
+                    "internal": { "billable": false },
+                    "mobile": { "rate": 15000, "currency": "JPY" }
+                },
+                "client": ["Client X GmbH", "Hauptstr. 1 & 2"]
+            }"#,
+        )
+        .unwrap()
+    }
+
+    fn days() -> Vec<DaySessions> {
+        let session = |seconds: i64, project: &str| Session {
+            seconds,
+            project: Some(project.into()),
+            ..Default::default()
+        };
+        vec![
+            DaySessions {
+                date: NaiveDate::from_ymd_opt(2026, 2, 2).unwrap(),
+                sessions: vec![session(3000, "api"), session(1800, "internal")],
+            },
+            DaySessions {
+                date: NaiveDate::from_ymd_opt(2026, 2, 3).unwrap(),
+                sessions: vec![session(1200, "api"), session(1800, "web")],
+            },
+        ]
+    }
+
+    #[test]
+    fn items_per_project_skip_non_billable_and_use_rates() {
+        let rounding = Rounding::new(15 * 60, RoundingMode::Up);
+        let items = line_items(&days(), &config(), LineItems::Project, Some(&rounding));
+        assert_eq!(
+            items,
+            vec![
+                LineItem {
+                    description: "api".into(),
+                    seconds: 4500,
+                    rate: 120.0,
+                    currency: "EUR".into(),
+                },
+                LineItem {
+                    description: "web".into(),
+                    seconds: 1800,
+                    rate: 100.0,
+                    currency: "EUR".into(),
+                },
+            ]
+        );
+        assert_eq!(items[0].amount(), 150.0);
+    }
+
+    #[test]
+    fn rate_overrides_every_project() {
+        let config = config().with_rate(90.0, Some("USD"));
+        let items = line_items(&days(), &config, LineItems::Project, None);
+        let priced: Vec<_> = items
+            .iter()
+            .map(|item| (item.description.as_str(), item.rate, item.currency.as_str()))
+            .collect();
+        assert_eq!(priced, [("api", 90.0, "USD"), ("web", 90.0, "USD")]);
+    }
+
+    #[test]
+    fn items_per_day_rounded_separately() {
+        let rounding = Rounding::new(15 * 60, RoundingMode::Up);
+        let items = line_items(&days(), &config(), LineItems::Day, Some(&rounding));
+        let described: Vec<(&str, i64)> = items
+            .iter()
+            .map(|i| (i.description.as_str(), i.seconds))
+            .collect();
+        assert_eq!(
+            described,
+            vec![
+                ("2026-02-02 api", 3600),
+                ("2026-02-03 api", 1800),
+                ("2026-02-03 web", 1800),
+            ]
+        );
+    }
+
+    fn render(markup: Markup) -> String {
+        let config = config();
+        let items = line_items(&days(), &config, LineItems::Project, None);
+        let date = |d| NaiveDate::from_ymd_opt(2026, 2, d).unwrap();
+        let invoice = Invoice {
+            number: "INV-7",
+            issued: date(28),
+            start: date(1),
+            end: date(28),
+            items: &items,
+            currency: "EUR",
+            config: &config,
+        };
+        render_invoice(markup.default_template(), &invoice, markup)
+    }
+
+    #[test]
+    fn markdown_invoice() {
+        let text = render(Markup::Markdown);
+        assert!(text.starts_with("# Invoice INV-7\n"));
+        assert!(text.contains("Period: 2026-02-01 – 2026-02-28"));
+        assert!(text.contains("Client X GmbH  \nHauptstr. 1 & 2"));
+        assert!(text.contains("| api | 1.17 | 120.00 | 140.00 |"));
+        assert!(text.contains("**Total: 190.00 EUR**"));
+    }
+
+    #[test]
+    fn html_invoice_escapes() {
+        let text = render(Markup::Html);
+        assert!(text.contains("<p>Client X GmbH<br>\nHauptstr. 1 &amp; 2</p>"));
+        assert!(text.contains("<tr><td>web</td><td>0.50</td><td>100.00</td><td>50.00</td></tr>"));
+    }
+
+    #[test]
+    fn items_in_other_currencies_are_converted() {
+        let mut days = days();
+        days[0].sessions.push(Session {
+            seconds: 7200,
+            project: Some("mobile".into()),
+            ..Default::default()
+        });
+        let config = config();
+        let mut items = line_items(&days, &config, LineItems::Project, None);
+        assert!(matches!(
+            items_currency(&items, &config),
+            Err(WakalyzeError::MixedCurrencies(_))
+        ));
+        assert!(matches!(
+            convert_items(&mut items.clone(), "EUR", None),
+            Err(WakalyzeError::MissingFxRate(_, _))
+        ));
+
+        let fx: FxRates =
+            serde_json::from_str(r#"{"amount": 1.0, "base": "EUR", "rates": {"JPY": 150.0}}"#)
+                .unwrap();
+        convert_items(&mut items, "EUR", Some(&fx)).unwrap();
+        assert_eq!(items_currency(&items, &config).unwrap(), "EUR");
+        assert_eq!(items[1].description, "mobile (15,000 JPY/h)");
+        assert_eq!(items[1].rate, 100.0);
+        assert_eq!(fx.convert(100.0, "EUR", "usd"), None);
+    }
+
+    #[test]
+    fn amounts_follow_the_currency() {
+        assert_eq!(format_money(1234567.891, "EUR"), "1,234,567.89 EUR");
+        assert_eq!(format_money(1234567.891, "JPY"), "1,234,568 JPY");
+        assert_eq!(format_amount(999.999, "USD"), "1,000.00");
+        assert_eq!(format_amount(-1500.0, "USD"), "-1,500.00");
+        assert_eq!(format_amount(-0.001, "USD"), "0.00");
+    }
+}
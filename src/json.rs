@@ -0,0 +1,449 @@
+use chrono::NaiveDate;
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::analysis::Report;
+use crate::core::Session;
+use crate::error::Result;
+use crate::summary::PeriodSummary;
+
+/// Version of the JSON/NDJSON output structure.
+///
+/// Bump when a field is removed, renamed, or changes meaning. Adding fields is not a breaking
+/// change and keeps the version.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Top-level document of `--format json`, also piped to plugins.
+#[derive(Debug, Clone, Serialize)]
+pub struct JsonReport<'a> {
+    pub schema_version: u32,
+    pub label: &'a str,
+    #[serde(flatten)]
+    pub report: &'a Report,
+}
+
+impl<'a> JsonReport<'a> {
+    pub fn new(label: &'a str, report: &'a Report) -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION,
+            label,
+            report,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct SessionRecord<'a> {
+    schema_version: u32,
+    r#type: &'static str,
+    date: NaiveDate,
+    #[serde(flatten)]
+    session: &'a Session,
+}
+
+#[derive(Serialize)]
+struct SummaryRecord<'a> {
+    schema_version: u32,
+    r#type: &'static str,
+    label: &'a str,
+    start: NaiveDate,
+    end: NaiveDate,
+    max_gap_seconds: i64,
+    estimator: &'a str,
+    summary: &'a PeriodSummary,
+}
+
+pub fn to_json(label: &str, report: &Report) -> Result<String> {
+    Ok(serde_json::to_string_pretty(&JsonReport::new(
+        label, report,
+    ))?)
+}
+
+/// One `session` record per line, followed by a single `summary` record.
+pub fn to_ndjson_lines(label: &str, report: &Report) -> Result<Vec<String>> {
+    let mut lines = Vec::new();
+    for day in &report.days {
+        for session in &day.sessions {
+            lines.push(serde_json::to_string(&SessionRecord {
+                schema_version: SCHEMA_VERSION,
+                r#type: "session",
+                date: day.date,
+                session,
+            })?);
+        }
+    }
+    lines.push(serde_json::to_string(&SummaryRecord {
+        schema_version: SCHEMA_VERSION,
+        r#type: "summary",
+        label,
+        start: report.start,
+        end: report.end,
+        max_gap_seconds: report.max_gap_seconds,
+        estimator: &report.estimator,
+        summary: &report.summary,
+    })?);
+    Ok(lines)
+}
+
+fn session_schema() -> Value {
+    json!({
+        "type": "object",
+        "required": [
+            "start", "start_iso", "start_local", "end", "end_iso", "end_local", "seconds",
+            "heartbeats", "project", "language", "languages", "editor", "branch",
+            "operating_system", "category", "note", "tags", "manual"
+        ],
+        "properties": {
+            "start": { "type": "integer", "description": "Session start, epoch seconds" },
+            "start_iso": { "type": "string", "format": "date-time" },
+            "start_local": { "type": "string", "format": "date-time", "description": "Session start with the local UTC offset" },
+            "end": { "type": "integer", "description": "Last heartbeat of the session, epoch seconds" },
+            "end_iso": { "type": "string", "format": "date-time" },
+            "end_local": { "type": "string", "format": "date-time", "description": "Session end with the local UTC offset" },
+            "seconds": { "type": "integer", "description": "Estimated working time" },
+            "heartbeats": { "type": "integer", "description": "Heartbeats the session was built from; 0 for manual entries" },
+            "project": { "type": ["string", "null"] },
+            "language": { "type": ["string", "null"], "description": "Language with the most heartbeats" },
+            "languages": {
+                "type": "array",
+                "description": "Languages seen in the session, most heartbeats first",
+                "items": {
+                    "type": "object",
+                    "required": ["language", "heartbeats"],
+                    "properties": {
+                        "language": { "type": "string" },
+                        "heartbeats": { "type": "integer" }
+                    }
+                }
+            },
+            "editor": { "type": ["string", "null"], "description": "Editor with the most heartbeats" },
+            "branch": { "type": ["string", "null"], "description": "Branch with the most heartbeats" },
+            "operating_system": { "type": ["string", "null"], "description": "Operating system with the most heartbeats" },
+            "category": { "type": ["string", "null"], "description": "Category with the most heartbeats, lowercased" },
+            "note": { "type": ["string", "null"], "description": "Added with `wakalyze tag`" },
+            "tags": { "type": "array", "items": { "type": "string" } },
+            "manual": { "type": "boolean", "description": "Entered with `wakalyze add`, not from heartbeats" }
+        }
+    })
+}
+
+/// Session statistics shared by the period summary and each day summary.
+fn with_stats(mut schema: Value) -> Value {
+    let stats = json!({
+        "sessions": { "type": "integer", "description": "Number of sessions" },
+        "average_seconds": { "type": "integer" },
+        "median_seconds": { "type": "integer" },
+        "p90_seconds": { "type": "integer", "description": "Nearest-rank 90th percentile" },
+        "longest_seconds": { "type": "integer" }
+    });
+    if let (Some(properties), Value::Object(stats)) = (schema["properties"].as_object_mut(), stats)
+    {
+        properties.extend(stats);
+    }
+    schema
+}
+
+fn summary_schema() -> Value {
+    let day = with_stats(json!({
+        "type": "object",
+        "required": ["date", "seconds", "sessions", "switches", "focus", "dst_shift_seconds"],
+        "properties": {
+            "date": { "type": "string", "format": "date" },
+            "seconds": { "type": "integer" },
+            "switches": { "type": "integer", "description": "Project changes between consecutive sessions" },
+            "focus": { "type": "number", "description": "Focus score, 0-100" },
+            "dst_shift_seconds": {
+                "type": "integer",
+                "description": "Local clock change on the day: positive when clocks go forward, 0 without DST transition"
+            }
+        }
+    }));
+    with_stats(json!({
+        "type": "object",
+        "required": [
+            "seconds", "sessions", "average_switches", "average_focus", "days", "projects",
+            "categories", "weekdays"
+        ],
+        "properties": {
+            "seconds": { "type": "integer", "description": "Total for the period" },
+            "average_switches": { "type": "number", "description": "Mean switches per active day" },
+            "average_focus": { "type": "number", "description": "Mean focus score per active day" },
+            "days": { "type": "array", "items": day },
+            "projects": {
+                "type": "array",
+                "description": "Sorted by seconds, descending",
+                "items": {
+                    "type": "object",
+                    "required": ["project", "seconds", "percent"],
+                    "properties": {
+                        "project": { "type": ["string", "null"] },
+                        "seconds": { "type": "integer" },
+                        "percent": { "type": "number", "description": "Share of the total, 0-100" }
+                    }
+                }
+            },
+            "categories": {
+                "type": "array",
+                "description": "Time per session category (sessions without one count as coding), sorted by seconds",
+                "items": {
+                    "type": "object",
+                    "required": ["name", "seconds", "percent"],
+                    "properties": {
+                        "name": { "type": ["string", "null"] },
+                        "seconds": { "type": "integer" },
+                        "percent": { "type": "number", "description": "Share of the total, 0-100" }
+                    }
+                }
+            },
+            "weekdays": {
+                "type": "array",
+                "description": "Average per weekday over the period, idle days included; Monday first",
+                "items": {
+                    "type": "object",
+                    "required": ["weekday", "days", "average_seconds"],
+                    "properties": {
+                        "weekday": { "enum": ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"] },
+                        "days": { "type": "integer", "description": "Occurrences in the period" },
+                        "average_seconds": { "type": "integer" }
+                    }
+                }
+            }
+        }
+    }))
+}
+
+/// JSON Schema of the `--format json` document.
+pub fn json_schema() -> Value {
+    json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "$id": format!("https://github.com/masaishi/wakalyze/schema/v{SCHEMA_VERSION}.json"),
+        "title": "wakalyze report",
+        "type": "object",
+        "required": [
+            "schema_version", "label", "start", "end", "max_gap_seconds", "estimator", "days",
+            "summary", "machine_overlaps", "punch_card", "sanitized", "project_conflicts", "corrections",
+            "skip_weekends", "domains", "entities", "meeting_overlaps"
+        ],
+        "properties": {
+            "schema_version": { "const": SCHEMA_VERSION },
+            "label": { "type": "string" },
+            "start": { "type": "string", "format": "date" },
+            "end": { "type": "string", "format": "date" },
+            "max_gap_seconds": { "type": "integer" },
+            "estimator": { "type": "string", "description": "How session time was estimated from the heartbeats (`gap` by default)" },
+            "days": {
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "required": ["date", "sessions"],
+                    "properties": {
+                        "date": { "type": "string", "format": "date" },
+                        "sessions": { "type": "array", "items": session_schema() }
+                    }
+                }
+            },
+            "summary": summary_schema(),
+            "machine_overlaps": {
+                "type": "array",
+                "description": "Windows where several machines sent heartbeats at once",
+                "items": {
+                    "type": "object",
+                    "required": ["start", "start_iso", "end", "end_iso", "seconds", "machines"],
+                    "properties": {
+                        "start": { "type": "integer" },
+                        "start_iso": { "type": "string", "format": "date-time" },
+                        "end": { "type": "integer" },
+                        "end_iso": { "type": "string", "format": "date-time" },
+                        "seconds": { "type": "integer" },
+                        "machines": { "type": "array", "items": { "type": "string" } }
+                    }
+                }
+            },
+            "punch_card": {
+                "type": "array",
+                "description": "Heartbeat counts per local weekday (Monday first) and hour (0-23)",
+                "minItems": 7,
+                "maxItems": 7,
+                "items": {
+                    "type": "array",
+                    "minItems": 24,
+                    "maxItems": 24,
+                    "items": { "type": "integer" }
+                }
+            },
+            "sanitized": {
+                "type": "object",
+                "description": "Heartbeat timestamps fixed or dropped before analysis",
+                "required": ["normalized", "dropped"],
+                "properties": {
+                    "normalized": { "type": "integer", "description": "Scaled from milli-/micro-/nanoseconds" },
+                    "dropped": { "type": "integer", "description": "Before 2000 or in the future" },
+                    "not_finite": { "type": "integer", "description": "Dropped: not a number" },
+                    "too_early": { "type": "integer", "description": "Dropped: before 2000" },
+                    "future": { "type": "integer", "description": "Dropped: more than a day in the future" },
+                    "fetched": { "type": "integer", "description": "Heartbeats fetched, neighbouring days included" },
+                    "missing_time": { "type": "integer", "description": "Without a time, not counted" },
+                    "duplicates": { "type": "integer", "description": "Dropped: same time, project, and machine" },
+                    "empty_project": { "type": "integer", "description": "Without a project, counted as unknown" }
+                }
+            },
+            "project_conflicts": {
+                "type": "array",
+                "description": "Timestamps where heartbeats disagreed on the project, and the project kept",
+                "items": {
+                    "type": "object",
+                    "required": ["time", "time_iso", "projects", "chosen"],
+                    "properties": {
+                        "time": { "type": "integer" },
+                        "time_iso": { "type": "string", "format": "date-time" },
+                        "projects": { "type": "array", "items": { "type": ["string", "null"] } },
+                        "chosen": { "type": ["string", "null"] }
+                    }
+                }
+            },
+            "corrections": {
+                "type": "array",
+                "description": "Rules from corrections.json that changed the report, in order",
+                "items": {
+                    "type": "object",
+                    "required": ["description", "sessions", "seconds"],
+                    "properties": {
+                        "description": { "type": "string" },
+                        "sessions": { "type": "integer", "description": "Sessions reassigned, dropped, or trimmed" },
+                        "seconds": { "type": "integer", "description": "Time reassigned, dropped, or trimmed" }
+                    }
+                }
+            },
+            "skip_weekends": { "type": "boolean", "description": "Saturdays and Sundays were left out" },
+            "domains": {
+                "type": "array",
+                "description": "Time on `browsing` heartbeats per domain, not counted in the sessions",
+                "items": {
+                    "type": "object",
+                    "required": ["domain", "seconds", "percent"],
+                    "properties": {
+                        "domain": { "type": ["string", "null"] },
+                        "seconds": { "type": "integer" },
+                        "percent": { "type": "number", "description": "Share of the browsing total" }
+                    }
+                }
+            },
+            "entities": {
+                "type": "array",
+                "description": "Time per file with `--entities`, else empty",
+                "items": {
+                    "type": "object",
+                    "required": ["entity", "project", "seconds", "percent"],
+                    "properties": {
+                        "entity": { "type": ["string", "null"] },
+                        "project": { "type": ["string", "null"], "description": "Project of the file's first heartbeat" },
+                        "seconds": { "type": "integer" },
+                        "percent": { "type": "number", "description": "Share of the time of all files" }
+                    }
+                }
+            },
+            "meeting_overlaps": {
+                "type": "array",
+                "description": "Session time per day during `--meetings` calendar events, before any exclusion",
+                "items": {
+                    "type": "object",
+                    "required": ["date", "seconds"],
+                    "properties": {
+                        "date": { "type": "string", "format": "date" },
+                        "seconds": { "type": "integer" }
+                    }
+                }
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::DaySessions;
+    use crate::summary::summarize;
+
+    fn report() -> Report {
+        let date = NaiveDate::from_ymd_opt(2026, 2, 1).unwrap();
+        let days = vec![DaySessions {
+            date,
+            sessions: vec![
+                Session {
+                    start: 100,
+                    end: 700,
+                    seconds: 600,
+                    project: Some("foo".into()),
+                    ..Default::default()
+                },
+                Session {
+                    start: 2000,
+                    end: 2300,
+                    seconds: 300,
+                    project: None,
+                    ..Default::default()
+                },
+            ],
+        }];
+        Report {
+            start: date,
+            end: date,
+            max_gap_seconds: 900,
+            estimator: "gap".to_string(),
+            summary: summarize(&days),
+            days,
+            machine_overlaps: vec![],
+            punch_card: Default::default(),
+            sanitized: Default::default(),
+            project_conflicts: vec![],
+            corrections: vec![],
+            skip_weekends: false,
+            domains: vec![],
+            entities: vec![],
+            meeting_overlaps: vec![],
+        }
+    }
+
+    #[test]
+    fn to_json_has_schema_version() {
+        let value: Value = serde_json::from_str(&to_json("2026/02", &report()).unwrap()).unwrap();
+        assert_eq!(value["schema_version"], SCHEMA_VERSION);
+        assert_eq!(value["label"], "2026/02");
+        assert_eq!(value["days"][0]["sessions"][0]["project"], "foo");
+        assert_eq!(value["summary"]["seconds"], 900);
+    }
+
+    #[test]
+    fn to_ndjson_lines_sessions_then_summary() {
+        let lines = to_ndjson_lines("2026/02", &report()).unwrap();
+        assert_eq!(lines.len(), 3);
+        let first: Value = serde_json::from_str(&lines[0]).unwrap();
+        assert_eq!(first["type"], "session");
+        assert_eq!(first["date"], "2026-02-01");
+        assert_eq!(first["seconds"], 600);
+        assert_eq!(first["schema_version"], SCHEMA_VERSION);
+        let last: Value = serde_json::from_str(&lines[2]).unwrap();
+        assert_eq!(last["type"], "summary");
+        assert_eq!(last["summary"]["seconds"], 900);
+    }
+
+    #[test]
+    fn json_schema_lists_all_top_level_fields() {
+        let schema = json_schema();
+        let value: Value = serde_json::from_str(&to_json("x", &report()).unwrap()).unwrap();
+        let properties = schema["properties"].as_object().unwrap();
+        for key in value.as_object().unwrap().keys() {
+            assert!(properties.contains_key(key), "schema is missing {key}");
+        }
+        let session_properties = schema["properties"]["days"]["items"]["properties"]["sessions"]
+            ["items"]["properties"]
+            .as_object()
+            .unwrap();
+        for key in value["days"][0]["sessions"][0].as_object().unwrap().keys() {
+            assert!(
+                session_properties.contains_key(key),
+                "schema is missing session.{key}"
+            );
+        }
+    }
+}
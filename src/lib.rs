@@ -0,0 +1,54 @@
+pub mod analysis;
+pub mod annotations;
+pub mod archive;
+pub mod billing;
+pub mod browsing;
+pub mod cache;
+pub mod categories;
+pub mod cli;
+pub mod client;
+pub mod clients;
+pub mod compare;
+pub mod config;
+pub mod conflicts;
+pub mod core;
+pub mod corrections;
+pub mod digest;
+pub mod durations;
+pub mod encryption;
+pub mod entities;
+pub mod error;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod format;
+pub mod goals;
+pub mod harvest;
+pub mod i18n;
+pub mod ignore;
+pub mod input;
+pub mod inspect;
+pub mod invoice;
+pub mod json;
+pub mod machines;
+pub mod manual;
+pub mod meetings;
+pub mod plugin;
+pub mod projects;
+pub mod punchcard;
+pub mod rounding;
+pub mod sessions;
+pub mod slack;
+pub mod standup;
+pub mod stats;
+pub mod summaries;
+pub mod summary;
+pub mod theme;
+pub mod timesheet;
+pub mod timewarrior;
+pub mod tui;
+pub mod update;
+pub mod velocity;
+pub mod verify;
+pub mod warm;
+pub mod webhook;
+pub mod zone;
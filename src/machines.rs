@@ -0,0 +1,245 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::Serialize;
+
+use crate::core::{iso8601, RawHeartbeat};
+
+/// Wall-clock window in which two or more machines were sending heartbeats at once.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MachineOverlap {
+    pub start: i64,
+    pub end: i64,
+    pub machines: Vec<String>,
+}
+
+impl MachineOverlap {
+    pub fn seconds(&self) -> i64 {
+        self.end - self.start
+    }
+}
+
+impl Serialize for MachineOverlap {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+        let mut state = serializer.serialize_struct("MachineOverlap", 6)?;
+        state.serialize_field("start", &self.start)?;
+        state.serialize_field("start_iso", &iso8601(self.start))?;
+        state.serialize_field("end", &self.end)?;
+        state.serialize_field("end_iso", &iso8601(self.end))?;
+        state.serialize_field("seconds", &self.seconds())?;
+        state.serialize_field("machines", &self.machines)?;
+        state.end()
+    }
+}
+
+/// Activity intervals per machine: consecutive heartbeats within `max_gap` are joined.
+fn machine_intervals(heartbeats: &[RawHeartbeat], max_gap: i64) -> Vec<(i64, i64, &str)> {
+    let mut by_machine: BTreeMap<&str, Vec<i64>> = BTreeMap::new();
+    for hb in heartbeats {
+        if let (Some(time), Some(machine)) = (hb.time, hb.machine.as_deref()) {
+            by_machine.entry(machine).or_default().push(time as i64);
+        }
+    }
+
+    let mut intervals = Vec::new();
+    for (machine, mut times) in by_machine {
+        times.sort_unstable();
+        let mut start = times[0];
+        let mut end = times[0];
+        for &t in &times[1..] {
+            if t - end > max_gap {
+                intervals.push((start, end, machine));
+                start = t;
+            }
+            end = t;
+        }
+        intervals.push((start, end, machine));
+    }
+    intervals
+}
+
+/// Windows where heartbeats from different machines overlap.
+///
+/// Heartbeats without a machine are ignored. Overlapping windows are merged, so each
+/// returned window lists every machine active in it.
+pub fn detect_overlaps(heartbeats: &[RawHeartbeat], max_gap: i64) -> Vec<MachineOverlap> {
+    let mut intervals = machine_intervals(heartbeats, max_gap);
+    intervals.sort_unstable();
+
+    let mut overlaps: Vec<MachineOverlap> = Vec::new();
+    for (i, &(start, end, machine)) in intervals.iter().enumerate() {
+        for &(other_start, other_end, other) in &intervals[i + 1..] {
+            if other_start >= end {
+                break;
+            }
+            if other == machine {
+                continue;
+            }
+            let window_start = other_start.max(start);
+            let window_end = other_end.min(end);
+            if window_end <= window_start {
+                continue;
+            }
+            match overlaps
+                .iter_mut()
+                .find(|o| window_start < o.end && o.start < window_end)
+            {
+                Some(existing) => {
+                    existing.start = existing.start.min(window_start);
+                    existing.end = existing.end.max(window_end);
+                    for name in [machine, other] {
+                        if !existing.machines.iter().any(|m| m == name) {
+                            existing.machines.push(name.to_string());
+                        }
+                    }
+                }
+                None => overlaps.push(MachineOverlap {
+                    start: window_start,
+                    end: window_end,
+                    machines: vec![machine.to_string(), other.to_string()],
+                }),
+            }
+        }
+    }
+
+    for overlap in &mut overlaps {
+        overlap.machines.sort();
+    }
+    overlaps.sort_by_key(|o| o.start);
+    overlaps
+}
+
+/// Drop heartbeats inside each overlap window except those of its busiest machine.
+///
+/// The machine with the most heartbeats in the window is kept; ties go to the first name.
+/// Heartbeats without a machine are always kept.
+pub fn dedupe_overlaps(heartbeats: &mut Vec<RawHeartbeat>, overlaps: &[MachineOverlap]) {
+    let in_window = |hb: &RawHeartbeat, overlap: &MachineOverlap| {
+        hb.time
+            .is_some_and(|t| t as i64 >= overlap.start && t as i64 <= overlap.end)
+    };
+
+    let mut dropped: Vec<(&MachineOverlap, BTreeSet<String>)> = Vec::new();
+    for overlap in overlaps {
+        let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+        for hb in heartbeats.iter().filter(|hb| in_window(hb, overlap)) {
+            if let Some(machine) = hb.machine.as_deref() {
+                *counts.entry(machine).or_default() += 1;
+            }
+        }
+        let keep = counts
+            .iter()
+            .rev()
+            .max_by_key(|(_, count)| **count)
+            .map(|(machine, _)| *machine);
+        let others = counts
+            .keys()
+            .filter(|m| Some(**m) != keep)
+            .map(|m| m.to_string())
+            .collect();
+        dropped.push((overlap, others));
+    }
+
+    heartbeats.retain(|hb| {
+        !dropped.iter().any(|(overlap, others)| {
+            in_window(hb, overlap) && hb.machine.as_ref().is_some_and(|m| others.contains(m))
+        })
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hb(time: i64, machine: &str) -> RawHeartbeat {
+        RawHeartbeat {
+            time: Some(time as f64),
+            project: Some("p".into()),
+            machine: Some(machine.into()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn no_overlap_for_single_machine() {
+        let hbs = vec![hb(0, "desk"), hb(60, "desk"), hb(120, "desk")];
+        assert!(detect_overlaps(&hbs, 900).is_empty());
+    }
+
+    #[test]
+    fn detects_window_where_machines_overlap() {
+        let hbs = vec![
+            hb(0, "desk"),
+            hb(600, "desk"),
+            hb(1200, "desk"),
+            hb(300, "laptop"),
+            hb(900, "laptop"),
+            hb(3000, "laptop"),
+        ];
+        let overlaps = detect_overlaps(&hbs, 900);
+        assert_eq!(
+            overlaps,
+            vec![MachineOverlap {
+                start: 300,
+                end: 900,
+                machines: vec!["desk".into(), "laptop".into()],
+            }]
+        );
+        assert_eq!(overlaps[0].seconds(), 600);
+    }
+
+    #[test]
+    fn sequential_machines_do_not_overlap() {
+        let hbs = vec![
+            hb(0, "desk"),
+            hb(600, "desk"),
+            hb(700, "laptop"),
+            hb(1200, "laptop"),
+        ];
+        assert!(detect_overlaps(&hbs, 900).is_empty());
+    }
+
+    #[test]
+    fn ignores_heartbeats_without_machine() {
+        let mut anonymous = hb(300, "x");
+        anonymous.machine = None;
+        let hbs = vec![hb(0, "desk"), hb(600, "desk"), anonymous];
+        assert!(detect_overlaps(&hbs, 900).is_empty());
+    }
+
+    #[test]
+    fn dedupe_keeps_busiest_machine() {
+        let mut hbs = vec![
+            hb(0, "desk"),
+            hb(200, "desk"),
+            hb(300, "desk"),
+            hb(400, "desk"),
+            hb(600, "desk"),
+            hb(100, "laptop"),
+            hb(500, "laptop"),
+            hb(2000, "laptop"),
+        ];
+        let overlaps = detect_overlaps(&hbs, 900);
+        dedupe_overlaps(&mut hbs, &overlaps);
+        let laptop: Vec<_> = hbs
+            .iter()
+            .filter(|hb| hb.machine.as_deref() == Some("laptop"))
+            .map(|hb| hb.time.unwrap() as i64)
+            .collect();
+        assert_eq!(laptop, vec![2000]);
+        assert_eq!(hbs.len(), 6);
+    }
+
+    #[test]
+    fn overlap_serializes_iso_times() {
+        let overlap = MachineOverlap {
+            start: 0,
+            end: 60,
+            machines: vec!["a".into(), "b".into()],
+        };
+        let value = serde_json::to_value(&overlap).unwrap();
+        assert_eq!(value["start_iso"], "1970-01-01T00:00:00Z");
+        assert_eq!(value["seconds"], 60);
+        assert_eq!(value["machines"][1], "b");
+    }
+}
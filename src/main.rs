@@ -1,28 +1,89 @@
-mod cli;
-mod client;
-mod config;
-mod core;
-mod error;
-mod format;
-
 use std::process::ExitCode;
 
 use clap::Parser;
 
-use crate::cli::{Cli, Commands};
+use wakalyze::cli::{self, Cli, Commands};
+use wakalyze::config::{load_config, select_profile};
+use wakalyze::plugin::find_plugin;
+use wakalyze::zone::{parse_timezone, select_timezone};
 
 fn main() -> ExitCode {
-    // Preprocess argv: if first arg is not a known subcommand or flag, insert "analyze"
+    // Preprocess argv: if first arg is not a known subcommand or flag, insert "analyze".
+    // An unknown name with a matching `wakalyze-<name>` on PATH is dispatched to that plugin.
     let mut args: Vec<String> = std::env::args().collect();
-    if let Some(first) = args.get(1) {
+    let mut plugin = None;
+    // A leading `--profile <name>` belongs to whatever command follows it.
+    let cmd = match args.get(1).map(String::as_str) {
+        Some("--profile") => 3,
+        Some(arg) if arg.starts_with("--profile=") => 2,
+        _ => 1,
+    };
+    if let Some(first) = args.get(cmd) {
         if !matches!(
             first.as_str(),
-            "config" | "analyze" | "--help" | "-h" | "--version" | "-V"
+            "config"
+                | "cache"
+                | "goals"
+                | "analyze"
+                | "schema"
+                | "inspect"
+                | "heartbeats"
+                | "sessions"
+                | "tag"
+                | "add"
+                | "report"
+                | "invoice"
+                | "digest"
+                | "archive"
+                | "verify"
+                | "week"
+                | "heatmap"
+                | "compare"
+                | "velocity"
+                | "watch"
+                | "tui"
+                | "self-update"
+                | "--help"
+                | "-h"
+                | "--version"
+                | "-V"
         ) {
-            args.insert(1, "analyze".to_string());
+            plugin = find_plugin(first);
+            if plugin.is_some() {
+                args[cmd] = "analyze".to_string();
+            } else {
+                args.insert(cmd, "analyze".to_string());
+            }
         }
     }
 
+    // `week <date>` is short for `analyze --week-of <date>`.
+    if args.get(cmd).map(String::as_str) == Some("week") {
+        args.splice(
+            cmd..cmd + 1,
+            ["analyze".to_string(), "--week-of".to_string()],
+        );
+    }
+
+    // `heatmap <period>` is short for `analyze --view heatmap <period>`.
+    if args.get(cmd).map(String::as_str) == Some("heatmap") {
+        args.splice(
+            cmd..cmd + 1,
+            [
+                "analyze".to_string(),
+                "--view".to_string(),
+                "heatmap".to_string(),
+            ],
+        );
+    }
+
+    // `archive` takes a period positionally, so `archive verify` is a command of its own.
+    if args.get(cmd).map(String::as_str) == Some("archive")
+        && args.get(cmd + 1).map(String::as_str) == Some("verify")
+    {
+        args.splice(cmd..cmd + 2, ["archive-verify".to_string()]);
+    }
+
     let cli = match Cli::try_parse_from(&args) {
         Ok(c) => c,
         Err(e) => {
@@ -31,9 +92,47 @@ fn main() -> ExitCode {
         }
     };
 
+    // `config` edits profiles itself, including ones that do not exist yet.
+    if !matches!(cli.command, Commands::Config { .. }) {
+        if let Err(e) = select_profile(cli.profile.clone()) {
+            eprintln!("error: {e}");
+            return ExitCode::FAILURE;
+        }
+        let timezone = cli.timezone.clone().or_else(|| load_config().timezone);
+        match timezone.as_deref().map(parse_timezone).transpose() {
+            Ok(zone) => select_timezone(zone),
+            Err(e) => {
+                eprintln!("error: {e}");
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+
     let result = match cli.command {
-        Commands::Analyze(analyze_args) => cli::handle_analyze(analyze_args),
-        Commands::Config { action } => cli::handle_config(action),
+        Commands::Analyze(analyze_args) => match plugin {
+            Some(path) => cli::handle_plugin(&path, analyze_args),
+            None => cli::handle_analyze(analyze_args),
+        },
+        Commands::Config { action } => cli::handle_config(action, cli.profile.as_deref()),
+        Commands::Goals { action } => cli::handle_goals(action),
+        Commands::Cache { action } => cli::handle_cache(action),
+        Commands::Schema => cli::handle_schema(),
+        Commands::Inspect(inspect_args) => cli::handle_inspect(inspect_args),
+        Commands::Heartbeats(heartbeats_args) => cli::handle_heartbeats(heartbeats_args),
+        Commands::Sessions(sessions_args) => cli::handle_sessions(sessions_args),
+        Commands::Tag(tag_args) => cli::handle_tag(tag_args),
+        Commands::Add(add_args) => cli::handle_add(add_args),
+        Commands::Report(report_args) => cli::handle_report(report_args),
+        Commands::Invoice(invoice_args) => cli::handle_invoice(invoice_args),
+        Commands::Digest(digest_args) => cli::handle_digest(digest_args),
+        Commands::Archive(archive_args) => cli::handle_archive(archive_args),
+        Commands::ArchiveVerify(verify_args) => cli::handle_archive_verify(verify_args),
+        Commands::Verify(verify_args) => cli::handle_verify(verify_args),
+        Commands::Tui(tui_args) => cli::handle_tui(tui_args),
+        Commands::Compare(compare_args) => cli::handle_compare(compare_args),
+        Commands::Watch(watch_args) => cli::handle_watch(watch_args),
+        Commands::Velocity(velocity_args) => cli::handle_velocity(velocity_args),
+        Commands::SelfUpdate(update_args) => cli::handle_self_update(update_args),
     };
 
     match result {
@@ -0,0 +1,139 @@
+//! Time entered by hand (`wakalyze add`) for work that sends no heartbeats, such as calls.
+//!
+//! Entries live in a local state file and are merged into the analyzed days as sessions
+//! marked `manual`.
+
+use std::path::{Path, PathBuf};
+
+use chrono::{NaiveDate, NaiveTime};
+use serde::{Deserialize, Serialize};
+
+use crate::config::{load_state, save_state, state_dir};
+use crate::core::{local_timestamp, parse_clock, Session};
+use crate::error::{Result, WakalyzeError};
+
+pub fn manual_path() -> PathBuf {
+    state_dir().join("manual.json")
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManualEntry {
+    pub date: NaiveDate,
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+    pub project: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+impl ManualEntry {
+    /// The entry as a session on its date; `None` if its times do not exist locally.
+    pub fn to_session(&self) -> Option<Session> {
+        let start = local_timestamp(self.date, self.start)?;
+        let end = local_timestamp(self.date, self.end)?;
+        Some(Session {
+            start,
+            end,
+            seconds: end - start,
+            project: Some(self.project.as_str().into()),
+            note: self.note.clone(),
+            manual: true,
+            ..Default::default()
+        })
+    }
+}
+
+/// Start and end of an `HH:MM-HH:MM` range on one day.
+pub fn parse_time_range(value: &str) -> Result<(NaiveTime, NaiveTime)> {
+    let invalid = || WakalyzeError::InvalidTimeRange(value.to_string());
+    let (start, end) = value.split_once('-').ok_or_else(invalid)?;
+    let (start, end) = (parse_clock(start.trim())?, parse_clock(end.trim())?);
+    if end <= start {
+        return Err(invalid());
+    }
+    Ok((start, end))
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ManualEntries {
+    pub entries: Vec<ManualEntry>,
+}
+
+impl ManualEntries {
+    pub fn load(path: &Path) -> Result<Self> {
+        load_state(path)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        save_state(path, self)
+    }
+
+    /// Entries dated from `start` to `end`, inclusive.
+    pub fn between(&self, start: NaiveDate, end: NaiveDate) -> Vec<ManualEntry> {
+        self.entries
+            .iter()
+            .filter(|entry| entry.date >= start && entry.date <= end)
+            .cloned()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn time(hour: u32, min: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(hour, min, 0).unwrap()
+    }
+
+    #[test]
+    fn parse_time_range_requires_end_after_start() {
+        assert_eq!(
+            parse_time_range("13:00-14:30").unwrap(),
+            (time(13, 0), time(14, 30))
+        );
+        assert!(matches!(
+            parse_time_range("14:00-13:00"),
+            Err(WakalyzeError::InvalidTimeRange(_))
+        ));
+        assert!(parse_time_range("13:00").is_err());
+        assert!(parse_time_range("1pm-2pm").is_err());
+    }
+
+    #[test]
+    fn entry_becomes_manual_session() {
+        let entry = ManualEntry {
+            date: NaiveDate::from_ymd_opt(2026, 2, 3).unwrap(),
+            start: time(13, 0),
+            end: time(14, 0),
+            project: "clientx".into(),
+            note: Some("architecture call".into()),
+        };
+        let session = entry.to_session().unwrap();
+        assert_eq!(session.seconds, 3600);
+        assert_eq!(session.end - session.start, 3600);
+        assert_eq!(session.project.as_deref(), Some("clientx"));
+        assert_eq!(session.note.as_deref(), Some("architecture call"));
+        assert!(session.manual);
+    }
+
+    #[test]
+    fn between_selects_by_date() {
+        let entry = |day| ManualEntry {
+            date: NaiveDate::from_ymd_opt(2026, 2, day).unwrap(),
+            start: time(9, 0),
+            end: time(10, 0),
+            project: "p".into(),
+            note: None,
+        };
+        let entries = ManualEntries {
+            entries: vec![entry(1), entry(3), entry(5)],
+        };
+        let selected = entries.between(
+            NaiveDate::from_ymd_opt(2026, 2, 2).unwrap(),
+            NaiveDate::from_ymd_opt(2026, 2, 5).unwrap(),
+        );
+        assert_eq!(selected, vec![entry(3), entry(5)]);
+    }
+}
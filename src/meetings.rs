@@ -0,0 +1,301 @@
+//! Meetings from an iCalendar (.ics) file, to tell coding time from time nominally spent in a
+//! call while the editor stayed open.
+//!
+//! Only single events are read: recurrence rules are not expanded, and all-day events are
+//! skipped. Times with a `TZID` are taken as local time.
+
+use std::path::Path;
+
+use chrono::{NaiveDate, NaiveDateTime};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+use crate::core::{local_timestamp, DaySessions, Session};
+use crate::error::{Result, WakalyzeError};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Meeting {
+    pub start: i64,
+    pub end: i64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+}
+
+/// What to do with session time that overlaps a meeting.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum MeetingMode {
+    /// Keep it, and report how much of the total it is
+    #[default]
+    Report,
+    /// Take it off the sessions
+    Exclude,
+}
+
+/// Session time of one date that fell within meetings.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MeetingOverlap {
+    pub date: NaiveDate,
+    pub seconds: i64,
+}
+
+/// Content lines with folded continuations joined.
+fn unfold(text: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in text.lines() {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(rest), Some(last)) => last.push_str(rest),
+            _ => lines.push(line.to_string()),
+        }
+    }
+    lines
+}
+
+/// Epoch seconds of a `DTSTART`/`DTEND` value; `None` for dates without a time.
+fn parse_time(params: &str, value: &str) -> Option<i64> {
+    if params.contains("VALUE=DATE") && !params.contains("VALUE=DATE-TIME") {
+        return None;
+    }
+    if let Some(utc) = value.strip_suffix('Z') {
+        let naive = NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").ok()?;
+        return Some(naive.and_utc().timestamp());
+    }
+    let naive = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok()?;
+    local_timestamp(naive.date(), naive.time())
+}
+
+/// Seconds of an ISO 8601 duration such as `PT1H30M` or `P1DT2H`.
+fn parse_duration(value: &str) -> Option<i64> {
+    let value = value.strip_prefix('P')?;
+    let mut seconds = 0;
+    let mut number = String::new();
+    for c in value.chars() {
+        match c {
+            '0'..='9' => number.push(c),
+            'T' => continue,
+            unit => {
+                let n: i64 = number.parse().ok()?;
+                number.clear();
+                seconds += n * match unit {
+                    'W' => 7 * 86_400,
+                    'D' => 86_400,
+                    'H' => 3_600,
+                    'M' => 60,
+                    'S' => 1,
+                    _ => return None,
+                };
+            }
+        }
+    }
+    number.is_empty().then_some(seconds)
+}
+
+/// Properties of a `VEVENT` read so far.
+#[derive(Default)]
+struct Event {
+    start: Option<i64>,
+    end: Option<i64>,
+    duration: Option<i64>,
+    summary: Option<String>,
+    cancelled: bool,
+}
+
+impl Event {
+    fn into_meeting(self) -> Option<Meeting> {
+        let start = self.start.filter(|_| !self.cancelled)?;
+        let end = self.end.or_else(|| Some(start + self.duration?))?;
+        (end > start).then_some(Meeting {
+            start,
+            end,
+            summary: self.summary,
+        })
+    }
+}
+
+/// The timed, non-cancelled events of a calendar, by start.
+pub fn parse_ics(text: &str) -> Result<Vec<Meeting>> {
+    let lines = unfold(text);
+    if !lines.iter().any(|line| line.trim() == "BEGIN:VCALENDAR") {
+        return Err(WakalyzeError::InvalidCalendar(
+            "no BEGIN:VCALENDAR".to_string(),
+        ));
+    }
+    let mut meetings = Vec::new();
+    let mut event: Option<Event> = None;
+    for line in &lines {
+        let line = line.trim_end();
+        if line == "BEGIN:VEVENT" {
+            event = Some(Event::default());
+            continue;
+        }
+        if line == "END:VEVENT" {
+            meetings.extend(event.take().and_then(Event::into_meeting));
+            continue;
+        }
+        let (Some(event), Some((name, value))) = (event.as_mut(), line.split_once(':')) else {
+            continue;
+        };
+        let (name, params) = name.split_once(';').unwrap_or((name, ""));
+        match name {
+            "DTSTART" => event.start = parse_time(params, value),
+            "DTEND" => event.end = parse_time(params, value),
+            "DURATION" => event.duration = parse_duration(value),
+            "SUMMARY" => event.summary = Some(value.replace("\\,", ",").replace("\\;", ";")),
+            "STATUS" => event.cancelled = value.eq_ignore_ascii_case("CANCELLED"),
+            _ => {}
+        }
+    }
+    meetings.sort_by_key(|meeting| meeting.start);
+    Ok(meetings)
+}
+
+pub fn load_meetings(path: &Path) -> Result<Vec<Meeting>> {
+    let text = std::fs::read_to_string(path)?;
+    parse_ics(&text).map_err(|e| WakalyzeError::InvalidCalendar(format!("{}: {e}", path.display())))
+}
+
+/// Meeting time as disjoint `(start, end)` spans, so overlapping meetings count once.
+fn merged_spans(meetings: &[Meeting]) -> Vec<(i64, i64)> {
+    let mut spans: Vec<(i64, i64)> = meetings.iter().map(|m| (m.start, m.end)).collect();
+    spans.sort_unstable();
+    let mut merged: Vec<(i64, i64)> = Vec::new();
+    for (start, end) in spans {
+        match merged.last_mut() {
+            Some(last) if start <= last.1 => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+    merged
+}
+
+/// Seconds of the session's span within `spans`, at most the time the session counts.
+fn overlap(session: &Session, spans: &[(i64, i64)]) -> i64 {
+    let within: i64 = spans
+        .iter()
+        .map(|&(start, end)| (end.min(session.end) - start.max(session.start)).max(0))
+        .sum();
+    within.min(session.seconds)
+}
+
+/// Session time during meetings, per date; with [`MeetingMode::Exclude`] it is also taken off
+/// the sessions, dropping those left empty. Manual sessions are left alone.
+pub fn apply_meetings(
+    days: &mut Vec<DaySessions>,
+    meetings: &[Meeting],
+    mode: MeetingMode,
+) -> Vec<MeetingOverlap> {
+    let spans = merged_spans(meetings);
+    let mut overlaps = Vec::new();
+    for day in days.iter_mut() {
+        let mut day_overlap = 0;
+        for session in day.sessions.iter_mut().filter(|s| !s.manual) {
+            let seconds = overlap(session, &spans);
+            day_overlap += seconds;
+            if mode == MeetingMode::Exclude {
+                session.seconds -= seconds;
+            }
+        }
+        if mode == MeetingMode::Exclude {
+            day.sessions.retain(|s| s.manual || s.seconds > 0);
+        }
+        if day_overlap > 0 {
+            overlaps.push(MeetingOverlap {
+                date: day.date,
+                seconds: day_overlap,
+            });
+        }
+    }
+    days.retain(|day| !day.sessions.is_empty());
+    overlaps
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveTime;
+
+    const CALENDAR: &str = "BEGIN:VCALENDAR\r\nVERSION:2.0\r\n\
+BEGIN:VEVENT\r\nDTSTART:20260202T090000Z\r\nDTEND:20260202T093000Z\r\nSUMMARY:Standup\\, daily\r\nEND:VEVENT\r\n\
+BEGIN:VEVENT\r\nDTSTART;TZID=Europe/Berlin:20260202T140000\r\nDURATION:PT1H\r\nSUMMARY:Plan\r\n ning\r\nEND:VEVENT\r\n\
+BEGIN:VEVENT\r\nDTSTART;VALUE=DATE:20260203\r\nDTEND;VALUE=DATE:20260204\r\nSUMMARY:Offsite\r\nEND:VEVENT\r\n\
+BEGIN:VEVENT\r\nDTSTART:20260202T160000Z\r\nDTEND:20260202T170000Z\r\nSTATUS:CANCELLED\r\nEND:VEVENT\r\n\
+END:VCALENDAR\r\n";
+
+    #[test]
+    fn parses_timed_events() {
+        let meetings = parse_ics(CALENDAR).unwrap();
+        let date = NaiveDate::from_ymd_opt(2026, 2, 2).unwrap();
+        let two_pm = local_timestamp(date, NaiveTime::from_hms_opt(14, 0, 0).unwrap()).unwrap();
+        let mut expected = vec![
+            Meeting {
+                start: 1_770_022_800,
+                end: 1_770_024_600,
+                summary: Some("Standup, daily".into()),
+            },
+            Meeting {
+                start: two_pm,
+                end: two_pm + 3600,
+                summary: Some("Planning".into()),
+            },
+        ];
+        expected.sort_by_key(|m| m.start);
+        assert_eq!(meetings, expected);
+        assert!(matches!(
+            parse_ics("BEGIN:VEVENT"),
+            Err(WakalyzeError::InvalidCalendar(_))
+        ));
+    }
+
+    #[test]
+    fn parses_durations() {
+        assert_eq!(parse_duration("PT1H30M"), Some(5400));
+        assert_eq!(parse_duration("P1DT2H"), Some(93_600));
+        assert_eq!(parse_duration("PT"), Some(0));
+        assert_eq!(parse_duration("1H"), None);
+        assert_eq!(parse_duration("PT5"), None);
+    }
+
+    fn days() -> Vec<DaySessions> {
+        let session = |start: i64, end: i64| Session {
+            start,
+            end,
+            seconds: end - start,
+            ..Default::default()
+        };
+        vec![DaySessions {
+            date: NaiveDate::from_ymd_opt(2026, 2, 2).unwrap(),
+            sessions: vec![session(0, 3600), session(5000, 5600)],
+        }]
+    }
+
+    #[test]
+    fn reports_or_excludes_overlaps_once() {
+        let meetings = vec![
+            Meeting {
+                start: 3000,
+                end: 5200,
+                summary: None,
+            },
+            Meeting {
+                start: 3300,
+                end: 5700,
+                summary: None,
+            },
+        ];
+        let mut reported = days();
+        let overlaps = apply_meetings(&mut reported, &meetings, MeetingMode::Report);
+        assert_eq!(
+            overlaps,
+            vec![MeetingOverlap {
+                date: reported[0].date,
+                seconds: 1200
+            }]
+        );
+        assert_eq!(reported, days());
+
+        let mut excluded = days();
+        apply_meetings(&mut excluded, &meetings, MeetingMode::Exclude);
+        assert_eq!(excluded[0].sessions.len(), 1);
+        assert_eq!(excluded[0].sessions[0].seconds, 3000);
+    }
+}
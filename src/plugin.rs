@@ -0,0 +1,172 @@
+use std::ffi::OsStr;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use crate::error::{Result, WakalyzeError};
+use crate::json::JsonReport;
+
+pub const PLUGIN_PREFIX: &str = "wakalyze-";
+
+pub fn is_plugin_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with('-')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+pub fn find_plugin(name: &str) -> Option<PathBuf> {
+    let path = std::env::var_os("PATH")?;
+    find_plugin_in(name, &path)
+}
+
+/// Look up `wakalyze-<name>` in the given PATH-style list, git-style.
+pub fn find_plugin_in(name: &str, path: &OsStr) -> Option<PathBuf> {
+    if !is_plugin_name(name) {
+        return None;
+    }
+    let file = format!("{PLUGIN_PREFIX}{name}{}", std::env::consts::EXE_SUFFIX);
+    std::env::split_paths(path)
+        .map(|dir| dir.join(&file))
+        .find(|candidate| is_executable(candidate))
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::metadata(path)
+        .map(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+/// Run a plugin with the report written to its stdin as a single JSON document.
+pub fn run_plugin(plugin: &Path, input: &JsonReport) -> Result<()> {
+    let name = plugin.display().to_string();
+    let failed = |reason: String| WakalyzeError::PluginFailed(name.clone(), reason);
+
+    let mut child = Command::new(plugin)
+        .stdin(Stdio::piped())
+        .spawn()
+        .map_err(|e| failed(e.to_string()))?;
+
+    let payload = serde_json::to_vec(input).map_err(|e| failed(e.to_string()))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // A plugin may exit without reading its input; that is not an error by itself.
+        match stdin.write_all(&payload) {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::BrokenPipe => {}
+            Err(e) => return Err(failed(e.to_string())),
+        }
+    }
+
+    let status = child.wait().map_err(|e| failed(e.to_string()))?;
+    if !status.success() {
+        return Err(failed(format!("exited with {status}")));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plugin_name_accepts_simple_names() {
+        assert!(is_plugin_name("csv"));
+        assert!(is_plugin_name("my_plugin-2"));
+    }
+
+    #[test]
+    fn plugin_name_rejects_paths_and_flags() {
+        assert!(!is_plugin_name(""));
+        assert!(!is_plugin_name("2026/02"));
+        assert!(!is_plugin_name("--help"));
+        assert!(!is_plugin_name("../evil"));
+    }
+
+    #[test]
+    fn find_plugin_in_missing() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(find_plugin_in("nothing", dir.path().as_os_str()).is_none());
+    }
+
+    #[cfg(unix)]
+    fn empty_report() -> crate::analysis::Report {
+        let date = chrono::NaiveDate::from_ymd_opt(2026, 2, 1).unwrap();
+        crate::analysis::Report {
+            start: date,
+            end: date,
+            max_gap_seconds: 900,
+            estimator: "gap".to_string(),
+            days: vec![],
+            summary: Default::default(),
+            machine_overlaps: vec![],
+            punch_card: Default::default(),
+            sanitized: Default::default(),
+            project_conflicts: vec![],
+            corrections: vec![],
+            skip_weekends: false,
+            domains: vec![],
+            entities: vec![],
+            meeting_overlaps: vec![],
+        }
+    }
+
+    #[cfg(unix)]
+    fn write_script(dir: &Path, name: &str, body: &str) -> PathBuf {
+        use std::os::unix::fs::PermissionsExt;
+        let path = dir.join(format!("{PLUGIN_PREFIX}{name}"));
+        std::fs::write(&path, format!("#!/bin/sh\n{body}\n")).unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        path
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn find_plugin_in_path_list() {
+        let empty = tempfile::tempdir().unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let script = write_script(dir.path(), "hello", "exit 0");
+        let path = std::env::join_paths([empty.path(), dir.path()]).unwrap();
+        assert_eq!(find_plugin_in("hello", &path), Some(script));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn find_plugin_in_skips_non_executable() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("wakalyze-plain"), "").unwrap();
+        assert!(find_plugin_in("plain", dir.path().as_os_str()).is_none());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn run_plugin_pipes_json() {
+        let dir = tempfile::tempdir().unwrap();
+        let out = dir.path().join("out.json");
+        let script = write_script(dir.path(), "dump", &format!("cat > '{}'", out.display()));
+        run_plugin(&script, &JsonReport::new("2026/02", &empty_report())).unwrap();
+
+        let value: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(out).unwrap()).unwrap();
+        assert_eq!(value["schema_version"], crate::json::SCHEMA_VERSION);
+        assert_eq!(value["label"], "2026/02");
+        assert_eq!(value["start"], "2026-02-01");
+        assert_eq!(value["summary"]["seconds"], 0);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn run_plugin_failure_status() {
+        let dir = tempfile::tempdir().unwrap();
+        let script = write_script(dir.path(), "fail", "exit 3");
+        let result = run_plugin(&script, &JsonReport::new("x", &empty_report()));
+        assert!(matches!(result, Err(WakalyzeError::PluginFailed(..))));
+    }
+}
@@ -0,0 +1,135 @@
+//! Renaming projects as heartbeats are read, e.g. to roll many repositories up per client.
+
+use std::collections::BTreeMap;
+
+use regex::Regex;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::ignore::glob_match;
+
+/// Projects matching the glob `pattern`, or `regex` if set, are renamed to `project`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MergeRule {
+    #[serde(rename = "match", default, skip_serializing_if = "String::is_empty")]
+    pub pattern: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub regex: Option<ProjectRegex>,
+    pub project: String,
+}
+
+impl MergeRule {
+    fn matches(&self, project: &str) -> bool {
+        match &self.regex {
+            Some(regex) => regex.0.is_match(project),
+            None => glob_match(&self.pattern, project),
+        }
+    }
+}
+
+/// A regular expression of a [`MergeRule`], compiled once and kept as its source in the
+/// config and archives.
+#[derive(Debug, Clone)]
+pub struct ProjectRegex(Regex);
+
+impl ProjectRegex {
+    pub fn new(pattern: &str) -> Result<Self, regex::Error> {
+        Regex::new(pattern).map(Self)
+    }
+}
+
+impl PartialEq for ProjectRegex {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.as_str() == other.0.as_str()
+    }
+}
+
+impl Eq for ProjectRegex {}
+
+impl Serialize for ProjectRegex {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.0.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for ProjectRegex {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let pattern = String::deserialize(deserializer)?;
+        ProjectRegex::new(&pattern).map_err(serde::de::Error::custom)
+    }
+}
+
+/// The `project_aliases` (exact names) and `project_rules` (globs) of the config.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProjectMap {
+    pub aliases: BTreeMap<String, String>,
+    pub rules: Vec<MergeRule>,
+}
+
+impl ProjectMap {
+    pub fn is_empty(&self) -> bool {
+        self.aliases.is_empty() && self.rules.is_empty()
+    }
+
+    /// The name `project` is reported under: an exact alias first, then the first matching
+    /// rule, else unchanged.
+    pub fn resolve<'a>(&'a self, project: &'a str) -> &'a str {
+        if let Some(alias) = self.aliases.get(project) {
+            return alias;
+        }
+        self.rules
+            .iter()
+            .find(|rule| rule.matches(project))
+            .map_or(project, |rule| &rule.project)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn aliases_before_rules_in_order() {
+        let map: ProjectMap = serde_json::from_str(
+            r#"{
+                "aliases": { "acme-legacy": "Legacy" },
+                "rules": [
+                    { "match": "acme-*", "project": "Acme" },
+                    { "match": "acme-web*", "project": "Never" }
+                ]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(map.resolve("acme-legacy"), "Legacy");
+        assert_eq!(map.resolve("acme-api"), "Acme");
+        assert_eq!(map.resolve("acme-web"), "Acme");
+        assert_eq!(map.resolve("wakalyze"), "wakalyze");
+    }
+
+    #[test]
+    fn regex_rules() {
+        let map: ProjectMap = serde_json::from_str(
+            r#"{
+                "aliases": {},
+                "rules": [
+                    { "regex": "^acme-(api|web)(-v\\d+)?$", "project": "Acme" },
+                    { "match": "acme-*", "project": "Acme other" }
+                ]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(map.resolve("acme-api-v2"), "Acme");
+        assert_eq!(map.resolve("acme-web"), "Acme");
+        assert_eq!(map.resolve("acme-docs"), "Acme other");
+
+        let json = serde_json::to_string(&map.rules[0]).unwrap();
+        assert_eq!(
+            json,
+            r#"{"regex":"^acme-(api|web)(-v\\d+)?$","project":"Acme"}"#
+        );
+        assert_eq!(
+            serde_json::from_str::<MergeRule>(&json).unwrap(),
+            map.rules[0]
+        );
+        assert!(serde_json::from_str::<MergeRule>(r#"{"regex":"(","project":"x"}"#).is_err());
+    }
+}
@@ -0,0 +1,69 @@
+use chrono::{Datelike, Timelike};
+use serde::Serialize;
+
+use crate::core::RawHeartbeat;
+use crate::zone;
+
+/// Heartbeat counts by local weekday (rows, Monday first) and hour of day (columns).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(transparent)]
+pub struct PunchCard {
+    pub counts: [[u32; 24]; 7],
+}
+
+impl PunchCard {
+    pub fn add(&mut self, timestamp: i64) {
+        if let Some(dt) = zone::at(timestamp).single() {
+            let weekday = dt.weekday().num_days_from_monday() as usize;
+            self.counts[weekday][dt.hour() as usize] += 1;
+        }
+    }
+
+    /// Busiest cell, for scaling intensities.
+    pub fn max(&self) -> u32 {
+        self.counts.iter().flatten().copied().max().unwrap_or(0)
+    }
+}
+
+pub fn punch_card<'a>(heartbeats: impl IntoIterator<Item = &'a RawHeartbeat>) -> PunchCard {
+    let mut card = PunchCard::default();
+    for time in heartbeats.into_iter().filter_map(|hb| hb.time) {
+        card.add(time as i64);
+    }
+    card
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Local, TimeZone};
+
+    fn hb_at(day: u32, hour: u32) -> RawHeartbeat {
+        let ts = Local
+            .with_ymd_and_hms(2026, 2, day, hour, 30, 0)
+            .unwrap()
+            .timestamp();
+        RawHeartbeat {
+            time: Some(ts as f64),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn counts_by_weekday_and_hour() {
+        // Feb 2, 2026 is a Monday, Feb 8 a Sunday
+        let heartbeats = vec![hb_at(2, 9), hb_at(2, 9), hb_at(9, 9), hb_at(8, 23)];
+        let card = punch_card(&heartbeats);
+        assert_eq!(card.counts[0][9], 3);
+        assert_eq!(card.counts[6][23], 1);
+        assert_eq!(card.max(), 3);
+        assert_eq!(card.counts.iter().flatten().sum::<u32>(), 4);
+    }
+
+    #[test]
+    fn serializes_as_nested_arrays() {
+        let value = serde_json::to_value(PunchCard::default()).unwrap();
+        assert_eq!(value.as_array().unwrap().len(), 7);
+        assert_eq!(value[0].as_array().unwrap().len(), 24);
+    }
+}
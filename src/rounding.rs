@@ -0,0 +1,167 @@
+use clap::ValueEnum;
+use serde::Serialize;
+use std::cmp::Reverse;
+
+use crate::core::DaySessions;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum RoundingMode {
+    /// Round up to the next increment
+    #[default]
+    Up,
+    /// Round to the nearest increment (halves round up)
+    Nearest,
+    /// Round down to the previous increment
+    Down,
+}
+
+/// Billing rounding of a duration to a fixed increment, e.g. up to the next 15 minutes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub struct Rounding {
+    pub increment_seconds: i64,
+    pub mode: RoundingMode,
+}
+
+impl Rounding {
+    pub fn new(increment_seconds: i64, mode: RoundingMode) -> Self {
+        Self {
+            increment_seconds,
+            mode,
+        }
+    }
+
+    /// `seconds` rounded to a multiple of the increment; a non-positive increment leaves it as is.
+    pub fn apply(&self, seconds: i64) -> i64 {
+        let step = self.increment_seconds;
+        if step <= 0 {
+            return seconds;
+        }
+        let units = match self.mode {
+            RoundingMode::Up => (seconds + step - 1).div_euclid(step),
+            RoundingMode::Nearest => (seconds + step / 2).div_euclid(step),
+            RoundingMode::Down => seconds.div_euclid(step),
+        };
+        units * step
+    }
+}
+
+/// What `--round` rounds.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum RoundingScope {
+    /// Every session on its own
+    #[default]
+    Session,
+    /// Each day's total, the difference taken up by its longest sessions
+    Day,
+}
+
+/// Round the sessions of `days`, or each day's total, to `rounding`'s increment.
+pub fn round_days(days: &mut [DaySessions], rounding: &Rounding, scope: RoundingScope) {
+    for day in days {
+        match scope {
+            RoundingScope::Session => {
+                for session in &mut day.sessions {
+                    session.seconds = rounding.apply(session.seconds);
+                }
+            }
+            RoundingScope::Day => {
+                let total: i64 = day.sessions.iter().map(|s| s.seconds).sum();
+                let mut difference = rounding.apply(total) - total;
+                let mut longest: Vec<_> = day.sessions.iter_mut().collect();
+                longest.sort_by_key(|s| Reverse(s.seconds));
+                for session in longest {
+                    if difference == 0 {
+                        break;
+                    }
+                    // Added time goes to the longest session; removed time may need several.
+                    let change = difference.max(-session.seconds);
+                    session.seconds += change;
+                    difference -= change;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const QUARTER: i64 = 15 * 60;
+
+    #[test]
+    fn rounds_to_increment() {
+        let up = Rounding::new(QUARTER, RoundingMode::Up);
+        assert_eq!(up.apply(0), 0);
+        assert_eq!(up.apply(1), QUARTER);
+        assert_eq!(up.apply(QUARTER), QUARTER);
+
+        let nearest = Rounding::new(QUARTER, RoundingMode::Nearest);
+        assert_eq!(nearest.apply(7 * 60 + 29), 0);
+        assert_eq!(nearest.apply(7 * 60 + 30), QUARTER);
+
+        let down = Rounding::new(QUARTER, RoundingMode::Down);
+        assert_eq!(down.apply(QUARTER * 2 - 1), QUARTER);
+    }
+
+    #[test]
+    fn rounds_sessions_or_day_totals() {
+        use crate::core::Session;
+        use chrono::NaiveDate;
+
+        let day = || DaySessions {
+            date: NaiveDate::from_ymd_opt(2026, 2, 2).unwrap(),
+            sessions: [10 * 60, 25 * 60, 2 * 60]
+                .into_iter()
+                .map(|seconds| Session {
+                    seconds,
+                    ..Default::default()
+                })
+                .collect(),
+        };
+        let seconds = |days: &[DaySessions]| -> Vec<i64> {
+            days[0].sessions.iter().map(|s| s.seconds / 60).collect()
+        };
+
+        let mut days = [day()];
+        round_days(
+            &mut days,
+            &Rounding::new(QUARTER, RoundingMode::Up),
+            RoundingScope::Session,
+        );
+        assert_eq!(seconds(&days), [15, 30, 15]);
+
+        let mut days = [day()];
+        round_days(
+            &mut days,
+            &Rounding::new(QUARTER, RoundingMode::Up),
+            RoundingScope::Day,
+        );
+        assert_eq!(seconds(&days), [10, 33, 2]);
+
+        // 37 minutes down to 30: the longest session gives up the 7.
+        let mut days = [day()];
+        let down = Rounding::new(30 * 60, RoundingMode::Down);
+        round_days(&mut days, &down, RoundingScope::Day);
+        assert_eq!(seconds(&days), [10, 18, 2]);
+
+        // 9 minutes down to 5 takes more than any one session has.
+        let mut days = [day()];
+        for session in &mut days[0].sessions {
+            session.seconds = 3 * 60;
+        }
+        round_days(
+            &mut days,
+            &Rounding::new(5 * 60, RoundingMode::Down),
+            RoundingScope::Day,
+        );
+        assert_eq!(seconds(&days), [0, 2, 3]);
+    }
+
+    #[test]
+    fn zero_increment_is_identity() {
+        assert_eq!(Rounding::new(0, RoundingMode::Up).apply(123), 123);
+    }
+}
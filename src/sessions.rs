@@ -0,0 +1,98 @@
+//! Stable session IDs, so scripts and other commands can refer to individual sessions.
+//!
+//! An ID is a hash of the user, the session start, and the project: it stays the same across
+//! runs as long as the heartbeats and the analysis settings (`--max-gap-minutes`,
+//! `--day-boundary`, ...) that decide where a session starts do not change.
+
+use chrono::NaiveDate;
+use serde::Serialize;
+
+use crate::core::{DaySessions, Session};
+
+/// Hex digits of an ID.
+pub const ID_LEN: usize = 12;
+
+/// 64-bit FNV-1a, which unlike `DefaultHasher` is the same on every build and platform.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+pub fn session_id(user: &str, session: &Session) -> String {
+    let key = format!(
+        "{user}\0{}\0{}",
+        session.start,
+        session.project.as_deref().unwrap_or("")
+    );
+    let hash = format!("{:016x}", fnv1a(key.as_bytes()));
+    hash[..ID_LEN].to_string()
+}
+
+/// A session with its ID and day, as listed by `wakalyze sessions`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SessionRow<'a> {
+    pub id: String,
+    pub date: NaiveDate,
+    #[serde(flatten)]
+    pub session: &'a Session,
+}
+
+pub fn session_rows<'a>(user: &str, days: &'a [DaySessions]) -> Vec<SessionRow<'a>> {
+    days.iter()
+        .flat_map(|day| {
+            day.sessions.iter().map(move |session| SessionRow {
+                id: session_id(user, session),
+                date: day.date,
+                session,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(start: i64, project: Option<&str>) -> Session {
+        Session {
+            start,
+            end: start + 600,
+            seconds: 600,
+            project: project.map(Into::into),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn ids_are_stable_and_distinguish_user_start_and_project() {
+        let id = session_id("me", &session(1_770_000_000, Some("api")));
+        assert_eq!(id.len(), ID_LEN);
+        assert!(id.chars().all(|c| c.is_ascii_hexdigit()));
+        assert_eq!(id, session_id("me", &session(1_770_000_000, Some("api"))));
+        assert_ne!(id, session_id("you", &session(1_770_000_000, Some("api"))));
+        assert_ne!(id, session_id("me", &session(1_770_000_001, Some("api"))));
+        assert_ne!(id, session_id("me", &session(1_770_000_000, Some("web"))));
+        assert_ne!(id, session_id("me", &session(1_770_000_000, None)));
+    }
+
+    #[test]
+    fn fnv1a_matches_reference() {
+        assert_eq!(fnv1a(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(fnv1a(b"a"), 0xaf63_dc4c_8601_ec8c);
+    }
+
+    #[test]
+    fn rows_carry_date_and_id() {
+        let days = vec![DaySessions {
+            date: NaiveDate::from_ymd_opt(2026, 2, 2).unwrap(),
+            sessions: vec![session(1_770_000_000, Some("api"))],
+        }];
+        let rows = session_rows("me", &days);
+        assert_eq!(rows.len(), 1);
+        let value = serde_json::to_value(&rows[0]).unwrap();
+        assert_eq!(value["id"], rows[0].id);
+        assert_eq!(value["date"], "2026-02-02");
+        assert_eq!(value["project"], "api");
+    }
+}
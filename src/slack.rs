@@ -0,0 +1,123 @@
+//! Period summaries posted to a Slack incoming webhook.
+
+use serde_json::json;
+
+use crate::analysis::Report;
+use crate::error::Result;
+use crate::format::format_duration;
+use crate::webhook::post_json;
+
+/// How many projects the message lists.
+pub const TOP_PROJECTS: usize = 5;
+
+/// Slack `mrkdwn` text: the period total, top projects, and progress towards `goal_seconds`.
+pub fn slack_text(label: &str, report: &Report, goal_seconds: Option<i64>) -> String {
+    let summary = &report.summary;
+    let mut lines = vec![format!(
+        "*wakalyze {label}*: {} across {} sessions",
+        format_duration(summary.seconds),
+        summary.stats.sessions
+    )];
+    if !summary.projects.is_empty() {
+        lines.push("Top projects:".to_string());
+        for total in summary.projects.iter().take(TOP_PROJECTS) {
+            lines.push(format!(
+                "• {} {} ({:.0}%)",
+                total.project.as_deref().unwrap_or("unknown"),
+                format_duration(total.seconds),
+                total.percent
+            ));
+        }
+    }
+    if let Some(goal) = goal_seconds.filter(|g| *g > 0) {
+        let percent = summary.seconds as f64 * 100.0 / goal as f64;
+        let remaining = goal - summary.seconds;
+        let status = if remaining > 0 {
+            format!("{} to go", format_duration(remaining))
+        } else {
+            "reached".to_string()
+        };
+        lines.push(format!(
+            "Goal: {} / {} ({percent:.0}%), {status}",
+            format_duration(summary.seconds),
+            format_duration(goal)
+        ));
+    }
+    lines.join("\n")
+}
+
+pub fn post_slack(webhook_url: &str, text: &str, timeout_secs: f64) -> Result<()> {
+    post_json(webhook_url, &json!({ "text": text }), &[], timeout_secs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{DaySessions, Session};
+    use crate::summary::summarize;
+    use chrono::NaiveDate;
+
+    fn report() -> Report {
+        let date = NaiveDate::from_ymd_opt(2026, 2, 6).unwrap();
+        let session = |seconds: i64, project: &str| Session {
+            seconds,
+            project: Some(project.into()),
+            ..Default::default()
+        };
+        let days = vec![DaySessions {
+            date,
+            sessions: vec![session(5400, "api"), session(1800, "web")],
+        }];
+        Report {
+            start: date,
+            end: date,
+            max_gap_seconds: 900,
+            estimator: "gap".to_string(),
+            summary: summarize(&days),
+            days,
+            machine_overlaps: vec![],
+            punch_card: Default::default(),
+            sanitized: Default::default(),
+            project_conflicts: vec![],
+            corrections: vec![],
+            skip_weekends: false,
+            domains: vec![],
+            entities: vec![],
+            meeting_overlaps: vec![],
+        }
+    }
+
+    #[test]
+    fn text_lists_total_projects_and_goal() {
+        let text = slack_text("2026/02 week 1", &report(), Some(4 * 3600));
+        assert_eq!(
+            text,
+            "*wakalyze 2026/02 week 1*: 2h00m across 2 sessions\n\
+             Top projects:\n\
+             • api 1h30m (75%)\n\
+             • web 0h30m (25%)\n\
+             Goal: 2h00m / 4h00m (50%), 2h00m to go"
+        );
+        assert!(slack_text("x", &report(), Some(3600)).ends_with("(200%), reached"));
+        assert!(!slack_text("x", &report(), None).contains("Goal"));
+    }
+
+    #[test]
+    fn post_sends_text_payload() {
+        let mut server = mockito::Server::new();
+        let mock = server
+            .mock("POST", "/hook")
+            .match_body(mockito::Matcher::Json(json!({ "text": "hi" })))
+            .with_status(200)
+            .create();
+        post_slack(&format!("{}/hook", server.url()), "hi", 5.0).unwrap();
+        mock.assert();
+    }
+
+    #[test]
+    fn post_fails_on_error_status() {
+        let mut server = mockito::Server::new();
+        server.mock("POST", "/hook").with_status(404).create();
+        assert!(post_slack(&format!("{}/hook", server.url()), "hi", 5.0).is_err());
+    }
+}
@@ -0,0 +1,128 @@
+//! `--format standup`: one terse bullet per day, ready to paste into a standup channel.
+
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use chrono::NaiveDate;
+
+use crate::core::DaySessions;
+use crate::format::format_duration;
+
+fn day_label(date: NaiveDate, today: NaiveDate) -> String {
+    if date == today {
+        "today".to_string()
+    } else if today.pred_opt() == Some(date) {
+        "yesterday".to_string()
+    } else {
+        date.format("%a %-m/%-d").to_string()
+    }
+}
+
+/// `- yesterday: 3h10m clientx-api (auth-refactor), 1h20m infra`: projects by time spent,
+/// with their branches (most time first) when `branches` is set and any were reported.
+pub fn standup_lines(days: &[DaySessions], today: NaiveDate, branches: bool) -> Vec<String> {
+    let mut lines = Vec::new();
+    for day in days {
+        let mut projects: BTreeMap<&str, (i64, BTreeMap<&Arc<str>, i64>)> = BTreeMap::new();
+        for session in &day.sessions {
+            let project = session.project.as_deref().unwrap_or("unknown");
+            let entry = projects.entry(project).or_default();
+            entry.0 += session.seconds;
+            if let Some(branch) = &session.branch {
+                *entry.1.entry(branch).or_default() += session.seconds;
+            }
+        }
+        let mut projects: Vec<_> = projects
+            .into_iter()
+            .filter(|(_, (seconds, _))| *seconds > 0)
+            .collect();
+        if projects.is_empty() {
+            continue;
+        }
+        projects.sort_by_key(|p| Reverse(p.1 .0));
+
+        let items: Vec<String> = projects
+            .into_iter()
+            .map(|(project, (seconds, project_branches))| {
+                let mut item = format!("{} {project}", format_duration(seconds));
+                if branches && !project_branches.is_empty() {
+                    let mut project_branches: Vec<_> = project_branches.into_iter().collect();
+                    project_branches.sort_by_key(|b| Reverse(b.1));
+                    let names: Vec<String> = project_branches
+                        .iter()
+                        .map(|(b, _)| b.to_string())
+                        .collect();
+                    item.push_str(&format!(" ({})", names.join(", ")));
+                }
+                item
+            })
+            .collect();
+        lines.push(format!(
+            "- {}: {}",
+            day_label(day.date, today),
+            items.join(", ")
+        ));
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Session;
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 2, day).unwrap()
+    }
+
+    fn session(seconds: i64, project: &str, branch: Option<&str>) -> Session {
+        Session {
+            seconds,
+            project: Some(project.into()),
+            branch: branch.map(Into::into),
+            ..Default::default()
+        }
+    }
+
+    fn days() -> Vec<DaySessions> {
+        vec![
+            DaySessions {
+                date: date(2),
+                sessions: vec![session(3600, "infra", None)],
+            },
+            DaySessions {
+                date: date(4),
+                sessions: vec![
+                    session(4800, "infra", None),
+                    session(7200, "clientx-api", Some("auth-refactor")),
+                    session(4200, "clientx-api", Some("main")),
+                ],
+            },
+            DaySessions {
+                date: date(5),
+                sessions: vec![session(0, "infra", None)],
+            },
+        ]
+    }
+
+    #[test]
+    fn bullets_per_day_by_time_spent() {
+        assert_eq!(
+            standup_lines(&days(), date(5), false),
+            vec![
+                "- Mon 2/2: 1h00m infra",
+                "- yesterday: 3h10m clientx-api, 1h20m infra",
+            ]
+        );
+    }
+
+    #[test]
+    fn branches_most_time_first() {
+        let lines = standup_lines(&days(), date(4), true);
+        assert_eq!(
+            lines[1],
+            "- today: 3h10m clientx-api (auth-refactor, main), 1h20m infra"
+        );
+    }
+}
@@ -0,0 +1,189 @@
+use serde::Serialize;
+
+use crate::core::Session;
+
+/// How a stretch of work was split into sessions.
+///
+/// `longest_seconds` is the maximum session length; median and p90 show the typical session
+/// without being skewed by a few marathon ones.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct SessionStats {
+    pub sessions: usize,
+    pub average_seconds: i64,
+    pub median_seconds: i64,
+    pub p90_seconds: i64,
+    pub longest_seconds: i64,
+}
+
+pub fn session_stats<'a>(sessions: impl IntoIterator<Item = &'a Session>) -> SessionStats {
+    let mut lengths: Vec<i64> = sessions.into_iter().map(|s| s.seconds).collect();
+    if lengths.is_empty() {
+        return SessionStats::default();
+    }
+    lengths.sort_unstable();
+    SessionStats {
+        sessions: lengths.len(),
+        average_seconds: lengths.iter().sum::<i64>() / lengths.len() as i64,
+        median_seconds: median(&lengths),
+        p90_seconds: percentile(&lengths, 90),
+        longest_seconds: *lengths.last().unwrap(),
+    }
+}
+
+/// Weights of the focus score:
+/// `100 × (share of time in sessions of at least deep_seconds) − switch_penalty × switches`,
+/// clamped to 0–100.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FocusFormula {
+    pub deep_seconds: i64,
+    pub switch_penalty: f64,
+}
+
+impl Default for FocusFormula {
+    fn default() -> Self {
+        Self {
+            deep_seconds: 45 * 60,
+            switch_penalty: 5.0,
+        }
+    }
+}
+
+/// Focus score (0–100) of one day's sessions; 0 for a day without tracked time.
+pub fn focus_score(sessions: &[Session], formula: &FocusFormula) -> f64 {
+    let total: i64 = sessions.iter().map(|s| s.seconds).sum();
+    if total <= 0 {
+        return 0.0;
+    }
+    let deep: i64 = sessions
+        .iter()
+        .filter(|s| s.seconds >= formula.deep_seconds)
+        .map(|s| s.seconds)
+        .sum();
+    let score = 100.0 * deep as f64 / total as f64
+        - formula.switch_penalty * count_switches(sessions) as f64;
+    score.clamp(0.0, 100.0)
+}
+
+/// Project changes between consecutive sessions, in session order.
+pub fn count_switches(sessions: &[Session]) -> usize {
+    sessions
+        .windows(2)
+        .filter(|pair| pair[0].project != pair[1].project)
+        .count()
+}
+
+/// Median of sorted values; the mean of the two middle values for an even count.
+pub fn median(sorted: &[i64]) -> i64 {
+    match sorted.len() {
+        0 => 0,
+        n if n % 2 == 1 => sorted[n / 2],
+        n => (sorted[n / 2 - 1] + sorted[n / 2]) / 2,
+    }
+}
+
+/// Nearest-rank percentile of sorted values.
+pub fn percentile(sorted: &[i64], p: u32) -> i64 {
+    if sorted.is_empty() {
+        return 0;
+    }
+    let rank = (p as usize * sorted.len()).div_ceil(100).max(1);
+    sorted[rank.min(sorted.len()) - 1]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(seconds: i64) -> Session {
+        Session {
+            start: 0,
+            end: seconds,
+            seconds,
+            project: None,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn session_stats_empty() {
+        assert_eq!(session_stats(&[]), SessionStats::default());
+    }
+
+    #[test]
+    fn session_stats_counts_average_longest() {
+        let sessions = vec![session(60), session(600), session(120)];
+        assert_eq!(
+            session_stats(&sessions),
+            SessionStats {
+                sessions: 3,
+                average_seconds: 260,
+                median_seconds: 120,
+                p90_seconds: 600,
+                longest_seconds: 600,
+            }
+        );
+    }
+
+    #[test]
+    fn count_switches_between_projects() {
+        let on = |project: &str| Session {
+            project: Some(project.into()),
+            ..session(60)
+        };
+        assert_eq!(count_switches(&[]), 0);
+        assert_eq!(count_switches(&[on("a"), on("a")]), 0);
+        assert_eq!(count_switches(&[on("a"), on("b"), on("a"), session(60)]), 3);
+    }
+
+    #[test]
+    fn focus_score_deep_share_minus_switches() {
+        let on = |project: &str, seconds: i64| Session {
+            project: Some(project.into()),
+            ..session(seconds)
+        };
+        let formula = FocusFormula::default();
+        assert_eq!(focus_score(&[], &formula), 0.0);
+        assert_eq!(focus_score(&[on("a", 3600)], &formula), 100.0);
+        // 3600 of 4800 seconds in deep sessions, one switch
+        let day = [on("a", 3600), on("b", 1200)];
+        assert_eq!(focus_score(&day, &formula), 70.0);
+        let lenient = FocusFormula {
+            deep_seconds: 10 * 60,
+            switch_penalty: 0.0,
+        };
+        assert_eq!(focus_score(&day, &lenient), 100.0);
+        let harsh = FocusFormula {
+            switch_penalty: 200.0,
+            ..formula
+        };
+        assert_eq!(focus_score(&day, &harsh), 0.0);
+    }
+
+    #[test]
+    fn median_even_and_odd() {
+        assert_eq!(median(&[]), 0);
+        assert_eq!(median(&[5]), 5);
+        assert_eq!(median(&[1, 3]), 2);
+        assert_eq!(median(&[1, 2, 10]), 2);
+    }
+
+    #[test]
+    fn percentile_nearest_rank() {
+        let values: Vec<i64> = (1..=10).collect();
+        assert_eq!(percentile(&values, 90), 9);
+        assert_eq!(percentile(&values, 100), 10);
+        assert_eq!(percentile(&values, 0), 1);
+        assert_eq!(percentile(&[], 90), 0);
+    }
+
+    #[test]
+    fn marathon_session_skews_average_not_median() {
+        let mut sessions: Vec<Session> = (0..9).map(|_| session(600)).collect();
+        sessions.push(session(36000));
+        let stats = session_stats(&sessions);
+        assert_eq!(stats.median_seconds, 600);
+        assert_eq!(stats.p90_seconds, 600);
+        assert_eq!(stats.average_seconds, 4140);
+        assert_eq!(stats.longest_seconds, 36000);
+    }
+}
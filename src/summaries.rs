@@ -0,0 +1,111 @@
+//! Time per project and day from the summaries endpoint, for `--source summaries`.
+//!
+//! One request covers the whole range, but the server reports no times of day, so the
+//! sessions built from it only carry totals.
+
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use chrono::{NaiveDate, NaiveTime};
+use serde::Deserialize;
+
+use crate::core::{local_timestamp, DaySessions, Session};
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct SummariesResponse {
+    #[serde(default)]
+    pub data: Vec<SummaryDay>,
+}
+
+/// One day of a summaries response.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct SummaryDay {
+    pub range: SummaryRange,
+    #[serde(default)]
+    pub projects: Vec<SummaryItem>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct SummaryRange {
+    pub date: NaiveDate,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct SummaryItem {
+    pub name: String,
+    pub total_seconds: f64,
+}
+
+/// One session per project and day, most time first and placed back to back from midnight,
+/// since the summaries have no times of day. Days of several servers are added up.
+pub fn summary_sessions(days: impl IntoIterator<Item = SummaryDay>) -> Vec<DaySessions> {
+    let mut totals: BTreeMap<NaiveDate, BTreeMap<String, i64>> = BTreeMap::new();
+    for day in days {
+        let projects = totals.entry(day.range.date).or_default();
+        for item in day.projects {
+            *projects.entry(item.name).or_default() += item.total_seconds.round() as i64;
+        }
+    }
+    totals
+        .into_iter()
+        .filter_map(|(date, projects)| {
+            let mut projects: Vec<(String, i64)> = projects
+                .into_iter()
+                .filter(|(_, seconds)| *seconds > 0)
+                .collect();
+            projects.sort_by_key(|(_, seconds)| Reverse(*seconds));
+            let mut start = local_timestamp(date, NaiveTime::MIN)?;
+            let sessions: Vec<Session> = projects
+                .into_iter()
+                .map(|(project, seconds)| {
+                    let session = Session {
+                        start,
+                        end: start + seconds,
+                        seconds,
+                        project: Some(Arc::from(project)),
+                        ..Default::default()
+                    };
+                    start += seconds;
+                    session
+                })
+                .collect();
+            (!sessions.is_empty()).then_some(DaySessions { date, sessions })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sessions_per_project_and_day() {
+        let response: SummariesResponse = serde_json::from_str(
+            r#"{"data":[
+                {"range":{"date":"2026-02-02","text":"Mon"},"grand_total":{"total_seconds":5400},
+                 "projects":[{"name":"web","total_seconds":1800.4},{"name":"api","total_seconds":3600}]},
+                {"range":{"date":"2026-02-03"},"projects":[]}
+            ]}"#,
+        )
+        .unwrap();
+        let other = SummaryDay {
+            range: SummaryRange {
+                date: NaiveDate::from_ymd_opt(2026, 2, 2).unwrap(),
+            },
+            projects: vec![SummaryItem {
+                name: "web".into(),
+                total_seconds: 600.0,
+            }],
+        };
+        let days = summary_sessions(response.data.into_iter().chain([other]));
+        assert_eq!(days.len(), 1);
+        let sessions: Vec<_> = days[0]
+            .sessions
+            .iter()
+            .map(|s| (s.project.as_deref().unwrap(), s.seconds))
+            .collect();
+        assert_eq!(sessions, [("api", 3600), ("web", 2400)]);
+        assert_eq!(days[0].sessions[1].start, days[0].sessions[0].end);
+    }
+}
@@ -0,0 +1,505 @@
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+
+use chrono::{Datelike, NaiveDate, Weekday};
+use clap::ValueEnum;
+use rayon::prelude::*;
+use serde::Serialize;
+
+use crate::categories::CODING;
+use crate::clients::{client_of, ClientRule};
+use crate::core::{dst_shift_seconds, iter_dates, DaySessions, Session};
+use crate::stats::{count_switches, focus_score, session_stats, FocusFormula, SessionStats};
+use crate::zone;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ProjectTotal {
+    pub project: Option<String>,
+    pub seconds: i64,
+    /// Share of the period total, 0–100.
+    pub percent: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DaySummary {
+    pub date: NaiveDate,
+    pub seconds: i64,
+    #[serde(flatten)]
+    pub stats: SessionStats,
+    /// Project changes between consecutive sessions.
+    pub switches: usize,
+    /// See [`FocusFormula`].
+    pub focus: f64,
+    /// Local clock change on this day, see [`dst_shift_seconds`].
+    pub dst_shift_seconds: i64,
+}
+
+/// Average time on one weekday over every occurrence of it in the period, idle days included.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct WeekdayAverage {
+    pub weekday: Weekday,
+    /// How many times the weekday occurs in the period.
+    pub days: usize,
+    pub average_seconds: i64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct PeriodSummary {
+    pub seconds: i64,
+    #[serde(flatten)]
+    pub stats: SessionStats,
+    /// Mean of the per-day `switches` over days with activity.
+    pub average_switches: f64,
+    /// Mean of the per-day `focus` over days with activity.
+    pub average_focus: f64,
+    pub days: Vec<DaySummary>,
+    pub projects: Vec<ProjectTotal>,
+    /// Time per session category, see [`GroupBy::Category`].
+    pub categories: Vec<GroupTotal>,
+    /// Monday first; only filled by [`summarize_period`].
+    pub weekdays: Vec<WeekdayAverage>,
+}
+
+pub fn percent_of(part: i64, total: i64) -> f64 {
+    if total <= 0 {
+        return 0.0;
+    }
+    part as f64 * 100.0 / total as f64
+}
+
+pub fn summarize_day(day: &DaySessions, focus: &FocusFormula) -> DaySummary {
+    DaySummary {
+        date: day.date,
+        seconds: day.sessions.iter().map(|s| s.seconds).sum(),
+        stats: session_stats(&day.sessions),
+        switches: count_switches(&day.sessions),
+        focus: focus_score(&day.sessions, focus),
+        dst_shift_seconds: dst_shift_seconds(day.date),
+    }
+}
+
+/// Project totals sorted by time spent (descending), then by name.
+pub fn project_totals(days: &[DaySessions]) -> Vec<ProjectTotal> {
+    let totals = days
+        .par_iter()
+        .fold(
+            BTreeMap::new,
+            |mut totals: BTreeMap<Option<&str>, i64>, day| {
+                for session in &day.sessions {
+                    *totals.entry(session.project.as_deref()).or_default() += session.seconds;
+                }
+                totals
+            },
+        )
+        .reduce(BTreeMap::new, |mut a, b| {
+            for (project, seconds) in b {
+                *a.entry(project).or_default() += seconds;
+            }
+            a
+        });
+    let total: i64 = totals.values().sum();
+    let mut projects: Vec<ProjectTotal> = totals
+        .into_iter()
+        .map(|(project, seconds)| ProjectTotal {
+            project: project.map(str::to_owned),
+            seconds,
+            percent: percent_of(seconds, total),
+        })
+        .collect();
+    projects.sort_by_key(|p| Reverse(p.seconds));
+    projects
+}
+
+/// Session attribute that `--by` totals the time by.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum GroupBy {
+    Project,
+    /// Language with the most heartbeats in the session
+    Language,
+    /// Editor the session's heartbeats were sent from
+    Editor,
+    /// Branch with the most heartbeats in the session
+    Branch,
+    /// Operating system the session's heartbeats were sent from
+    Os,
+    /// Kind of activity (coding, debugging, ...); sessions without one count as coding
+    Category,
+    /// Client of the session's project, by the config's `clients`
+    Client,
+}
+
+impl GroupBy {
+    pub fn label(self) -> &'static str {
+        match self {
+            GroupBy::Project => "projects",
+            GroupBy::Language => "languages",
+            GroupBy::Editor => "editors",
+            GroupBy::Branch => "branches",
+            GroupBy::Os => "operating systems",
+            GroupBy::Category => "categories",
+            GroupBy::Client => "clients",
+        }
+    }
+
+    fn key<'a>(self, session: &'a Session, clients: &'a [ClientRule]) -> Option<&'a str> {
+        match self {
+            GroupBy::Project => session.project.as_deref(),
+            GroupBy::Language => session.dominant_language(),
+            GroupBy::Editor => session.editor.as_deref(),
+            GroupBy::Branch => session.branch.as_deref(),
+            GroupBy::Os => session.operating_system.as_deref(),
+            GroupBy::Category => Some(session.category.as_deref().unwrap_or(CODING)),
+            GroupBy::Client => session
+                .project
+                .as_deref()
+                .and_then(|project| client_of(clients, project)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GroupTotal {
+    pub name: Option<String>,
+    pub seconds: i64,
+    /// Share of the period total, 0–100.
+    pub percent: f64,
+}
+
+/// Totals per value of `by`, sorted by time spent (descending), then by name. `clients` are
+/// the rules of [`GroupBy::Client`].
+pub fn group_totals(days: &[DaySessions], by: GroupBy, clients: &[ClientRule]) -> Vec<GroupTotal> {
+    let mut totals: BTreeMap<Option<&str>, i64> = BTreeMap::new();
+    for session in days.iter().flat_map(|day| &day.sessions) {
+        *totals.entry(by.key(session, clients)).or_default() += session.seconds;
+    }
+    let total: i64 = totals.values().sum();
+    let mut groups: Vec<GroupTotal> = totals
+        .into_iter()
+        .map(|(name, seconds)| GroupTotal {
+            name: name.map(str::to_owned),
+            seconds,
+            percent: percent_of(seconds, total),
+        })
+        .collect();
+    groups.sort_by_key(|g| Reverse(g.seconds));
+    groups
+}
+
+pub fn weekday_averages(
+    days: &[DaySessions],
+    start: NaiveDate,
+    end: NaiveDate,
+) -> Vec<WeekdayAverage> {
+    let mut occurrences = [0usize; 7];
+    let mut seconds = [0i64; 7];
+    for date in iter_dates(start, end) {
+        occurrences[date.weekday().num_days_from_monday() as usize] += 1;
+    }
+    for day in days.iter().filter(|d| d.date >= start && d.date <= end) {
+        let index = day.date.weekday().num_days_from_monday() as usize;
+        seconds[index] += day.sessions.iter().map(|s| s.seconds).sum::<i64>();
+    }
+    std::iter::successors(Some(Weekday::Mon), |d| Some(d.succ()))
+        .take(7)
+        .enumerate()
+        .filter(|(i, _)| occurrences[*i] > 0)
+        .map(|(i, weekday)| WeekdayAverage {
+            weekday,
+            days: occurrences[i],
+            average_seconds: seconds[i] / occurrences[i] as i64,
+        })
+        .collect()
+}
+
+fn day_average(days: &[DaySummary], value: impl Fn(&DaySummary) -> f64) -> f64 {
+    if days.is_empty() {
+        return 0.0;
+    }
+    days.iter().map(value).sum::<f64>() / days.len() as f64
+}
+
+/// Like [`summarize_with`], plus weekday averages over every date from `start` to `end`.
+pub fn summarize_period(
+    days: &[DaySessions],
+    start: NaiveDate,
+    end: NaiveDate,
+    focus: &FocusFormula,
+) -> PeriodSummary {
+    PeriodSummary {
+        weekdays: weekday_averages(days, start, end),
+        ..summarize_with(days, focus)
+    }
+}
+
+pub fn summarize(days: &[DaySessions]) -> PeriodSummary {
+    summarize_with(days, &FocusFormula::default())
+}
+
+pub fn summarize_with(days: &[DaySessions], focus: &FocusFormula) -> PeriodSummary {
+    // The worker threads do not see a zone scoped to this one.
+    let zone = zone::selected();
+    let days_summary: Vec<DaySummary> = days
+        .par_iter()
+        .map(|day| zone::scoped(zone, || summarize_day(day, focus)))
+        .collect();
+    PeriodSummary {
+        seconds: days_summary.iter().map(|d| d.seconds).sum(),
+        stats: session_stats(days.iter().flat_map(|d| &d.sessions)),
+        average_switches: day_average(&days_summary, |d| d.switches as f64),
+        average_focus: day_average(&days_summary, |d| d.focus),
+        days: days_summary,
+        projects: project_totals(days),
+        categories: group_totals(days, GroupBy::Category, &[]),
+        weekdays: Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{LanguageShare, Session};
+
+    fn session(seconds: i64, project: Option<&str>) -> Session {
+        Session {
+            start: 0,
+            end: seconds,
+            seconds,
+            project: project.map(Into::into),
+            ..Default::default()
+        }
+    }
+
+    fn day(d: u32, sessions: Vec<Session>) -> DaySessions {
+        DaySessions {
+            date: NaiveDate::from_ymd_opt(2026, 2, d).unwrap(),
+            sessions,
+        }
+    }
+
+    #[test]
+    fn summarize_empty() {
+        assert_eq!(summarize(&[]), PeriodSummary::default());
+    }
+
+    #[test]
+    fn summarize_day_totals() {
+        let d = day(1, vec![session(60, Some("a")), session(120, Some("b"))]);
+        let summary = summarize_day(&d, &FocusFormula::default());
+        assert_eq!(summary.seconds, 180);
+        assert_eq!(summary.stats.sessions, 2);
+        assert_eq!(summary.stats.average_seconds, 90);
+        assert_eq!(summary.stats.longest_seconds, 120);
+    }
+
+    #[test]
+    fn summarize_period_totals() {
+        let days = vec![
+            day(1, vec![session(60, Some("a"))]),
+            day(2, vec![session(120, Some("a")), session(30, None)]),
+        ];
+        let summary = summarize(&days);
+        assert_eq!(summary.seconds, 210);
+        assert_eq!(summary.stats.sessions, 3);
+        assert_eq!(summary.stats.longest_seconds, 120);
+        assert_eq!(summary.days.len(), 2);
+        assert_eq!(
+            summary.projects,
+            vec![
+                ProjectTotal {
+                    project: Some("a".into()),
+                    seconds: 180,
+                    percent: 180.0 * 100.0 / 210.0,
+                },
+                ProjectTotal {
+                    project: None,
+                    seconds: 30,
+                    percent: 30.0 * 100.0 / 210.0,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn switches_per_day_and_period_average() {
+        let days = vec![
+            day(
+                1,
+                vec![
+                    session(60, Some("a")),
+                    session(60, Some("b")),
+                    session(60, Some("a")),
+                ],
+            ),
+            day(2, vec![session(60, Some("a"))]),
+        ];
+        let summary = summarize(&days);
+        assert_eq!(summary.days[0].switches, 2);
+        assert_eq!(summary.days[1].switches, 0);
+        assert_eq!(summary.average_switches, 1.0);
+    }
+
+    #[test]
+    fn focus_per_day_and_period_average() {
+        let days = vec![
+            day(1, vec![session(3600, Some("a"))]),
+            day(2, vec![session(600, Some("a"))]),
+        ];
+        let summary = summarize(&days);
+        assert_eq!(summary.days[0].focus, 100.0);
+        assert_eq!(summary.days[1].focus, 0.0);
+        assert_eq!(summary.average_focus, 50.0);
+
+        let lenient = FocusFormula {
+            deep_seconds: 60,
+            ..Default::default()
+        };
+        assert_eq!(summarize_with(&days, &lenient).average_focus, 100.0);
+    }
+
+    #[test]
+    fn project_totals_ties_sorted_by_name() {
+        let days = vec![day(1, vec![session(60, Some("b")), session(60, Some("a"))])];
+        let projects = project_totals(&days);
+        assert_eq!(projects[0].project.as_deref(), Some("a"));
+        assert_eq!(projects[1].project.as_deref(), Some("b"));
+    }
+
+    #[test]
+    fn project_totals_deterministic_across_many_days() {
+        let days: Vec<DaySessions> = (1..=28)
+            .map(|d| day(d, vec![session(d as i64, Some("a")), session(1, Some("b"))]))
+            .collect();
+        let projects = project_totals(&days);
+        assert_eq!(projects[0].seconds, (1..=28).sum::<i64>());
+        assert_eq!(projects[1].seconds, 28);
+        assert_eq!(projects, project_totals(&days));
+    }
+
+    #[test]
+    fn group_totals_by_operating_system() {
+        let on = |seconds: i64, os: Option<&str>| Session {
+            operating_system: os.map(Into::into),
+            ..session(seconds, Some("a"))
+        };
+        let days = vec![
+            day(1, vec![on(600, Some("Linux")), on(300, Some("Mac"))]),
+            day(2, vec![on(300, Some("Linux")), on(100, None)]),
+        ];
+        let totals: Vec<_> = group_totals(&days, GroupBy::Os, &[])
+            .into_iter()
+            .map(|g| (g.name, g.seconds))
+            .collect();
+        assert_eq!(
+            totals,
+            vec![
+                (Some("Linux".to_string()), 900),
+                (Some("Mac".to_string()), 300),
+                (None, 100)
+            ]
+        );
+        assert_eq!(group_totals(&days, GroupBy::Project, &[])[0].seconds, 1300);
+    }
+
+    #[test]
+    fn group_totals_by_language_and_editor() {
+        let on = |seconds: i64, language: &str, editor: &str| Session {
+            languages: vec![LanguageShare {
+                language: language.into(),
+                heartbeats: 1,
+            }],
+            editor: Some(editor.into()),
+            ..session(seconds, Some("a"))
+        };
+        let days = vec![day(
+            1,
+            vec![
+                on(600, "Rust", "vim"),
+                on(300, "TypeScript", "vscode"),
+                on(200, "Rust", "vscode"),
+            ],
+        )];
+        let names = |by| -> Vec<_> {
+            group_totals(&days, by, &[])
+                .into_iter()
+                .map(|g| (g.name.unwrap(), g.seconds))
+                .collect()
+        };
+        assert_eq!(
+            names(GroupBy::Language),
+            [("Rust".to_string(), 800), ("TypeScript".to_string(), 300)]
+        );
+        assert_eq!(
+            names(GroupBy::Editor),
+            [("vim".to_string(), 600), ("vscode".to_string(), 500)]
+        );
+    }
+
+    #[test]
+    fn group_totals_by_branch() {
+        let on = |seconds: i64, branch: Option<&str>| Session {
+            branch: branch.map(Into::into),
+            ..session(seconds, Some("a"))
+        };
+        let days = vec![day(1, vec![on(600, Some("feature/x")), on(300, None)])];
+        let totals = group_totals(&days, GroupBy::Branch, &[]);
+        assert_eq!(totals[0].name.as_deref(), Some("feature/x"));
+        assert_eq!(totals[1].name, None);
+    }
+
+    #[test]
+    fn percent_of_zero_total() {
+        assert_eq!(percent_of(0, 0), 0.0);
+        assert_eq!(percent_of(30, 120), 25.0);
+    }
+
+    #[test]
+    fn project_percents_sum_to_hundred() {
+        let days = vec![day(
+            1,
+            vec![session(60, Some("a")), session(120, Some("b"))],
+        )];
+        let total: f64 = project_totals(&days).iter().map(|p| p.percent).sum();
+        assert!((total - 100.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn weekday_averages_include_idle_days() {
+        // Feb 2026: Feb 2 and 9 are Mondays, Feb 6 is a Friday
+        let days = vec![
+            day(2, vec![session(3600, Some("a"))]),
+            day(6, vec![session(1800, Some("a"))]),
+        ];
+        let start = NaiveDate::from_ymd_opt(2026, 2, 1).unwrap();
+        let end = NaiveDate::from_ymd_opt(2026, 2, 14).unwrap();
+        let averages = weekday_averages(&days, start, end);
+        assert_eq!(averages.len(), 7);
+        assert_eq!(averages[0].weekday, Weekday::Mon);
+        assert_eq!(averages[0].days, 2);
+        assert_eq!(averages[0].average_seconds, 1800);
+        assert_eq!(averages[4].weekday, Weekday::Fri);
+        assert_eq!(averages[4].average_seconds, 900);
+        assert_eq!(averages[6].weekday, Weekday::Sun);
+        assert_eq!(averages[6].average_seconds, 0);
+    }
+
+    #[test]
+    fn weekday_averages_short_range_skips_absent_weekdays() {
+        let start = NaiveDate::from_ymd_opt(2026, 2, 2).unwrap();
+        let end = NaiveDate::from_ymd_opt(2026, 2, 3).unwrap();
+        let averages = weekday_averages(&[], start, end);
+        let weekdays: Vec<Weekday> = averages.iter().map(|a| a.weekday).collect();
+        assert_eq!(weekdays, vec![Weekday::Mon, Weekday::Tue]);
+    }
+
+    #[test]
+    fn summary_serializes() {
+        let summary = summarize(&[day(1, vec![session(60, Some("a"))])]);
+        let value = serde_json::to_value(&summary).unwrap();
+        assert_eq!(value["seconds"], 60);
+        assert_eq!(value["sessions"], 1);
+        assert_eq!(value["days"][0]["date"], "2026-02-01");
+        assert_eq!(value["days"][0]["sessions"], 1);
+        assert_eq!(value["days"][0]["longest_seconds"], 60);
+        assert_eq!(value["projects"][0]["project"], "a");
+    }
+}
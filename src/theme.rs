@@ -0,0 +1,222 @@
+//! Colors of the text output: a built-in preset, optionally with some roles overridden in the
+//! `theme` section of the config.
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Result, WakalyzeError};
+
+const ANSI_RESET: &str = "\x1b[0m";
+
+/// Built-in color sets.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Preset {
+    /// For dark backgrounds.
+    #[default]
+    Dark,
+    /// For light backgrounds, without yellow.
+    Light,
+    /// Solarized accents (256-color terminals).
+    Solarized,
+    /// No colors at all.
+    None,
+}
+
+/// What a colored piece of output is.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Role {
+    /// Day headings of the session list.
+    Date,
+    /// Project names of the session list.
+    Project,
+    /// Week totals of the calendar.
+    Total,
+    /// Warnings on stderr.
+    Warning,
+    /// The longest session of a day (`--highlight-longest`).
+    Highlight,
+    /// Weekend cells of the calendar.
+    Weekend,
+    /// Holiday cells of the calendar.
+    Holiday,
+}
+
+/// The `theme` section of the config. Colors are names (`bold`, `dim`, `red`, `bright blue`,
+/// ..., space-separated) or raw SGR codes such as `38;5;214`; an empty string turns a role off.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ThemeConfig {
+    #[serde(default)]
+    pub preset: Preset,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub date: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub warning: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub highlight: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weekend: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub holiday: Option<String>,
+}
+
+/// SGR code per role; empty codes leave the text as it is. The default has no colors.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Theme {
+    date: String,
+    project: String,
+    total: String,
+    warning: String,
+    highlight: String,
+    weekend: String,
+    holiday: String,
+}
+
+impl Theme {
+    pub fn preset(preset: Preset) -> Self {
+        // date, project, total, warning, highlight, weekend, holiday
+        let codes = match preset {
+            Preset::Dark => ["1", "36", "1", "33", "1;33", "2", "35"],
+            Preset::Light => ["1", "34", "1", "31", "1;31", "90", "35"],
+            Preset::Solarized => [
+                "1;38;5;33",
+                "38;5;37",
+                "1;38;5;64",
+                "38;5;166",
+                "1;38;5;136",
+                "38;5;240",
+                "38;5;125",
+            ],
+            Preset::None => [""; 7],
+        };
+        let [date, project, total, warning, highlight, weekend, holiday] = codes.map(String::from);
+        Self {
+            date,
+            project,
+            total,
+            warning,
+            highlight,
+            weekend,
+            holiday,
+        }
+    }
+
+    /// The preset of `config` with its overrides.
+    pub fn from_config(config: &ThemeConfig) -> Result<Self> {
+        let mut theme = Self::preset(config.preset);
+        let overrides = [
+            (&mut theme.date, &config.date),
+            (&mut theme.project, &config.project),
+            (&mut theme.total, &config.total),
+            (&mut theme.warning, &config.warning),
+            (&mut theme.highlight, &config.highlight),
+            (&mut theme.weekend, &config.weekend),
+            (&mut theme.holiday, &config.holiday),
+        ];
+        for (code, color) in overrides {
+            if let Some(color) = color {
+                *code = parse_color(color)?;
+            }
+        }
+        Ok(theme)
+    }
+
+    fn code(&self, role: Role) -> &str {
+        match role {
+            Role::Date => &self.date,
+            Role::Project => &self.project,
+            Role::Total => &self.total,
+            Role::Warning => &self.warning,
+            Role::Highlight => &self.highlight,
+            Role::Weekend => &self.weekend,
+            Role::Holiday => &self.holiday,
+        }
+    }
+
+    /// `text` in the color of `role`.
+    pub fn paint(&self, role: Role, text: &str) -> String {
+        match self.code(role) {
+            "" => text.to_string(),
+            code => format!("\x1b[{code}m{text}{ANSI_RESET}"),
+        }
+    }
+}
+
+/// SGR code of a color name such as `bold bright blue`, or of a raw code such as `38;5;214`.
+pub fn parse_color(value: &str) -> Result<String> {
+    let value = value.trim();
+    if value.split(';').all(|n| n.parse::<u8>().is_ok()) {
+        return Ok(value.to_string());
+    }
+    let invalid = || WakalyzeError::InvalidColor(value.to_string());
+    let mut codes = Vec::new();
+    let mut bright = false;
+    for word in value.split_whitespace() {
+        let word = word.to_lowercase();
+        let code = match word.as_str() {
+            "bright" => {
+                bright = true;
+                continue;
+            }
+            "bold" => 1,
+            "dim" => 2,
+            "italic" => 3,
+            "underline" => 4,
+            color => {
+                let base = match color {
+                    "black" => 30,
+                    "red" => 31,
+                    "green" => 32,
+                    "yellow" => 33,
+                    "blue" => 34,
+                    "magenta" => 35,
+                    "cyan" => 36,
+                    "white" => 37,
+                    _ => return Err(invalid()),
+                };
+                if std::mem::take(&mut bright) {
+                    base + 60
+                } else {
+                    base
+                }
+            }
+        };
+        codes.push(code.to_string());
+    }
+    if bright {
+        return Err(invalid());
+    }
+    Ok(codes.join(";"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_names_and_codes() {
+        assert_eq!(parse_color("bold bright Blue").unwrap(), "1;94");
+        assert_eq!(parse_color("38;5;214").unwrap(), "38;5;214");
+        assert_eq!(parse_color("").unwrap(), "");
+        assert!(matches!(
+            parse_color("mauve"),
+            Err(WakalyzeError::InvalidColor(_))
+        ));
+        assert!(parse_color("bright").is_err());
+    }
+
+    #[test]
+    fn overrides_apply_on_top_of_the_preset() {
+        let config: ThemeConfig =
+            serde_json::from_str(r#"{ "preset": "light", "warning": "magenta", "date": "" }"#)
+                .unwrap();
+        let theme = Theme::from_config(&config).unwrap();
+        assert_eq!(theme.paint(Role::Warning, "w"), "\x1b[35mw\x1b[0m");
+        assert_eq!(theme.paint(Role::Date, "2/2"), "2/2");
+        assert_eq!(theme.paint(Role::Project, "api"), "\x1b[34mapi\x1b[0m");
+        assert_eq!(Theme::preset(Preset::None), Theme::default());
+    }
+}
@@ -0,0 +1,222 @@
+//! Timesheet interchange export (XML or CSV) for enterprise bulk upload.
+//!
+//! The layout comes from the `timesheet` section of the config file: element/column names for
+//! each field, constant fields (employee ID, activity type, ...), and codes (e.g. WBS
+//! elements) for Wakapi projects.
+
+use std::collections::BTreeMap;
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use crate::core::DaySessions;
+use crate::format::{csv_field, xml_escape};
+use crate::rounding::Rounding;
+
+/// Element/column names of the per-entry fields.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TimesheetFields {
+    pub date: String,
+    pub project: String,
+    pub hours: String,
+}
+
+impl Default for TimesheetFields {
+    fn default() -> Self {
+        Self {
+            date: "Date".to_string(),
+            project: "Project".to_string(),
+            hours: "Hours".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TimesheetLayout {
+    /// XML root element.
+    pub root: String,
+    /// XML element of each entry.
+    pub entry: String,
+    pub fields: TimesheetFields,
+    /// Fields with the same value on every entry, written after the mapped ones.
+    pub constants: BTreeMap<String, String>,
+    /// Wakapi project → code written in the project field; unmapped projects keep their name.
+    pub projects: BTreeMap<String, String>,
+}
+
+impl Default for TimesheetLayout {
+    fn default() -> Self {
+        Self {
+            root: "Timesheet".to_string(),
+            entry: "Entry".to_string(),
+            fields: TimesheetFields::default(),
+            constants: BTreeMap::new(),
+            projects: BTreeMap::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TimesheetEntry {
+    pub date: NaiveDate,
+    /// Mapped project code.
+    pub project: String,
+    pub seconds: i64,
+}
+
+/// One entry per day and mapped project code, rounded per entry.
+pub fn timesheet_entries(
+    days: &[DaySessions],
+    layout: &TimesheetLayout,
+    rounding: Option<&Rounding>,
+) -> Vec<TimesheetEntry> {
+    let mut entries = Vec::new();
+    for day in days {
+        let mut totals: BTreeMap<&str, i64> = BTreeMap::new();
+        for session in &day.sessions {
+            let project = session.project.as_deref().unwrap_or("unknown");
+            let code = layout.projects.get(project).map_or(project, String::as_str);
+            *totals.entry(code).or_default() += session.seconds;
+        }
+        for (project, seconds) in totals {
+            entries.push(TimesheetEntry {
+                date: day.date,
+                project: project.to_string(),
+                seconds: rounding.map_or(seconds, |r| r.apply(seconds)),
+            });
+        }
+    }
+    entries
+}
+
+fn hours(seconds: i64) -> String {
+    format!("{:.2}", seconds as f64 / 3600.0)
+}
+
+/// Field names in output order: the mapped fields, then the constants.
+fn field_names(layout: &TimesheetLayout) -> Vec<&str> {
+    let mut names = vec![
+        layout.fields.date.as_str(),
+        layout.fields.project.as_str(),
+        layout.fields.hours.as_str(),
+    ];
+    names.extend(layout.constants.keys().map(String::as_str));
+    names
+}
+
+/// Values of an entry, in the order of [`field_names`].
+fn field_values(entry: &TimesheetEntry, layout: &TimesheetLayout) -> Vec<String> {
+    let mut values = vec![
+        entry.date.to_string(),
+        entry.project.clone(),
+        hours(entry.seconds),
+    ];
+    values.extend(layout.constants.values().cloned());
+    values
+}
+
+pub fn to_timesheet_xml(entries: &[TimesheetEntry], layout: &TimesheetLayout) -> Vec<String> {
+    let mut lines = vec![
+        r#"<?xml version="1.0" encoding="UTF-8"?>"#.to_string(),
+        format!("<{}>", layout.root),
+    ];
+    for entry in entries {
+        lines.push(format!("  <{}>", layout.entry));
+        for (name, value) in field_names(layout).iter().zip(field_values(entry, layout)) {
+            lines.push(format!("    <{name}>{}</{name}>", xml_escape(&value)));
+        }
+        lines.push(format!("  </{}>", layout.entry));
+    }
+    lines.push(format!("</{}>", layout.root));
+    lines
+}
+
+pub fn to_timesheet_csv(entries: &[TimesheetEntry], layout: &TimesheetLayout) -> Vec<String> {
+    let csv_line = |fields: Vec<&str>| {
+        fields
+            .into_iter()
+            .map(csv_field)
+            .collect::<Vec<_>>()
+            .join(",")
+    };
+    let mut lines = vec![csv_line(field_names(layout))];
+    for entry in entries {
+        let values = field_values(entry, layout);
+        lines.push(csv_line(values.iter().map(String::as_str).collect()));
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Session;
+
+    fn layout() -> TimesheetLayout {
+        serde_json::from_str(
+            r#"{
+                "root": "TimeSheet",
+                "entry": "TimeEntry",
+                "fields": { "project": "WBSElement" },
+                "constants": { "EmployeeID": "E42" },
+                "projects": { "api": "P-1001", "web": "P-1001" }
+            }"#,
+        )
+        .unwrap()
+    }
+
+    fn days() -> Vec<DaySessions> {
+        let session = |seconds: i64, project: &str| Session {
+            seconds,
+            project: Some(project.into()),
+            ..Default::default()
+        };
+        vec![DaySessions {
+            date: NaiveDate::from_ymd_opt(2026, 2, 3).unwrap(),
+            sessions: vec![
+                session(3600, "api"),
+                session(1800, "web"),
+                session(900, "R&D"),
+            ],
+        }]
+    }
+
+    #[test]
+    fn entries_grouped_by_project_code() {
+        let entries = timesheet_entries(&days(), &layout(), None);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].project, "P-1001");
+        assert_eq!(entries[0].seconds, 5400);
+        assert_eq!(entries[1].project, "R&D");
+    }
+
+    #[test]
+    fn xml_uses_layout_names_and_escapes() {
+        let layout = layout();
+        let lines = to_timesheet_xml(&timesheet_entries(&days(), &layout, None), &layout);
+        assert_eq!(lines[1], "<TimeSheet>");
+        assert_eq!(lines[2], "  <TimeEntry>");
+        assert_eq!(lines[3], "    <Date>2026-02-03</Date>");
+        assert_eq!(lines[4], "    <WBSElement>P-1001</WBSElement>");
+        assert_eq!(lines[5], "    <Hours>1.50</Hours>");
+        assert_eq!(lines[6], "    <EmployeeID>E42</EmployeeID>");
+        assert!(lines.contains(&"    <WBSElement>R&amp;D</WBSElement>".to_string()));
+        assert_eq!(lines.last().unwrap(), "</TimeSheet>");
+    }
+
+    #[test]
+    fn csv_header_then_rows() {
+        let layout = layout();
+        let lines = to_timesheet_csv(&timesheet_entries(&days(), &layout, None), &layout);
+        assert_eq!(
+            lines,
+            vec![
+                "Date,WBSElement,Hours,EmployeeID",
+                "2026-02-03,P-1001,1.50,E42",
+                "2026-02-03,R&D,0.25,E42",
+            ]
+        );
+    }
+}
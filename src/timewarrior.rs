@@ -0,0 +1,89 @@
+//! Timewarrior export: one `timew track` command per session, tagged with the project.
+
+use crate::core::{iso8601, DaySessions};
+
+/// Quote a tag for POSIX shells unless it is made of safe characters only.
+fn shell_quote(value: &str) -> String {
+    let safe = |c: char| c.is_ascii_alphanumeric() || "-_.:/@+".contains(c);
+    if !value.is_empty() && value.chars().all(safe) {
+        value.to_string()
+    } else {
+        format!("'{}'", value.replace('\'', r"'\''"))
+    }
+}
+
+/// `timew track <start> - <end> <project> <tags>...` lines, the tags coming from
+/// `wakalyze tag`; times are UTC, sessions without a project or tags are tracked untagged.
+pub fn timew_commands(days: &[DaySessions]) -> Vec<String> {
+    days.iter()
+        .flat_map(|day| &day.sessions)
+        .map(|session| {
+            let mut command = format!(
+                "timew track {} - {}",
+                iso8601(session.start),
+                iso8601(session.end)
+            );
+            let tags = session.project.as_deref().into_iter();
+            for tag in tags.chain(session.tags.iter().map(String::as_str)) {
+                command.push(' ');
+                command.push_str(&shell_quote(tag));
+            }
+            command
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Session;
+    use chrono::NaiveDate;
+
+    #[test]
+    fn commands_per_session_with_quoted_tags() {
+        let session = |start: i64, project: Option<&str>| Session {
+            start,
+            end: start + 600,
+            seconds: 600,
+            project: project.map(Into::into),
+            ..Default::default()
+        };
+        let days = vec![DaySessions {
+            date: NaiveDate::from_ymd_opt(2026, 2, 2).unwrap(),
+            sessions: vec![
+                session(1_770_000_000, Some("wakalyze")),
+                session(1_770_003_600, Some("client's app")),
+                session(1_770_007_200, None),
+            ],
+        }];
+        assert_eq!(
+            timew_commands(&days),
+            vec![
+                "timew track 2026-02-02T02:40:00Z - 2026-02-02T02:50:00Z wakalyze",
+                r"timew track 2026-02-02T03:40:00Z - 2026-02-02T03:50:00Z 'client'\''s app'",
+                "timew track 2026-02-02T04:40:00Z - 2026-02-02T04:50:00Z",
+            ]
+        );
+    }
+
+    #[test]
+    fn tags_follow_the_project() {
+        let days = vec![DaySessions {
+            date: NaiveDate::from_ymd_opt(2026, 2, 2).unwrap(),
+            sessions: vec![Session {
+                start: 1_770_000_000,
+                end: 1_770_000_600,
+                project: Some("wakalyze".into()),
+                tags: vec!["billable".into(), "pair programming".into()],
+                ..Default::default()
+            }],
+        }];
+        assert_eq!(
+            timew_commands(&days),
+            vec![
+                "timew track 2026-02-02T02:40:00Z - 2026-02-02T02:50:00Z wakalyze billable \
+                 'pair programming'"
+            ]
+        );
+    }
+}
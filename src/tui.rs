@@ -0,0 +1,344 @@
+//! `wakalyze tui`: an interactive dashboard of one month with panels for the daily totals,
+//! the project breakdown, and a scrollable session list.
+
+use chrono::{Datelike, Months, NaiveDate};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Modifier, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Paragraph, Row, Table, TableState};
+use ratatui::{DefaultTerminal, Frame};
+
+use crate::analysis::Report;
+use crate::core::{is_weekend, DaySessions};
+use crate::error::Result;
+use crate::format::{
+    build_chart_lines, build_project_lines, chart_rows, format_date_short, format_duration,
+    format_time, Precision,
+};
+use crate::i18n::Lang;
+use crate::summary::summarize;
+
+/// Loads the label and report of the month starting on the given date.
+pub type MonthLoader<'a> = dyn FnMut(NaiveDate) -> Result<(String, Report)> + 'a;
+
+/// State of the dashboard between key presses.
+pub struct Dashboard {
+    /// First day of the shown month.
+    pub month: NaiveDate,
+    pub label: String,
+    pub report: Report,
+    /// Only the sessions of this project, cycled with `p`.
+    pub project: Option<String>,
+    /// Leave Saturdays and Sundays out, toggled with `w`.
+    pub hide_weekends: bool,
+    /// Highlighted row of the session list.
+    pub selected: usize,
+    /// Why the last month switch failed, shown in the status line.
+    pub status: Option<String>,
+    pub lang: Lang,
+}
+
+impl Dashboard {
+    pub fn new(label: String, report: Report, lang: Lang) -> Self {
+        Self {
+            month: report.start.with_day(1).unwrap_or(report.start),
+            label,
+            report,
+            project: None,
+            hide_weekends: false,
+            selected: 0,
+            status: None,
+            lang,
+        }
+    }
+
+    /// Every day of the month with the filters applied; days without sessions included.
+    pub fn days(&self) -> Vec<DaySessions> {
+        self.report
+            .all_days()
+            .into_iter()
+            .filter(|day| !(self.hide_weekends && is_weekend(day.date)))
+            .map(|mut day| {
+                if let Some(project) = &self.project {
+                    day.sessions
+                        .retain(|s| s.project.as_deref() == Some(project.as_str()));
+                }
+                day
+            })
+            .collect()
+    }
+
+    fn session_count(&self) -> usize {
+        self.days().iter().map(|day| day.sessions.len()).sum()
+    }
+
+    /// Show all projects, then each project of the month (most time first), then all again.
+    pub fn cycle_project(&mut self) {
+        let projects: Vec<&str> = self
+            .report
+            .summary
+            .projects
+            .iter()
+            .filter_map(|total| total.project.as_deref())
+            .collect();
+        let next = match &self.project {
+            None => projects.first(),
+            Some(current) => projects
+                .iter()
+                .position(|p| p == current)
+                .and_then(|i| projects.get(i + 1)),
+        };
+        self.project = next.map(|p| p.to_string());
+        self.selected = 0;
+    }
+
+    pub fn toggle_weekends(&mut self) {
+        self.hide_weekends = !self.hide_weekends;
+        self.selected = 0;
+    }
+
+    /// Switch `months` months back or forward; a failed load keeps the current month.
+    pub fn shift_month(&mut self, months: i32, load: &mut MonthLoader) {
+        let shifted = if months < 0 {
+            self.month
+                .checked_sub_months(Months::new(months.unsigned_abs()))
+        } else {
+            self.month.checked_add_months(Months::new(months as u32))
+        };
+        let Some(month) = shifted else {
+            return;
+        };
+        match load(month) {
+            Ok((label, report)) => {
+                self.month = month;
+                self.label = label;
+                self.report = report;
+                self.selected = 0;
+                self.status = None;
+            }
+            Err(e) => self.status = Some(e.to_string()),
+        }
+    }
+
+    /// Apply a key press; `false` once the dashboard should close.
+    pub fn handle_key(&mut self, key: KeyCode, load: &mut MonthLoader) -> bool {
+        match key {
+            KeyCode::Char('q') | KeyCode::Esc => return false,
+            KeyCode::Left | KeyCode::Char('h') => self.shift_month(-1, load),
+            KeyCode::Right | KeyCode::Char('l') => self.shift_month(1, load),
+            KeyCode::Up | KeyCode::Char('k') => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.selected = (self.selected + 1).min(self.session_count().saturating_sub(1));
+            }
+            KeyCode::Char('p') => self.cycle_project(),
+            KeyCode::Char('w') => self.toggle_weekends(),
+            _ => {}
+        }
+        true
+    }
+}
+
+/// Run the dashboard until `q`, loading other months with `load`.
+pub fn run(mut dashboard: Dashboard, load: &mut MonthLoader) -> Result<()> {
+    let mut terminal = ratatui::init();
+    let result = event_loop(&mut terminal, &mut dashboard, load);
+    ratatui::restore();
+    result
+}
+
+fn event_loop(
+    terminal: &mut DefaultTerminal,
+    dashboard: &mut Dashboard,
+    load: &mut MonthLoader,
+) -> Result<()> {
+    let mut table = TableState::default();
+    loop {
+        table.select(Some(dashboard.selected));
+        terminal.draw(|frame| draw(frame, dashboard, &mut table))?;
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        let month = dashboard.month;
+        if !dashboard.handle_key(key.code, load) {
+            return Ok(());
+        }
+        // Loading a month may have drawn a progress bar over the screen.
+        if dashboard.month != month || dashboard.status.is_some() {
+            terminal.clear()?;
+        }
+    }
+}
+
+fn draw(frame: &mut Frame, dashboard: &Dashboard, table: &mut TableState) {
+    let [top, bottom, status] = Layout::vertical([
+        Constraint::Percentage(50),
+        Constraint::Min(5),
+        Constraint::Length(1),
+    ])
+    .areas(frame.area());
+    let [days_area, projects_area] =
+        Layout::horizontal([Constraint::Percentage(55), Constraint::Percentage(45)]).areas(top);
+    let days = dashboard.days();
+    let total: i64 = days
+        .iter()
+        .flat_map(|d| &d.sessions)
+        .map(|s| s.seconds)
+        .sum();
+
+    let width = usize::from(days_area.width.saturating_sub(2));
+    let chart = build_chart_lines("", &chart_rows(&days, false, dashboard.lang), width);
+    let title = format!(" {} {} ", dashboard.label, format_duration(total));
+    frame.render_widget(
+        Paragraph::new(
+            chart
+                .into_iter()
+                .skip(1)
+                .map(Line::from)
+                .collect::<Vec<_>>(),
+        )
+        .block(Block::bordered().title(title)),
+        days_area,
+    );
+
+    let projects = build_project_lines(&summarize(&days).projects, true, Precision::Minutes);
+    frame.render_widget(
+        Paragraph::new(
+            projects
+                .into_iter()
+                .skip(1)
+                .map(Line::from)
+                .collect::<Vec<_>>(),
+        )
+        .block(Block::bordered().title(" projects ")),
+        projects_area,
+    );
+
+    let rows = days.iter().flat_map(|day| {
+        day.sessions.iter().map(|s| {
+            Row::new([
+                format_date_short(day.date),
+                format!("{} ~ {}", format_time(s.start), format_time(s.end)),
+                format_duration(s.seconds),
+                s.project.as_deref().unwrap_or("unknown").to_string(),
+                s.branch.as_deref().unwrap_or_default().to_string(),
+            ])
+        })
+    });
+    let sessions = Table::new(
+        rows,
+        [
+            Constraint::Length(5),
+            Constraint::Length(17),
+            Constraint::Length(6),
+            Constraint::Fill(1),
+            Constraint::Fill(1),
+        ],
+    )
+    .header(
+        Row::new(["date", "time", "length", "project", "branch"])
+            .style(Style::new().add_modifier(Modifier::BOLD)),
+    )
+    .row_highlight_style(Style::new().add_modifier(Modifier::REVERSED))
+    .block(Block::bordered().title(" sessions "));
+    frame.render_stateful_widget(sessions, bottom, table);
+
+    let help = match &dashboard.status {
+        Some(error) => format!("error: {error}"),
+        None => format!(
+            "←/→ month  ↑/↓ session  p project: {}  w weekends: {}  q quit",
+            dashboard.project.as_deref().unwrap_or("all"),
+            if dashboard.hide_weekends {
+                "hidden"
+            } else {
+                "shown"
+            }
+        ),
+    };
+    frame.render_widget(Paragraph::new(help), status);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::{Analysis, MemorySource};
+    use crate::core::RawHeartbeat;
+    use crate::error::WakalyzeError;
+    use chrono::{Local, TimeZone};
+
+    fn hb_at(day: u32, min: u32, project: &str) -> RawHeartbeat {
+        let ts = Local
+            .with_ymd_and_hms(2026, 2, day, 9, min, 0)
+            .unwrap()
+            .timestamp();
+        RawHeartbeat {
+            time: Some(ts as f64),
+            project: Some(project.to_string()),
+            ..Default::default()
+        }
+    }
+
+    fn dashboard() -> Dashboard {
+        let date = |day| NaiveDate::from_ymd_opt(2026, 2, day).unwrap();
+        let source = MemorySource::new(vec![
+            hb_at(2, 0, "api"),
+            hb_at(2, 10, "api"),
+            // 2026-02-07 is a Saturday.
+            hb_at(7, 0, "web"),
+            hb_at(7, 10, "web"),
+        ]);
+        let report = Analysis::range(date(1), date(28)).run(&source).unwrap();
+        Dashboard::new("2026/02".into(), report, Lang::En)
+    }
+
+    fn sessions(dashboard: &Dashboard) -> Vec<String> {
+        dashboard
+            .days()
+            .iter()
+            .flat_map(|d| &d.sessions)
+            .filter_map(|s| s.project.as_deref().map(str::to_owned))
+            .collect()
+    }
+
+    #[test]
+    fn filters_cycle_projects_and_toggle_weekends() {
+        let mut dashboard = dashboard();
+        assert_eq!(dashboard.days().len(), 28);
+        assert_eq!(sessions(&dashboard).len(), 2);
+
+        dashboard.cycle_project();
+        let first = dashboard.project.clone().unwrap();
+        assert!(sessions(&dashboard).iter().all(|p| *p == first));
+        dashboard.cycle_project();
+        dashboard.cycle_project();
+        assert_eq!(dashboard.project, None);
+
+        dashboard.toggle_weekends();
+        assert_eq!(dashboard.days().len(), 20);
+        assert_eq!(sessions(&dashboard), ["api"]);
+    }
+
+    #[test]
+    fn keys_switch_months_and_move_the_selection() {
+        let mut dashboard = dashboard();
+        let mut loaded = Vec::new();
+        let mut load = |month: NaiveDate| -> Result<(String, Report)> {
+            loaded.push(month);
+            Err(WakalyzeError::NothingToUpdate)
+        };
+        assert!(dashboard.handle_key(KeyCode::Down, &mut load));
+        assert!(dashboard.handle_key(KeyCode::Down, &mut load));
+        assert_eq!(dashboard.selected, 1);
+        assert!(dashboard.handle_key(KeyCode::Left, &mut load));
+        assert!(dashboard.status.is_some());
+        assert_eq!(
+            dashboard.month,
+            NaiveDate::from_ymd_opt(2026, 2, 1).unwrap()
+        );
+        assert!(!dashboard.handle_key(KeyCode::Char('q'), &mut load));
+        assert_eq!(loaded, [NaiveDate::from_ymd_opt(2026, 1, 1).unwrap()]);
+    }
+}
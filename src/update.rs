@@ -0,0 +1,218 @@
+//! `wakalyze self-update`: replace the running binary with the one of the latest GitHub
+//! release, after checking it against the release's SHA-256 checksum.
+//!
+//! Releases ship `wakalyze-<target>.tar.gz` and `wakalyze-<target>.tar.gz.sha256` for macOS
+//! (x86_64, aarch64) and x86_64 Linux; they are not signed, so the checksum is all that is
+//! verified. Unpacking uses the system `tar`.
+
+use std::path::Path;
+use std::process::Command;
+
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+
+use crate::archive::to_hex;
+use crate::error::{Result, WakalyzeError};
+
+pub const LATEST_RELEASE_URL: &str =
+    "https://api.github.com/repos/masaishi/wakalyze/releases/latest";
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Asset {
+    pub name: String,
+    pub browser_download_url: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Release {
+    pub tag_name: String,
+    #[serde(default)]
+    pub assets: Vec<Asset>,
+}
+
+impl Release {
+    /// The version of the tag, without its `v`.
+    pub fn version(&self) -> &str {
+        self.tag_name.trim_start_matches('v')
+    }
+
+    fn asset(&self, name: &str) -> Result<&Asset> {
+        self.assets
+            .iter()
+            .find(|asset| asset.name == name)
+            .ok_or_else(|| {
+                WakalyzeError::SelfUpdate(format!("release {} has no {name}", self.tag_name))
+            })
+    }
+}
+
+/// Target triple of the release binary for this platform, if one is published.
+pub fn target() -> Option<&'static str> {
+    match (std::env::consts::OS, std::env::consts::ARCH) {
+        ("macos", "x86_64") => Some("x86_64-apple-darwin"),
+        ("macos", "aarch64") => Some("aarch64-apple-darwin"),
+        ("linux", "x86_64") => Some("x86_64-unknown-linux-gnu"),
+        _ => None,
+    }
+}
+
+fn client(timeout_secs: f64) -> Result<reqwest::blocking::Client> {
+    Ok(reqwest::blocking::Client::builder()
+        .timeout(std::time::Duration::from_secs_f64(timeout_secs))
+        .user_agent("wakalyze")
+        .build()?)
+}
+
+pub fn latest_release(url: &str, timeout_secs: f64) -> Result<Release> {
+    Ok(client(timeout_secs)?
+        .get(url)
+        .send()?
+        .error_for_status()?
+        .json()?)
+}
+
+/// `major.minor.patch` of a version; pre-release and build suffixes are ignored.
+fn parse_version(version: &str) -> Option<(u64, u64, u64)> {
+    let core = version.trim_start_matches('v').split(['-', '+']).next()?;
+    let mut parts = core.split('.').map(|part| part.parse().ok());
+    let version = (parts.next()??, parts.next()??, parts.next()??);
+    parts.next().is_none().then_some(version)
+}
+
+/// Whether `latest` is a later version than `current`. A development build (`0.0.0-dev`)
+/// is older than any release.
+pub fn is_newer(current: &str, latest: &str) -> bool {
+    match (parse_version(current), parse_version(latest)) {
+        (Some(current), Some(latest)) => latest > current,
+        (None, Some(_)) => true,
+        _ => false,
+    }
+}
+
+/// Check `bytes` against a `shasum -a 256` line (`<hex>  <file name>`).
+pub fn verify_checksum(bytes: &[u8], checksum_file: &str) -> Result<()> {
+    let expected = checksum_file.split_whitespace().next().unwrap_or_default();
+    let actual = to_hex(&Sha256::digest(bytes));
+    if expected.eq_ignore_ascii_case(&actual) {
+        Ok(())
+    } else {
+        Err(WakalyzeError::SelfUpdate(format!(
+            "checksum mismatch: expected {expected}, downloaded {actual}"
+        )))
+    }
+}
+
+/// Download the release binary for `target` and check it; the `.tar.gz` bytes.
+pub fn download(release: &Release, target: &str, timeout_secs: f64) -> Result<Vec<u8>> {
+    let name = format!("wakalyze-{target}.tar.gz");
+    let archive = release.asset(&name)?;
+    let checksum = release.asset(&format!("{name}.sha256"))?;
+    let client = client(timeout_secs)?;
+    let checksum = client
+        .get(&checksum.browser_download_url)
+        .send()?
+        .error_for_status()?
+        .text()?;
+    let bytes = client
+        .get(&archive.browser_download_url)
+        .send()?
+        .error_for_status()?
+        .bytes()?;
+    verify_checksum(&bytes, &checksum)?;
+    Ok(bytes.to_vec())
+}
+
+/// Unpack the `wakalyze` binary of a release archive over `exe`. The new binary is written
+/// next to it first and renamed into place, so a failure leaves the old one intact.
+pub fn install(archive: &[u8], exe: &Path) -> Result<()> {
+    let failed = |message: String| WakalyzeError::SelfUpdate(message);
+    let dir = exe
+        .parent()
+        .ok_or_else(|| failed(format!("{} has no directory", exe.display())))?;
+    let staging = dir.join(format!(".wakalyze-update-{}", std::process::id()));
+    std::fs::create_dir_all(&staging)?;
+    let result = (|| -> Result<()> {
+        let tarball = staging.join("wakalyze.tar.gz");
+        std::fs::write(&tarball, archive)?;
+        let status = Command::new("tar")
+            .arg("xzf")
+            .arg(&tarball)
+            .arg("-C")
+            .arg(&staging)
+            .status()
+            .map_err(|e| failed(format!("tar: {e}")))?;
+        if !status.success() {
+            return Err(failed(format!("tar exited with {status}")));
+        }
+        let binary = staging.join("wakalyze");
+        if !binary.is_file() {
+            return Err(failed("the archive has no wakalyze binary".to_string()));
+        }
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&binary, std::fs::Permissions::from_mode(0o755))?;
+        }
+        std::fs::rename(&binary, exe)?;
+        Ok(())
+    })();
+    std::fs::remove_dir_all(&staging).ok();
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compares_versions() {
+        assert!(is_newer("0.3.1", "v0.4.0"));
+        assert!(is_newer("0.0.0-dev", "0.0.1"));
+        assert!(!is_newer("1.2.0", "v1.2.0"));
+        assert!(!is_newer("1.10.0", "1.9.9"));
+        assert!(!is_newer("1.0.0", "nightly"));
+        assert_eq!(parse_version("1.2"), None);
+        assert_eq!(parse_version("1.2.3+build.5"), Some((1, 2, 3)));
+    }
+
+    #[test]
+    fn verifies_checksums() {
+        let line =
+            "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824  hello.tar.gz\n";
+        assert!(verify_checksum(b"hello", line).is_ok());
+        assert!(matches!(
+            verify_checksum(b"hello!", line),
+            Err(WakalyzeError::SelfUpdate(_))
+        ));
+    }
+
+    #[test]
+    fn downloads_and_checks_the_platform_asset() {
+        let mut server = mockito::Server::new();
+        let release = Release {
+            tag_name: "v1.0.0".to_string(),
+            assets: ["wakalyze-t.tar.gz", "wakalyze-t.tar.gz.sha256"]
+                .map(|name| Asset {
+                    name: name.to_string(),
+                    browser_download_url: format!("{}/{name}", server.url()),
+                })
+                .to_vec(),
+        };
+        server
+            .mock("GET", "/wakalyze-t.tar.gz")
+            .with_body("hello")
+            .create();
+        let checksum = server
+            .mock("GET", "/wakalyze-t.tar.gz.sha256")
+            .with_body(
+                "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824  wakalyze-t.tar.gz",
+            )
+            .create();
+        assert_eq!(download(&release, "t", 5.0).unwrap(), b"hello");
+        checksum.assert();
+        assert!(matches!(
+            download(&release, "other", 5.0),
+            Err(WakalyzeError::SelfUpdate(_))
+        ));
+    }
+}
@@ -0,0 +1,388 @@
+//! Effort against output: hours per commit of a project's local checkout and, optionally, per
+//! pull request merged on GitHub or GitLab, month by month.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::process::Command;
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate};
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::core::{local_date, local_timestamp, DaySessions};
+use crate::error::{Result, WakalyzeError};
+
+/// Where the pull requests of a checkout are merged, from its `origin` remote.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Forge {
+    /// `repo` is `owner/name`.
+    GitHub { api: String, repo: String },
+    /// `repo` is the full namespaced path, e.g. `group/sub/name`.
+    GitLab { api: String, repo: String },
+}
+
+/// The forge of a remote URL (`https://host/owner/name.git` or `git@host:owner/name.git`);
+/// `None` for hosts other than github.com or a host with `gitlab` in its name.
+pub fn parse_remote(url: &str) -> Option<Forge> {
+    let url = url.trim();
+    let rest = match url.split_once("://") {
+        Some((_, rest)) => rest,
+        None => url.split_once('@').map_or(url, |(_, rest)| rest),
+    };
+    let rest = rest.rsplit_once('@').map_or(rest, |(_, rest)| rest);
+    let (host, path) = rest.split_once([':', '/'])?;
+    // `ssh://git@host:22/owner/name` has a port before the path.
+    let path = match path.split_once('/') {
+        Some((port, path)) if port.chars().all(|c| c.is_ascii_digit()) => path,
+        _ => path,
+    };
+    let repo = path.trim_matches('/').trim_end_matches(".git").to_string();
+    if !repo.contains('/') {
+        return None;
+    }
+    let host = host.to_lowercase();
+    if host == "github.com" {
+        Some(Forge::GitHub {
+            api: "https://api.github.com".to_string(),
+            repo,
+        })
+    } else if host.contains("gitlab") {
+        Some(Forge::GitLab {
+            api: format!("https://{host}/api/v4"),
+            repo,
+        })
+    } else {
+        None
+    }
+}
+
+fn git(repo: &Path, args: &[&str]) -> Result<String> {
+    let failed = |message: String| WakalyzeError::Git(repo.display().to_string(), message);
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args(args)
+        .output()
+        .map_err(|e| failed(e.to_string()))?;
+    if !output.status.success() {
+        return Err(failed(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// URL of the checkout's `origin` remote, if it has one.
+pub fn remote_url(repo: &Path) -> Option<String> {
+    git(repo, &["remote", "get-url", "origin"])
+        .ok()
+        .map(|url| url.trim().to_string())
+}
+
+/// Commit times (epoch seconds) of `start`..=`end` in local time, merges left out. Only the
+/// commits of `author` (a name or email pattern) when given, else of the checkout's
+/// `user.email`.
+pub fn commit_times(
+    repo: &Path,
+    start: NaiveDate,
+    end: NaiveDate,
+    author: Option<&str>,
+) -> Result<Vec<i64>> {
+    let author = match author {
+        Some(author) => author.to_string(),
+        None => git(repo, &["config", "user.email"])?.trim().to_string(),
+    };
+    let since = format!("--since={start} 00:00");
+    let until = format!("--until={} 00:00", end + Duration::days(1));
+    let author = format!("--author={author}");
+    let log = git(
+        repo,
+        &[
+            "log",
+            "--all",
+            "--no-merges",
+            "--format=%ct",
+            &since,
+            &until,
+            &author,
+        ],
+    )?;
+    Ok(log
+        .lines()
+        .filter_map(|line| line.trim().parse().ok())
+        .collect())
+}
+
+fn merged_at(value: Option<&Value>) -> Option<i64> {
+    DateTime::parse_from_rfc3339(value?.as_str()?)
+        .ok()
+        .map(|time| time.timestamp())
+}
+
+/// Merge times of the pull (merge) requests opened by the token's user and merged in
+/// `start`..=`end`. Reads the first 100 only.
+pub fn merged_pr_times(
+    forge: &Forge,
+    token: &str,
+    start: NaiveDate,
+    end: NaiveDate,
+    timeout_secs: f64,
+) -> Result<Vec<i64>> {
+    let client = reqwest::blocking::Client::builder()
+        .timeout(std::time::Duration::from_secs_f64(timeout_secs))
+        .user_agent("wakalyze")
+        .build()?;
+    let (first, last) = (
+        local_timestamp(start, Default::default()).unwrap_or_default(),
+        local_timestamp(end + Duration::days(1), Default::default()).unwrap_or_default(),
+    );
+    let times: Vec<i64> = match forge {
+        Forge::GitHub { api, repo } => {
+            let query = format!("repo:{repo} is:pr is:merged author:@me merged:{start}..{end}");
+            let body: Value = client
+                .get(format!("{api}/search/issues"))
+                .query(&[("q", query.as_str()), ("per_page", "100")])
+                .bearer_auth(token)
+                .send()?
+                .error_for_status()?
+                .json()?;
+            body["items"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|item| merged_at(item.pointer("/pull_request/merged_at")))
+                .collect()
+        }
+        Forge::GitLab { api, repo } => {
+            let after = format!("{start}T00:00:00Z");
+            let body: Value = client
+                .get(format!(
+                    "{api}/projects/{}/merge_requests",
+                    repo.replace('/', "%2F")
+                ))
+                .query(&[
+                    ("state", "merged"),
+                    ("scope", "created_by_me"),
+                    ("updated_after", after.as_str()),
+                    ("per_page", "100"),
+                ])
+                .header("PRIVATE-TOKEN", token)
+                .send()?
+                .error_for_status()?
+                .json()?;
+            body.as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|mr| merged_at(mr.get("merged_at")))
+                .collect()
+        }
+    };
+    Ok(times
+        .into_iter()
+        .filter(|&time| (first..last).contains(&time))
+        .collect())
+}
+
+/// One project's effort and output in one month.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct VelocityRow {
+    pub project: String,
+    /// First day of the month.
+    pub month: NaiveDate,
+    pub seconds: i64,
+    pub commits: usize,
+    /// `None` when merged pull requests were not looked up.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pull_requests: Option<usize>,
+}
+
+impl VelocityRow {
+    pub fn hours_per_commit(&self) -> Option<f64> {
+        (self.commits > 0).then(|| self.seconds as f64 / 3600.0 / self.commits as f64)
+    }
+
+    pub fn hours_per_pull_request(&self) -> Option<f64> {
+        let prs = self.pull_requests.filter(|&prs| prs > 0)?;
+        Some(self.seconds as f64 / 3600.0 / prs as f64)
+    }
+}
+
+fn month_of(date: NaiveDate) -> NaiveDate {
+    date.with_day(1).unwrap_or(date)
+}
+
+fn entry<'a>(
+    months: &'a mut BTreeMap<NaiveDate, VelocityRow>,
+    project: &str,
+    month: NaiveDate,
+    pull_requests: Option<&[i64]>,
+) -> &'a mut VelocityRow {
+    months.entry(month).or_insert_with(|| VelocityRow {
+        project: project.to_string(),
+        month,
+        seconds: 0,
+        commits: 0,
+        pull_requests: pull_requests.map(|_| 0),
+    })
+}
+
+/// Rows of `project` for every month with time, commits, or pull requests.
+pub fn velocity_rows(
+    days: &[DaySessions],
+    project: &str,
+    commits: &[i64],
+    pull_requests: Option<&[i64]>,
+) -> Vec<VelocityRow> {
+    let mut months: BTreeMap<NaiveDate, VelocityRow> = BTreeMap::new();
+    for day in days {
+        let seconds: i64 = day
+            .sessions
+            .iter()
+            .filter(|s| s.project.as_deref() == Some(project))
+            .map(|s| s.seconds)
+            .sum();
+        if seconds > 0 {
+            entry(&mut months, project, month_of(day.date), pull_requests).seconds += seconds;
+        }
+    }
+    for month in commits.iter().filter_map(|&t| local_date(t)).map(month_of) {
+        entry(&mut months, project, month, pull_requests).commits += 1;
+    }
+    for &time in pull_requests.unwrap_or_default() {
+        if let Some(date) = local_date(time) {
+            *entry(&mut months, project, month_of(date), pull_requests)
+                .pull_requests
+                .get_or_insert(0) += 1;
+        }
+    }
+    months.into_values().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Session;
+    use chrono::NaiveTime;
+
+    #[test]
+    fn forges_of_remotes() {
+        let github = |repo: &str| Forge::GitHub {
+            api: "https://api.github.com".to_string(),
+            repo: repo.to_string(),
+        };
+        assert_eq!(
+            parse_remote("git@github.com:masaishi/wakalyze.git"),
+            Some(github("masaishi/wakalyze"))
+        );
+        assert_eq!(
+            parse_remote("https://github.com/masaishi/wakalyze"),
+            Some(github("masaishi/wakalyze"))
+        );
+        assert_eq!(
+            parse_remote("ssh://git@gitlab.example.com:2222/team/api/server.git"),
+            Some(Forge::GitLab {
+                api: "https://gitlab.example.com/api/v4".to_string(),
+                repo: "team/api/server".to_string(),
+            })
+        );
+        assert_eq!(parse_remote("https://example.com/a/b.git"), None);
+        assert_eq!(parse_remote("/srv/git/repo"), None);
+    }
+
+    #[test]
+    fn rows_per_month() {
+        let date = |month: u32, day: u32| NaiveDate::from_ymd_opt(2026, month, day).unwrap();
+        let at = |month: u32, day: u32| {
+            local_timestamp(date(month, day), NaiveTime::from_hms_opt(12, 0, 0).unwrap()).unwrap()
+        };
+        let session = |seconds: i64, project: &str| Session {
+            seconds,
+            project: Some(project.into()),
+            ..Default::default()
+        };
+        let days = vec![
+            DaySessions {
+                date: date(1, 5),
+                sessions: vec![session(7200, "api"), session(3600, "web")],
+            },
+            DaySessions {
+                date: date(2, 2),
+                sessions: vec![session(5400, "api")],
+            },
+        ];
+        let commits = [at(1, 5), at(1, 6), at(1, 7), at(2, 2), at(3, 1)];
+        let rows = velocity_rows(&days, "api", &commits, Some(&[at(2, 3)][..]));
+        let summary: Vec<_> = rows
+            .iter()
+            .map(|r| (r.month, r.seconds, r.commits, r.pull_requests))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (date(1, 1), 7200, 3, Some(0)),
+                (date(2, 1), 5400, 1, Some(1)),
+                (date(3, 1), 0, 1, Some(0)),
+            ]
+        );
+        assert_eq!(rows[0].hours_per_commit(), Some(2.0 / 3.0));
+        assert_eq!(rows[0].hours_per_pull_request(), None);
+        assert_eq!(rows[1].hours_per_pull_request(), Some(1.5));
+    }
+
+    #[test]
+    fn merged_pull_requests_from_github() {
+        let mut server = mockito::Server::new();
+        let mock = server
+            .mock("GET", "/search/issues")
+            .match_query(mockito::Matcher::UrlEncoded(
+                "q".into(),
+                "repo:o/r is:pr is:merged author:@me merged:2026-02-01..2026-02-28".into(),
+            ))
+            .match_header("authorization", "Bearer token")
+            .with_body(
+                r#"{"items": [
+                    {"pull_request": {"merged_at": "2026-02-10T12:00:00Z"}},
+                    {"pull_request": {"merged_at": null}}
+                ]}"#,
+            )
+            .create();
+        let forge = Forge::GitHub {
+            api: server.url(),
+            repo: "o/r".to_string(),
+        };
+        let start = NaiveDate::from_ymd_opt(2026, 2, 1).unwrap();
+        let end = NaiveDate::from_ymd_opt(2026, 2, 28).unwrap();
+        let times = merged_pr_times(&forge, "token", start, end, 5.0).unwrap();
+        mock.assert();
+        assert_eq!(times, vec![1_770_724_800]);
+    }
+
+    #[test]
+    fn merged_merge_requests_from_gitlab() {
+        let mut server = mockito::Server::new();
+        let mock = server
+            .mock("GET", "/projects/team%2Fapi/merge_requests")
+            .match_query(mockito::Matcher::AllOf(vec![
+                mockito::Matcher::UrlEncoded("state".into(), "merged".into()),
+                mockito::Matcher::UrlEncoded("scope".into(), "created_by_me".into()),
+                mockito::Matcher::UrlEncoded("updated_after".into(), "2026-02-01T00:00:00Z".into()),
+            ]))
+            .match_header("private-token", "token")
+            .with_body(
+                r#"[
+                    {"merged_at": "2026-02-10T12:00:00Z"},
+                    {"merged_at": "2026-03-02T12:00:00Z"}
+                ]"#,
+            )
+            .create();
+        let forge = Forge::GitLab {
+            api: server.url(),
+            repo: "team/api".to_string(),
+        };
+        let start = NaiveDate::from_ymd_opt(2026, 2, 1).unwrap();
+        let end = NaiveDate::from_ymd_opt(2026, 2, 28).unwrap();
+        let times = merged_pr_times(&forge, "token", start, end, 5.0).unwrap();
+        mock.assert();
+        assert_eq!(times, vec![1_770_724_800]);
+    }
+}
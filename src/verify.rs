@@ -0,0 +1,187 @@
+//! `wakalyze verify`: compare local heartbeats (the cache or an archive) with a fresh fetch,
+//! to notice data the server lost or imported after the fact.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use chrono::NaiveDate;
+
+use crate::analysis::Report;
+use crate::core::{local_date, RawHeartbeat};
+use crate::format::format_duration;
+
+/// Identity of a heartbeat: every field, with the time by its bits so it can be ordered.
+type HeartbeatKey<'a> = (
+    Option<u64>,
+    Option<&'a str>,
+    Option<&'a str>,
+    Option<&'a str>,
+    Option<&'a str>,
+    Option<&'a str>,
+    Option<&'a str>,
+    Option<&'a str>,
+    Option<&'a str>,
+);
+
+fn key(hb: &RawHeartbeat) -> HeartbeatKey<'_> {
+    (
+        hb.time.map(f64::to_bits),
+        hb.project.as_deref(),
+        hb.machine.as_deref(),
+        hb.entity.as_deref(),
+        hb.language.as_deref(),
+        hb.editor.as_deref(),
+        hb.branch.as_deref(),
+        hb.operating_system.as_deref(),
+        hb.category.as_deref(),
+    )
+}
+
+/// Differences on one local date.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DayDiff {
+    pub date: NaiveDate,
+    /// Heartbeats on the server that are not kept locally.
+    pub added: usize,
+    /// Heartbeats kept locally that the server no longer returns.
+    pub removed: usize,
+    pub local_seconds: i64,
+    pub fresh_seconds: i64,
+}
+
+impl DayDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added == 0 && self.removed == 0 && self.local_seconds == self.fresh_seconds
+    }
+}
+
+fn day_totals(report: &Report) -> BTreeMap<NaiveDate, i64> {
+    report
+        .summary
+        .days
+        .iter()
+        .map(|day| (day.date, day.seconds))
+        .collect()
+}
+
+fn day_mut(days: &mut BTreeMap<NaiveDate, DayDiff>, date: NaiveDate) -> &mut DayDiff {
+    days.entry(date).or_insert_with(|| DayDiff {
+        date,
+        ..Default::default()
+    })
+}
+
+/// The days of the period whose heartbeats or totals differ, oldest first.
+pub fn diff_days(
+    local: &[RawHeartbeat],
+    fresh: &[RawHeartbeat],
+    local_report: &Report,
+    fresh_report: &Report,
+) -> Vec<DayDiff> {
+    let local_keys: BTreeSet<_> = local.iter().map(key).collect();
+    let fresh_keys: BTreeSet<_> = fresh.iter().map(key).collect();
+
+    let mut days: BTreeMap<NaiveDate, DayDiff> = BTreeMap::new();
+    let date_of = |k: &HeartbeatKey<'_>| {
+        local_date(f64::from_bits(k.0?) as i64)
+            .filter(|date| *date >= local_report.start && *date <= local_report.end)
+    };
+    for date in fresh_keys.difference(&local_keys).filter_map(date_of) {
+        day_mut(&mut days, date).added += 1;
+    }
+    for date in local_keys.difference(&fresh_keys).filter_map(date_of) {
+        day_mut(&mut days, date).removed += 1;
+    }
+    for (date, seconds) in day_totals(local_report) {
+        day_mut(&mut days, date).local_seconds = seconds;
+    }
+    for (date, seconds) in day_totals(fresh_report) {
+        day_mut(&mut days, date).fresh_seconds = seconds;
+    }
+    days.into_values().filter(|d| !d.is_empty()).collect()
+}
+
+/// `2026-02-03: +12 -0 heartbeats, 3h10m -> 3h25m (+0h15m)`.
+pub fn diff_line(diff: &DayDiff) -> String {
+    let change = diff.fresh_seconds - diff.local_seconds;
+    let sign = if change < 0 { '-' } else { '+' };
+    format!(
+        "{}: +{} -{} heartbeats, {} -> {} ({sign}{})",
+        diff.date,
+        diff.added,
+        diff.removed,
+        format_duration(diff.local_seconds),
+        format_duration(diff.fresh_seconds),
+        format_duration(change.abs())
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::{Analysis, MemorySource};
+    use crate::core::local_timestamp;
+    use chrono::NaiveTime;
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 2, day).unwrap()
+    }
+
+    fn heartbeats(day: u32, hour: u32, count: i64) -> Vec<RawHeartbeat> {
+        let start =
+            local_timestamp(date(day), NaiveTime::from_hms_opt(hour, 0, 0).unwrap()).unwrap();
+        (0..count)
+            .map(|i| RawHeartbeat {
+                time: Some((start + i * 60) as f64),
+                project: Some("api".into()),
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    fn report(heartbeats: &[RawHeartbeat]) -> Report {
+        Analysis::range(date(2), date(4))
+            .run(&MemorySource::new(heartbeats.to_vec()))
+            .unwrap()
+    }
+
+    #[test]
+    fn reports_added_removed_and_changed_totals() {
+        let local = [heartbeats(2, 9, 11), heartbeats(3, 9, 11)].concat();
+        let fresh = [
+            heartbeats(2, 9, 11),
+            heartbeats(3, 9, 6),
+            heartbeats(4, 9, 6),
+        ]
+        .concat();
+        let diffs = diff_days(&local, &fresh, &report(&local), &report(&fresh));
+        assert_eq!(
+            diffs,
+            vec![
+                DayDiff {
+                    date: date(3),
+                    added: 0,
+                    removed: 5,
+                    local_seconds: 600,
+                    fresh_seconds: 300,
+                },
+                DayDiff {
+                    date: date(4),
+                    added: 6,
+                    removed: 0,
+                    local_seconds: 0,
+                    fresh_seconds: 300,
+                },
+            ]
+        );
+        assert_eq!(
+            diff_line(&diffs[0]),
+            "2026-02-03: +0 -5 heartbeats, 0h10m -> 0h05m (-0h05m)"
+        );
+    }
+
+    #[test]
+    fn identical_data_has_no_differences() {
+        let local = heartbeats(2, 9, 11);
+        assert!(diff_days(&local, &local, &report(&local), &report(&local)).is_empty());
+    }
+}
@@ -0,0 +1,243 @@
+//! `wakalyze cache warm`: fill the heartbeat cache for a whole period ahead of time, e.g. from
+//! cron, so later analyses never wait on the network.
+
+use std::sync::Mutex;
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+use chrono::NaiveDate;
+use rayon::prelude::*;
+
+use crate::analysis::HeartbeatSource;
+use crate::cache::{CachedSource, HeartbeatCache};
+use crate::core::{month_last_day, parse_month};
+use crate::error::{Result, WakalyzeError};
+
+/// First and last day of a `YYYY` or `YYYY/MM` period.
+pub fn parse_period(value: &str) -> Result<(NaiveDate, NaiveDate)> {
+    let invalid = || WakalyzeError::InvalidPeriod(value.to_string());
+    if value.len() != 4 {
+        let first = parse_month(value).map_err(|_| invalid())?;
+        return Ok((first, month_last_day(first)));
+    }
+    let year: i32 = value.parse().map_err(|_| invalid())?;
+    let first = NaiveDate::from_ymd_opt(year, 1, 1).ok_or_else(invalid)?;
+    let last = NaiveDate::from_ymd_opt(year, 12, 31).ok_or_else(invalid)?;
+    Ok((first, last))
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct WarmOptions {
+    /// Dates fetched at once.
+    pub concurrency: usize,
+    /// Further attempts after a failed fetch.
+    pub retries: u32,
+    /// Wait before the first retry, doubled for each further one.
+    pub backoff: Duration,
+    /// Minimum time between the starts of two requests, over all workers.
+    pub min_interval: Duration,
+}
+
+/// What happened to one date.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WarmOutcome {
+    /// Complete in the cache already; not requested.
+    Cached,
+    /// Fetched (after `attempts` tries) and stored.
+    Fetched { heartbeats: usize, attempts: u32 },
+    /// Every attempt failed; the last error.
+    Failed(String),
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WarmSummary {
+    pub cached: usize,
+    pub fetched: usize,
+    pub failed: usize,
+}
+
+/// Spaces out request starts by `interval`, shared by all workers.
+struct RateLimiter {
+    interval: Duration,
+    next: Mutex<Instant>,
+}
+
+impl RateLimiter {
+    fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            next: Mutex::new(Instant::now()),
+        }
+    }
+
+    fn wait(&self) {
+        let slot = {
+            let mut next = self.next.lock().unwrap_or_else(|e| e.into_inner());
+            let slot = (*next).max(Instant::now());
+            *next = slot + self.interval;
+            slot
+        };
+        sleep(slot.saturating_duration_since(Instant::now()));
+    }
+}
+
+/// Fetch every date the cache does not hold completely, calling `log` once per date as it
+/// finishes (in completion order).
+pub fn warm<S: HeartbeatSource + Sync + ?Sized>(
+    source: &S,
+    cache: HeartbeatCache,
+    dates: &[NaiveDate],
+    today: NaiveDate,
+    options: WarmOptions,
+    log: impl Fn(NaiveDate, &WarmOutcome) + Sync,
+) -> WarmSummary {
+    let limiter = RateLimiter::new(options.min_interval);
+    let cached = CachedSource::new(source, cache.clone(), today);
+    let warm_date = |date: NaiveDate| {
+        let outcome = if cache.load(date).is_some_and(|day| day.complete) {
+            WarmOutcome::Cached
+        } else {
+            let mut attempts = 0;
+            loop {
+                limiter.wait();
+                attempts += 1;
+                match cached.fetch_heartbeats(date) {
+                    Ok(heartbeats) => {
+                        break WarmOutcome::Fetched {
+                            heartbeats: heartbeats.len(),
+                            attempts,
+                        }
+                    }
+                    Err(e) if attempts > options.retries => {
+                        break WarmOutcome::Failed(e.to_string())
+                    }
+                    Err(_) => sleep(options.backoff * 2u32.pow(attempts - 1)),
+                }
+            }
+        };
+        log(date, &outcome);
+        outcome
+    };
+
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(options.concurrency.max(1))
+        .build()
+        .expect("failed to build worker pool");
+    let outcomes: Vec<WarmOutcome> =
+        pool.install(|| dates.par_iter().map(|&d| warm_date(d)).collect());
+
+    let mut summary = WarmSummary::default();
+    for outcome in &outcomes {
+        match outcome {
+            WarmOutcome::Cached => summary.cached += 1,
+            WarmOutcome::Fetched { .. } => summary.fetched += 1,
+            WarmOutcome::Failed(_) => summary.failed += 1,
+        }
+    }
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{iter_dates, RawHeartbeat};
+    use std::collections::HashMap;
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 2, day).unwrap()
+    }
+
+    /// Fails the first `failures[date]` requests for a date, then serves one heartbeat.
+    struct FlakySource {
+        failures: HashMap<NaiveDate, u32>,
+        requests: Mutex<HashMap<NaiveDate, u32>>,
+    }
+
+    impl HeartbeatSource for FlakySource {
+        fn fetch_heartbeats(&self, date: NaiveDate) -> Result<Vec<RawHeartbeat>> {
+            let mut requests = self.requests.lock().unwrap();
+            let count = requests.entry(date).or_default();
+            *count += 1;
+            if *count <= self.failures.get(&date).copied().unwrap_or(0) {
+                return Err(WakalyzeError::InvalidResponse(
+                    serde_json::from_str::<()>("x").unwrap_err(),
+                ));
+            }
+            Ok(vec![RawHeartbeat {
+                time: Some(1_770_000_000.0),
+                ..Default::default()
+            }])
+        }
+    }
+
+    fn options() -> WarmOptions {
+        WarmOptions {
+            concurrency: 2,
+            retries: 2,
+            backoff: Duration::ZERO,
+            min_interval: Duration::ZERO,
+        }
+    }
+
+    #[test]
+    fn parse_year_or_month() {
+        assert_eq!(
+            parse_period("2026").unwrap(),
+            (
+                NaiveDate::from_ymd_opt(2026, 1, 1).unwrap(),
+                NaiveDate::from_ymd_opt(2026, 12, 31).unwrap()
+            )
+        );
+        assert_eq!(parse_period("2026/02").unwrap(), (date(1), date(28)));
+        assert!(matches!(
+            parse_period("26"),
+            Err(WakalyzeError::InvalidPeriod(_))
+        ));
+        assert!(parse_period("2026/13").is_err());
+    }
+
+    #[test]
+    fn retries_then_skips_complete_days() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = HeartbeatCache::new(dir.path(), "k");
+        let source = FlakySource {
+            failures: HashMap::from([(date(2), 2), (date(3), 5)]),
+            requests: Mutex::new(HashMap::new()),
+        };
+        let dates = iter_dates(date(1), date(3));
+        let logged = Mutex::new(Vec::new());
+        let summary = warm(
+            &source,
+            cache.clone(),
+            &dates,
+            date(10),
+            options(),
+            |d, outcome| {
+                logged.lock().unwrap().push((d, outcome.clone()));
+            },
+        );
+        assert_eq!(
+            summary,
+            WarmSummary {
+                cached: 0,
+                fetched: 2,
+                failed: 1
+            }
+        );
+        let mut logged = logged.into_inner().unwrap();
+        logged.sort_by_key(|(d, _)| *d);
+        assert_eq!(
+            logged[1].1,
+            WarmOutcome::Fetched {
+                heartbeats: 1,
+                attempts: 3
+            }
+        );
+        assert!(matches!(logged[2].1, WarmOutcome::Failed(_)));
+        assert_eq!(source.requests.lock().unwrap()[&date(3)], 3);
+
+        let again = warm(&source, cache, &dates, date(10), options(), |_, _| {});
+        assert_eq!(again.cached, 2);
+        assert_eq!(again.fetched, 1);
+    }
+}
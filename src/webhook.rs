@@ -0,0 +1,81 @@
+//! POSTing results to HTTP endpoints.
+
+use serde::Serialize;
+
+use crate::error::{Result, WakalyzeError};
+
+/// Split a `Name: value` header argument.
+pub fn parse_header(value: &str) -> Result<(String, String)> {
+    match value.split_once(':') {
+        Some((name, val)) if !name.trim().is_empty() => {
+            Ok((name.trim().to_string(), val.trim().to_string()))
+        }
+        _ => Err(WakalyzeError::InvalidHeader(value.to_string())),
+    }
+}
+
+/// POST `body` as JSON with extra `headers`; non-2xx responses are errors.
+pub fn post_json<T: Serialize + ?Sized>(
+    url: &str,
+    body: &T,
+    headers: &[(String, String)],
+    timeout_secs: f64,
+) -> Result<()> {
+    let client = reqwest::blocking::Client::builder()
+        .timeout(std::time::Duration::from_secs_f64(timeout_secs))
+        .build()?;
+    let mut request = client.post(url).json(body);
+    for (name, value) in headers {
+        request = request.header(name, value);
+    }
+    request.send()?.error_for_status()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn parse_header_splits_name_and_value() {
+        assert_eq!(
+            parse_header("Authorization: Bearer a:b").unwrap(),
+            ("Authorization".to_string(), "Bearer a:b".to_string())
+        );
+        assert!(matches!(
+            parse_header("no-colon"),
+            Err(WakalyzeError::InvalidHeader(_))
+        ));
+        assert!(parse_header(": value").is_err());
+    }
+
+    #[test]
+    fn post_json_sends_body_and_headers() {
+        let mut server = mockito::Server::new();
+        let mock = server
+            .mock("POST", "/ingest")
+            .match_header("X-Token", "secret")
+            .match_header("content-type", "application/json")
+            .match_body(mockito::Matcher::Json(json!({ "seconds": 60 })))
+            .with_status(204)
+            .create();
+        let headers = vec![("X-Token".to_string(), "secret".to_string())];
+        post_json(
+            &format!("{}/ingest", server.url()),
+            &json!({ "seconds": 60 }),
+            &headers,
+            5.0,
+        )
+        .unwrap();
+        mock.assert();
+    }
+
+    #[test]
+    fn post_json_fails_on_error_status() {
+        let mut server = mockito::Server::new();
+        server.mock("POST", "/ingest").with_status(500).create();
+        let url = format!("{}/ingest", server.url());
+        assert!(post_json(&url, &json!({}), &[], 5.0).is_err());
+    }
+}
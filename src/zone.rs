@@ -0,0 +1,140 @@
+//! Time zone of day boundaries and displayed times: that of the [`crate::analysis::Analysis`]
+//! running on this thread, else `--timezone` or the config's `timezone`, else the system's.
+
+use std::cell::Cell;
+use std::sync::{PoisonError, RwLock};
+
+use chrono::{DateTime, FixedOffset, Local, LocalResult, NaiveDate, NaiveDateTime, TimeZone};
+use chrono_tz::Tz;
+
+use crate::error::{Result, WakalyzeError};
+
+static TIMEZONE: RwLock<Option<Tz>> = RwLock::new(None);
+
+thread_local! {
+    static SCOPED: Cell<Option<Tz>> = const { Cell::new(None) };
+}
+
+/// An IANA time zone name such as `Asia/Tokyo` or `UTC`.
+pub fn parse_timezone(name: &str) -> Result<Tz> {
+    name.trim()
+        .parse()
+        .map_err(|_| WakalyzeError::InvalidTimezone(name.to_string()))
+}
+
+/// Use `zone` instead of the system time zone by default, on every thread; `None` goes back to
+/// the system's.
+pub fn select_timezone(zone: Option<Tz>) {
+    *TIMEZONE.write().unwrap_or_else(PoisonError::into_inner) = zone;
+}
+
+/// The zone in effect on this thread; `None` for the system's.
+pub fn selected() -> Option<Tz> {
+    SCOPED
+        .get()
+        .or_else(|| *TIMEZONE.read().unwrap_or_else(PoisonError::into_inner))
+}
+
+/// Run `f` with `zone` in effect on this thread; `None` keeps the current one.
+pub fn scoped<T>(zone: Option<Tz>, f: impl FnOnce() -> T) -> T {
+    /// Restores the previous zone, also when `f` panics.
+    struct Restore(Option<Tz>);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            SCOPED.set(self.0);
+        }
+    }
+
+    let Some(zone) = zone else {
+        return f();
+    };
+    let _restore = Restore(SCOPED.replace(Some(zone)));
+    f()
+}
+
+/// The instant of an epoch timestamp in the selected time zone.
+pub fn at(timestamp: i64) -> LocalResult<DateTime<FixedOffset>> {
+    at_in(selected(), timestamp)
+}
+
+/// The instants a wall-clock time stands for in the selected time zone.
+pub fn from_local(naive: &NaiveDateTime) -> LocalResult<DateTime<FixedOffset>> {
+    from_local_in(selected(), naive)
+}
+
+/// Today's date in the selected time zone.
+pub fn today() -> NaiveDate {
+    at(chrono::Utc::now().timestamp())
+        .earliest()
+        .map(|dt| dt.date_naive())
+        .unwrap_or_else(|| Local::now().date_naive())
+}
+
+fn at_in(zone: Option<Tz>, timestamp: i64) -> LocalResult<DateTime<FixedOffset>> {
+    match zone {
+        Some(tz) => tz.timestamp_opt(timestamp, 0).map(|dt| dt.fixed_offset()),
+        None => Local
+            .timestamp_opt(timestamp, 0)
+            .map(|dt| dt.fixed_offset()),
+    }
+}
+
+fn from_local_in(zone: Option<Tz>, naive: &NaiveDateTime) -> LocalResult<DateTime<FixedOffset>> {
+    match zone {
+        Some(tz) => tz.from_local_datetime(naive).map(|dt| dt.fixed_offset()),
+        None => Local.from_local_datetime(naive).map(|dt| dt.fixed_offset()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_iana_names() {
+        assert_eq!(
+            parse_timezone("Asia/Tokyo").unwrap(),
+            chrono_tz::Asia::Tokyo
+        );
+        assert_eq!(parse_timezone(" UTC ").unwrap(), chrono_tz::UTC);
+        assert!(matches!(
+            parse_timezone("Mars/Olympus"),
+            Err(WakalyzeError::InvalidTimezone(_))
+        ));
+    }
+
+    #[test]
+    fn converts_in_the_given_zone() {
+        let tokyo = Some(chrono_tz::Asia::Tokyo);
+        // 2026-02-01 20:00 UTC is already the next morning in Tokyo.
+        let dt = at_in(tokyo, 1_769_976_000).single().unwrap();
+        assert_eq!(dt.to_rfc3339(), "2026-02-02T05:00:00+09:00");
+
+        let naive = dt.naive_local();
+        let back = from_local_in(tokyo, &naive).single().unwrap();
+        assert_eq!(back.timestamp(), 1_769_976_000);
+
+        // New York skips 02:00–03:00 on 2026-03-08.
+        let skipped = NaiveDate::from_ymd_opt(2026, 3, 8)
+            .unwrap()
+            .and_hms_opt(2, 30, 0)
+            .unwrap();
+        let new_york = Some(chrono_tz::America::New_York);
+        assert_eq!(from_local_in(new_york, &skipped), LocalResult::None);
+    }
+
+    #[test]
+    fn scoped_zones_nest_and_restore() {
+        let outer = selected();
+        let tokyo = chrono_tz::Asia::Tokyo;
+        let utc = chrono_tz::UTC;
+        scoped(Some(tokyo), || {
+            assert_eq!(selected(), Some(tokyo));
+            scoped(Some(utc), || assert_eq!(selected(), Some(utc)));
+            scoped(None, || assert_eq!(selected(), Some(tokyo)));
+            assert_eq!(selected(), Some(tokyo));
+        });
+        assert_eq!(selected(), outer);
+    }
+}
@@ -31,6 +31,118 @@ fn config_set_then_show() {
         .stdout(predicate::str::contains("testuser"));
 }
 
+#[test]
+fn profile_set_then_show_and_select() {
+    let dir = tempfile::tempdir().unwrap();
+
+    cargo_bin_cmd!("wakalyze")
+        .env("XDG_CONFIG_HOME", dir.path())
+        .args(["config", "set", "--profile", "work", "--user", "me.work"])
+        .assert()
+        .success();
+
+    cargo_bin_cmd!("wakalyze")
+        .env("XDG_CONFIG_HOME", dir.path())
+        .args(["config", "show"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("profile work:\n  user: me.work"));
+
+    cargo_bin_cmd!("wakalyze")
+        .env("XDG_CONFIG_HOME", dir.path())
+        .args(["--profile", "home", "2026/01"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("no profile home"));
+}
+
+#[test]
+fn input_analyzes_exported_heartbeats_without_auth() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("heartbeats.json");
+    // 2026-01-15 12:00 and 12:05 UTC
+    std::fs::write(
+        &input,
+        r#"{"data":[{"time":1768478400,"project":"offline"},{"time":1768478700,"project":"offline"}]}"#,
+    )
+    .unwrap();
+
+    cargo_bin_cmd!("wakalyze")
+        .env_remove("WAKAPI_KEY")
+        .env_remove("WAKAPI_USER")
+        .env("XDG_CONFIG_HOME", dir.path())
+        .env("XDG_STATE_HOME", dir.path())
+        .args(["2026/01", "--format", "json", "--input"])
+        .arg(&input)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"offline\""));
+}
+
+#[test]
+fn timezone_sets_day_and_displayed_time() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("heartbeats.json");
+    // 2026-01-15 20:00 and 20:05 UTC, the next morning in Tokyo
+    std::fs::write(
+        &input,
+        r#"[{"time":1768507200,"project":"travel"},{"time":1768507500,"project":"travel"}]"#,
+    )
+    .unwrap();
+    let analyze = |timezone: &str| {
+        cargo_bin_cmd!("wakalyze")
+            .env_remove("WAKAPI_KEY")
+            .env_remove("WAKAPI_USER")
+            .env("XDG_CONFIG_HOME", dir.path())
+            .env("XDG_STATE_HOME", dir.path())
+            .args(["2026/01", "--format", "json", "--timezone", timezone, "--input"])
+            .arg(&input)
+            .assert()
+    };
+
+    analyze("Asia/Tokyo")
+        .success()
+        .stdout(predicate::str::contains("2026-01-16T05:00:00+09:00"));
+    analyze("UTC")
+        .success()
+        .stdout(predicate::str::contains("2026-01-15T20:00:00Z"));
+    analyze("Mars/Olympus")
+        .failure()
+        .stderr(predicate::str::contains("unknown time zone Mars/Olympus"));
+}
+
+#[test]
+fn heatmap_shades_the_days_of_a_year() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("heartbeats.json");
+    // 2025-01-15 12:00 and 12:05 UTC
+    std::fs::write(
+        &input,
+        r#"[{"time":1736942400,"project":"heat"},{"time":1736942700,"project":"heat"}]"#,
+    )
+    .unwrap();
+    let heatmap = |thresholds: &str| {
+        cargo_bin_cmd!("wakalyze")
+            .env_remove("WAKAPI_KEY")
+            .env_remove("WAKAPI_USER")
+            .env("XDG_CONFIG_HOME", dir.path())
+            .env("XDG_STATE_HOME", dir.path())
+            .args(["heatmap", "2025", "--heatmap-thresholds", thresholds])
+            .args(["--timezone", "UTC", "--input"])
+            .arg(&input)
+            .assert()
+    };
+
+    heatmap("1h,3h,5h")
+        .success()
+        .stdout(predicate::str::contains("    Jan"))
+        .stdout(predicate::str::contains("Dec"))
+        .stdout(predicate::str::contains("░ <1h00m"));
+    heatmap("3h,1h,5h")
+        .failure()
+        .stderr(predicate::str::contains("heatmap thresholds must be three increasing"));
+}
+
 #[test]
 fn missing_auth_shows_error() {
     cargo_bin_cmd!("wakalyze")
@@ -77,3 +189,360 @@ fn implicit_analyze_subcommand() {
         .failure()
         .stderr(predicate::str::contains("missing auth"));
 }
+
+#[test]
+fn schema_command_prints_json_schema() {
+    cargo_bin_cmd!("wakalyze")
+        .arg("schema")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"schema_version\""));
+}
+
+#[test]
+fn inspect_rejects_invalid_date() {
+    cargo_bin_cmd!("wakalyze")
+        .args(["inspect", "2026/02/03"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("YYYY-MM-DD"));
+}
+
+#[test]
+fn report_subcommand_parses_period() {
+    cargo_bin_cmd!("wakalyze")
+        .env_remove("WAKAPI_KEY")
+        .env("WAKAPI_USER", "testuser")
+        .env("XDG_CONFIG_HOME", tempfile::tempdir().unwrap().path())
+        .args(["report", "2026/02", "1", "--goal-hours", "30"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("missing auth"));
+}
+
+#[test]
+fn invoice_subcommand_parses_options() {
+    cargo_bin_cmd!("wakalyze")
+        .env_remove("WAKAPI_KEY")
+        .env("WAKAPI_USER", "testuser")
+        .env("XDG_CONFIG_HOME", tempfile::tempdir().unwrap().path())
+        .args(["invoice", "2026/02", "--items", "day", "--markup", "html"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("missing auth"));
+}
+
+#[test]
+fn invoice_rate_prices_rounded_hours() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("heartbeats.json");
+    // Five minutes on 2026-01-15, billed as a quarter hour
+    std::fs::write(
+        &input,
+        r#"[{"time":1768478400,"project":"client-x"},{"time":1768478700,"project":"client-x"}]"#,
+    )
+    .unwrap();
+
+    cargo_bin_cmd!("wakalyze")
+        .env_remove("WAKAPI_KEY")
+        .env_remove("WAKAPI_USER")
+        .env("XDG_CONFIG_HOME", dir.path())
+        .env("XDG_STATE_HOME", dir.path())
+        .args(["invoice", "2026/01", "--project", "client-x", "--rate", "120"])
+        .args(["--currency", "USD", "--round-minutes", "15", "--input"])
+        .arg(&input)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("| client-x | 0.25 | 120.00 | 30.00 |"));
+}
+
+#[test]
+fn compare_shows_the_change_per_project() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("heartbeats.json");
+    // Five minutes on 2026-01-15 and ten on 2026-02-10
+    std::fs::write(
+        &input,
+        r#"[{"time":1768478400,"project":"client-x"},{"time":1768478700,"project":"client-x"},
+            {"time":1770724800,"project":"client-x"},{"time":1770725400,"project":"client-x"}]"#,
+    )
+    .unwrap();
+
+    cargo_bin_cmd!("wakalyze")
+        .env_remove("WAKAPI_KEY")
+        .env_remove("WAKAPI_USER")
+        .env("XDG_CONFIG_HOME", dir.path())
+        .env("XDG_STATE_HOME", dir.path())
+        .args(["compare", "2026/01", "2026/02", "--input"])
+        .arg(&input)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "client-x  0h05m    0h10m    +0h05m  +100.0%",
+        ));
+}
+
+#[test]
+fn goals_status_shows_each_configured_goal() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::create_dir_all(dir.path().join("wakalyze")).unwrap();
+    std::fs::write(
+        dir.path().join("wakalyze").join("config.json"),
+        r#"{"goals":[{"period":"month","hours":20}]}"#,
+    )
+    .unwrap();
+    let input = dir.path().join("heartbeats.json");
+    std::fs::write(&input, "[]").unwrap();
+
+    cargo_bin_cmd!("wakalyze")
+        .env_remove("WAKAPI_KEY")
+        .env_remove("WAKAPI_USER")
+        .env("XDG_CONFIG_HOME", dir.path())
+        .env("XDG_STATE_HOME", dir.path())
+        .args(["goals", "status", "--input"])
+        .arg(&input)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("month   all      0h00m  20h00m  20h00m"));
+}
+
+#[test]
+fn watch_rejects_a_zero_interval() {
+    cargo_bin_cmd!("wakalyze")
+        .env_remove("WAKAPI_KEY")
+        .env("XDG_CONFIG_HOME", tempfile::tempdir().unwrap().path())
+        .args(["watch", "--interval", "0"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "--interval must be at least 1 second",
+        ));
+}
+
+#[test]
+fn round_rounds_sessions_before_the_totals() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("heartbeats.json");
+    // Five minutes on 2026-01-15
+    std::fs::write(
+        &input,
+        r#"[{"time":1768478400,"project":"client-x"},{"time":1768478700,"project":"client-x"}]"#,
+    )
+    .unwrap();
+
+    cargo_bin_cmd!("wakalyze")
+        .env_remove("WAKAPI_KEY")
+        .env_remove("WAKAPI_USER")
+        .env("XDG_CONFIG_HOME", dir.path())
+        .env("XDG_STATE_HOME", dir.path())
+        .args(["2026/01", "--round", "15m", "--totals", "--input"])
+        .arg(&input)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("(0h15m) client-x"))
+        .stdout(predicate::str::contains("total 0h15m"));
+}
+
+#[test]
+fn group_by_client_totals_the_projects_of_each_client() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::create_dir_all(dir.path().join("wakalyze")).unwrap();
+    std::fs::write(
+        dir.path().join("wakalyze").join("config.json"),
+        r#"{"clients":[{"client":"Acme","projects":["acme-*"]}]}"#,
+    )
+    .unwrap();
+    let input = dir.path().join("heartbeats.json");
+    // Five minutes on each of two Acme projects on 2026-01-15
+    std::fs::write(
+        &input,
+        r#"[{"time":1768478400,"project":"acme-api"},{"time":1768478700,"project":"acme-api"},
+            {"time":1768482000,"project":"acme-web"},{"time":1768482300,"project":"acme-web"}]"#,
+    )
+    .unwrap();
+
+    cargo_bin_cmd!("wakalyze")
+        .env_remove("WAKAPI_KEY")
+        .env_remove("WAKAPI_USER")
+        .env("XDG_CONFIG_HOME", dir.path())
+        .env("XDG_STATE_HOME", dir.path())
+        .args(["2026/01", "--group-by", "client", "--input"])
+        .arg(&input)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("clients"))
+        .stdout(predicate::str::contains("100.0% Acme"));
+}
+
+#[test]
+fn round_conflicts_with_round_minutes() {
+    cargo_bin_cmd!("wakalyze")
+        .env_remove("WAKAPI_KEY")
+        .env("XDG_CONFIG_HOME", tempfile::tempdir().unwrap().path())
+        .args(["2026/01", "--round", "15m", "--round-minutes", "15"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+}
+
+#[test]
+fn future_period_is_rejected_before_fetching() {
+    cargo_bin_cmd!("wakalyze")
+        .env_remove("WAKAPI_KEY")
+        .env("XDG_CONFIG_HOME", tempfile::tempdir().unwrap().path())
+        .args(["analyze", "2999/01"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("2999-01-01 is in the future"));
+}
+
+#[test]
+fn week_subcommand_resolves_the_containing_week() {
+    cargo_bin_cmd!("wakalyze")
+        .env_remove("WAKAPI_KEY")
+        .env("XDG_CONFIG_HOME", tempfile::tempdir().unwrap().path())
+        .args(["week", "2999-01-08"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("2999-01-06 is in the future"));
+}
+
+#[test]
+fn year_takes_no_week_number() {
+    cargo_bin_cmd!("wakalyze")
+        .env_remove("WAKAPI_KEY")
+        .env("XDG_CONFIG_HOME", tempfile::tempdir().unwrap().path())
+        .args(["2025", "2"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "a week number needs a month, not a year",
+        ));
+}
+
+#[test]
+fn relative_period_takes_no_week_number() {
+    cargo_bin_cmd!("wakalyze")
+        .env_remove("WAKAPI_KEY")
+        .env("XDG_CONFIG_HOME", tempfile::tempdir().unwrap().path())
+        .args(["last-week", "2"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "a week number needs a month, not a day or week",
+        ));
+}
+
+#[test]
+fn from_to_range_must_not_end_before_it_starts() {
+    cargo_bin_cmd!("wakalyze")
+        .env_remove("WAKAPI_KEY")
+        .env("XDG_CONFIG_HOME", tempfile::tempdir().unwrap().path())
+        .args(["--from", "2026-02-10", "--to", "2026-01-15"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "--to 2026-01-15 is before --from 2026-02-10",
+        ));
+}
+
+#[test]
+fn from_to_range_exports_to_timewarrior() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("heartbeats.json");
+    // Five minutes on 2026-01-15
+    std::fs::write(
+        &input,
+        r#"[{"time":1768478400,"project":"client-x"},{"time":1768478700,"project":"client-x"}]"#,
+    )
+    .unwrap();
+
+    cargo_bin_cmd!("wakalyze")
+        .env_remove("WAKAPI_KEY")
+        .env_remove("WAKAPI_USER")
+        .env("XDG_CONFIG_HOME", dir.path())
+        .env("XDG_STATE_HOME", dir.path())
+        .args(["--from", "2026-01-14", "--to", "2026-01-16", "--format", "timew"])
+        .arg("--input")
+        .arg(&input)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("timew track"))
+        .stdout(predicate::str::contains("client-x"));
+}
+
+#[test]
+fn sessions_subcommand_rejects_export_formats() {
+    cargo_bin_cmd!("wakalyze")
+        .env("WAKAPI_USER", "testuser")
+        .args(["sessions", "2026/02", "--format", "harvest"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("text, json, or ndjson"));
+}
+
+#[test]
+fn sessions_from_input_need_no_user() {
+    let dir = tempfile::tempdir().unwrap();
+    let input = dir.path().join("heartbeats.json");
+    // Five minutes on 2026-01-15
+    std::fs::write(
+        &input,
+        r#"[{"time":1768478400,"project":"client-x"},{"time":1768478700,"project":"client-x"}]"#,
+    )
+    .unwrap();
+
+    cargo_bin_cmd!("wakalyze")
+        .env_remove("WAKAPI_KEY")
+        .env_remove("WAKAPI_USER")
+        .env("XDG_CONFIG_HOME", dir.path())
+        .env("XDG_STATE_HOME", dir.path())
+        .args(["sessions", "2026/01", "--format", "json", "--input"])
+        .arg(&input)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("client-x"));
+}
+
+#[test]
+fn tag_stores_and_shows_annotation() {
+    let state = tempfile::tempdir().unwrap();
+    cargo_bin_cmd!("wakalyze")
+        .env("XDG_STATE_HOME", state.path())
+        .args(["tag", "0123456789ab", "--note", "pairing", "--tag", "billable"])
+        .assert()
+        .success()
+        .stdout("0123456789ab: #billable (pairing)\n");
+    cargo_bin_cmd!("wakalyze")
+        .env("XDG_STATE_HOME", state.path())
+        .args(["tag", "0123456789AB", "--untag", "billable"])
+        .assert()
+        .success()
+        .stdout("0123456789ab: (pairing)\n");
+    cargo_bin_cmd!("wakalyze")
+        .env("XDG_STATE_HOME", state.path())
+        .args(["tag", "not-an-id"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("12 hex digits"));
+}
+
+#[test]
+fn add_records_manual_entry() {
+    let state = tempfile::tempdir().unwrap();
+    cargo_bin_cmd!("wakalyze")
+        .env("XDG_STATE_HOME", state.path())
+        .args(["add", "2026-02-03", "13:00-14:00", "--project", "clientx"])
+        .args(["--note", "architecture call"])
+        .assert()
+        .success()
+        .stdout("added 2026-02-03 13:00-14:00 clientx\n");
+    let stored = std::fs::read_to_string(state.path().join("wakalyze/manual.json")).unwrap();
+    assert!(stored.contains("architecture call"));
+    cargo_bin_cmd!("wakalyze")
+        .env("XDG_STATE_HOME", state.path())
+        .args(["add", "2026-02-03", "14:00-13:00", "--project", "clientx"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("end after the start"));
+}